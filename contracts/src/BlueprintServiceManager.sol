@@ -8,28 +8,97 @@ import {IVerifier} from "./IVerifier.sol";
 /// @author Tangle Network Team
 /// @notice Manages the CoSNARKs zkSaaS Blueprint instance, handling circuit
 /// registration metadata (verification keys) and verifying proof generation jobs.
+/// @dev Job results are the ABI encoding of the Rust job outputs:
+/// `[u8; 32]` is `bytes32`, `Vec<u8>` is `bytes`, other `Vec<T>` are `T[]`,
+/// structs are tuples and unit enums (`ProvingBackend`) their lowercase name.
+/// `Option<T>` is a `T[]` of zero or one element, and enums carrying data
+/// (`ProofOutput`) hold one such array per variant, of which exactly one is set.
 contract CoSNARKsZkSaaSBlueprintServiceManager is BlueprintServiceManagerBase {
     // --- Constants ---
 
     // Job IDs (must match Rust constants)
     uint64 public constant REGISTER_CIRCUIT_JOB_ID = 0;
     uint64 public constant GENERATE_PROOF_JOB_ID = 1;
+    uint64 public constant IMPORT_REGISTRY_JOB_ID = 2;
+    uint64 public constant LIST_SESSIONS_JOB_ID = 3;
+    uint64 public constant CLONE_CIRCUIT_JOB_ID = 4;
+    uint64 public constant FORCE_CLOSE_SESSION_JOB_ID = 5;
+    uint64 public constant GET_PROOF_JOB_ID = 6;
+    uint64 public constant CANCEL_PROOF_JOB_ID = 7;
+    uint64 public constant BEGIN_ARTIFACT_UPLOAD_JOB_ID = 8;
+    uint64 public constant ARTIFACT_CHUNK_JOB_ID = 9;
+    uint64 public constant FINALIZE_ARTIFACT_UPLOAD_JOB_ID = 10;
+    uint64 public constant EXPORT_CIRCUIT_JOB_ID = 11;
+    uint64 public constant SYNC_CIRCUITS_JOB_ID = 12;
 
     // --- Storage ---
 
-    /// @notice Maps circuit ID (bytes32 representation) to its verification key info.
-    mapping(bytes32 => VerificationKeyInfo) public verificationKeys;
+    /// @notice Maps circuit ID and backend name hash to its verification key info.
+    mapping(bytes32 => mapping(bytes32 => VerificationKeyInfo))
+        public verificationKeys;
 
-    /// @notice Information needed to verify proofs for a specific circuit.
+    /// @notice Backend used for a circuit's proofs when the job does not name one:
+    /// the first backend it was registered with.
+    mapping(bytes32 => string) public defaultBackends;
+
+    /// @notice Information needed to verify proofs for a specific circuit and backend.
     struct VerificationKeyInfo {
-        address verifier; // Address of the specific IVerifier contract (e.g., Groth16Verifier)
+        address verifier; // IVerifier contract; zero if the operators deployed none
         bytes verificationKey; // The actual verification key bytes
-        bool exists; // Flag to check if the circuit ID is registered
+        bool exists; // Flag to check if the circuit ID is registered for the backend
+    }
+
+    /// @notice `RegisteredBackend` of a `register_circuit` result.
+    struct RegisteredBackend {
+        string backend;
+        address[] verifierAddress; // Option<VerifierAddress>
+        bytes verificationKey;
+    }
+
+    /// @notice `ProofResult` of a `generate_proof` result.
+    struct ProofResult {
+        bytes proofBytes;
+        bytes[] publicInputs;
+        ProofMetadata[] metadata; // Option<ProofMetadata>
+    }
+
+    /// @notice `ProofMetadata`, attached if the operators enable it.
+    struct ProofMetadata {
+        bytes[] participants;
+        string provingBackend;
+        uint64 provingMs;
+        uint64 generatedAt;
+    }
+
+    /// @notice `ProofPointer` to a proof too large for a job result.
+    struct ProofPointer {
+        bytes32 proofHash;
+        uint64 size;
+        string[] retrievalEndpoint; // Option<String>
+    }
+
+    /// @notice `ProofOutput`: either the proof itself or a pointer to it.
+    struct ProofOutput {
+        ProofResult[] proof;
+        ProofPointer[] pointer;
+    }
+
+    /// @notice `ProofTimings`, returned in benchmark mode.
+    struct ProofTimings {
+        uint64 witnessLoadMs;
+        bool fromCache;
+        bool sessionReused;
+        uint64 configExchangeMs;
+        uint64 mpcEstablishMs;
+        uint64 provingMs;
+        uint64[] verificationMs; // Option<u64>
+        uint64 totalMs;
     }
 
     // --- Events ---
     event CircuitRegistered(
         bytes32 indexed circuitId,
+        string backend,
         address verifier,
         uint256 vkLength
     );
@@ -38,6 +107,12 @@ contract CoSNARKsZkSaaSBlueprintServiceManager is BlueprintServiceManagerBase {
         uint64 indexed jobCallId,
         bool success
     );
+    event ProofStored(
+        bytes32 indexed circuitId,
+        uint64 indexed jobCallId,
+        bytes32 proofHash,
+        uint64 size
+    );
 
     // --- Constructor ---
     // Inherits constructor logic from RootChainEnabled via BlueprintServiceManagerBase
@@ -45,12 +120,8 @@ contract CoSNARKsZkSaaSBlueprintServiceManager is BlueprintServiceManagerBase {
     // --- Lifecycle Hooks ---
 
     /// @inheritdoc IBlueprintServiceManager
-    /// @dev Stores the verification key when a circuit registration job completes successfully.
-    /// The result format needs careful definition between Rust and Solidity.
-    /// Expected result from REGISTER_CIRCUIT_JOB_ID:
-    /// - Field 0: circuitId (bytes32 or string -> bytes32)
-    /// - Field 1: verifierAddress (address) - Address of the deployed IVerifier contract
-    /// - Field 2: verificationKey (bytes)
+    /// @dev Stores the verification keys of registered circuits and verifies
+    /// inline proofs. The other jobs keep no on-chain state.
     function onJobResult(
         uint64 serviceId,
         uint8 job,
@@ -65,83 +136,95 @@ contract CoSNARKsZkSaaSBlueprintServiceManager is BlueprintServiceManagerBase {
         onlyFromMaster // Ensures only the Master MBSM can call this
     {
         if (job == REGISTER_CIRCUIT_JOB_ID) {
-            _handleCircuitRegistrationResult(jobCallId, outputs);
+            _handleCircuitRegistrationResult(outputs);
         } else if (job == GENERATE_PROOF_JOB_ID) {
-            _handleProofGenerationResult(serviceId, jobCallId, outputs);
-        } else {
-            // Handle other potential future job results or ignore
+            _handleProofGenerationResult(jobCallId, inputs, outputs);
         }
     }
 
     // --- Internal Result Handlers ---
 
-    function _handleCircuitRegistrationResult(
-        uint64 jobCallId,
-        bytes calldata resultData
-    ) internal {
-        // Decode the result fields (adjust indices and types as needed)
-        // (bytes memory circuitIdBytes, address verifierAddress, bytes memory vkBytes) =
-        //     Codec.decodeResult(resultData, (Field.Bytes, Field.Address, Field.Bytes));
-
-        (
-            bytes memory circuitIdBytes,
-            address verifierAddress,
-            bytes memory vkBytes
-        ) = abi.decode(resultData, (bytes, address, bytes));
-        bytes32 circuitId = bytes32(circuitIdBytes); // Assuming ID fits in bytes32, otherwise hash
-
-        require(
-            verifierAddress != address(0),
-            "Verifier address cannot be zero"
+    /// @dev Result of REGISTER_CIRCUIT_JOB_ID: `([u8; 32], Vec<RegisteredBackend>)`.
+    function _handleCircuitRegistrationResult(bytes calldata resultData) internal {
+        (bytes32 circuitId, RegisteredBackend[] memory backends) = abi.decode(
+            resultData,
+            (bytes32, RegisteredBackend[])
         );
-        require(vkBytes.length > 0, "Verification key cannot be empty");
-        // Potentially add check: require(!verificationKeys[circuitId].exists, "Circuit already registered");
-
-        verificationKeys[circuitId] = VerificationKeyInfo({
-            verifier: verifierAddress,
-            verificationKey: vkBytes,
-            exists: true
-        });
+        require(backends.length > 0, "No backend registered");
 
-        emit CircuitRegistered(circuitId, verifierAddress, vkBytes.length);
-        // Consider adding jobCallId to the event if useful
+        for (uint256 i = 0; i < backends.length; i++) {
+            RegisteredBackend memory registered = backends[i];
+            require(
+                registered.verificationKey.length > 0,
+                "Verification key cannot be empty"
+            );
+            address verifier = registered.verifierAddress.length > 0
+                ? registered.verifierAddress[0]
+                : address(0);
+            verificationKeys[circuitId][
+                keccak256(bytes(registered.backend))
+            ] = VerificationKeyInfo({
+                verifier: verifier,
+                verificationKey: registered.verificationKey,
+                exists: true
+            });
+            emit CircuitRegistered(
+                circuitId,
+                registered.backend,
+                verifier,
+                registered.verificationKey.length
+            );
+        }
+        defaultBackends[circuitId] = backends[0].backend;
     }
 
+    /// @dev Result of GENERATE_PROOF_JOB_ID: `(ProofOutput, Option<ProofTimings>)`.
+    /// The circuit and backend are the first two job arguments:
+    /// `([u8; 32], Option<ProvingBackend>, ...)`.
     function _handleProofGenerationResult(
-        uint64 serviceId,
         uint64 jobCallId,
+        bytes calldata inputs,
         bytes calldata resultData
     ) internal {
-        // Decode the result fields for GENERATE_PROOF_JOB_ID
-        // Expected result format:
-        // - Field 0: circuitId (bytes32) - ID of the circuit proof was for
-        // - Field 1: proofBytes (bytes) - The actual ZK proof
-        // - Field 2: publicInputs (bytes[]) - Array of public input bytes strings
-        // (
-        //     bytes memory circuitIdBytes,
-        //     bytes memory proofBytes,
-        //     bytes[] memory publicInputBytes
-        // ) = Codec.decodeResult(
-        //         resultData,
-        //         (Field.Bytes, Field.Bytes, Field.BytesArray)
-        //     );
-
-        (
-            bytes memory circuitIdBytes,
-            bytes memory proofBytes,
-            bytes[] memory publicInputBytes
-        ) = abi.decode(resultData, (bytes, bytes, bytes[]));
-        bytes32 circuitId = bytes32(circuitIdBytes);
-
-        VerificationKeyInfo storage vkInfo = verificationKeys[circuitId];
+        (bytes32 circuitId, string[] memory requestedBackend) = abi.decode(
+            inputs,
+            (bytes32, string[])
+        );
+        (ProofOutput memory output, ) = abi.decode(
+            resultData,
+            (ProofOutput, ProofTimings[])
+        );
+
+        if (output.pointer.length > 0) {
+            // Too large to return inline; clients fetch it from the operators
+            emit ProofStored(
+                circuitId,
+                jobCallId,
+                output.pointer[0].proofHash,
+                output.pointer[0].size
+            );
+            return;
+        }
+        require(output.proof.length > 0, "Empty proof output");
+
+        string memory backend = requestedBackend.length > 0
+            ? requestedBackend[0]
+            : defaultBackends[circuitId];
+        VerificationKeyInfo storage vkInfo = verificationKeys[circuitId][
+            keccak256(bytes(backend))
+        ];
         require(vkInfo.exists, "Circuit not registered");
+        if (vkInfo.verifier == address(0)) {
+            // No verifier deployed for this backend; nothing to check on-chain
+            return;
+        }
 
         // Call the appropriate verifier
         IVerifier verifier = IVerifier(vkInfo.verifier);
         bool success = verifier.verifyProof(
             vkInfo.verificationKey,
-            proofBytes,
-            publicInputBytes
+            output.proof[0].proofBytes,
+            output.proof[0].publicInputs
         );
 
         emit ProofVerified(circuitId, jobCallId, success);
@@ -156,28 +239,23 @@ contract CoSNARKsZkSaaSBlueprintServiceManager is BlueprintServiceManagerBase {
     // --- Other Hooks (Optional Overrides) ---
 
     /// @inheritdoc IBlueprintServiceManager
-    /// @dev Example: could enforce specific inputs for job calls if needed.
+    /// @dev Rejects calls to job IDs the blueprint does not define.
     function onJobCall(
         uint64 serviceId,
         uint8 job,
         uint64 jobCallId,
         bytes calldata inputs
     ) external payable override onlyFromMaster {
-        // Optional: Add validation logic for inputs based on the job ID
-        if (job == REGISTER_CIRCUIT_JOB_ID) {
-            // Validate register inputs
-        } else if (job == GENERATE_PROOF_JOB_ID) {
-            // Validate proof generation inputs
-        }
-        // ... rest of logic or simply rely on base implementation (if any)
+        require(job <= SYNC_CIRCUITS_JOB_ID, "Unknown job");
     }
 
     // --- View Functions (Optional) ---
 
-    /// @notice Get the verification key info for a circuit.
+    /// @notice Get the verification key info for a circuit and backend (e.g. "groth16").
     function getVerificationKeyInfo(
-        bytes32 circuitId
+        bytes32 circuitId,
+        string calldata backend
     ) external view returns (VerificationKeyInfo memory) {
-        return verificationKeys[circuitId];
+        return verificationKeys[circuitId][keccak256(bytes(backend))];
     }
 }
@@ -10,22 +10,19 @@ use blueprint_sdk::runner::tangle::config::TangleConfig;
 use blueprint_sdk::tangle::consumer::TangleConsumer;
 use blueprint_sdk::tangle::layers::TangleLayer;
 use blueprint_sdk::tangle::producer::TangleProducer;
+use color_eyre::Result;
 use color_eyre::eyre;
-use color_eyre::{Result, eyre::Context};
 use cosnarks_zksaas_blueprint_lib::context::CosnarksContext;
 use cosnarks_zksaas_blueprint_lib::jobs::{
-    GENERATE_PROOF_JOB_ID, REGISTER_CIRCUIT_JOB_ID, generate_proof_job, register_circuit,
+    ARTIFACT_CHUNK_JOB_ID, BEGIN_ARTIFACT_UPLOAD_JOB_ID, CANCEL_PROOF_JOB_ID, CLONE_CIRCUIT_JOB_ID,
+    EXPORT_CIRCUIT_JOB_ID, FINALIZE_ARTIFACT_UPLOAD_JOB_ID, FORCE_CLOSE_SESSION_JOB_ID,
+    GENERATE_PROOF_JOB_ID, GET_PROOF_JOB_ID, IMPORT_REGISTRY_JOB_ID, LIST_SESSIONS_JOB_ID,
+    REGISTER_CIRCUIT_JOB_ID, SYNC_CIRCUITS_JOB_ID, artifact_chunk, begin_artifact_upload,
+    cancel_proof, clone_circuit, export_circuit, finalize_artifact_upload, force_close_session,
+    generate_proof_job, get_proof, import_registry, list_sessions, register_circuit, sync_circuits,
 };
-use std::net::SocketAddr;
-use std::path::{Path, PathBuf};
 use tracing::level_filters::LevelFilter;
 
-// Define default paths relative to the config/data directory
-const MPC_CERT_FILENAME: &str = "mpc_cert.der";
-const MPC_KEY_FILENAME: &str = "mpc_key.der";
-// Default base port for MPC net binding
-const DEFAULT_MPC_BASE_PORT: u16 = 10000;
-
 #[tokio::main]
 async fn main() -> Result<()> {
     // Initialize logging and error handling
@@ -46,40 +43,44 @@ async fn main() -> Result<()> {
         .map_err(|e| eyre::eyre!("Failed to get secret for signer key: {}", e))?;
     let signer = TanglePairSigner::new(secret_pair.0);
 
-    // Determine MPC Net paths and generate cert/key if needed
-    let mpc_net_dir = env
-        .data_dir
-        .clone()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join("mpc_net");
-    std::fs::create_dir_all(&mpc_net_dir).context("Creating mpc_net directory")?;
-    let cert_path = mpc_net_dir.join(MPC_CERT_FILENAME);
-    let key_path = mpc_net_dir.join(MPC_KEY_FILENAME);
-    let base_bind_addr_str = "0.0.0.0";
-    let base_port = DEFAULT_MPC_BASE_PORT;
-
-    let base_bind_addr: SocketAddr = format!("{}:{}", base_bind_addr_str, base_port)
-        .parse()
-        .context("Parsing base MPC bind address")?;
-
-    // Generate cert/key only if they don't exist
-    if !cert_path.exists() || !key_path.exists() {
-        generate_mpc_cert(&cert_path, &key_path)?;
-    }
-
     // Initialize Tangle client, producer, and consumer
     let client = env.tangle_client().await?;
     let producer = TangleProducer::finalized_blocks(client.rpc_client.clone()).await?;
     let consumer = TangleConsumer::new(client.rpc_client.clone(), signer);
 
-    // Initialize the custom context
+    // Initialize the custom context (generates the MPC-Net cert/key if missing)
     let context = CosnarksContext::<SpEcdsa>::new(env.clone().into()).await?;
+    let shutdown_token = context.cancellation_token().clone();
+    // Surface a misconfigured keystore now rather than on the first proof job
+    if let Err(e) = context.verify_membership().await {
+        tracing::warn!(error = %e, "Could not verify operator set membership");
+    }
 
     // Configure the router, mapping job IDs to handlers
     let router = Router::new()
         // Apply TangleLayer to enforce standard Tangle job context requirements
         .route(REGISTER_CIRCUIT_JOB_ID, register_circuit.layer(TangleLayer))
         .route(GENERATE_PROOF_JOB_ID, generate_proof_job.layer(TangleLayer))
+        .route(IMPORT_REGISTRY_JOB_ID, import_registry.layer(TangleLayer))
+        .route(LIST_SESSIONS_JOB_ID, list_sessions.layer(TangleLayer))
+        .route(CLONE_CIRCUIT_JOB_ID, clone_circuit.layer(TangleLayer))
+        .route(
+            FORCE_CLOSE_SESSION_JOB_ID,
+            force_close_session.layer(TangleLayer),
+        )
+        .route(GET_PROOF_JOB_ID, get_proof.layer(TangleLayer))
+        .route(CANCEL_PROOF_JOB_ID, cancel_proof.layer(TangleLayer))
+        .route(
+            BEGIN_ARTIFACT_UPLOAD_JOB_ID,
+            begin_artifact_upload.layer(TangleLayer),
+        )
+        .route(ARTIFACT_CHUNK_JOB_ID, artifact_chunk.layer(TangleLayer))
+        .route(
+            FINALIZE_ARTIFACT_UPLOAD_JOB_ID,
+            finalize_artifact_upload.layer(TangleLayer),
+        )
+        .route(EXPORT_CIRCUIT_JOB_ID, export_circuit.layer(TangleLayer))
+        .route(SYNC_CIRCUITS_JOB_ID, sync_circuits.layer(TangleLayer))
         .with_context(context); // Pass the shared context to all routes
 
     // Build and run the Blueprint
@@ -88,30 +89,14 @@ async fn main() -> Result<()> {
         .router(router)
         .producer(producer)
         .consumer(consumer)
-        // Add .background_service or .with_shutdown_handler if needed later
+        // Cancel in-flight jobs (e.g. artifact downloads) on shutdown
+        .with_shutdown_handler(async move { shutdown_token.cancel() })
         .run()
         .await?;
 
     Ok(())
 }
 
-/// Generates a self-signed certificate and private key for MPC-Net TLS.
-fn generate_mpc_cert(cert_path: &Path, key_path: &Path) -> Result<()> {
-    tracing::info!(cert_path = %cert_path.display(), key_path = %key_path.display(), "Generating self-signed MPC certificate and key...");
-    // Use common names relevant to the service, or just localhost for simple cases
-    // SANS are important for TLS verification
-    let sans = vec!["localhost".to_string()];
-    let cert = rcgen::generate_simple_self_signed(sans).context("generating self-signed cert")?;
-    let key = cert.get_key_pair().serialize_der();
-    std::fs::write(key_path, key).context("writing key file")?;
-    let cert_pem = cert.serialize_pem()?; // Save PEM for easier inspection if needed
-    std::fs::write(cert_path.with_extension("pem"), cert_pem).context("writing cert PEM file")?;
-    let cert_der = cert.serialize_der()?; // Save DER as expected by mpc-net
-    std::fs::write(cert_path, cert_der).context("writing certificate DER file")?;
-    tracing::info!("MPC certificate and key generated successfully.");
-    Ok(())
-}
-
 pub fn setup_log() {
     use tracing_subscriber::util::SubscriberInitExt;
 
@@ -0,0 +1,569 @@
+//! Lightweight parsing of circuit artifacts, used to validate them at registration.
+
+use crate::error::{Error, Result};
+use crate::noir::parse_program_abi;
+use crate::types::{CircuitType, ProvingBackend};
+use std::io::Read;
+use std::path::Path;
+
+const R1CS_MAGIC: &[u8; 4] = b"r1cs";
+const R1CS_HEADER_SECTION: u32 = 1;
+const GZIP_MAGIC: &[u8; 2] = &[0x1f, 0x8b];
+const ZKEY_MAGIC: &[u8; 4] = b"zkey";
+const ZKEY_HEADER_SECTION: u32 = 1;
+const ZKEY_PROTOCOL_HEADER_SECTION: u32 = 2;
+const ZKEY_PROTOCOL_GROTH16: u32 = 1;
+const ZKEY_PROTOCOL_PLONK: u32 = 2;
+const PTAU_MAGIC: &[u8; 4] = b"ptau";
+const PTAU_HEADER_SECTION: u32 = 1;
+/// Largest ptau power with a representable domain size
+const MAX_PTAU_POWER: u32 = 63;
+
+/// Bytes read from the start of a `.ptau` file to find its header section,
+/// which snarkjs writes first; the files themselves run to gigabytes.
+pub const PTAU_HEADER_READ_LIMIT: u64 = 4 * 1024;
+
+/// Scalar field moduli supported by the co-circom backends, big-endian hex.
+const SUPPORTED_R1CS_PRIMES: &[(&str, &str)] = &[
+    (
+        "bn254",
+        "30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001",
+    ),
+    (
+        "bls12-381",
+        "73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001",
+    ),
+];
+
+/// Header section of a Circom `.r1cs` file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct R1csHeader {
+    pub version: u32,
+    /// Size of a field element in bytes
+    pub field_size: u32,
+    /// Field prime, little-endian
+    pub prime: Vec<u8>,
+    pub num_wires: u32,
+    pub num_pub_out: u32,
+    pub num_pub_in: u32,
+    pub num_prv_in: u32,
+    pub num_labels: u64,
+    pub num_constraints: u32,
+}
+
+impl R1csHeader {
+    /// Number of public signals (outputs followed by public inputs).
+    #[must_use]
+    pub fn num_public_inputs(&self) -> u32 {
+        self.num_pub_out.saturating_add(self.num_pub_in)
+    }
+}
+
+/// Header of a snarkjs `.zkey` proving key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZkeyHeader {
+    /// snarkjs protocol ID (1 = Groth16, 2 = Plonk)
+    pub protocol: u32,
+    pub num_vars: u32,
+    pub num_public: u32,
+    /// Size of the evaluation domain, a power of two covering all constraints
+    pub domain_size: u32,
+}
+
+/// Header of a snarkjs powers-of-tau (`.ptau`) file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PtauHeader {
+    /// The file holds powers for evaluation domains of up to `2^power` points
+    pub power: u32,
+}
+
+impl PtauHeader {
+    /// Largest evaluation domain a setup from this file can use.
+    #[must_use]
+    pub fn max_domain_size(&self) -> u64 {
+        1u64 << self.power
+    }
+}
+
+/// Parses the header section of an `.r1cs` artifact.
+pub fn parse_r1cs_header(data: &[u8]) -> Result<R1csHeader> {
+    let (version, section) = find_section(data, R1CS_MAGIC, "r1cs", R1CS_HEADER_SECTION)?;
+    let mut header = ByteReader::new(section);
+    let field_size = header.read_u32()?;
+    let prime = header.take(field_size as usize)?.to_vec();
+    Ok(R1csHeader {
+        version,
+        field_size,
+        prime,
+        num_wires: header.read_u32()?,
+        num_pub_out: header.read_u32()?,
+        num_pub_in: header.read_u32()?,
+        num_prv_in: header.read_u32()?,
+        num_labels: header.read_u64()?,
+        num_constraints: header.read_u32()?,
+    })
+}
+
+/// Parses the protocol and circuit dimensions from a snarkjs `.zkey` proving key.
+pub fn parse_zkey_header(data: &[u8]) -> Result<ZkeyHeader> {
+    let (_, section) = find_section(data, ZKEY_MAGIC, "zkey", ZKEY_HEADER_SECTION)?;
+    let protocol = ByteReader::new(section).read_u32()?;
+
+    // Groth16 and Plonk protocol headers share the same leading fields
+    let (_, section) = find_section(data, ZKEY_MAGIC, "zkey", ZKEY_PROTOCOL_HEADER_SECTION)?;
+    let mut header = ByteReader::new(section);
+    let n8q = header.read_u32()?;
+    header.take(n8q as usize)?;
+    let n8r = header.read_u32()?;
+    header.take(n8r as usize)?;
+    Ok(ZkeyHeader {
+        protocol,
+        num_vars: header.read_u32()?,
+        num_public: header.read_u32()?,
+        domain_size: header.read_u32()?,
+    })
+}
+
+/// Parses the header section of a `.ptau` file.
+pub fn parse_ptau_header(data: &[u8]) -> Result<PtauHeader> {
+    let (_, section) = find_section(data, PTAU_MAGIC, "ptau", PTAU_HEADER_SECTION)?;
+    let mut header = ByteReader::new(section);
+    let n8 = header.read_u32()?;
+    header.take(n8 as usize)?;
+    let power = header.read_u32()?;
+    if power > MAX_PTAU_POWER {
+        return Err(Error::ArtifactParseError(format!(
+            "ptau power {} is out of range",
+            power
+        )));
+    }
+    Ok(PtauHeader { power })
+}
+
+/// Reads the header of the `.ptau` file at `path` without loading the file.
+pub fn read_ptau_header(path: &Path) -> Result<PtauHeader> {
+    let mut prefix = Vec::new();
+    std::fs::File::open(path)?
+        .take(PTAU_HEADER_READ_LIMIT)
+        .read_to_end(&mut prefix)?;
+    parse_ptau_header(&prefix)
+}
+
+/// Checks that a Groth16 setup for the circuit in `r1cs` fits the powers of tau in `ptau`.
+///
+/// Like snarkjs, the setup needs a domain covering every constraint plus one
+/// per public signal and one for the constant wire. Checking up front fails in
+/// milliseconds instead of partway through phase-2 setup.
+pub fn check_ptau_capacity(r1cs: &R1csHeader, ptau: &PtauHeader) -> Result<()> {
+    let required = u64::from(r1cs.num_constraints) + u64::from(r1cs.num_public_inputs()) + 1;
+    let supported = ptau.max_domain_size();
+    if required > supported {
+        return Err(Error::IncompatibleBackend(format!(
+            "circuit too large for ptau: {} > {}",
+            required, supported
+        )));
+    }
+    Ok(())
+}
+
+/// Checks that externally generated keys belong to the circuit in `artifact`.
+///
+/// Only Circom keys in snarkjs format can be validated: the `.zkey` proving key
+/// must be for `backend` and match the R1CS wire count, public signal count and
+/// constraint count, and the JSON verification key must agree on protocol and
+/// public signal count.
+pub fn validate_pregenerated_keys(
+    circuit_type: &CircuitType,
+    backend: &ProvingBackend,
+    artifact: &[u8],
+    proving_key: &[u8],
+    verification_key: &[u8],
+) -> Result<()> {
+    let expected_protocol = match (circuit_type, backend) {
+        (CircuitType::Circom, ProvingBackend::Groth16) => ZKEY_PROTOCOL_GROTH16,
+        (CircuitType::Circom, ProvingBackend::Plonk) => ZKEY_PROTOCOL_PLONK,
+        _ => {
+            return Err(Error::IncompatibleBackend(format!(
+                "Pre-generated keys are not supported for {:?}/{:?} circuits",
+                circuit_type, backend
+            )));
+        }
+    };
+    let r1cs = parse_r1cs_header(artifact)?;
+    let zkey = parse_zkey_header(proving_key)?;
+
+    if zkey.protocol != expected_protocol {
+        return Err(Error::ArtifactParseError(format!(
+            "Proving key is for snarkjs protocol {}, not {:?}",
+            zkey.protocol, backend
+        )));
+    }
+    if zkey.num_vars != r1cs.num_wires
+        || zkey.num_public != r1cs.num_public_inputs()
+        || zkey.domain_size < r1cs.num_constraints
+    {
+        return Err(Error::ArtifactParseError(format!(
+            "Proving key (vars {}, public {}, domain {}) does not match the circuit (wires {}, public {}, constraints {})",
+            zkey.num_vars,
+            zkey.num_public,
+            zkey.domain_size,
+            r1cs.num_wires,
+            r1cs.num_public_inputs(),
+            r1cs.num_constraints
+        )));
+    }
+
+    let vk: serde_json::Value = serde_json::from_slice(verification_key).map_err(|e| {
+        Error::ArtifactParseError(format!("Verification key is not valid JSON: {}", e))
+    })?;
+    let vk_protocol = vk.get("protocol").and_then(|p| p.as_str());
+    let vk_public = vk.get("nPublic").and_then(|n| n.as_u64());
+    if vk_protocol != Some(backend.name()) || vk_public != Some(u64::from(zkey.num_public)) {
+        return Err(Error::ArtifactParseError(format!(
+            "Verification key (protocol {:?}, nPublic {:?}) does not match the proving key",
+            vk_protocol, vk_public
+        )));
+    }
+    Ok(())
+}
+
+/// Returns the format version and the first section of `section_type` in an
+/// iden3 binary file (`.r1cs`, `.zkey`, ...) starting with `magic`.
+fn find_section<'a>(
+    data: &'a [u8],
+    magic: &[u8; 4],
+    format: &str,
+    section_type: u32,
+) -> Result<(u32, &'a [u8])> {
+    let mut reader = ByteReader::new(data);
+    if reader.take(4)? != magic {
+        return Err(Error::ArtifactParseError(format!(
+            "Missing {} magic bytes",
+            format
+        )));
+    }
+    let version = reader.read_u32()?;
+    let num_sections = reader.read_u32()?;
+
+    for _ in 0..num_sections {
+        let current_type = reader.read_u32()?;
+        let section_size = reader.read_u64()?;
+        let section = reader.take(usize::try_from(section_size).map_err(|_| {
+            Error::ArtifactParseError(format!("Section size {} too large", section_size))
+        })?)?;
+        if current_type == section_type {
+            return Ok((version, section));
+        }
+    }
+
+    Err(Error::ArtifactParseError(format!(
+        "{} file has no section of type {}",
+        format, section_type
+    )))
+}
+
+/// Cheaply checks that `data` looks like an artifact of `circuit_type` usable by `backend`.
+///
+/// Run before key generation so a misdeclared or corrupt artifact is rejected
+/// without paying for setup. Circom artifacts must carry a parseable R1CS header
+/// over a supported field; Noir artifacts must be gzipped ACIR bytecode or a
+/// compiled program JSON with a `bytecode` field.
+pub fn validate_artifact_header(
+    data: &[u8],
+    circuit_type: &CircuitType,
+    backend: &ProvingBackend,
+) -> Result<()> {
+    match (circuit_type, backend) {
+        (CircuitType::Circom, ProvingBackend::Groth16 | ProvingBackend::Plonk) => {
+            let header = parse_r1cs_header(data)?;
+            let prime_hex = hex::encode(header.prime.iter().rev().copied().collect::<Vec<u8>>());
+            if !SUPPORTED_R1CS_PRIMES
+                .iter()
+                .any(|(_, supported)| *supported == prime_hex)
+            {
+                return Err(Error::ArtifactParseError(format!(
+                    "r1cs field prime 0x{} is not supported by {:?}",
+                    prime_hex, backend
+                )));
+            }
+            Ok(())
+        }
+        (CircuitType::Noir, ProvingBackend::UltraHonk) => {
+            if data.starts_with(GZIP_MAGIC) || is_noir_program_json(data) {
+                Ok(())
+            } else {
+                Err(Error::ArtifactParseError(
+                    "Artifact is neither gzipped ACIR nor a compiled Noir program".to_string(),
+                ))
+            }
+        }
+        _ => Err(Error::ArtifactParseError(format!(
+            "{:?} artifacts cannot be proven with {:?}",
+            circuit_type, backend
+        ))),
+    }
+}
+
+/// Returns whether `data` is a compiled Noir program (JSON object with `bytecode`).
+fn is_noir_program_json(data: &[u8]) -> bool {
+    serde_json::from_slice::<serde_json::Value>(data)
+        .map(|value| value.get("bytecode").is_some_and(|b| b.is_string()))
+        .unwrap_or(false)
+}
+
+/// Returns the number of public inputs declared by an artifact, if the format exposes it.
+///
+/// Circom R1CS headers carry the count directly. Compiled Noir programs are
+/// counted from their ABI; raw ACIR is not parsed yet and yields `None`.
+pub fn count_public_inputs(circuit_type: &CircuitType, data: &[u8]) -> Result<Option<u32>> {
+    match circuit_type {
+        CircuitType::Circom => Ok(Some(parse_r1cs_header(data)?.num_public_inputs())),
+        CircuitType::Noir => Ok(parse_program_abi(data)?.map(|abi| abi.num_public_inputs())),
+    }
+}
+
+/// Minimal little-endian cursor over a byte slice.
+struct ByteReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| {
+                Error::ArtifactParseError(format!(
+                    "Unexpected end of artifact at offset {} (wanted {} bytes)",
+                    self.pos, len
+                ))
+            })?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn read_u32(&mut self) -> Result<u32> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes(bytes.try_into().expect("4 bytes")))
+    }
+
+    fn read_u64(&mut self) -> Result<u64> {
+        let bytes = self.take(8)?;
+        Ok(u64::from_le_bytes(bytes.try_into().expect("8 bytes")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn r1cs_with_header(num_pub_out: u32, num_pub_in: u32) -> Vec<u8> {
+        let mut bn254_prime = hex::decode(SUPPORTED_R1CS_PRIMES[0].1).unwrap();
+        bn254_prime.reverse();
+
+        let mut header = Vec::new();
+        header.extend_from_slice(&32u32.to_le_bytes());
+        header.extend_from_slice(&bn254_prime);
+        for value in [10u32, num_pub_out, num_pub_in, 3] {
+            header.extend_from_slice(&value.to_le_bytes());
+        }
+        header.extend_from_slice(&11u64.to_le_bytes());
+        header.extend_from_slice(&7u32.to_le_bytes());
+
+        let mut data = Vec::new();
+        data.extend_from_slice(R1CS_MAGIC);
+        data.extend_from_slice(&1u32.to_le_bytes());
+        data.extend_from_slice(&2u32.to_le_bytes());
+        // An unrelated section before the header must be skipped
+        data.extend_from_slice(&2u32.to_le_bytes());
+        data.extend_from_slice(&3u64.to_le_bytes());
+        data.extend_from_slice(&[1, 2, 3]);
+        data.extend_from_slice(&R1CS_HEADER_SECTION.to_le_bytes());
+        data.extend_from_slice(&(header.len() as u64).to_le_bytes());
+        data.extend_from_slice(&header);
+        data
+    }
+
+    fn zkey_with_header(
+        protocol: u32,
+        num_vars: u32,
+        num_public: u32,
+        domain_size: u32,
+    ) -> Vec<u8> {
+        let mut protocol_header = Vec::new();
+        for _ in 0..2 {
+            // n8q/q, then n8r/r
+            protocol_header.extend_from_slice(&32u32.to_le_bytes());
+            protocol_header.extend_from_slice(&[0u8; 32]);
+        }
+        for value in [num_vars, num_public, domain_size] {
+            protocol_header.extend_from_slice(&value.to_le_bytes());
+        }
+
+        let mut data = Vec::new();
+        data.extend_from_slice(ZKEY_MAGIC);
+        data.extend_from_slice(&1u32.to_le_bytes());
+        data.extend_from_slice(&2u32.to_le_bytes());
+        data.extend_from_slice(&ZKEY_HEADER_SECTION.to_le_bytes());
+        data.extend_from_slice(&4u64.to_le_bytes());
+        data.extend_from_slice(&protocol.to_le_bytes());
+        data.extend_from_slice(&ZKEY_PROTOCOL_HEADER_SECTION.to_le_bytes());
+        data.extend_from_slice(&(protocol_header.len() as u64).to_le_bytes());
+        data.extend_from_slice(&protocol_header);
+        data
+    }
+
+    fn ptau_with_power(power: u32) -> Vec<u8> {
+        let mut header = Vec::new();
+        header.extend_from_slice(&32u32.to_le_bytes());
+        header.extend_from_slice(&[0u8; 32]);
+        header.extend_from_slice(&power.to_le_bytes());
+        header.extend_from_slice(&power.to_le_bytes()); // ceremony power
+
+        let mut data = Vec::new();
+        data.extend_from_slice(PTAU_MAGIC);
+        data.extend_from_slice(&1u32.to_le_bytes());
+        data.extend_from_slice(&2u32.to_le_bytes());
+        data.extend_from_slice(&PTAU_HEADER_SECTION.to_le_bytes());
+        data.extend_from_slice(&(header.len() as u64).to_le_bytes());
+        data.extend_from_slice(&header);
+        // The points section follows; only its declared size matters here
+        data.extend_from_slice(&2u32.to_le_bytes());
+        data.extend_from_slice(&u64::MAX.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn parses_r1cs_header() {
+        let header = parse_r1cs_header(&r1cs_with_header(1, 2)).unwrap();
+        assert_eq!(header.version, 1);
+        assert_eq!(header.field_size, 32);
+        assert_eq!(header.num_wires, 10);
+        assert_eq!(header.num_public_inputs(), 3);
+        assert_eq!(header.num_prv_in, 3);
+        assert_eq!(header.num_labels, 11);
+        assert_eq!(header.num_constraints, 7);
+    }
+
+    #[test]
+    fn rejects_truncated_r1cs() {
+        let data = r1cs_with_header(1, 2);
+        assert!(matches!(
+            parse_r1cs_header(&data[..data.len() - 1]),
+            Err(Error::ArtifactParseError(_))
+        ));
+        assert!(matches!(
+            parse_r1cs_header(b"not an r1cs file"),
+            Err(Error::ArtifactParseError(_))
+        ));
+    }
+
+    #[test]
+    fn validates_artifact_header_against_declaration() {
+        let r1cs = r1cs_with_header(1, 2);
+        let noir = br#"{"noir_version":"1.0.0","bytecode":"H4sIAAAAAAAA/","abi":{}}"#;
+
+        validate_artifact_header(&r1cs, &CircuitType::Circom, &ProvingBackend::Groth16).unwrap();
+        validate_artifact_header(noir, &CircuitType::Noir, &ProvingBackend::UltraHonk).unwrap();
+        validate_artifact_header(
+            &[0x1f, 0x8b, 0x08, 0x00],
+            &CircuitType::Noir,
+            &ProvingBackend::UltraHonk,
+        )
+        .unwrap();
+
+        for (data, circuit_type, backend) in [
+            (&noir[..], CircuitType::Circom, ProvingBackend::Plonk),
+            (&r1cs[..], CircuitType::Noir, ProvingBackend::UltraHonk),
+            (&r1cs[..], CircuitType::Circom, ProvingBackend::UltraHonk),
+        ] {
+            assert!(matches!(
+                validate_artifact_header(data, &circuit_type, &backend),
+                Err(Error::ArtifactParseError(_))
+            ));
+        }
+
+        // Same layout over an unsupported field
+        let mut foreign_field = r1cs.clone();
+        let prime_offset = foreign_field.len() - 60; // header section ends the file
+        foreign_field[prime_offset] ^= 0xFF;
+        assert!(matches!(
+            validate_artifact_header(
+                &foreign_field,
+                &CircuitType::Circom,
+                &ProvingBackend::Groth16
+            ),
+            Err(Error::ArtifactParseError(_))
+        ));
+    }
+
+    #[test]
+    fn validates_pregenerated_keys_against_r1cs() {
+        // r1cs_with_header: 10 wires, 7 constraints
+        let r1cs = r1cs_with_header(1, 2);
+        let pk = zkey_with_header(ZKEY_PROTOCOL_GROTH16, 10, 3, 8);
+        let vk = br#"{"protocol":"groth16","curve":"bn128","nPublic":3}"#;
+        let (circom, groth16) = (CircuitType::Circom, ProvingBackend::Groth16);
+
+        validate_pregenerated_keys(&circom, &groth16, &r1cs, &pk, vk).unwrap();
+
+        for bad_pk in [
+            zkey_with_header(ZKEY_PROTOCOL_PLONK, 10, 3, 8),
+            zkey_with_header(ZKEY_PROTOCOL_GROTH16, 11, 3, 8),
+            zkey_with_header(ZKEY_PROTOCOL_GROTH16, 10, 2, 8),
+            zkey_with_header(ZKEY_PROTOCOL_GROTH16, 10, 3, 4),
+        ] {
+            assert!(matches!(
+                validate_pregenerated_keys(&circom, &groth16, &r1cs, &bad_pk, vk),
+                Err(Error::ArtifactParseError(_))
+            ));
+        }
+        let plonk_vk = br#"{"protocol":"plonk","nPublic":3}"#;
+        assert!(matches!(
+            validate_pregenerated_keys(&circom, &groth16, &r1cs, &pk, plonk_vk),
+            Err(Error::ArtifactParseError(_))
+        ));
+        assert!(matches!(
+            validate_pregenerated_keys(
+                &CircuitType::Noir,
+                &ProvingBackend::UltraHonk,
+                &r1cs,
+                &pk,
+                vk
+            ),
+            Err(Error::IncompatibleBackend(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_circuits_too_large_for_the_ptau() {
+        // r1cs_with_header: 7 constraints and 3 public signals, needing a domain of 11
+        let r1cs = parse_r1cs_header(&r1cs_with_header(1, 2)).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pot.ptau");
+        std::fs::write(&path, ptau_with_power(4)).unwrap();
+        let ptau = read_ptau_header(&path).unwrap();
+        assert_eq!(ptau.max_domain_size(), 16);
+        check_ptau_capacity(&r1cs, &ptau).unwrap();
+
+        let small = parse_ptau_header(&ptau_with_power(3)).unwrap();
+        match check_ptau_capacity(&r1cs, &small) {
+            Err(Error::IncompatibleBackend(message)) => {
+                assert_eq!(message, "circuit too large for ptau: 11 > 8");
+            }
+            other => panic!("expected IncompatibleBackend, got {:?}", other),
+        }
+        assert!(matches!(
+            parse_ptau_header(&ptau_with_power(64)),
+            Err(Error::ArtifactParseError(_))
+        ));
+    }
+}
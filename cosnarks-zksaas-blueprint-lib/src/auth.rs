@@ -0,0 +1,58 @@
+//! Access policy for proof requests.
+
+use crate::types::CircuitId;
+use std::collections::HashSet;
+
+/// Decides whether a caller may request proofs for a circuit.
+///
+/// Checked by `generate_proof_job` before any work is done; a rejected request
+/// fails with `Error::Unauthorized`. Install a custom policy with
+/// `CosnarksContext::with_proof_authorizer`.
+pub trait ProofAuthorizer: Send + Sync {
+    /// Returns whether the account `caller` may prove `circuit_id`.
+    fn authorize(&self, caller: &[u8; 32], circuit_id: &CircuitId) -> bool;
+}
+
+/// Lets every caller prove every circuit. The default policy.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AllowAll;
+
+impl ProofAuthorizer for AllowAll {
+    fn authorize(&self, _caller: &[u8; 32], _circuit_id: &CircuitId) -> bool {
+        true
+    }
+}
+
+/// Lets only the listed accounts prove, for any circuit.
+#[derive(Debug, Clone, Default)]
+pub struct CallerAllowList {
+    callers: HashSet<[u8; 32]>,
+}
+
+impl CallerAllowList {
+    pub fn new(callers: impl IntoIterator<Item = [u8; 32]>) -> Self {
+        Self {
+            callers: callers.into_iter().collect(),
+        }
+    }
+}
+
+impl ProofAuthorizer for CallerAllowList {
+    fn authorize(&self, caller: &[u8; 32], _circuit_id: &CircuitId) -> bool {
+        self.callers.contains(caller)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allow_list_only_admits_listed_callers() {
+        let policy = CallerAllowList::new([[1u8; 32], [2u8; 32]]);
+        assert!(policy.authorize(&[1u8; 32], &[0xAA; 32]));
+        assert!(policy.authorize(&[2u8; 32], &[0xBB; 32]));
+        assert!(!policy.authorize(&[3u8; 32], &[0xAA; 32]));
+        assert!(AllowAll.authorize(&[3u8; 32], &[0xAA; 32]));
+    }
+}
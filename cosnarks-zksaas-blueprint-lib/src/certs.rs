@@ -0,0 +1,176 @@
+//! Self-signed TLS material for MPC-Net, and the optional operator CA peer
+//! certificates are verified against.
+
+use crate::error::{Error, Result};
+use crate::redact::Redacted;
+use rustls::RootCertStore;
+use rustls::server::WebPkiClientVerifier;
+use rustls::server::danger::ClientCertVerifier;
+use rustls_pki_types::{CertificateDer, UnixTime};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Largest certificate file read when fingerprinting a certificate.
+pub const MAX_CERT_SIZE: u64 = 64 * 1024;
+/// Subdirectory of the certificate directory peer certificates are stored in.
+pub const PEER_CERT_DIR: &str = "peers";
+
+/// Generates the MPC-Net certificate and key if either file is missing.
+///
+/// Returns whether new material was written.
+pub fn ensure_mpc_cert(cert_path: &Path, key_path: &Path) -> Result<bool> {
+    if cert_path.is_file() && key_path.is_file() {
+        return Ok(false);
+    }
+    generate_mpc_cert(cert_path, key_path)?;
+    Ok(true)
+}
+
+/// Generates a self-signed certificate and private key for MPC-Net TLS.
+///
+/// The certificate is written in DER (as expected by mpc-net) and, for easier
+/// inspection, PEM next to it.
+pub fn generate_mpc_cert(cert_path: &Path, key_path: &Path) -> Result<()> {
+    tracing::info!(cert_path = %Redacted(cert_path), key_path = %Redacted(key_path), "Generating self-signed MPC certificate and key...");
+    for parent in [cert_path.parent(), key_path.parent()]
+        .into_iter()
+        .flatten()
+    {
+        fs::create_dir_all(parent)?;
+    }
+
+    // SANs are important for TLS verification
+    let sans = vec!["localhost".to_string()];
+    let cert = rcgen::generate_simple_self_signed(sans).map_err(cert_error)?;
+    fs::write(key_path, cert.get_key_pair().serialize_der())?;
+    fs::write(
+        cert_path.with_extension("pem"),
+        cert.serialize_pem().map_err(cert_error)?,
+    )?;
+    fs::write(cert_path, cert.serialize_der().map_err(cert_error)?)?;
+    tracing::info!("MPC certificate and key generated successfully.");
+    Ok(())
+}
+
+/// SHA-256 fingerprint of a DER-encoded certificate.
+#[must_use]
+pub fn cert_fingerprint(cert_der: &[u8]) -> [u8; 32] {
+    Sha256::digest(cert_der).into()
+}
+
+/// Reads the certificate at `cert_path` and returns its fingerprint.
+///
+/// Files larger than `MAX_CERT_SIZE` are rejected without being read in full,
+/// since the path may have been supplied by a peer.
+pub fn read_cert_fingerprint(cert_path: &Path) -> Result<[u8; 32]> {
+    Ok(cert_fingerprint(&read_cert(cert_path)?))
+}
+
+/// Reads the DER-encoded certificate at `cert_path`, rejecting files larger
+/// than `MAX_CERT_SIZE`.
+pub fn read_cert(cert_path: &Path) -> Result<Vec<u8>> {
+    let mut cert_der = Vec::new();
+    fs::File::open(cert_path)?
+        .take(MAX_CERT_SIZE + 1)
+        .read_to_end(&mut cert_der)?;
+    if cert_der.len() as u64 > MAX_CERT_SIZE {
+        return Err(Error::CertificateError(format!(
+            "Certificate {} exceeds {} bytes",
+            Redacted(cert_path),
+            MAX_CERT_SIZE
+        )));
+    }
+    Ok(cert_der)
+}
+
+/// Stores a peer's DER-encoded certificate as
+/// `cert_dir/peers/<hex fingerprint>.der` and returns its path.
+///
+/// The certificate is written to a temporary file and renamed into place, so
+/// concurrent sessions storing the same certificate never read a partial file.
+pub fn store_peer_cert(cert_dir: &Path, cert_der: &[u8]) -> Result<PathBuf> {
+    let peer_dir = cert_dir.join(PEER_CERT_DIR);
+    fs::create_dir_all(&peer_dir)?;
+    let cert_path = peer_dir.join(format!("{}.der", hex::encode(cert_fingerprint(cert_der))));
+    let mut staged = tempfile::NamedTempFile::new_in(&peer_dir)?;
+    staged.write_all(cert_der)?;
+    staged.as_file().sync_all()?;
+    staged
+        .persist(&cert_path)
+        .map_err(|e| Error::IoError(e.error))?;
+    Ok(cert_path)
+}
+
+/// A certificate authority shared by the operators of a consortium.
+///
+/// With an operator CA configured, MPC-Net certificates are only trusted if
+/// they chain to it, instead of trusting whichever self-signed certificate a
+/// peer reveals during the config exchange.
+#[derive(Clone)]
+pub struct OperatorCa {
+    verifier: Arc<dyn ClientCertVerifier>,
+}
+
+impl OperatorCa {
+    /// Loads the CA certificates at `path`, either a PEM bundle or a single
+    /// DER-encoded certificate.
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read(path)?;
+        let ca_certs = if contents.starts_with(b"-----BEGIN") {
+            rustls_pemfile::certs(&mut contents.as_slice())
+                .map(|cert| cert.map(|cert| cert.to_vec()))
+                .collect::<std::io::Result<Vec<_>>>()
+                .map_err(|e| {
+                    Error::CertificateError(format!(
+                        "Invalid operator CA bundle {}: {}",
+                        Redacted(path),
+                        e
+                    ))
+                })?
+        } else {
+            vec![contents]
+        };
+        Self::from_der(ca_certs)
+    }
+
+    /// Trusts the given DER-encoded CA certificates.
+    pub fn from_der(ca_certs: Vec<Vec<u8>>) -> Result<Self> {
+        if ca_certs.is_empty() {
+            return Err(Error::CertificateError(
+                "Operator CA bundle contains no certificates".to_string(),
+            ));
+        }
+        let mut roots = RootCertStore::empty();
+        for ca_cert in ca_certs {
+            roots
+                .add(CertificateDer::from(ca_cert))
+                .map_err(|e| Error::CertificateError(format!("Invalid operator CA: {}", e)))?;
+        }
+        let verifier = WebPkiClientVerifier::builder(Arc::new(roots))
+            .build()
+            .map_err(|e| Error::CertificateError(format!("Invalid operator CA: {}", e)))?;
+        Ok(Self { verifier })
+    }
+
+    /// Checks that `cert_der` is a currently valid certificate issued by the CA.
+    pub fn verify(&self, cert_der: &[u8]) -> Result<()> {
+        self.verifier
+            .verify_client_cert(&CertificateDer::from(cert_der), &[], UnixTime::now())
+            .map(|_| ())
+            .map_err(|e| Error::CertificateError(format!("Not issued by the operator CA: {}", e)))
+    }
+}
+
+impl fmt::Debug for OperatorCa {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OperatorCa").finish_non_exhaustive()
+    }
+}
+
+fn cert_error(e: rcgen::Error) -> Error {
+    Error::CertificateError(e.to_string())
+}
@@ -0,0 +1,649 @@
+use crate::download::DownloadHeader;
+use crate::error::{Error, Result};
+use crate::network::SessionEviction;
+use crate::p2p::CommitmentScheme;
+use crate::types::{CircuitType, ProvingBackend, PublicKeyBytes};
+use crate::upload::DEFAULT_MAX_UPLOADS_PER_CALLER;
+use crate::witness::WitnessEncoding;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+use url::Url;
+
+/// Operator-level configuration of the zkSaaS service.
+///
+/// Loaded from environment variables by [`ServiceConfig::from_env`]; every
+/// setting is optional and falls back to the value in [`Default`].
+#[derive(Debug, Clone)]
+pub struct ServiceConfig {
+    /// Scratch directory for in-progress downloads (`WORK_DIR`).
+    /// Defaults to `<data_dir>/tmp`.
+    pub work_dir: Option<PathBuf>,
+    /// If set, run a ping round with this timeout before each MPC session
+    /// (`MPC_LIVENESS_TIMEOUT_MS`).
+    pub liveness_timeout: Option<Duration>,
+    /// CA certificates (PEM bundle or DER) that peer MPC-Net certificates must
+    /// be issued by, relative to `DATA_DIR` (`MPC_OPERATOR_CA_PATH`). Unset,
+    /// peers' self-signed certificates are trusted.
+    pub operator_ca_path: Option<PathBuf>,
+    /// Hash used for config exchange commitments (`MPC_COMMITMENT_SCHEME`).
+    pub commitment_scheme: CommitmentScheme,
+    /// Namespace of the libp2p protocol name (`P2P_PROTOCOL_NAMESPACE`); see
+    /// [`ServiceConfig::protocol_name`].
+    pub protocol_namespace: String,
+    /// Maximum clock skew tolerated for peer MPC-Net announcements
+    /// (`MPC_ANNOUNCE_MAX_SKEW_SECS`).
+    pub announce_max_skew: Duration,
+    /// Timeout for establishing the MPC-Net connections of a session
+    /// (`MPC_NET_TIMEOUT_SECS`).
+    pub mpc_net_timeout: Duration,
+    /// How many times a proof whose MPC session failed is retried on a fresh
+    /// session; 0 disables retries (`MPC_SESSION_RETRIES`).
+    pub mpc_session_retries: u32,
+    /// How long the libp2p peer set must stay unchanged at startup before this
+    /// node announces its MPC-Net listener (`MPC_DISCOVERY_SETTLE_SECS`).
+    pub discovery_settle: Duration,
+    /// Fail proof jobs whose participants have not all announced their MPC-Net
+    /// listener (`REQUIRE_PEER_ANNOUNCEMENTS`).
+    pub require_peer_announcements: bool,
+    /// When idle cached MPC sessions are evicted (`SESSION_IDLE_GRACE_SECS`).
+    pub session_eviction: SessionEviction,
+    /// Generate a self-signed MPC-Net certificate and key on startup if they
+    /// are missing (`MPC_GENERATE_CERTS`).
+    pub generate_missing_certs: bool,
+    /// Start in register-only mode, without proof jobs, if the p2p network
+    /// fails to start (`P2P_REGISTER_ONLY_FALLBACK`).
+    pub register_only_fallback: bool,
+    /// Maximum number of public inputs a circuit may declare (`MAX_PUBLIC_INPUTS`).
+    pub max_public_inputs: u32,
+    /// Backend used for Circom circuits registered without one
+    /// (`DEFAULT_CIRCOM_BACKEND`).
+    pub default_circom_backend: ProvingBackend,
+    /// Backend used for Noir circuits registered without one
+    /// (`DEFAULT_NOIR_BACKEND`).
+    pub default_noir_backend: ProvingBackend,
+    /// Maximum size of a circuit artifact, downloaded or inline (`MAX_ARTIFACT_SIZE_BYTES`).
+    pub max_artifact_size: u64,
+    /// Minimum size of a downloaded artifact or key; smaller downloads fail
+    /// registration (`MIN_ARTIFACT_SIZE_BYTES`).
+    pub min_artifact_size: u64,
+    /// Maximum number of artifact downloads in flight across all registration jobs
+    /// (`MAX_CONCURRENT_DOWNLOADS`). Further downloads wait for a free slot.
+    pub max_concurrent_downloads: usize,
+    /// Members of a single artifact bundle downloaded at once
+    /// (`BUNDLE_DOWNLOAD_CONCURRENCY`), within `max_concurrent_downloads`.
+    pub bundle_download_concurrency: usize,
+    /// Hosts artifacts and keys may be downloaded from (`ALLOWED_ARTIFACT_HOSTS`,
+    /// comma-separated). Any host is accepted if unset.
+    pub allowed_artifact_hosts: Option<Vec<String>>,
+    /// Headers sent with downloads from specific hosts, such as API keys for
+    /// private artifact storage (`DOWNLOAD_HEADERS`).
+    pub download_headers: Vec<DownloadHeader>,
+    /// Retries of an artifact download failing with a connection error, a
+    /// timeout, a 5xx status or 429 (`DOWNLOAD_MAX_RETRIES`).
+    pub download_max_retries: u32,
+    /// Delay before the first download retry, doubling on every further one
+    /// (`DOWNLOAD_RETRY_DELAY_MS`).
+    pub download_retry_delay: Duration,
+    /// Consecutive failed downloads from a host after which further downloads
+    /// from it fail fast (`DOWNLOAD_BREAKER_THRESHOLD`); 0 disables the breaker.
+    pub download_breaker_threshold: u32,
+    /// How long downloads from a host fail fast once its breaker opened
+    /// (`DOWNLOAD_BREAKER_COOLDOWN_SECS`).
+    pub download_breaker_cooldown: Duration,
+    /// Unix mode of stored proving and verification keys (`KEY_FILE_MODE`, octal).
+    pub key_file_mode: u32,
+    /// Unix mode of stored circuit artifacts (`ARTIFACT_FILE_MODE`, octal).
+    pub artifact_file_mode: u32,
+    /// Fsync stored keys and artifacts before recording the circuit
+    /// (`SYNC_ARTIFACT_WRITES`).
+    pub sync_artifact_writes: bool,
+    /// Store artifacts once per distinct content, shared by the circuits using them
+    /// (`CONTENT_ADDRESSED_ARTIFACTS`).
+    pub content_addressed_artifacts: bool,
+    /// Circuit infos kept in memory in front of the state database; 0 disables
+    /// the cache (`CIRCUIT_INFO_CACHE_SIZE`).
+    pub circuit_info_cache_size: usize,
+    /// How long a chunked artifact upload may sit idle before it is discarded
+    /// (`ARTIFACT_UPLOAD_TTL_SECS`).
+    pub artifact_upload_ttl: Duration,
+    /// Chunked uploads one caller may have open at a time
+    /// (`MAX_UPLOADS_PER_CALLER`).
+    pub max_uploads_per_caller: usize,
+    /// Maximum size of a witness supplied to a proof job (`MAX_WITNESS_SIZE_BYTES`).
+    pub max_witness_size: u64,
+    /// How string values of Circom witnesses encode field elements
+    /// (`WITNESS_FIELD_ENCODING`: `auto`, `decimal` or `hex`).
+    pub witness_field_encoding: WitnessEncoding,
+    /// Bytes of an exported circuit returned per `export_circuit` call, before
+    /// base64 encoding (`EXPORT_CHUNK_SIZE_BYTES`).
+    pub export_chunk_size: u64,
+    /// Attach `ProofMetadata` (participants, timing, backend) to proof results
+    /// (`PROOF_METADATA`). Off by default, keeping the minimal on-chain encoding.
+    pub proof_metadata: bool,
+    /// Check each new proof locally against the stored verification key before
+    /// returning it, for backends with a fast verifier (`LOCAL_PROOF_VERIFICATION`).
+    /// Off by default: until real proving lands, the check only rejects
+    /// malformed proofs (see [`crate::verify::verify_proof`]).
+    pub local_proof_verification: bool,
+    /// Accept the `rng_seed` argument of `generate_proof`, making proofs
+    /// reproducible (`ALLOW_PROOF_RNG_SEED`). Test and dev deployments only:
+    /// seeded proofs are linkable.
+    pub allow_proof_rng_seed: bool,
+    /// Prove with the deterministic `MockProver` instead of the real prover
+    /// (`MOCK_PROVER`). Tests only: mock proofs prove nothing, so it only
+    /// exists with the `test-utils` feature.
+    #[cfg(any(test, feature = "test-utils"))]
+    pub mock_prover: bool,
+    /// Upper bound on key generation for a single registration (`KEYGEN_TIMEOUT_SECS`).
+    /// Unbounded if unset.
+    pub keygen_timeout: Option<Duration>,
+    /// Powers-of-tau file used for Groth16 setup (`PTAU_PATH`). When set,
+    /// circuits too large for it are rejected before setup starts.
+    pub ptau_path: Option<PathBuf>,
+    /// How long generated proofs are served from the proof cache (`PROOF_CACHE_TTL_SECS`).
+    /// Proof caching is disabled if unset.
+    pub proof_cache_ttl: Option<Duration>,
+    /// Maximum number of cached proofs; the oldest are evicted first (`PROOF_CACHE_MAX_ENTRIES`).
+    pub proof_cache_max_entries: usize,
+    /// Maximum number of failed proof jobs kept in the dead-letter store; the
+    /// oldest are evicted first, and 0 disables it (`PROOF_FAILURES_MAX_ENTRIES`).
+    pub proof_failures_max_entries: usize,
+    /// Interval at which expired proofs are swept from the cache
+    /// (`PROOF_CACHE_SWEEP_INTERVAL_SECS`).
+    pub proof_cache_sweep_interval: Duration,
+    /// Proofs whose encoding exceeds this many bytes are kept in the proof store
+    /// and returned as a `ProofPointer` (`PROOF_POINTER_THRESHOLD_BYTES`).
+    /// Proofs are always returned inline if unset.
+    pub proof_pointer_threshold: Option<u64>,
+    /// Endpoint advertised in proof pointers for fetching stored proofs
+    /// (`PROOF_RETRIEVAL_ENDPOINT`).
+    pub proof_retrieval_endpoint: Option<String>,
+    /// How long proofs are kept in the proof store (`STORED_PROOF_TTL_SECS`).
+    pub stored_proof_ttl: Duration,
+    /// Maximum number of proof jobs proving at once; further jobs queue for a
+    /// free slot (`MAX_CONCURRENT_PROOFS`).
+    pub max_concurrent_proofs: usize,
+    /// Accounts allowed to request proofs (`PROOF_ALLOWED_CALLERS`, comma-separated
+    /// hex account IDs). Every caller is allowed if unset.
+    pub proof_allowed_callers: Option<Vec<[u8; 32]>>,
+    /// Where the service operators are read from (`OPERATOR_SOURCE`, with the
+    /// static set in `STATIC_OPERATORS`).
+    pub operator_source: OperatorSource,
+    /// Upper bound on the Tangle query listing the service operators
+    /// (`OPERATOR_QUERY_TIMEOUT_SECS`).
+    pub operator_query_timeout: Duration,
+    /// Maximum number of service operators accepted from Tangle (`MAX_OPERATORS`).
+    pub max_operators: usize,
+    /// Address to serve the `/healthz` and `/readyz` probes on (`HEALTH_LISTEN_ADDR`).
+    /// No probe server is started if unset.
+    pub health_listen_addr: Option<SocketAddr>,
+    /// Interval at which the circuit store is compacted (`STORE_COMPACTION_INTERVAL_SECS`).
+    /// Scheduled compaction is disabled if unset.
+    pub store_compaction_interval: Option<Duration>,
+    /// Webhook every generated proof is POSTed to as JSON (`PROOF_WEBHOOK_URL`).
+    /// Proofs are only returned in the job result if unset.
+    pub proof_webhook_url: Option<Url>,
+    /// Secret used to sign webhook bodies with HMAC-SHA256 (`PROOF_WEBHOOK_SECRET`).
+    pub proof_webhook_secret: Option<String>,
+    /// Retries of a failed webhook delivery (`PROOF_WEBHOOK_MAX_RETRIES`).
+    pub proof_webhook_max_retries: u32,
+}
+
+impl Default for ServiceConfig {
+    fn default() -> Self {
+        Self {
+            work_dir: None,
+            liveness_timeout: None,
+            operator_ca_path: None,
+            commitment_scheme: CommitmentScheme::default(),
+            protocol_namespace: "cosnarks-zksaas".to_string(),
+            announce_max_skew: Duration::from_secs(30),
+            mpc_net_timeout: Duration::from_secs(60),
+            mpc_session_retries: 0,
+            discovery_settle: Duration::from_secs(5),
+            require_peer_announcements: false,
+            session_eviction: SessionEviction::default(),
+            generate_missing_certs: true,
+            register_only_fallback: false,
+            max_public_inputs: 1024,
+            default_circom_backend: ProvingBackend::Groth16,
+            default_noir_backend: ProvingBackend::UltraHonk,
+            max_artifact_size: 256 * 1024 * 1024,
+            min_artifact_size: 1,
+            max_concurrent_downloads: 4,
+            bundle_download_concurrency: 4,
+            allowed_artifact_hosts: None,
+            download_headers: Vec::new(),
+            download_max_retries: 2,
+            download_retry_delay: Duration::from_millis(500),
+            download_breaker_threshold: 5,
+            download_breaker_cooldown: Duration::from_secs(60),
+            key_file_mode: 0o600,
+            artifact_file_mode: 0o644,
+            sync_artifact_writes: true,
+            content_addressed_artifacts: false,
+            circuit_info_cache_size: 128,
+            artifact_upload_ttl: Duration::from_secs(60 * 60),
+            max_uploads_per_caller: DEFAULT_MAX_UPLOADS_PER_CALLER,
+            max_witness_size: 16 * 1024 * 1024,
+            witness_field_encoding: WitnessEncoding::Auto,
+            export_chunk_size: 256 * 1024,
+            proof_metadata: false,
+            local_proof_verification: false,
+            allow_proof_rng_seed: false,
+            #[cfg(any(test, feature = "test-utils"))]
+            mock_prover: false,
+            keygen_timeout: None,
+            ptau_path: None,
+            proof_cache_ttl: None,
+            proof_cache_max_entries: 1024,
+            proof_failures_max_entries: 1000,
+            proof_cache_sweep_interval: Duration::from_secs(60),
+            proof_pointer_threshold: None,
+            proof_retrieval_endpoint: None,
+            stored_proof_ttl: Duration::from_secs(24 * 60 * 60),
+            max_concurrent_proofs: 2,
+            proof_allowed_callers: None,
+            operator_source: OperatorSource::Tangle,
+            operator_query_timeout: Duration::from_secs(30),
+            max_operators: 256,
+            health_listen_addr: None,
+            store_compaction_interval: None,
+            proof_webhook_url: None,
+            proof_webhook_secret: None,
+            proof_webhook_max_retries: 3,
+        }
+    }
+}
+
+impl ServiceConfig {
+    /// Builds the configuration from environment variables.
+    pub fn from_env() -> Result<Self> {
+        let defaults = Self::default();
+        #[cfg(not(any(test, feature = "test-utils")))]
+        if env_var::<bool>("MOCK_PROVER")?.unwrap_or(false) {
+            return Err(Error::ConfigError(
+                "MOCK_PROVER requires a build with the test-utils feature".to_string(),
+            ));
+        }
+        Ok(Self {
+            work_dir: env_var::<PathBuf>("WORK_DIR")?,
+            liveness_timeout: env_var::<u64>("MPC_LIVENESS_TIMEOUT_MS")?.map(Duration::from_millis),
+            operator_ca_path: env_var::<PathBuf>("MPC_OPERATOR_CA_PATH")?,
+            commitment_scheme: env_var("MPC_COMMITMENT_SCHEME")?
+                .unwrap_or(defaults.commitment_scheme),
+            protocol_namespace: match env_var::<String>("P2P_PROTOCOL_NAMESPACE")? {
+                Some(namespace) => parse_protocol_namespace(&namespace)?,
+                None => defaults.protocol_namespace,
+            },
+            announce_max_skew: env_var::<u64>("MPC_ANNOUNCE_MAX_SKEW_SECS")?
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.announce_max_skew),
+            mpc_net_timeout: env_var::<u64>("MPC_NET_TIMEOUT_SECS")?
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.mpc_net_timeout),
+            mpc_session_retries: env_var("MPC_SESSION_RETRIES")?
+                .unwrap_or(defaults.mpc_session_retries),
+            discovery_settle: env_var::<u64>("MPC_DISCOVERY_SETTLE_SECS")?
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.discovery_settle),
+            require_peer_announcements: env_var("REQUIRE_PEER_ANNOUNCEMENTS")?
+                .unwrap_or(defaults.require_peer_announcements),
+            session_eviction: SessionEviction {
+                idle_grace: env_var::<u64>("SESSION_IDLE_GRACE_SECS")?
+                    .map(Duration::from_secs)
+                    .unwrap_or(defaults.session_eviction.idle_grace),
+            },
+            generate_missing_certs: env_var("MPC_GENERATE_CERTS")?
+                .unwrap_or(defaults.generate_missing_certs),
+            register_only_fallback: env_var("P2P_REGISTER_ONLY_FALLBACK")?
+                .unwrap_or(defaults.register_only_fallback),
+            max_public_inputs: env_var("MAX_PUBLIC_INPUTS")?.unwrap_or(defaults.max_public_inputs),
+            default_circom_backend: env_var("DEFAULT_CIRCOM_BACKEND")?
+                .unwrap_or(defaults.default_circom_backend),
+            default_noir_backend: env_var("DEFAULT_NOIR_BACKEND")?
+                .unwrap_or(defaults.default_noir_backend),
+            max_artifact_size: env_var("MAX_ARTIFACT_SIZE_BYTES")?
+                .unwrap_or(defaults.max_artifact_size),
+            min_artifact_size: env_var("MIN_ARTIFACT_SIZE_BYTES")?
+                .unwrap_or(defaults.min_artifact_size),
+            max_concurrent_downloads: env_var("MAX_CONCURRENT_DOWNLOADS")?
+                .unwrap_or(defaults.max_concurrent_downloads),
+            bundle_download_concurrency: env_var("BUNDLE_DOWNLOAD_CONCURRENCY")?
+                .unwrap_or(defaults.bundle_download_concurrency),
+            allowed_artifact_hosts: env_var::<String>("ALLOWED_ARTIFACT_HOSTS")?
+                .map(|hosts| parse_host_list(&hosts)),
+            download_headers: env_var::<String>("DOWNLOAD_HEADERS")?
+                .map(|headers| parse_download_headers(&headers))
+                .transpose()?
+                .unwrap_or_default(),
+            download_max_retries: env_var("DOWNLOAD_MAX_RETRIES")?
+                .unwrap_or(defaults.download_max_retries),
+            download_retry_delay: env_var::<u64>("DOWNLOAD_RETRY_DELAY_MS")?
+                .map(Duration::from_millis)
+                .unwrap_or(defaults.download_retry_delay),
+            download_breaker_threshold: env_var("DOWNLOAD_BREAKER_THRESHOLD")?
+                .unwrap_or(defaults.download_breaker_threshold),
+            download_breaker_cooldown: env_var::<u64>("DOWNLOAD_BREAKER_COOLDOWN_SECS")?
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.download_breaker_cooldown),
+            key_file_mode: env_var::<String>("KEY_FILE_MODE")?
+                .map(|mode| parse_file_mode("KEY_FILE_MODE", &mode))
+                .transpose()?
+                .unwrap_or(defaults.key_file_mode),
+            artifact_file_mode: env_var::<String>("ARTIFACT_FILE_MODE")?
+                .map(|mode| parse_file_mode("ARTIFACT_FILE_MODE", &mode))
+                .transpose()?
+                .unwrap_or(defaults.artifact_file_mode),
+            sync_artifact_writes: env_var("SYNC_ARTIFACT_WRITES")?
+                .unwrap_or(defaults.sync_artifact_writes),
+            content_addressed_artifacts: env_var("CONTENT_ADDRESSED_ARTIFACTS")?
+                .unwrap_or(defaults.content_addressed_artifacts),
+            circuit_info_cache_size: env_var("CIRCUIT_INFO_CACHE_SIZE")?
+                .unwrap_or(defaults.circuit_info_cache_size),
+            artifact_upload_ttl: env_var::<u64>("ARTIFACT_UPLOAD_TTL_SECS")?
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.artifact_upload_ttl),
+            max_uploads_per_caller: env_var("MAX_UPLOADS_PER_CALLER")?
+                .unwrap_or(defaults.max_uploads_per_caller),
+            max_witness_size: env_var("MAX_WITNESS_SIZE_BYTES")?
+                .unwrap_or(defaults.max_witness_size),
+            witness_field_encoding: env_var("WITNESS_FIELD_ENCODING")?
+                .unwrap_or(defaults.witness_field_encoding),
+            export_chunk_size: env_var("EXPORT_CHUNK_SIZE_BYTES")?
+                .unwrap_or(defaults.export_chunk_size),
+            proof_metadata: env_var("PROOF_METADATA")?.unwrap_or(defaults.proof_metadata),
+            local_proof_verification: env_var("LOCAL_PROOF_VERIFICATION")?
+                .unwrap_or(defaults.local_proof_verification),
+            allow_proof_rng_seed: env_var("ALLOW_PROOF_RNG_SEED")?
+                .unwrap_or(defaults.allow_proof_rng_seed),
+            #[cfg(any(test, feature = "test-utils"))]
+            mock_prover: env_var("MOCK_PROVER")?.unwrap_or(defaults.mock_prover),
+            keygen_timeout: env_var::<u64>("KEYGEN_TIMEOUT_SECS")?.map(Duration::from_secs),
+            ptau_path: env_var("PTAU_PATH")?,
+            proof_cache_ttl: env_var::<u64>("PROOF_CACHE_TTL_SECS")?.map(Duration::from_secs),
+            proof_cache_max_entries: env_var("PROOF_CACHE_MAX_ENTRIES")?
+                .unwrap_or(defaults.proof_cache_max_entries),
+            proof_failures_max_entries: env_var("PROOF_FAILURES_MAX_ENTRIES")?
+                .unwrap_or(defaults.proof_failures_max_entries),
+            proof_cache_sweep_interval: env_var::<u64>("PROOF_CACHE_SWEEP_INTERVAL_SECS")?
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.proof_cache_sweep_interval),
+            proof_pointer_threshold: env_var("PROOF_POINTER_THRESHOLD_BYTES")?,
+            proof_retrieval_endpoint: env_var("PROOF_RETRIEVAL_ENDPOINT")?,
+            stored_proof_ttl: env_var::<u64>("STORED_PROOF_TTL_SECS")?
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.stored_proof_ttl),
+            max_concurrent_proofs: env_var("MAX_CONCURRENT_PROOFS")?
+                .unwrap_or(defaults.max_concurrent_proofs),
+            proof_allowed_callers: env_var::<String>("PROOF_ALLOWED_CALLERS")?
+                .map(|callers| parse_account_list("PROOF_ALLOWED_CALLERS", &callers))
+                .transpose()?,
+            operator_source: operator_source_from_env()?,
+            operator_query_timeout: env_var::<u64>("OPERATOR_QUERY_TIMEOUT_SECS")?
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.operator_query_timeout),
+            max_operators: env_var("MAX_OPERATORS")?.unwrap_or(defaults.max_operators),
+            health_listen_addr: env_var("HEALTH_LISTEN_ADDR")?,
+            store_compaction_interval: env_var::<u64>("STORE_COMPACTION_INTERVAL_SECS")?
+                .map(Duration::from_secs),
+            proof_webhook_url: env_var("PROOF_WEBHOOK_URL")?,
+            proof_webhook_secret: env_var("PROOF_WEBHOOK_SECRET")?,
+            proof_webhook_max_retries: env_var("PROOF_WEBHOOK_MAX_RETRIES")?
+                .unwrap_or(defaults.proof_webhook_max_retries),
+        })
+    }
+
+    /// The libp2p protocol name, `/<namespace>/mpc/<version>`.
+    ///
+    /// The version is the crate's semver-compatibility level (the major version,
+    /// or `0.<minor>` before 1.0), so a breaking release stops discovering
+    /// older peers, while patch releases keep talking to each other. Peers with
+    /// different namespaces never discover each other, isolating test networks
+    /// and parallel deployments on shared infrastructure.
+    pub fn protocol_name(&self) -> String {
+        let major = env!("CARGO_PKG_VERSION_MAJOR");
+        let version = if major == "0" {
+            format!("0.{}", env!("CARGO_PKG_VERSION_MINOR"))
+        } else {
+            major.to_string()
+        };
+        format!("/{}/mpc/{}", self.protocol_namespace, version)
+    }
+
+    /// Backend a circuit of `circuit_type` is registered with if the request
+    /// names none.
+    pub fn default_backend(&self, circuit_type: &CircuitType) -> ProvingBackend {
+        match circuit_type {
+            CircuitType::Circom => self.default_circom_backend.clone(),
+            CircuitType::Noir => self.default_noir_backend.clone(),
+        }
+    }
+}
+
+/// Where the service operators, and with them the participants of every MPC
+/// session, are read from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OperatorSource {
+    /// The operators registered for the service on Tangle
+    Tangle,
+    /// A fixed operator set, for local deployments and consortiums running
+    /// without a live chain
+    Static(Vec<StaticOperator>),
+}
+
+/// An operator of a static operator set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StaticOperator {
+    /// Account ID the operator calls jobs from
+    pub account: [u8; 32],
+    /// Encoded public key the operator takes part in MPC sessions with
+    pub public_key: PublicKeyBytes,
+}
+
+/// Reads `OPERATOR_SOURCE` (`tangle` or `static`) and, for a static source,
+/// the comma-separated `<account hex>:<public key hex>` pairs in `STATIC_OPERATORS`.
+fn operator_source_from_env() -> Result<OperatorSource> {
+    let source = env_var::<String>("OPERATOR_SOURCE")?.unwrap_or_else(|| "tangle".to_string());
+    match source.trim().to_ascii_lowercase().as_str() {
+        "tangle" => Ok(OperatorSource::Tangle),
+        "static" => {
+            let operators = env_var::<String>("STATIC_OPERATORS")?.ok_or_else(|| {
+                Error::ConfigError("OPERATOR_SOURCE=static requires STATIC_OPERATORS".to_string())
+            })?;
+            parse_static_operators(&operators).map(OperatorSource::Static)
+        }
+        other => Err(Error::ConfigError(format!(
+            "Unknown operator source: {}",
+            other
+        ))),
+    }
+}
+
+/// Parses comma-separated `<account hex>:<public key hex>` pairs.
+fn parse_static_operators(operators: &str) -> Result<Vec<StaticOperator>> {
+    let invalid = || Error::InvalidInput("Invalid STATIC_OPERATORS format".to_string());
+    operators
+        .split(',')
+        .map(str::trim)
+        .filter(|operator| !operator.is_empty())
+        .map(|operator| {
+            let (account_hex, key_hex) = operator.split_once(':').ok_or_else(invalid)?;
+            let mut account = [0u8; 32];
+            hex::decode_to_slice(account_hex.trim().trim_start_matches("0x"), &mut account)
+                .map_err(|_| invalid())?;
+            let public_key =
+                hex::decode(key_hex.trim().trim_start_matches("0x")).map_err(|_| invalid())?;
+            if public_key.is_empty() {
+                return Err(invalid());
+            }
+            Ok(StaticOperator {
+                account,
+                public_key,
+            })
+        })
+        .collect()
+}
+
+/// Splits a comma-separated host list, normalizing hosts to lowercase.
+fn parse_host_list(hosts: &str) -> Vec<String> {
+    hosts
+        .split(',')
+        .map(|host| host.trim().to_ascii_lowercase())
+        .filter(|host| !host.is_empty())
+        .collect()
+}
+
+/// Parses `host=Name: value` entries separated by `;`.
+///
+/// Errors never include the configured values, which are usually secrets.
+fn parse_download_headers(headers: &str) -> Result<Vec<DownloadHeader>> {
+    headers
+        .split(';')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (host, header) = entry.split_once('=').ok_or_else(|| {
+                Error::InvalidInput("Invalid DOWNLOAD_HEADERS format".to_string())
+            })?;
+            let (name, value) = header.split_once(':').ok_or_else(|| {
+                Error::InvalidInput(format!(
+                    "Invalid DOWNLOAD_HEADERS entry for host '{}'",
+                    host.trim()
+                ))
+            })?;
+            DownloadHeader::new(host, name.trim(), value.trim())
+        })
+        .collect()
+}
+
+/// Parses an octal permission mode such as `600` or `0o640`.
+fn parse_file_mode(name: &str, mode: &str) -> Result<u32> {
+    let digits = mode.trim().trim_start_matches("0o");
+    u32::from_str_radix(digits, 8)
+        .ok()
+        .filter(|mode| *mode <= 0o7777)
+        .ok_or_else(|| Error::InvalidInput(format!("Invalid {} format", name)))
+}
+
+/// Parses a comma-separated list of hex-encoded 32-byte account IDs.
+fn parse_account_list(name: &str, accounts: &str) -> Result<Vec<[u8; 32]>> {
+    accounts
+        .split(',')
+        .map(str::trim)
+        .filter(|account| !account.is_empty())
+        .map(|account| {
+            let mut id = [0u8; 32];
+            hex::decode_to_slice(account.trim_start_matches("0x"), &mut id)
+                .map_err(|_| Error::InvalidInput(format!("Invalid {} format", name)))?;
+            Ok(id)
+        })
+        .collect()
+}
+
+/// Checks that `namespace` is a single, non-empty segment of a protocol name.
+fn parse_protocol_namespace(namespace: &str) -> Result<String> {
+    let namespace = namespace.trim();
+    if namespace.is_empty() || namespace.contains('/') || namespace.contains(char::is_whitespace) {
+        return Err(Error::ConfigError(format!(
+            "Invalid P2P_PROTOCOL_NAMESPACE '{}': expected a non-empty name without '/' or spaces",
+            namespace
+        )));
+    }
+    Ok(namespace.to_string())
+}
+
+/// Reads and parses an optional environment variable.
+fn env_var<T: FromStr>(name: &str) -> Result<Option<T>> {
+    match std::env::var(name) {
+        Ok(value) => value
+            .parse()
+            .map(Some)
+            .map_err(|_| Error::InvalidInput(format!("Invalid {} format", name))),
+        Err(_) => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_static_operators() {
+        let account = "11".repeat(32);
+        let operators =
+            parse_static_operators(&format!(" 0x{}:0x0203 ,,{}:04 ,", account, "22".repeat(32)))
+                .unwrap();
+        assert_eq!(operators, vec![
+            StaticOperator {
+                account: [0x11; 32],
+                public_key: vec![0x02, 0x03],
+            },
+            StaticOperator {
+                account: [0x22; 32],
+                public_key: vec![0x04],
+            },
+        ]);
+        assert_eq!(parse_static_operators("").unwrap(), Vec::new());
+    }
+
+    #[test]
+    fn rejects_malformed_static_operators() {
+        let account = "11".repeat(32);
+        for operators in [
+            // No separator, short or non-hex account, missing or non-hex key
+            account.clone(),
+            format!("{}:02", "11".repeat(31)),
+            format!("{}:02", "zz".repeat(32)),
+            format!("{}:", account),
+            format!("{}:0x2g", account),
+            format!("{}:02,{}", account, account),
+        ] {
+            assert!(
+                matches!(
+                    parse_static_operators(&operators),
+                    Err(Error::InvalidInput(_))
+                ),
+                "accepted {}",
+                operators
+            );
+        }
+    }
+
+    #[test]
+    fn parses_protocol_namespaces() {
+        assert_eq!(parse_protocol_namespace(" staging ").unwrap(), "staging");
+        for namespace in ["", "   ", "a/b", "/staging", "two words", "tab\tname"] {
+            assert!(
+                matches!(
+                    parse_protocol_namespace(namespace),
+                    Err(Error::ConfigError(_))
+                ),
+                "accepted {:?}",
+                namespace
+            );
+        }
+    }
+
+    #[test]
+    fn protocol_name_includes_namespace_and_compatibility_version() {
+        let config = ServiceConfig {
+            protocol_namespace: "staging".to_string(),
+            ..ServiceConfig::default()
+        };
+        let name = config.protocol_name();
+        let version = name.strip_prefix("/staging/mpc/").unwrap();
+        let major = env!("CARGO_PKG_VERSION_MAJOR");
+        if major == "0" {
+            assert_eq!(version, format!("0.{}", env!("CARGO_PKG_VERSION_MINOR")));
+        } else {
+            assert_eq!(version, major);
+        }
+        assert_eq!(
+            ServiceConfig::default().protocol_name(),
+            format!("/cosnarks-zksaas/mpc/{}", version)
+        );
+    }
+}
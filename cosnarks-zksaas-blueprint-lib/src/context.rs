@@ -1,17 +1,37 @@
+use crate::auth::{AllowAll, CallerAllowList, ProofAuthorizer};
+use crate::certs::{OperatorCa, ensure_mpc_cert, read_cert};
+use crate::config::{OperatorSource, ServiceConfig, StaticOperator};
+use crate::discovery::PeerReputation;
+use crate::download::ArtifactDownloader;
 use crate::error::{Error, Result};
-use crate::network::MpcNetworkManager;
-use crate::state::CircuitStore;
+use crate::health::{Readiness, Subsystem, spawn_health_server};
+use crate::jobs::REGISTER_CIRCUIT_JOB_ID;
+use crate::jobs::export_circuit::ExportCache;
+use crate::metrics::CheatingMetrics;
+use crate::network::{GOSSIP_POLL_INTERVAL, MpcListenAddress, MpcNetworkManager};
+#[cfg(any(test, feature = "test-utils"))]
+use crate::prover::MockProver;
+use crate::prover::{PlaceholderProver, ProverBackend};
+use crate::queue::ProofQueue;
+use crate::redact::Redacted;
+use crate::state::{CircuitStore, FileModes};
+use crate::types::CircuitId;
+use crate::upload::ArtifactUploads;
+use crate::webhook::ProofWebhook;
 use blueprint_sdk::clients::GadgetServicesClient;
 use blueprint_sdk::contexts::tangle::TangleClientContext;
 use blueprint_sdk::crypto::{BytesEncoding, KeyType};
-use blueprint_sdk::macros::context::{KeystoreContext, ServicesContext, TangleClientContext};
 use blueprint_sdk::networking::discovery::peers::VerificationIdentifierKey;
 use blueprint_sdk::networking::service_handle::NetworkServiceHandle;
 use blueprint_sdk::runner::config::BlueprintEnvironment;
-use std::net::{IpAddr, Ipv4Addr, SocketAddr};
-use std::path::PathBuf;
-use std::sync::{Arc, Mutex};
-use tokio::sync::RwLock;
+use blueprint_sdk::tangle::serde::from_field;
+use blueprint_sdk::tangle_subxt::tangle_testnet_runtime::api;
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
 
 /// Main context for the zkSaaS Blueprint service
 #[derive(Clone)]
@@ -21,18 +41,52 @@ where
 {
     /// The shared Blueprint environment
     pub environment: Arc<BlueprintEnvironment>,
+    /// Operator-level service configuration
+    pub config: Arc<ServiceConfig>,
     /// Store for circuit metadata and artifact paths
     pub circuit_store: CircuitStore,
-    /// The MPC network manager for coordinating multi-party computations
-    pub mpc_network_manager: Arc<MpcNetworkManager<K>>,
+    /// Downloader shared by all jobs, bounding concurrent artifact downloads
+    pub downloader: ArtifactDownloader,
+    /// The MPC network manager for coordinating multi-party computations;
+    /// `None` in register-only mode, after the p2p network failed to start
+    pub mpc_network_manager: Option<Arc<MpcNetworkManager<K>>>,
+    /// Policy deciding who may request proofs for which circuit
+    pub proof_authorizer: Arc<dyn ProofAuthorizer>,
+    /// Prover producing and verifying proofs
+    pub prover: Arc<dyn ProverBackend>,
+    /// Webhook receiving every generated proof, if configured
+    pub proof_webhook: Option<ProofWebhook>,
+    /// Queue bounding concurrent proof jobs, tracking them for cancellation
+    pub proof_queue: ProofQueue,
+    /// Reliability of each peer across the MPC sessions run with it
+    pub peer_reputation: PeerReputation,
+    /// Cheating incidents per guilty operator, with registered alert hooks
+    pub cheating_metrics: CheatingMetrics,
+    /// Chunked artifact uploads awaiting registration
+    pub artifact_uploads: ArtifactUploads,
+    /// Encoded exports of recently exported circuits
+    pub export_cache: ExportCache,
+    /// Readiness of the subsystems, served on `/readyz` if configured
+    pub readiness: Readiness,
+    /// Root cancellation token, cancelled on service shutdown.
+    /// Jobs derive child tokens from it so in-flight work stops promptly.
+    pub cancellation_token: CancellationToken,
 }
 
 impl<K: KeyType> CosnarksContext<K>
 where
     K::Public: Unpin,
 {
-    /// Create a new CosnarksContext
+    /// Create a new CosnarksContext, reading the service configuration from the environment
     pub async fn new(environment: Arc<BlueprintEnvironment>) -> Result<Self> {
+        Self::new_with_config(environment, ServiceConfig::from_env()?).await
+    }
+
+    /// Create a new CosnarksContext with an explicit service configuration
+    pub async fn new_with_config(
+        environment: Arc<BlueprintEnvironment>,
+        config: ServiceConfig,
+    ) -> Result<Self> {
         let data_dir = environment.data_dir.as_ref().ok_or_else(|| {
             Error::MissingConfiguration(
                 "Data directory (data_dir) must be set in Blueprint environment".to_string(),
@@ -40,74 +94,239 @@ where
         })?;
 
         // Create circuit store
-        let circuit_store = CircuitStore::new(data_dir.clone())?;
+        let circuit_store = match &config.work_dir {
+            Some(work_dir) => CircuitStore::with_work_dir(data_dir.clone(), work_dir.clone())?,
+            None => CircuitStore::new(data_dir.clone())?,
+        }
+        .with_file_modes(FileModes {
+            key: config.key_file_mode,
+            artifact: config.artifact_file_mode,
+        })
+        .with_sync_files(config.sync_artifact_writes)
+        .with_content_addressed(config.content_addressed_artifacts)
+        .with_info_cache_capacity(config.circuit_info_cache_size);
 
         // -- Networking Setup --
-        // Define a unique protocol name for this service
-        let protocol_name = "/cosnarks-zksaas/mpc/1.0.0"; // Example protocol ID
-        let network_config = environment
-            .libp2p_network_config(protocol_name, false)
-            .map_err(Into::<blueprint_sdk::Error>::into)?;
-        let local_verification_key = K::public_from_secret(&network_config.instance_key_pair);
-        // TODO: Fetch allowed keys dynamically if needed, e.g., from Tangle
-        // For now, assume AllowAll or configuration via environment
-        let allowed_keys =
-            blueprint_sdk::networking::AllowedKeys::InstancePublicKeys(Default::default());
-        let (allowed_keys_tx, allowed_keys_rx) = crossbeam_channel::unbounded(); // Required by libp2p_start_network
-
-        let network_handle = environment
-            .libp2p_start_network(network_config, allowed_keys, allowed_keys_rx)
-            .map_err(Into::<blueprint_sdk::Error>::into)?;
-
-        // -- MPC Network Manager Setup --
-        // These should ideally come from secure configuration
-        let mpc_listen_dns: SocketAddr = std::env::var("MPC_LISTEN_DNS")
-            .map_err(|_| {
-                Error::MissingConfiguration(
-                    "MPC_LISTEN_DNS environment variable not set".to_string(),
-                )
-            })?
-            .parse()
-            .map_err(|_| Error::InvalidInput("Invalid MPC_LISTEN_DNS format".to_string()))?;
-        let key_path = data_dir.join(
-            std::env::var("MPC_KEY_PATH").unwrap_or_else(|_| "mpc_certs/mpc_key.der".to_string()),
-        );
-        let cert_path = data_dir.join(
-            std::env::var("MPC_CERT_PATH").unwrap_or_else(|_| "mpc_certs/mpc_cert.der".to_string()),
-        );
+        let mpc_network_manager = match fallback_to_register_only(
+            start_p2p_network::<K>(&environment, &config.protocol_name()),
+            config.register_only_fallback,
+        )? {
+            Some((network_handle, local_verification_key)) => {
+                Some(Arc::new(mpc_network_manager_from_env(
+                    network_handle,
+                    local_verification_key,
+                    data_dir,
+                    &config,
+                )?))
+            }
+            None => None,
+        };
+
+        let readiness = Readiness::new(circuit_store.clone());
+        readiness.set_ready(Subsystem::P2p, mpc_network_manager.is_some());
+        if let Some(manager) = &mpc_network_manager {
+            // mpc-net binds its listener per session; check the address is free now
+            let bind_addr = manager.listen_address().bind_addr();
+            match tokio::net::TcpListener::bind(bind_addr).await {
+                Ok(_) => readiness.set_ready(Subsystem::MpcNet, true),
+                Err(e) => {
+                    tracing::error!(%bind_addr, error = %e, "MPC-Net listen address cannot be bound")
+                }
+            }
+        }
 
-        // Ensure certificates directory exists
-        if let Some(parent) = key_path.parent() {
-            std::fs::create_dir_all(parent)?;
+        let cancellation_token = CancellationToken::new();
+        if let Some(addr) = config.health_listen_addr {
+            spawn_health_server(addr, readiness.clone(), cancellation_token.child_token()).await?;
+        }
+        if let Some(manager) = &mpc_network_manager {
+            spawn_session_evictor(manager.clone(), cancellation_token.child_token());
+            spawn_gossip_receiver(manager.clone(), cancellation_token.child_token());
+            spawn_ready_announcement(
+                manager.clone(),
+                config.discovery_settle,
+                cancellation_token.child_token(),
+            );
         }
-        if let Some(parent) = cert_path.parent() {
-            std::fs::create_dir_all(parent)?;
+        if let Some(ttl) = config.proof_cache_ttl {
+            spawn_proof_cache_sweeper(
+                circuit_store.clone(),
+                ttl,
+                config.proof_cache_max_entries,
+                config.proof_cache_sweep_interval,
+                cancellation_token.child_token(),
+            );
+        }
+        spawn_stored_proof_sweeper(
+            circuit_store.clone(),
+            config.stored_proof_ttl,
+            config.proof_cache_sweep_interval,
+            cancellation_token.child_token(),
+        );
+        let artifact_uploads = ArtifactUploads::new(
+            circuit_store.get_tmp_path().join("uploads"),
+            config.max_artifact_size,
+            config.artifact_upload_ttl,
+        )
+        .with_max_per_caller(config.max_uploads_per_caller);
+        spawn_upload_sweeper(artifact_uploads.clone(), cancellation_token.child_token());
+        if let Some(interval) = config.store_compaction_interval {
+            spawn_store_compactor(
+                circuit_store.clone(),
+                interval,
+                cancellation_token.child_token(),
+            );
         }
-        // TODO: Add check if key/cert files actually exist?
 
-        let mpc_network_manager = Arc::new(MpcNetworkManager::new(
-            network_handle,
-            VerificationIdentifierKey::InstancePublicKey(local_verification_key),
-            mpc_listen_dns,
-            key_path,
-            cert_path,
-        ));
+        let downloader =
+            ArtifactDownloader::new(config.max_concurrent_downloads, config.max_artifact_size)
+                .with_min_size(config.min_artifact_size)
+                .with_headers(config.download_headers.clone())
+                .with_allowed_hosts(config.allowed_artifact_hosts.clone())
+                .with_retries(config.download_max_retries, config.download_retry_delay)
+                .with_circuit_breaker(
+                    config.download_breaker_threshold,
+                    config.download_breaker_cooldown,
+                );
+        let proof_authorizer: Arc<dyn ProofAuthorizer> = match &config.proof_allowed_callers {
+            Some(callers) => Arc::new(CallerAllowList::new(callers.iter().copied())),
+            None => Arc::new(AllowAll),
+        };
+        let prover: Arc<dyn ProverBackend> = Arc::new(PlaceholderProver);
+        #[cfg(any(test, feature = "test-utils"))]
+        let prover: Arc<dyn ProverBackend> = if config.mock_prover {
+            tracing::warn!("MOCK_PROVER is set; proofs are deterministic mocks and prove nothing");
+            Arc::new(MockProver)
+        } else {
+            prover
+        };
+        let proof_webhook = config.proof_webhook_url.clone().map(|url| {
+            let secret = config
+                .proof_webhook_secret
+                .as_ref()
+                .map(|secret| secret.as_bytes().to_vec());
+            ProofWebhook::new(url, secret)
+                .with_retries(config.proof_webhook_max_retries, Duration::from_secs(1))
+        });
+
+        let proof_queue = ProofQueue::new(config.max_concurrent_proofs);
 
         Ok(Self {
             environment,
+            config: Arc::new(config),
             circuit_store,
+            downloader,
             mpc_network_manager,
+            proof_authorizer,
+            prover,
+            proof_webhook,
+            proof_queue,
+            peer_reputation: PeerReputation::default(),
+            cheating_metrics: CheatingMetrics::default(),
+            artifact_uploads,
+            export_cache: ExportCache::default(),
+            readiness,
+            cancellation_token,
         })
     }
 
+    /// Provides access to the service configuration.
+    pub fn config(&self) -> &ServiceConfig {
+        &self.config
+    }
+
     /// Provides immutable access to the CircuitStore.
     pub fn circuit_store(&self) -> &CircuitStore {
         &self.circuit_store
     }
 
+    /// Provides access to the shared artifact downloader.
+    pub fn downloader(&self) -> &ArtifactDownloader {
+        &self.downloader
+    }
+
     /// Provides immutable access to the MpcNetworkManager.
-    pub fn mpc_network_manager(&self) -> &Arc<MpcNetworkManager<K>> {
-        &self.mpc_network_manager
+    ///
+    /// Fails with `Error::NetworkError` in register-only mode.
+    pub fn try_mpc_network_manager(&self) -> Result<&Arc<MpcNetworkManager<K>>> {
+        self.mpc_network_manager
+            .as_ref()
+            .ok_or_else(|| Error::NetworkError("p2p unavailable".to_string()))
+    }
+
+    /// Returns whether this node has announced its MPC-Net listener to its
+    /// peers after startup; never in register-only mode.
+    pub fn is_ready(&self) -> bool {
+        self.mpc_network_manager
+            .as_ref()
+            .is_some_and(|manager| manager.is_ready())
+    }
+
+    /// Returns whether the context started without a p2p network
+    /// (`P2P_REGISTER_ONLY_FALLBACK`), and so cannot run proof jobs.
+    pub fn is_register_only(&self) -> bool {
+        self.mpc_network_manager.is_none()
+    }
+
+    /// Replaces the proof authorization policy.
+    #[must_use]
+    pub fn with_proof_authorizer(mut self, authorizer: Arc<dyn ProofAuthorizer>) -> Self {
+        self.proof_authorizer = authorizer;
+        self
+    }
+
+    /// Provides access to the proof authorization policy.
+    pub fn proof_authorizer(&self) -> &dyn ProofAuthorizer {
+        self.proof_authorizer.as_ref()
+    }
+
+    /// Replaces the prover. Every operator of the service must use the same one.
+    #[must_use]
+    pub fn with_prover(mut self, prover: Arc<dyn ProverBackend>) -> Self {
+        self.prover = prover;
+        self
+    }
+
+    /// Provides access to the prover.
+    pub fn prover(&self) -> &dyn ProverBackend {
+        self.prover.as_ref()
+    }
+
+    /// Provides access to the proof webhook, if one is configured.
+    pub fn proof_webhook(&self) -> Option<&ProofWebhook> {
+        self.proof_webhook.as_ref()
+    }
+
+    /// Provides access to the proof job queue.
+    pub fn proof_queue(&self) -> &ProofQueue {
+        &self.proof_queue
+    }
+
+    /// Provides access to the per-peer reputation, e.g. to export the scores
+    /// as metrics.
+    pub fn peer_reputation(&self) -> &PeerReputation {
+        &self.peer_reputation
+    }
+
+    /// Provides access to the cheating incident counters. Register alert hooks
+    /// with `CheatingMetrics::on_incident`.
+    pub fn cheating_metrics(&self) -> &CheatingMetrics {
+        &self.cheating_metrics
+    }
+
+    /// Provides access to the in-progress chunked artifact uploads.
+    pub fn artifact_uploads(&self) -> &ArtifactUploads {
+        &self.artifact_uploads
+    }
+
+    /// Provides access to the cache of encoded circuit exports.
+    pub fn export_cache(&self) -> &ExportCache {
+        &self.export_cache
+    }
+
+    /// Provides access to the root cancellation token.
+    pub fn cancellation_token(&self) -> &CancellationToken {
+        &self.cancellation_token
     }
 
     /// Provides access to the configured data directory.
@@ -115,21 +334,589 @@ where
         self.environment.data_dir.clone()
     }
 
-    /// Retrieves the list of registered operator public keys for the service.
+    /// Retrieves the list of registered operator public keys for the service,
+    /// from Tangle or the static set configured as the `operator_source`.
+    ///
+    /// The Tangle query is bounded by `operator_query_timeout` and fails with
+    /// `Error::NetworkError` if it does not answer in time. Operator sets larger
+    /// than `max_operators` are rejected before their keys are decoded.
     /// TODO: Implement actual fetching from Tangle state.
     pub async fn get_operators(&self) -> Result<Vec<K::Public>> {
-        let operators = self
-            .environment
-            .tangle_client()
-            .await
-            .map_err(Into::<blueprint_sdk::Error>::into)?
-            .get_operators()
-            .await
-            .map_err(Into::<blueprint_sdk::Error>::into)?
-            .values()
-            .map(|k| K::Public::from_bytes(&k.0).unwrap())
-            .collect();
-
-        Ok(operators)
+        Ok(self
+            .query_operators()
+            .await?
+            .into_iter()
+            .map(|(_, key)| key)
+            .collect())
+    }
+
+    /// Returns whether `account` is a registered operator of the service.
+    pub async fn is_service_operator(&self, account: &[u8; 32]) -> Result<bool> {
+        Ok(self
+            .query_operators()
+            .await?
+            .iter()
+            .any(|(operator, _)| operator == account))
+    }
+
+    /// Returns whether `account` owns the service instance. Always `false` with a
+    /// static operator source, which has no on-chain service to look up.
+    ///
+    /// The query is bounded by `operator_query_timeout`.
+    pub async fn is_service_owner(&self, account: &[u8; 32]) -> Result<bool> {
+        if let OperatorSource::Static(_) = &self.config.operator_source {
+            return Ok(false);
+        }
+        let service_id = self.service_id()?;
+        let timeout = self.config.operator_query_timeout;
+        let query = async {
+            let client = self
+                .environment
+                .tangle_client()
+                .await
+                .map_err(Into::<blueprint_sdk::Error>::into)?;
+            let instance = client
+                .subxt_client()
+                .storage()
+                .at_latest()
+                .await
+                .map_err(|e| Error::NetworkError(format!("Failed to read Tangle storage: {}", e)))?
+                .fetch(&api::storage().services().instances(service_id))
+                .await
+                .map_err(|e| {
+                    Error::NetworkError(format!("Failed to read service instance: {}", e))
+                })?;
+            Ok::<_, Error>(instance.is_some_and(|instance| instance.owner.0 == *account))
+        };
+        tokio::time::timeout(timeout, query).await.map_err(|_| {
+            Error::NetworkError(format!("Service owner query timed out after {:?}", timeout))
+        })?
+    }
+
+    /// Returns whether `account` may call admin jobs: a service operator or the
+    /// service owner.
+    pub async fn is_service_admin(&self, account: &[u8; 32]) -> Result<bool> {
+        Ok(self.is_service_operator(account).await? || self.is_service_owner(account).await?)
+    }
+
+    /// Lists the circuits registered on-chain: the circuit IDs returned by the
+    /// service's `register_circuit` calls that have a result.
+    ///
+    /// The service's job calls and results are each read in one pass. The query
+    /// is bounded by `operator_query_timeout` and fails with
+    /// `Error::NetworkError` if Tangle does not answer in time. Results that do
+    /// not start with a 32-byte circuit ID are logged and skipped.
+    pub async fn get_onchain_circuits(&self) -> Result<Vec<CircuitId>> {
+        let service_id = self.service_id()?;
+        let timeout = self.config.operator_query_timeout;
+        let query = async {
+            let client = self
+                .environment
+                .tangle_client()
+                .await
+                .map_err(Into::<blueprint_sdk::Error>::into)?;
+            let storage = client
+                .subxt_client()
+                .storage()
+                .at_latest()
+                .await
+                .map_err(|e| {
+                    Error::NetworkError(format!("Failed to read Tangle storage: {}", e))
+                })?;
+            // Find the register_circuit calls in a single pass over the
+            // service's job calls, rather than one lookup per result
+            let mut calls = storage
+                .iter(api::storage().services().job_calls_iter1(service_id))
+                .await
+                .map_err(|e| Error::NetworkError(format!("Failed to list job calls: {}", e)))?;
+            let mut register_calls = HashSet::new();
+            while let Some(entry) = calls.next().await {
+                let entry = entry
+                    .map_err(|e| Error::NetworkError(format!("Failed to read job call: {}", e)))?;
+                if entry.value.job == REGISTER_CIRCUIT_JOB_ID {
+                    register_calls.insert(entry.keys.1.into_key());
+                }
+            }
+
+            let mut results = storage
+                .iter(api::storage().services().job_results_iter1(service_id))
+                .await
+                .map_err(|e| Error::NetworkError(format!("Failed to list job results: {}", e)))?;
+            let mut circuits = Vec::new();
+            while let Some(entry) = results.next().await {
+                let result = entry
+                    .map_err(|e| Error::NetworkError(format!("Failed to read job result: {}", e)))?
+                    .value;
+                if !register_calls.contains(&result.call_id) {
+                    continue;
+                }
+                let circuit_id = result
+                    .result
+                    .0
+                    .first()
+                    .and_then(|field| from_field::<Vec<u8>>(field).ok())
+                    .and_then(|bytes| CircuitId::try_from(bytes.as_slice()).ok());
+                match circuit_id {
+                    Some(circuit_id) => circuits.push(circuit_id),
+                    None => tracing::warn!(
+                        call_id = result.call_id,
+                        "register_circuit result holds no circuit ID"
+                    ),
+                }
+            }
+            Ok::<_, Error>(circuits)
+        };
+        tokio::time::timeout(timeout, query).await.map_err(|_| {
+            Error::NetworkError(format!(
+                "On-chain circuit query timed out after {:?}",
+                timeout
+            ))
+        })?
+    }
+
+    /// Readiness of the subsystems, as served on `/readyz`.
+    pub fn readiness(&self) -> &Readiness {
+        &self.readiness
+    }
+
+    /// Confirms that the local node's key is among the registered service operators.
+    ///
+    /// Meant to be run once at startup: a node outside the operator set cannot
+    /// take part in any MPC session, and would otherwise only find out when its
+    /// first proof job fails. Logs an error and returns `false` if the key is missing.
+    /// The result is reported on `/readyz`.
+    pub async fn verify_membership(&self) -> Result<bool> {
+        let mpc_network_manager = self.try_mpc_network_manager()?;
+        let operators = self.get_operators().await?;
+        let is_member = operators
+            .iter()
+            .any(|operator| mpc_network_manager.is_local_key(operator));
+        self.readiness
+            .set_ready(Subsystem::OperatorMembership, is_member);
+        if is_member {
+            tracing::info!(
+                num_operators = operators.len(),
+                "Local node is a registered service operator"
+            );
+        } else {
+            tracing::error!(
+                num_operators = operators.len(),
+                "Local node key is NOT in the service operator set; proof jobs will fail \
+                 until this node is registered as an operator"
+            );
+        }
+        Ok(is_member)
+    }
+
+    /// Queries the service operators as (account ID, public key) pairs.
+    async fn query_operators(&self) -> Result<Vec<([u8; 32], K::Public)>> {
+        if let OperatorSource::Static(operators) = &self.config.operator_source {
+            return self.static_operators(operators);
+        }
+        let timeout = self.config.operator_query_timeout;
+        let query = async {
+            self.environment
+                .tangle_client()
+                .await
+                .map_err(Into::<blueprint_sdk::Error>::into)?
+                .get_operators()
+                .await
+                .map_err(Into::<blueprint_sdk::Error>::into)
+        };
+        let operators = tokio::time::timeout(timeout, query).await.map_err(|_| {
+            Error::NetworkError(format!("Operator query timed out after {:?}", timeout))
+        })??;
+
+        let max_operators = self.config.max_operators;
+        if operators.len() > max_operators {
+            return Err(Error::NetworkError(format!(
+                "Service reports {} operators, exceeding the maximum of {}",
+                operators.len(),
+                max_operators
+            )));
+        }
+
+        operators
+            .iter()
+            .map(|(account, k)| Ok((account.0, decode_operator_key::<K>(&account.0, &k.0)?)))
+            .collect()
+    }
+
+    /// ID of the Tangle service instance this operator serves.
+    fn service_id(&self) -> Result<u64> {
+        self.environment
+            .protocol_settings
+            .tangle()
+            .map_err(|e| Error::ConfigError(format!("No Tangle protocol settings: {}", e)))?
+            .service_id
+            .ok_or_else(|| Error::ConfigError("No Tangle service ID configured".to_string()))
+    }
+
+    /// Decodes a configured static operator set.
+    fn static_operators(&self, operators: &[StaticOperator]) -> Result<Vec<([u8; 32], K::Public)>> {
+        let max_operators = self.config.max_operators;
+        if operators.len() > max_operators {
+            return Err(Error::ConfigError(format!(
+                "{} static operators configured, exceeding the maximum of {}",
+                operators.len(),
+                max_operators
+            )));
+        }
+        operators
+            .iter()
+            .map(|operator| {
+                let key = K::Public::from_bytes(&operator.public_key).map_err(|_| {
+                    Error::ConfigError(format!(
+                        "Invalid public key for static operator {}",
+                        hex::encode(operator.account)
+                    ))
+                })?;
+                Ok((operator.account, key))
+            })
+            .collect()
+    }
+}
+
+/// Decodes the public key a service operator registered on-chain. A malformed
+/// key fails the operator lookup with `Error::NetworkError`.
+fn decode_operator_key<K: KeyType>(account: &[u8; 32], key: &[u8]) -> Result<K::Public> {
+    K::Public::from_bytes(key).map_err(|_| {
+        Error::NetworkError(format!(
+            "Service operator {} registered an invalid public key",
+            hex::encode(account)
+        ))
+    })
+}
+
+/// Passes on a started p2p network, or with `register_only_fallback` logs a
+/// failure to start it and returns `None`, so the context starts in
+/// register-only mode.
+fn fallback_to_register_only<T>(
+    started: Result<T>,
+    register_only_fallback: bool,
+) -> Result<Option<T>> {
+    match started {
+        Ok(network) => Ok(Some(network)),
+        Err(e) if register_only_fallback => {
+            tracing::error!(
+                error = %e,
+                "p2p network failed to start; serving registrations only, proof jobs will fail"
+            );
+            Ok(None)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Starts the libp2p network under `protocol_name`, returning its handle and
+/// the local instance key.
+fn start_p2p_network<K: KeyType>(
+    environment: &BlueprintEnvironment,
+    protocol_name: &str,
+) -> Result<(NetworkServiceHandle<K>, K::Public)>
+where
+    K::Public: Unpin,
+{
+    let network_config = environment
+        .libp2p_network_config(protocol_name, false)
+        .map_err(Into::<blueprint_sdk::Error>::into)?;
+    let local_verification_key = K::public_from_secret(&network_config.instance_key_pair);
+    // TODO: Fetch allowed keys dynamically if needed, e.g., from Tangle
+    // For now, assume AllowAll or configuration via environment
+    let allowed_keys =
+        blueprint_sdk::networking::AllowedKeys::InstancePublicKeys(Default::default());
+    let (allowed_keys_tx, allowed_keys_rx) = crossbeam_channel::unbounded(); // Required by libp2p_start_network
+
+    let network_handle = environment
+        .libp2p_start_network(network_config, allowed_keys, allowed_keys_rx)
+        .map_err(Into::<blueprint_sdk::Error>::into)?;
+    Ok((network_handle, local_verification_key))
+}
+
+/// Sets up the MPC network manager on top of a started p2p network, reading the
+/// MPC-Net address and certificate paths from the environment.
+fn mpc_network_manager_from_env<K: KeyType>(
+    network_handle: NetworkServiceHandle<K>,
+    local_verification_key: K::Public,
+    data_dir: &Path,
+    config: &ServiceConfig,
+) -> Result<MpcNetworkManager<K>>
+where
+    K::Public: Unpin,
+{
+    // These should ideally come from secure configuration
+    let mpc_listen_dns = std::env::var("MPC_LISTEN_DNS").map_err(|_| {
+        Error::MissingConfiguration("MPC_LISTEN_DNS environment variable not set".to_string())
+    })?;
+    let mpc_bind_addr: Option<SocketAddr> = std::env::var("MPC_BIND_ADDR")
+        .ok()
+        .map(|addr| addr.parse())
+        .transpose()
+        .map_err(|_| Error::InvalidInput("Invalid MPC_BIND_ADDR format".to_string()))?;
+    let listen_address = MpcListenAddress::new(&mpc_listen_dns, mpc_bind_addr)?;
+    let key_path = data_dir.join(
+        std::env::var("MPC_KEY_PATH").unwrap_or_else(|_| "mpc_certs/mpc_key.der".to_string()),
+    );
+    let cert_path = data_dir.join(
+        std::env::var("MPC_CERT_PATH").unwrap_or_else(|_| "mpc_certs/mpc_cert.der".to_string()),
+    );
+
+    // Ensure certificates directory exists
+    if let Some(parent) = key_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    if let Some(parent) = cert_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    if config.generate_missing_certs {
+        ensure_mpc_cert(&cert_path, &key_path)?;
+    } else if !cert_path.is_file() || !key_path.is_file() {
+        return Err(Error::MissingConfiguration(format!(
+            "MPC-Net certificate ({}) or key ({}) not found; check MPC_CERT_PATH and MPC_KEY_PATH",
+            Redacted(&cert_path),
+            Redacted(&key_path)
+        )));
+    }
+
+    // Peers would reject a certificate the operator CA did not issue in every session
+    let operator_ca = config
+        .operator_ca_path
+        .as_ref()
+        .map(|ca_path| -> Result<OperatorCa> {
+            let operator_ca = OperatorCa::load(&data_dir.join(ca_path))?;
+            operator_ca.verify(&read_cert(&cert_path)?).map_err(|e| {
+                Error::ConfigError(format!(
+                    "MPC-Net certificate ({}) is not usable with MPC_OPERATOR_CA_PATH: {}",
+                    Redacted(&cert_path),
+                    e
+                ))
+            })?;
+            Ok(operator_ca)
+        })
+        .transpose()?;
+
+    let mut mpc_network_manager = MpcNetworkManager::new(
+        network_handle,
+        VerificationIdentifierKey::InstancePublicKey(local_verification_key),
+        listen_address,
+        key_path,
+        cert_path,
+    )
+    .with_commitment_scheme(config.commitment_scheme)
+    .with_announce_max_skew(config.announce_max_skew)
+    .with_mpc_net_timeout(config.mpc_net_timeout)
+    .with_session_eviction(config.session_eviction);
+    if let Some(operator_ca) = operator_ca {
+        mpc_network_manager = mpc_network_manager.with_operator_ca(operator_ca);
+    }
+    if let Some(timeout) = config.liveness_timeout {
+        mpc_network_manager = mpc_network_manager.with_liveness_check(timeout);
+    }
+    Ok(mpc_network_manager)
+}
+
+/// Periodically evicts expired and excess proofs from the proof cache until `cancel` fires.
+fn spawn_proof_cache_sweeper(
+    circuit_store: CircuitStore,
+    ttl: Duration,
+    max_entries: usize,
+    interval: Duration,
+    cancel: CancellationToken,
+) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            tokio::select! {
+                _ = cancel.cancelled() => break,
+                _ = ticker.tick() => {}
+            }
+            let store = circuit_store.clone();
+            match tokio::task::spawn_blocking(move || store.sweep_proof_cache(ttl, max_entries))
+                .await
+            {
+                Ok(Ok(0)) => {}
+                Ok(Ok(evicted)) => tracing::debug!(evicted, "Swept proof cache"),
+                Ok(Err(e)) => tracing::warn!(error = %e, "Proof cache sweep failed"),
+                Err(e) => tracing::warn!(error = %e, "Proof cache sweep task panicked"),
+            }
+        }
+    });
+}
+
+/// Periodically removes expired proofs from the proof store until `cancel` fires.
+fn spawn_stored_proof_sweeper(
+    circuit_store: CircuitStore,
+    ttl: Duration,
+    interval: Duration,
+    cancel: CancellationToken,
+) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            tokio::select! {
+                _ = cancel.cancelled() => break,
+                _ = ticker.tick() => {}
+            }
+            let store = circuit_store.clone();
+            match tokio::task::spawn_blocking(move || store.sweep_stored_proofs(ttl)).await {
+                Ok(Ok(0)) => {}
+                Ok(Ok(removed)) => tracing::debug!(removed, "Swept proof store"),
+                Ok(Err(e)) => tracing::warn!(error = %e, "Proof store sweep failed"),
+                Err(e) => tracing::warn!(error = %e, "Proof store sweep task panicked"),
+            }
+        }
+    });
+}
+
+/// Discards idle artifact uploads until `cancel` fires, checking once per upload TTL.
+fn spawn_upload_sweeper(uploads: ArtifactUploads, cancel: CancellationToken) {
+    let interval = uploads.ttl().max(Duration::from_secs(1));
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            tokio::select! {
+                _ = cancel.cancelled() => break,
+                _ = ticker.tick() => {}
+            }
+            let uploads = uploads.clone();
+            match tokio::task::spawn_blocking(move || uploads.sweep_expired()).await {
+                Ok(Ok(0)) => {}
+                Ok(Ok(discarded)) => tracing::debug!(discarded, "Swept idle artifact uploads"),
+                Ok(Err(e)) => tracing::warn!(error = %e, "Artifact upload sweep failed"),
+                Err(e) => tracing::warn!(error = %e, "Artifact upload sweep task panicked"),
+            }
+        }
+    });
+}
+
+/// Evicts idle MPC sessions until `cancel` fires, checking once per idle grace period.
+fn spawn_session_evictor<K: KeyType>(manager: Arc<MpcNetworkManager<K>>, cancel: CancellationToken)
+where
+    K::Public: Unpin,
+{
+    let interval = manager
+        .session_eviction()
+        .idle_grace
+        .max(Duration::from_secs(1));
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            tokio::select! {
+                _ = cancel.cancelled() => break,
+                _ = ticker.tick() => {}
+            }
+            match manager.evict_idle_sessions().await {
+                0 => {}
+                evicted => tracing::debug!(evicted, "Evicted idle MPC sessions"),
+            }
+        }
+    });
+}
+
+/// Routes inbound libp2p messages until `cancel` fires, recording peer
+/// announcements and passing MPC protocol messages on.
+fn spawn_gossip_receiver<K: KeyType>(manager: Arc<MpcNetworkManager<K>>, cancel: CancellationToken)
+where
+    K::Public: Unpin,
+{
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(GOSSIP_POLL_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = cancel.cancelled() => break,
+                _ = ticker.tick() => {}
+            }
+            match manager.receive_gossip().await {
+                0 => {}
+                accepted => tracing::debug!(accepted, "Recorded MPC-Net announcements"),
+            }
+        }
+    });
+}
+
+/// Announces the local MPC-Net listener once libp2p discovery has settled,
+/// unless `cancel` fires first.
+fn spawn_ready_announcement<K: KeyType>(
+    manager: Arc<MpcNetworkManager<K>>,
+    settle: Duration,
+    cancel: CancellationToken,
+) where
+    K::Public: Unpin,
+{
+    // Announce regardless after this long, even if peers keep joining
+    let timeout = settle.saturating_mul(12);
+    tokio::spawn(async move {
+        let peers = tokio::select! {
+            _ = cancel.cancelled() => return,
+            peers = manager.wait_for_stable_peers(settle, timeout) => peers,
+        };
+        tracing::debug!(peers, "libp2p discovery settled");
+        if let Err(e) = manager.announce_ready() {
+            tracing::warn!(error = %e, "Failed to announce MPC-Net listener");
+        }
+    });
+}
+
+/// Periodically compacts the circuit store until `cancel` fires.
+fn spawn_store_compactor(
+    circuit_store: CircuitStore,
+    interval: Duration,
+    cancel: CancellationToken,
+) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            tokio::select! {
+                _ = cancel.cancelled() => break,
+                _ = ticker.tick() => {}
+            }
+            let store = circuit_store.clone();
+            match tokio::task::spawn_blocking(move || store.compact()).await {
+                Ok(Ok(report)) => tracing::info!(?report, "Compacted circuit store"),
+                Ok(Err(e)) => tracing::warn!(error = %e, "Circuit store compaction failed"),
+                Err(e) => tracing::warn!(error = %e, "Circuit store compaction task panicked"),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn falls_back_to_register_only_when_enabled() {
+        let failed = || Err::<(), _>(Error::NetworkError("no listen address".to_string()));
+        assert!(matches!(
+            fallback_to_register_only(failed(), true),
+            Ok(None)
+        ));
+        assert!(matches!(
+            fallback_to_register_only(failed(), false),
+            Err(Error::NetworkError(_))
+        ));
+        assert!(matches!(
+            fallback_to_register_only(Ok(()), true),
+            Ok(Some(()))
+        ));
+        assert!(matches!(
+            fallback_to_register_only(Ok(()), false),
+            Ok(Some(()))
+        ));
+    }
+
+    #[test]
+    fn rejects_invalid_onchain_operator_keys() {
+        use blueprint_sdk::crypto::sp_core::SpEcdsa;
+
+        let account = [7u8; 32];
+        let key = SpEcdsa::public_from_secret(&SpEcdsa::generate_with_seed(None).unwrap());
+        let decoded = decode_operator_key::<SpEcdsa>(&account, &key.to_bytes()).unwrap();
+        assert_eq!(decoded, key);
+
+        assert!(matches!(
+            decode_operator_key::<SpEcdsa>(&account, &[1u8; 5]),
+            Err(Error::NetworkError(msg)) if msg.contains(&hex::encode(account))
+        ));
     }
 }
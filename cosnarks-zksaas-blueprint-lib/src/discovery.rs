@@ -0,0 +1,211 @@
+//! Tracking of MPC-Net listener announcements gossiped by peers, and of how
+//! reliably each peer has taken part in MPC sessions.
+
+use crate::error::{Error, Result};
+use crate::types::{MpcNetAnnounce, PublicKeyBytes};
+use blueprint_sdk::crypto::KeyType;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Latest accepted announcement of each peer, with replay protection.
+///
+/// An announcement's `nonce` is the sender's Unix time in milliseconds. It is
+/// accepted only if it is strictly newer than the last one accepted from that
+/// peer and within `max_skew` of the local clock, so a captured announcement
+/// can neither be replayed later nor override a newer one.
+pub struct DiscoveryState<K: KeyType>
+where
+    K::Public: Ord,
+{
+    max_skew: Duration,
+    peers: BTreeMap<K::Public, MpcNetAnnounce<K>>,
+}
+
+impl<K: KeyType> DiscoveryState<K>
+where
+    K::Public: Ord,
+{
+    /// Creates an empty state tolerating `max_skew` between peer clocks.
+    pub fn new(max_skew: Duration) -> Self {
+        Self {
+            max_skew,
+            peers: BTreeMap::new(),
+        }
+    }
+
+    /// Validates and records an announcement received at local time `now_ms`.
+    ///
+    /// Fails with `Error::StaleAnnouncement` if the nonce does not advance past
+    /// the peer's last accepted nonce or lies outside the clock-skew window.
+    pub fn accept(&mut self, announce: MpcNetAnnounce<K>, now_ms: u64) -> Result<()> {
+        let max_skew_ms = self.max_skew.as_millis() as u64;
+        if now_ms.abs_diff(announce.nonce) > max_skew_ms {
+            return Err(Error::StaleAnnouncement(format!(
+                "nonce {} is outside the {:?} window around local time {}",
+                announce.nonce, self.max_skew, now_ms
+            )));
+        }
+        if let Some(last) = self.peers.get(&announce.public_key) {
+            if announce.nonce <= last.nonce {
+                return Err(Error::StaleAnnouncement(format!(
+                    "nonce {} does not advance past last seen nonce {}",
+                    announce.nonce, last.nonce
+                )));
+            }
+        }
+        self.peers.insert(announce.public_key.clone(), announce);
+        Ok(())
+    }
+
+    /// Returns the latest accepted announcement of `peer`.
+    pub fn get(&self, peer: &K::Public) -> Option<&MpcNetAnnounce<K>> {
+        self.peers.get(peer)
+    }
+
+    /// Returns the last accepted nonce of `peer`.
+    pub fn last_nonce(&self, peer: &K::Public) -> Option<u64> {
+        self.peers.get(peer).map(|announce| announce.nonce)
+    }
+}
+
+/// How a peer has fared in the MPC sessions this operator ran with it.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PeerScore {
+    pub sessions_succeeded: u64,
+    pub sessions_failed: u64,
+    /// Config exchanges in which the peer was blamed for a `CommitmentMismatch`
+    pub cheating_incidents: u64,
+}
+
+impl PeerScore {
+    /// Share of sessions with the peer that succeeded, smoothed towards 1/2 so
+    /// that a peer without history is neither favored nor penalized.
+    #[must_use]
+    pub fn reliability(&self) -> f64 {
+        let sessions = self.sessions_succeeded + self.sessions_failed;
+        (self.sessions_succeeded as f64 + 1.0) / (sessions as f64 + 2.0)
+    }
+}
+
+/// Per-peer reputation, keyed by public key bytes and shared by all clones.
+///
+/// Scores are local to this operator and only reported, never used to choose
+/// participants, which every operator must agree on.
+#[derive(Debug, Clone, Default)]
+pub struct PeerReputation {
+    scores: Arc<Mutex<BTreeMap<PublicKeyBytes, PeerScore>>>,
+}
+
+impl PeerReputation {
+    /// Records a session with `participants`, successful or not.
+    pub fn record_session(&self, participants: &[PublicKeyBytes], succeeded: bool) {
+        let mut scores = self.lock_scores();
+        for peer in participants {
+            let score = scores.entry(peer.clone()).or_default();
+            if succeeded {
+                score.sessions_succeeded += 1;
+            } else {
+                score.sessions_failed += 1;
+            }
+        }
+    }
+
+    /// Records that `peer` was caught cheating during a config exchange.
+    pub fn record_cheating(&self, peer: &PublicKeyBytes) {
+        self.lock_scores()
+            .entry(peer.clone())
+            .or_default()
+            .cheating_incidents += 1;
+    }
+
+    /// Returns the score of `peer`, empty if it never took part in a session.
+    pub fn score(&self, peer: &PublicKeyBytes) -> PeerScore {
+        self.lock_scores().get(peer).copied().unwrap_or_default()
+    }
+
+    /// Returns a copy of every peer's score, for metrics.
+    pub fn scores(&self) -> BTreeMap<PublicKeyBytes, PeerScore> {
+        self.lock_scores().clone()
+    }
+
+    fn lock_scores(&self) -> MutexGuard<'_, BTreeMap<PublicKeyBytes, PeerScore>> {
+        // Scores are plain counters, still meaningful after a panic mid-update
+        self.scores.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Current Unix time in milliseconds, as used for announcement nonces.
+pub fn announce_nonce_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blueprint_sdk::crypto::sp_core::SpEcdsa;
+    use std::path::PathBuf;
+
+    fn announce(public_key: <SpEcdsa as KeyType>::Public, nonce: u64) -> MpcNetAnnounce<SpEcdsa> {
+        MpcNetAnnounce {
+            public_key,
+            listen_addr: "127.0.0.1:9000".to_string(),
+            cert_path: PathBuf::from("cert.der"),
+            nonce,
+        }
+    }
+
+    #[test]
+    fn rejects_replayed_and_skewed_announcements() {
+        let peer = SpEcdsa::public_from_secret(&SpEcdsa::generate_with_seed(None).unwrap());
+        let mut state = DiscoveryState::<SpEcdsa>::new(Duration::from_secs(30));
+        let now = 1_700_000_000_000;
+
+        state.accept(announce(peer.clone(), now), now).unwrap();
+        // Replay of the same announcement, and an older one
+        assert!(matches!(
+            state.accept(announce(peer.clone(), now), now + 1),
+            Err(Error::StaleAnnouncement(_))
+        ));
+        assert!(matches!(
+            state.accept(announce(peer.clone(), now - 1), now + 1),
+            Err(Error::StaleAnnouncement(_))
+        ));
+        // Too far in the past or the future
+        assert!(matches!(
+            state.accept(announce(peer.clone(), now + 31_000), now),
+            Err(Error::StaleAnnouncement(_))
+        ));
+        assert!(matches!(
+            state.accept(announce(peer.clone(), now + 5_000), now + 60_000),
+            Err(Error::StaleAnnouncement(_))
+        ));
+
+        state
+            .accept(announce(peer.clone(), now + 5_000), now + 5_000)
+            .unwrap();
+        assert_eq!(state.last_nonce(&peer), Some(now + 5_000));
+    }
+
+    #[test]
+    fn reputation_counts_sessions_and_cheating_per_peer() {
+        let reputation = PeerReputation::default();
+        let (reliable, flaky, cheater) = (vec![1u8], vec![2u8], vec![3u8]);
+
+        reputation.record_session(&[reliable.clone(), flaky.clone(), cheater.clone()], true);
+        reputation.record_session(&[flaky.clone()], false);
+        reputation.record_session(&[flaky.clone()], false);
+        reputation.record_cheating(&cheater);
+        reputation.record_session(&[cheater.clone()], false);
+
+        assert_eq!(reputation.score(&reliable).sessions_succeeded, 1);
+        assert_eq!(reputation.score(&flaky).sessions_failed, 2);
+        assert_eq!(reputation.score(&cheater).cheating_incidents, 1);
+        assert_eq!(reputation.score(&vec![4u8]), PeerScore::default());
+        assert_eq!(reputation.scores().len(), 3);
+    }
+}
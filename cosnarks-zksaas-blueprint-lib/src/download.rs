@@ -0,0 +1,876 @@
+//! Artifact downloads shared by all registration jobs.
+
+use crate::error::{Error, Result};
+use crate::metrics::{TransferMetrics, TransferTotals};
+use reqwest::header::{HeaderName, HeaderValue};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tempfile::NamedTempFile;
+use tokio::sync::Semaphore;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, warn};
+use url::Url;
+
+/// Redirects followed per download, as in reqwest's default policy.
+const MAX_REDIRECTS: usize = 10;
+
+/// An HTTP header sent with every download from one host, e.g. the API key of
+/// private artifact storage.
+///
+/// The value is marked sensitive and never appears in `Debug` output or logs.
+#[derive(Clone, PartialEq, Eq)]
+pub struct DownloadHeader {
+    host: String,
+    name: HeaderName,
+    value: HeaderValue,
+}
+
+impl DownloadHeader {
+    /// Creates a header for downloads from `host` (matched case-insensitively).
+    ///
+    /// Fails with `Error::InvalidInput` if `name` or `value` is not a valid
+    /// HTTP header. The message names the invalid header, never its value.
+    pub fn new(host: &str, name: &str, value: &str) -> Result<Self> {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|_| Error::InvalidInput(format!("Invalid header name '{}'", name)))?;
+        let mut value = HeaderValue::from_str(value)
+            .map_err(|_| Error::InvalidInput(format!("Invalid value for header '{}'", name)))?;
+        value.set_sensitive(true);
+        Ok(Self {
+            host: host.trim().to_ascii_lowercase(),
+            name,
+            value,
+        })
+    }
+
+    /// Host this header is sent to.
+    #[must_use]
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    /// Name of the header.
+    #[must_use]
+    pub fn name(&self) -> &HeaderName {
+        &self.name
+    }
+}
+
+impl fmt::Debug for DownloadHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DownloadHeader")
+            .field("host", &self.host)
+            .field("name", &self.name)
+            .field("value", &"<redacted>")
+            .finish()
+    }
+}
+
+/// Per-host circuit breaker for artifact downloads.
+///
+/// After `failure_threshold` consecutive failed downloads from a host, the
+/// circuit for that host opens and further downloads from it fail immediately
+/// for `cooldown`. After the cooldown a single probe download is let through
+/// while the others keep failing fast; the circuit closes again if the host
+/// answers the probe and reopens if it fails. A probe that never reports back
+/// (e.g. a cancelled download) is replaced by another one a cooldown later.
+#[derive(Debug, Default)]
+struct HostCircuitBreaker {
+    // 0 disables the breaker
+    failure_threshold: u32,
+    cooldown: Duration,
+    hosts: Mutex<HashMap<String, HostHealth>>,
+}
+
+#[derive(Debug, Default)]
+struct HostHealth {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+    // A probe download is in flight while the circuit is half-open
+    probing: bool,
+}
+
+impl HostCircuitBreaker {
+    fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            failure_threshold,
+            cooldown,
+            hosts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Fails with `Error::NetworkError` if the circuit for `host` is open, or
+    /// half-open with another download probing the host.
+    fn check(&self, host: &str) -> Result<()> {
+        let Ok(mut hosts) = self.hosts.lock() else {
+            return Ok(());
+        };
+        let Some(health) = hosts.get_mut(host) else {
+            return Ok(());
+        };
+        match health.open_until {
+            Some(open_until) if Instant::now() < open_until => {
+                debug!(%host, probing = health.probing, "Skipping download from host with an open circuit");
+                Err(Error::NetworkError("host circuit-open".to_string()))
+            }
+            Some(_) => {
+                // Half-open: let this download probe the host, and hold the
+                // others back for another cooldown while it runs
+                debug!(%host, "Probing host after its circuit cooldown");
+                health.probing = true;
+                health.open_until = Some(Instant::now() + self.cooldown);
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Records the outcome of a download from `host`.
+    fn record(&self, host: &str, success: bool) {
+        if self.failure_threshold == 0 {
+            return;
+        }
+        let Ok(mut hosts) = self.hosts.lock() else {
+            return;
+        };
+        if success {
+            hosts.remove(host);
+            return;
+        }
+        let health = hosts.entry(host.to_string()).or_default();
+        health.consecutive_failures = health.consecutive_failures.saturating_add(1);
+        health.probing = false;
+        if health.consecutive_failures >= self.failure_threshold {
+            warn!(
+                %host,
+                failures = health.consecutive_failures,
+                cooldown = ?self.cooldown,
+                "Opening download circuit for failing host"
+            );
+            health.open_until = Some(Instant::now() + self.cooldown);
+        }
+    }
+}
+
+/// Downloads artifacts into a spool directory, bounding concurrency and size.
+///
+/// Every job shares the same downloader through the context, so its permits
+/// limit downloads across all in-flight registrations. Callers beyond the
+/// limit wait in FIFO order for a permit.
+#[derive(Debug, Clone)]
+pub struct ArtifactDownloader {
+    client: reqwest::Client,
+    permits: Arc<Semaphore>,
+    max_size: u64,
+    min_size: u64,
+    metrics: Arc<TransferMetrics>,
+    headers: Arc<Vec<DownloadHeader>>,
+    max_retries: u32,
+    retry_delay: Duration,
+    breaker: Arc<HostCircuitBreaker>,
+    allowed_hosts: Option<Arc<Vec<String>>>,
+}
+
+impl ArtifactDownloader {
+    /// Creates a downloader allowing `max_concurrent` simultaneous downloads
+    /// of at most `max_size` bytes each.
+    ///
+    /// Responses with a `gzip` or `deflate` `Content-Encoding` are decoded
+    /// transparently, so the size limit and any checksum apply to the decoded bytes.
+    pub fn new(max_concurrent: usize, max_size: u64) -> Self {
+        Self {
+            client: build_client(None, Arc::new(Vec::new())),
+            permits: Arc::new(Semaphore::new(max_concurrent.max(1))),
+            max_size,
+            min_size: 1,
+            metrics: Arc::new(TransferMetrics::default()),
+            headers: Arc::new(Vec::new()),
+            max_retries: 0,
+            retry_delay: Duration::from_secs(1),
+            breaker: Arc::new(HostCircuitBreaker::default()),
+            allowed_hosts: None,
+        }
+    }
+
+    /// Restricts downloads, including every redirect they follow, to
+    /// `allowed_hosts`. A redirect to any other host fails the download with
+    /// `Error::DisallowedUrl`. Any host is allowed by default.
+    #[must_use]
+    pub fn with_allowed_hosts(mut self, allowed_hosts: Option<Vec<String>>) -> Self {
+        self.allowed_hosts = allowed_hosts.map(Arc::new);
+        self.client = build_client(self.allowed_hosts.clone(), self.headers.clone());
+        self
+    }
+
+    /// Sets headers to send with downloads from their respective hosts.
+    ///
+    /// A download from a host with configured headers must not be redirected
+    /// to another host, which would receive the headers too; such a redirect
+    /// fails the download with `Error::DisallowedUrl`.
+    #[must_use]
+    pub fn with_headers(mut self, headers: Vec<DownloadHeader>) -> Self {
+        self.headers = Arc::new(headers);
+        self.client = build_client(self.allowed_hosts.clone(), self.headers.clone());
+        self
+    }
+
+    /// Sets how often a download failing with a network error or an error
+    /// status is retried and the delay before the first retry, which doubles
+    /// on every further attempt. Downloads are not retried by default.
+    #[must_use]
+    pub fn with_retries(mut self, max_retries: u32, retry_delay: Duration) -> Self {
+        self.max_retries = max_retries;
+        self.retry_delay = retry_delay;
+        self
+    }
+
+    /// Opens a host's circuit after `failure_threshold` consecutive failed
+    /// downloads from it, failing further downloads from that host with
+    /// `Error::NetworkError("host circuit-open")` for `cooldown` instead of
+    /// retrying them. Retries count as failures too. A threshold of 0 (the
+    /// default) disables the breaker.
+    #[must_use]
+    pub fn with_circuit_breaker(mut self, failure_threshold: u32, cooldown: Duration) -> Self {
+        self.breaker = Arc::new(HostCircuitBreaker::new(failure_threshold, cooldown));
+        self
+    }
+
+    /// Sets the minimum size of a downloaded artifact; 1 byte by default, so
+    /// empty responses are rejected.
+    #[must_use]
+    pub fn with_min_size(mut self, min_size: u64) -> Self {
+        self.min_size = min_size;
+        self
+    }
+
+    /// Bytes and downloads transferred by this downloader (and its clones) so far.
+    #[must_use]
+    pub fn transfer_totals(&self) -> TransferTotals {
+        self.metrics.totals()
+    }
+
+    /// Maximum size of a single artifact in bytes.
+    #[must_use]
+    pub fn max_size(&self) -> u64 {
+        self.max_size
+    }
+
+    /// Downloads `url` into a temporary file within `spool_dir`.
+    /// Downloads larger than the maximum size are aborted, and ones smaller
+    /// than the minimum size fail with `Error::ArtifactParseError`, so a
+    /// misconfigured server is caught at registration rather than at proving.
+    ///
+    /// The download (or the wait for a permit) is aborted as soon as `cancel`
+    /// fires. Dropping the in-progress future drops the spool file with it, so
+    /// no partial download is left behind.
+    ///
+    /// Connection errors, timeouts, 5xx statuses and `429 Too Many Requests`
+    /// are retried as configured with [`Self::with_retries`], unless the host's
+    /// circuit opens in the meantime (see [`Self::with_circuit_breaker`]). Other
+    /// error statuses fail with `Error::InvalidInput` without a retry.
+    pub async fn download(
+        &self,
+        url: &Url,
+        spool_dir: &Path,
+        cancel: &CancellationToken,
+    ) -> Result<NamedTempFile> {
+        let result = tokio::select! {
+            biased;
+            _ = cancel.cancelled() => {
+                warn!(%url, "Artifact download cancelled");
+                Err(Error::Cancelled(format!("Download of {} was cancelled", url)))
+            }
+            result = self.download_with_retries(url, spool_dir) => result,
+        };
+        self.metrics.record_download(result.is_ok());
+        result
+    }
+
+    async fn download_with_retries(&self, url: &Url, spool_dir: &Path) -> Result<NamedTempFile> {
+        let host = url.host_str().unwrap_or_default().to_ascii_lowercase();
+        let mut attempt = 0;
+        let mut delay = self.retry_delay;
+        loop {
+            self.breaker.check(&host)?;
+            match self.download_with_permit(url, spool_dir).await {
+                Ok(spool) => {
+                    self.breaker.record(&host, true);
+                    return Ok(spool);
+                }
+                Err(e) if is_host_failure(&e) => {
+                    self.breaker.record(&host, false);
+                    if attempt >= self.max_retries {
+                        return Err(e);
+                    }
+                    warn!(%url, attempt, error = %e, "Artifact download failed, retrying");
+                    tokio::time::sleep(delay).await;
+                    delay = delay.saturating_mul(2);
+                    attempt += 1;
+                }
+                Err(e) => {
+                    // The host answered; it is the download that was rejected
+                    self.breaker.record(&host, true);
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    async fn download_with_permit(&self, url: &Url, spool_dir: &Path) -> Result<NamedTempFile> {
+        let _permit = self
+            .permits
+            .acquire()
+            .await
+            .map_err(|_| Error::Internal("Download semaphore closed".to_string()))?;
+        debug!(%url, available = self.permits.available_permits(), "Acquired download permit");
+        let host = url.host_str().unwrap_or_default().to_ascii_lowercase();
+        let headers: Vec<&DownloadHeader> = self
+            .headers
+            .iter()
+            .filter(|header| header.host == host)
+            .collect();
+        if !headers.is_empty() {
+            let names: Vec<&str> = headers.iter().map(|header| header.name.as_str()).collect();
+            debug!(%url, ?names, "Sending configured download headers");
+        }
+        let spool = spool_artifact(
+            &self.client,
+            url,
+            &headers,
+            spool_dir,
+            self.max_size,
+            &self.metrics,
+        )
+        .await?;
+        validate_min_size(spool.as_file().metadata()?.len(), self.min_size)?;
+        Ok(spool)
+    }
+}
+
+/// Builds the HTTP client shared by a downloader's clones.
+///
+/// Redirects are followed up to [`MAX_REDIRECTS`] times, and only to hosts in
+/// `allowed_hosts` if it is set. reqwest re-sends request headers on every
+/// redirect, so a download from a host with configured `headers` only follows
+/// redirects within that host.
+fn build_client(
+    allowed_hosts: Option<Arc<Vec<String>>>,
+    headers: Arc<Vec<DownloadHeader>>,
+) -> reqwest::Client {
+    let policy = reqwest::redirect::Policy::custom(move |attempt| {
+        if attempt.previous().len() > MAX_REDIRECTS {
+            return attempt.error(Error::NetworkError("Too many redirects".to_string()));
+        }
+        let original = attempt.previous().first();
+        if let Some(Err(e)) =
+            original.map(|original| validate_header_redirect(original, attempt.url(), &headers))
+        {
+            return attempt.error(e);
+        }
+        match validate_allowed_host(
+            attempt.url(),
+            allowed_hosts.as_ref().map(|hosts| hosts.as_slice()),
+        ) {
+            Ok(()) => attempt.follow(),
+            Err(e) => attempt.error(e),
+        }
+    });
+    reqwest::Client::builder()
+        .gzip(true)
+        .deflate(true)
+        .redirect(policy)
+        .build()
+        .expect("Failed to build HTTP client")
+}
+
+/// Unwraps an error raised by the redirect policy of [`build_client`], so a
+/// rejected redirect is reported as such rather than as a network error.
+fn redirect_error(error: reqwest::Error) -> Error {
+    if error.is_redirect() {
+        let policy_error =
+            std::error::Error::source(&error).and_then(|source| source.downcast_ref::<Error>());
+        if let Some(Error::DisallowedUrl(message)) = policy_error {
+            return Error::DisallowedUrl(format!("Redirect rejected: {}", message));
+        }
+    }
+    error.into()
+}
+
+/// Returns whether `error` means the host failed to serve a download, as
+/// opposed to serving an artifact or a response that was rejected: a
+/// connection error, a timeout, or a status [`is_host_failure_status`] (which
+/// [`spool_artifact`] reports as `Error::NetworkError`).
+fn is_host_failure(error: &Error) -> bool {
+    match error {
+        Error::NetworkError(_) => true,
+        Error::ReqwestError(e) => e.is_connect() || e.is_timeout(),
+        _ => false,
+    }
+}
+
+/// Returns whether a response with `status` means the host is failing or
+/// overloaded, and the download may succeed if retried later.
+fn is_host_failure_status(status: reqwest::StatusCode) -> bool {
+    status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+}
+
+/// Rejects URLs whose host is not in `allowed_hosts` with `Error::DisallowedUrl`.
+/// Every URL passes if no allow-list is configured.
+pub(crate) fn validate_allowed_host(url: &Url, allowed_hosts: Option<&[String]>) -> Result<()> {
+    let Some(allowed_hosts) = allowed_hosts else {
+        return Ok(());
+    };
+    let host = url.host_str().unwrap_or_default().to_ascii_lowercase();
+    if allowed_hosts.iter().any(|allowed| *allowed == host) {
+        return Ok(());
+    }
+    Err(Error::DisallowedUrl(format!(
+        "Host '{}' of {} is not in the allowed artifact hosts",
+        host, url
+    )))
+}
+
+/// Rejects a redirect from `original` to another host with
+/// `Error::DisallowedUrl` if `headers` are configured for the host of `original`.
+fn validate_header_redirect(
+    original: &Url,
+    target: &Url,
+    headers: &[DownloadHeader],
+) -> Result<()> {
+    let original_host = original.host_str().unwrap_or_default().to_ascii_lowercase();
+    let target_host = target.host_str().unwrap_or_default().to_ascii_lowercase();
+    if original_host == target_host || !headers.iter().any(|header| header.host == original_host) {
+        return Ok(());
+    }
+    Err(Error::DisallowedUrl(format!(
+        "Host '{}' of {} is not the host the configured download headers are for",
+        target_host, target
+    )))
+}
+
+/// Rejects artifacts larger than `max_size` bytes.
+pub(crate) fn validate_artifact_size(size: u64, max_size: u64) -> Result<()> {
+    if size > max_size {
+        return Err(Error::InvalidInput(format!(
+            "Artifact is {} bytes, exceeding the maximum of {} bytes",
+            size, max_size
+        )));
+    }
+    Ok(())
+}
+
+/// Rejects downloads smaller than `min_size` bytes.
+fn validate_min_size(size: u64, min_size: u64) -> Result<()> {
+    if size == 0 && min_size > 0 {
+        return Err(Error::ArtifactParseError("empty artifact".to_string()));
+    }
+    if size < min_size {
+        return Err(Error::ArtifactParseError(format!(
+            "artifact is {} bytes, below the minimum of {} bytes",
+            size, min_size
+        )));
+    }
+    Ok(())
+}
+
+/// Streams the (decoded) response body for `url` chunk by chunk into a fresh temp file.
+async fn spool_artifact(
+    client: &reqwest::Client,
+    url: &Url,
+    headers: &[&DownloadHeader],
+    spool_dir: &Path,
+    max_size: u64,
+    metrics: &TransferMetrics,
+) -> Result<NamedTempFile> {
+    let mut request = client.get(url.clone());
+    for header in headers {
+        request = request.header(header.name.clone(), header.value.clone());
+    }
+    let mut response = request.send().await.map_err(redirect_error)?;
+    let status = response.status();
+    if is_host_failure_status(status) {
+        return Err(Error::NetworkError(format!(
+            "Failed to download artifact from {}: Status {}",
+            url, status
+        )));
+    }
+    if !status.is_success() {
+        return Err(Error::InvalidInput(format!(
+            "Failed to download artifact from {}: Status {}",
+            url, status
+        )));
+    }
+    // Unknown for encoded responses; the streamed check below covers those
+    if let Some(content_length) = response.content_length() {
+        validate_artifact_size(content_length, max_size)?;
+    }
+    let mut spool = NamedTempFile::new_in(spool_dir)?;
+    let mut downloaded: u64 = 0;
+    while let Some(chunk) = response.chunk().await? {
+        downloaded += chunk.len() as u64;
+        metrics.record_bytes(chunk.len() as u64);
+        validate_artifact_size(downloaded, max_size)?;
+        spool.write_all(&chunk)?;
+    }
+    spool.flush()?;
+    Ok(spool)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use httpmock::prelude::*;
+
+    #[test]
+    fn validates_allowed_hosts() {
+        let url = Url::parse("https://Artifacts.Example.com/circuit.r1cs").unwrap();
+        assert!(validate_allowed_host(&url, None).is_ok());
+        let allowed = vec!["artifacts.example.com".to_string()];
+        assert!(validate_allowed_host(&url, Some(&allowed)).is_ok());
+        let other = Url::parse("https://example.com/circuit.r1cs").unwrap();
+        assert!(matches!(
+            validate_allowed_host(&other, Some(&allowed)),
+            Err(Error::DisallowedUrl(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn decodes_gzip_content_encoding() {
+        let artifact = b"r1cs artifact bytes ".repeat(64);
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&artifact).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/circuit.r1cs");
+            then.status(200)
+                .header("Content-Encoding", "gzip")
+                .body(&compressed);
+        });
+
+        let spool_dir = tempfile::tempdir().unwrap();
+        let url = Url::parse(&server.url("/circuit.r1cs")).unwrap();
+        let downloader = ArtifactDownloader::new(1, artifact.len() as u64);
+        let spool = downloader
+            .download(&url, spool_dir.path(), &CancellationToken::new())
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read(spool.path()).unwrap(), artifact);
+        let totals = downloader.transfer_totals();
+        assert_eq!(totals.bytes_downloaded, artifact.len() as u64);
+        assert_eq!(totals.downloads_completed, 1);
+
+        // The size limit applies to the decoded bytes
+        let downloader = ArtifactDownloader::new(1, compressed.len() as u64);
+        let result = downloader
+            .download(&url, spool_dir.path(), &CancellationToken::new())
+            .await;
+        assert!(matches!(result, Err(Error::InvalidInput(_))));
+        assert_eq!(downloader.transfer_totals().downloads_failed, 1);
+    }
+
+    #[tokio::test]
+    async fn checks_every_redirect_against_allowed_hosts() {
+        let allowed = MockServer::start();
+        let other = MockServer::start();
+        let artifact = allowed.mock(|when, then| {
+            when.method(GET).path("/circuit.r1cs");
+            then.status(200).body("r1cs artifact");
+        });
+        let same_host = allowed.mock(|when, then| {
+            when.method(GET).path("/moved.r1cs");
+            then.status(302)
+                .header("Location", allowed.url("/circuit.r1cs"));
+        });
+        let cross_host = allowed.mock(|when, then| {
+            when.method(GET).path("/elsewhere.r1cs");
+            then.status(302).header(
+                "Location",
+                other.url("/circuit.r1cs").replace("127.0.0.1", "localhost"),
+            );
+        });
+        let redirected = other.mock(|when, then| {
+            when.method(GET).path("/circuit.r1cs");
+            then.status(200).body("r1cs artifact");
+        });
+
+        let spool_dir = tempfile::tempdir().unwrap();
+        let downloader = ArtifactDownloader::new(1, 1024)
+            .with_allowed_hosts(Some(vec!["127.0.0.1".to_string()]));
+        let url = Url::parse(&allowed.url("/moved.r1cs")).unwrap();
+        downloader
+            .download(&url, spool_dir.path(), &CancellationToken::new())
+            .await
+            .unwrap();
+        same_host.assert();
+        artifact.assert();
+
+        let url = Url::parse(&allowed.url("/elsewhere.r1cs")).unwrap();
+        let result = downloader
+            .download(&url, spool_dir.path(), &CancellationToken::new())
+            .await;
+        assert!(matches!(result, Err(Error::DisallowedUrl(_))));
+        cross_host.assert();
+        redirected.assert_hits(0);
+    }
+
+    #[tokio::test]
+    async fn limits_concurrent_downloads() {
+        let server = MockServer::start();
+        let slow = server.mock(|when, then| {
+            when.method(GET).path("/slow.r1cs");
+            then.status(200)
+                .body("r1cs artifact")
+                .delay(Duration::from_millis(200));
+        });
+
+        let spool_dir = tempfile::tempdir().unwrap();
+        let url = Url::parse(&server.url("/slow.r1cs")).unwrap();
+        let downloader = ArtifactDownloader::new(2, 1024);
+        let started = Instant::now();
+        let results = futures::future::join_all(
+            (0..4).map(|_| downloader.download(&url, spool_dir.path(), &CancellationToken::new())),
+        )
+        .await;
+        for result in results {
+            result.unwrap();
+        }
+        slow.assert_hits(4);
+        // Four downloads two at a time take at least two rounds
+        assert!(started.elapsed() >= Duration::from_millis(400));
+        assert_eq!(downloader.permits.available_permits(), 2);
+    }
+
+    #[tokio::test]
+    async fn sends_configured_headers_to_their_host_only() {
+        let server = MockServer::start();
+        let authorized = server.mock(|when, then| {
+            when.method(GET)
+                .path("/private.r1cs")
+                .header("x-api-key", "secret-key");
+            then.status(200).body("private artifact");
+        });
+
+        let header = DownloadHeader::new("127.0.0.1", "X-Api-Key", "secret-key").unwrap();
+        assert!(!format!("{:?}", header).contains("secret-key"));
+        let spool_dir = tempfile::tempdir().unwrap();
+        let url = Url::parse(&server.url("/private.r1cs")).unwrap();
+
+        let downloader = ArtifactDownloader::new(1, 1024).with_headers(vec![header]);
+        let spool = downloader
+            .download(&url, spool_dir.path(), &CancellationToken::new())
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read(spool.path()).unwrap(), b"private artifact");
+        authorized.assert();
+
+        // Headers for another host are not sent
+        let other =
+            DownloadHeader::new("artifacts.example.com", "X-Api-Key", "secret-key").unwrap();
+        let downloader = ArtifactDownloader::new(1, 1024).with_headers(vec![other]);
+        let result = downloader
+            .download(&url, spool_dir.path(), &CancellationToken::new())
+            .await;
+        // Without the header the request matches no mock, and a 404 is not a host failure
+        assert!(matches!(result, Err(Error::InvalidInput(ref msg)) if msg.contains("404")));
+
+        assert!(matches!(
+            DownloadHeader::new("127.0.0.1", "X-Api-Key", "line\nbreak"),
+            Err(Error::InvalidInput(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn does_not_follow_cross_host_redirects_with_configured_headers() {
+        let server = MockServer::start();
+        let other = MockServer::start();
+        let same_host = server.mock(|when, then| {
+            when.method(GET).path("/moved.r1cs");
+            then.status(302)
+                .header("Location", server.url("/private.r1cs"));
+        });
+        let private = server.mock(|when, then| {
+            when.method(GET)
+                .path("/private.r1cs")
+                .header("x-api-key", "secret-key");
+            then.status(200).body("private artifact");
+        });
+        let cross_host = server.mock(|when, then| {
+            when.method(GET).path("/elsewhere.r1cs");
+            then.status(302).header(
+                "Location",
+                other.url("/private.r1cs").replace("127.0.0.1", "localhost"),
+            );
+        });
+        let leaked = other.mock(|when, then| {
+            when.method(GET).path("/private.r1cs");
+            then.status(200).body("private artifact");
+        });
+
+        let header = DownloadHeader::new("127.0.0.1", "X-Api-Key", "secret-key").unwrap();
+        let spool_dir = tempfile::tempdir().unwrap();
+        let downloader = ArtifactDownloader::new(1, 1024).with_headers(vec![header]);
+
+        // Redirects within the host still carry the headers
+        let url = Url::parse(&server.url("/moved.r1cs")).unwrap();
+        downloader
+            .download(&url, spool_dir.path(), &CancellationToken::new())
+            .await
+            .unwrap();
+        same_host.assert();
+        private.assert();
+
+        let url = Url::parse(&server.url("/elsewhere.r1cs")).unwrap();
+        let result = downloader
+            .download(&url, spool_dir.path(), &CancellationToken::new())
+            .await;
+        assert!(matches!(result, Err(Error::DisallowedUrl(_))));
+        cross_host.assert();
+        leaked.assert_hits(0);
+
+        // Without headers for the host, the same redirect is followed
+        let downloader = ArtifactDownloader::new(1, 1024);
+        downloader
+            .download(&url, spool_dir.path(), &CancellationToken::new())
+            .await
+            .unwrap();
+        leaked.assert();
+    }
+
+    #[tokio::test]
+    async fn rejects_empty_and_undersized_artifacts() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/empty.r1cs");
+            then.status(200).body("");
+        });
+        server.mock(|when, then| {
+            when.method(GET).path("/short.r1cs");
+            then.status(200).body("r1cs");
+        });
+
+        let spool_dir = tempfile::tempdir().unwrap();
+        let downloader = ArtifactDownloader::new(1, 1024);
+        let download = |path: &str| {
+            let url = Url::parse(&server.url(path)).unwrap();
+            let downloader = downloader.clone();
+            let spool_dir = spool_dir.path().to_path_buf();
+            async move {
+                downloader
+                    .download(&url, &spool_dir, &CancellationToken::new())
+                    .await
+            }
+        };
+        match download("/empty.r1cs").await {
+            Err(Error::ArtifactParseError(msg)) => assert_eq!(msg, "empty artifact"),
+            other => panic!("unexpected result: {:?}", other.map(|_| ())),
+        }
+        assert!(download("/short.r1cs").await.is_ok());
+
+        let downloader = downloader.with_min_size(8);
+        let url = Url::parse(&server.url("/short.r1cs")).unwrap();
+        let result = downloader
+            .download(&url, spool_dir.path(), &CancellationToken::new())
+            .await;
+        assert!(matches!(result, Err(Error::ArtifactParseError(_))));
+        assert_eq!(downloader.transfer_totals().downloads_failed, 2);
+    }
+
+    #[tokio::test]
+    async fn opens_circuit_for_failing_hosts() {
+        let server = MockServer::start();
+        let failing = server.mock(|when, then| {
+            when.method(GET).path("/down.r1cs");
+            then.status(503);
+        });
+
+        let spool_dir = tempfile::tempdir().unwrap();
+        let url = Url::parse(&server.url("/down.r1cs")).unwrap();
+        let downloader = ArtifactDownloader::new(1, 1024)
+            .with_retries(1, Duration::from_millis(1))
+            .with_circuit_breaker(3, Duration::from_millis(200));
+        let download = || downloader.download(&url, spool_dir.path(), &CancellationToken::new());
+
+        let is_circuit_open = |result: Result<NamedTempFile>| matches!(result, Err(Error::NetworkError(msg)) if msg == "host circuit-open");
+
+        // A download and its retry fail; the next download's first failure opens
+        // the circuit, which cuts its retry short
+        assert!(matches!(download().await, Err(Error::NetworkError(msg)) if msg.contains("503")));
+        failing.assert_hits(2);
+        assert!(is_circuit_open(download().await));
+        failing.assert_hits(3);
+        assert!(is_circuit_open(download().await));
+        failing.assert_hits(3);
+
+        // After the cooldown one request is let through, and its failure reopens the circuit
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        assert!(is_circuit_open(download().await));
+        failing.assert_hits(4);
+    }
+
+    #[tokio::test]
+    async fn lets_a_single_probe_through_after_cooldown() {
+        let server = MockServer::start();
+        let mut failing = server.mock(|when, then| {
+            when.method(GET).path("/flaky.r1cs");
+            then.status(429);
+        });
+
+        let spool_dir = tempfile::tempdir().unwrap();
+        let url = Url::parse(&server.url("/flaky.r1cs")).unwrap();
+        let downloader =
+            ArtifactDownloader::new(2, 1024).with_circuit_breaker(1, Duration::from_millis(100));
+        let download = || downloader.download(&url, spool_dir.path(), &CancellationToken::new());
+
+        assert!(matches!(download().await, Err(Error::NetworkError(msg)) if msg.contains("429")));
+        failing.assert_hits(1);
+        failing.delete();
+        let recovered = server.mock(|when, then| {
+            when.method(GET).path("/flaky.r1cs");
+            then.status(200)
+                .body(b"artifact")
+                .delay(Duration::from_millis(100));
+        });
+
+        // Only one of two concurrent downloads probes the half-open circuit
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        let (first, second) = tokio::join!(download(), download());
+        assert_eq!(u8::from(first.is_ok()) + u8::from(second.is_ok()), 1);
+        recovered.assert_hits(1);
+
+        // The successful probe closed the circuit
+        assert!(download().await.is_ok());
+        recovered.assert_hits(2);
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_client_errors() {
+        let server = MockServer::start();
+        let missing = server.mock(|when, then| {
+            when.method(GET).path("/missing.r1cs");
+            then.status(404);
+        });
+
+        let spool_dir = tempfile::tempdir().unwrap();
+        let url = Url::parse(&server.url("/missing.r1cs")).unwrap();
+        let downloader = ArtifactDownloader::new(1, 1024)
+            .with_retries(3, Duration::from_millis(1))
+            .with_circuit_breaker(1, Duration::from_secs(60));
+        let download = || downloader.download(&url, spool_dir.path(), &CancellationToken::new());
+
+        // A 404 is neither retried nor counted against the host
+        for hits in 1..=2 {
+            assert!(
+                matches!(download().await, Err(Error::InvalidInput(msg)) if msg.contains("404"))
+            );
+            missing.assert_hits(hits);
+        }
+    }
+}
@@ -1,4 +1,6 @@
 use crate::p2p::Blame;
+use round_based::PartyIndex;
+use std::time::Duration;
 use thiserror::Error;
 
 // Alias for Result used throughout the library
@@ -31,6 +33,18 @@ pub enum Error {
     #[error("Networking Error: {0}")]
     NetworkError(String),
 
+    #[error("MPC-Net TLS Handshake Failed: {0}")]
+    MpcNetTlsHandshake(String),
+
+    #[error("MPC-Net Certificate Rejected: {0}")]
+    MpcNetCertificate(String),
+
+    #[error("MPC-Net Connection Refused: {0}")]
+    MpcNetConnectionRefused(String),
+
+    #[error("MPC-Net Connection Timed Out: {0}")]
+    MpcNetTimeout(String),
+
     #[error("Invalid Input: {0}")]
     InvalidInput(String),
 
@@ -40,21 +54,107 @@ pub enum Error {
     #[error("Invalid URL: {0}")]
     UrlParseError(#[from] url::ParseError),
 
+    #[error("Disallowed URL: {0}")]
+    DisallowedUrl(String),
+
+    #[error("Artifact Parse Error: {0}")]
+    ArtifactParseError(String),
+
     #[error("Incompatible Circuit Type/Proving Backend: {0}")]
     IncompatibleBackend(String),
 
+    #[error("Missing Proving Key for circuit {circuit_id}")]
+    MissingProvingKey { circuit_id: String },
+
+    #[error("Key Generation Timeout: exceeded {timeout:?} for circuit {circuit_id}")]
+    KeyGenerationTimeout {
+        circuit_id: String,
+        timeout: std::time::Duration,
+    },
+
+    #[error("Insufficient Participants: {0}")]
+    InsufficientParticipants(String),
+
     #[error("MPC Protocol Error: {0}")]
     MpcProtocolError(String),
 
+    #[error("Proof Self-Check Failed: {0}")]
+    ProofSelfCheckFailed(String),
+
     #[error("Commitment Mismatch - Cheating Detected: {guilty_parties:?}")]
     CommitmentMismatch { guilty_parties: Vec<Blame> },
 
+    #[error("Excess Messages: {guilty_parties:?} sent more messages than a round allows")]
+    ExcessMessages { guilty_parties: Vec<Blame> },
+
+    #[error(
+        "Unresponsive Parties: {parties:?} did not answer the liveness check within {timeout:?}"
+    )]
+    UnresponsiveParties {
+        parties: Vec<PartyIndex>,
+        timeout: Duration,
+    },
+
+    #[error("MPC Session Failed after {} attempts: {}", .attempts.len(), .attempts.join("; "))]
+    SessionRetriesExhausted { attempts: Vec<String> },
+
+    #[error("Stale Announcement: {0}")]
+    StaleAnnouncement(String),
+
+    #[error("Certificate Error: {0}")]
+    CertificateError(String),
+
     #[error("Invalid DNS Name Format: {0}")]
     InvalidDnsName(String),
 
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+
+    #[error("Operation Cancelled: {0}")]
+    Cancelled(String),
+
+    #[error("Deadline Exceeded: {0}")]
+    DeadlineExceeded(String),
+
     #[error("Round-based Protocol Error: {0}")]
     ExchangeRoundBasedError(String),
 
     #[error("Internal Error: {0}")]
     Internal(String),
 }
+
+impl Error {
+    /// Returns whether the error comes from the MPC layer and may go away with
+    /// a fresh session: networking failures, protocol failures and peers that
+    /// were unresponsive or cheated. Certificate rejections and cancellations
+    /// are not retried, since a new session would fail the same way.
+    #[must_use]
+    pub fn is_transient_mpc_failure(&self) -> bool {
+        matches!(
+            self,
+            Error::NetworkError(_)
+                | Error::MpcNetTlsHandshake(_)
+                | Error::MpcNetConnectionRefused(_)
+                | Error::MpcNetTimeout(_)
+                | Error::MpcProtocolError(_)
+                | Error::ExchangeRoundBasedError(_)
+                | Error::UnresponsiveParties { .. }
+                | Error::CommitmentMismatch { .. }
+                | Error::ExcessMessages { .. }
+        )
+    }
+
+    /// Returns the parties the error blames, if it names any.
+    #[must_use]
+    pub fn failed_parties(&self) -> Vec<PartyIndex> {
+        match self {
+            Error::UnresponsiveParties { parties, .. } => parties.clone(),
+            Error::CommitmentMismatch { guilty_parties }
+            | Error::ExcessMessages { guilty_parties } => guilty_parties
+                .iter()
+                .map(|blame| blame.guilty_party)
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
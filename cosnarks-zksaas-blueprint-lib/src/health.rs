@@ -0,0 +1,240 @@
+//! HTTP liveness (`/healthz`) and readiness (`/readyz`) probes, for running the
+//! service under an orchestrator such as Kubernetes.
+
+use crate::state::CircuitStore;
+use std::collections::BTreeSet;
+use std::net::SocketAddr;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_util::sync::CancellationToken;
+
+/// Largest probe request read; anything past the request line is ignored.
+const MAX_REQUEST_SIZE: usize = 1024;
+/// How long a client may take to send its request before it is dropped.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A subsystem `/readyz` reports on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Subsystem {
+    /// The circuit store's sled database is open and readable
+    Storage,
+    /// The libp2p network started
+    P2p,
+    /// The MPC-Net listen address could be bound at startup
+    MpcNet,
+    /// The local key is in the service operator set (`verify_membership`)
+    OperatorMembership,
+}
+
+impl Subsystem {
+    /// Every subsystem, in the order they are reported.
+    pub const ALL: [Subsystem; 4] = [
+        Subsystem::Storage,
+        Subsystem::P2p,
+        Subsystem::MpcNet,
+        Subsystem::OperatorMembership,
+    ];
+
+    /// Name of the subsystem in probe responses.
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Subsystem::Storage => "storage",
+            Subsystem::P2p => "p2p",
+            Subsystem::MpcNet => "mpc_net",
+            Subsystem::OperatorMembership => "operator_membership",
+        }
+    }
+}
+
+/// Readiness of the service's subsystems, shared by the context and the probe server.
+///
+/// Storage is checked on every probe; the other subsystems are marked ready
+/// by the code that brings them up.
+#[derive(Debug, Clone)]
+pub struct Readiness {
+    circuit_store: CircuitStore,
+    ready: Arc<RwLock<BTreeSet<Subsystem>>>,
+}
+
+impl Readiness {
+    /// Creates the readiness of a service storing circuits in `circuit_store`,
+    /// with no other subsystem ready yet.
+    pub fn new(circuit_store: CircuitStore) -> Self {
+        Self {
+            circuit_store,
+            ready: Arc::new(RwLock::new(BTreeSet::new())),
+        }
+    }
+
+    /// Records whether `subsystem` is ready.
+    pub fn set_ready(&self, subsystem: Subsystem, ready: bool) {
+        if let Ok(mut subsystems) = self.ready.write() {
+            if ready {
+                subsystems.insert(subsystem);
+            } else {
+                subsystems.remove(&subsystem);
+            }
+        }
+    }
+
+    /// Returns the subsystems that are not ready, in reporting order.
+    pub fn not_ready(&self) -> Vec<Subsystem> {
+        let storage_ok = self.circuit_store.check_health().is_ok();
+        let ready = match self.ready.read() {
+            Ok(ready) => ready.clone(),
+            Err(_) => BTreeSet::new(),
+        };
+        Subsystem::ALL
+            .into_iter()
+            .filter(|subsystem| match subsystem {
+                Subsystem::Storage => !storage_ok,
+                other => !ready.contains(other),
+            })
+            .collect()
+    }
+}
+
+/// Serves the probes on `addr` until `cancel` fires.
+pub async fn spawn_health_server(
+    addr: SocketAddr,
+    readiness: Readiness,
+    cancel: CancellationToken,
+) -> std::io::Result<SocketAddr> {
+    let listener = TcpListener::bind(addr).await?;
+    let local_addr = listener.local_addr()?;
+    tracing::info!(addr = %local_addr, "Serving /healthz and /readyz");
+    tokio::spawn(async move {
+        loop {
+            let stream = tokio::select! {
+                accepted = listener.accept() => match accepted {
+                    Ok((stream, _)) => stream,
+                    Err(e) => {
+                        tracing::warn!(error = %e, "Failed to accept health probe connection");
+                        continue;
+                    }
+                },
+                _ = cancel.cancelled() => break,
+            };
+            let readiness = readiness.clone();
+            tokio::spawn(async move {
+                if let Err(e) = handle_connection(stream, &readiness).await {
+                    tracing::debug!(error = %e, "Health probe connection failed");
+                }
+            });
+        }
+    });
+    Ok(local_addr)
+}
+
+/// Reads one request from `stream` and answers it.
+async fn handle_connection(mut stream: TcpStream, readiness: &Readiness) -> std::io::Result<()> {
+    let mut request = Vec::with_capacity(MAX_REQUEST_SIZE);
+    let mut buf = [0u8; 256];
+    while !request.windows(2).any(|window| window == b"\r\n") && request.len() < MAX_REQUEST_SIZE {
+        let read = tokio::time::timeout(REQUEST_TIMEOUT, stream.read(&mut buf))
+            .await
+            .map_err(|_| std::io::Error::from(std::io::ErrorKind::TimedOut))??;
+        if read == 0 {
+            break;
+        }
+        request.extend_from_slice(&buf[..read]);
+    }
+    let request_line = String::from_utf8_lossy(&request);
+    let mut parts = request_line.split_whitespace();
+    let (status, body) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some(path)) => respond(path, readiness),
+        _ => (405, serde_json::json!({ "error": "method not allowed" })),
+    };
+    let body = body.to_string();
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason_phrase(status),
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+/// Returns the status code and JSON body for a probe of `path`.
+fn respond(path: &str, readiness: &Readiness) -> (u16, serde_json::Value) {
+    match path {
+        "/healthz" => (200, serde_json::json!({ "status": "ok" })),
+        "/readyz" => {
+            let not_ready: Vec<&str> = readiness.not_ready().iter().map(Subsystem::name).collect();
+            let status = if not_ready.is_empty() { 200 } else { 503 };
+            (
+                status,
+                serde_json::json!({ "ready": not_ready.is_empty(), "not_ready": not_ready }),
+            )
+        }
+        _ => (404, serde_json::json!({ "error": "not found" })),
+    }
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        503 => "Service Unavailable",
+        _ => "",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn readyz_lists_subsystems_that_are_not_ready() {
+        let dir = tempfile::tempdir().unwrap();
+        let readiness = Readiness::new(CircuitStore::new(dir.path().to_path_buf()).unwrap());
+        readiness.set_ready(Subsystem::P2p, true);
+        readiness.set_ready(Subsystem::MpcNet, true);
+
+        let cancel = CancellationToken::new();
+        let addr = spawn_health_server(
+            SocketAddr::from(([127, 0, 0, 1], 0)),
+            readiness.clone(),
+            cancel.clone(),
+        )
+        .await
+        .unwrap();
+        // Returns the status line and the JSON body of a probe
+        let get = |path: &'static str| async move {
+            let mut stream = TcpStream::connect(addr).await.unwrap();
+            stream
+                .write_all(format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).as_bytes())
+                .await
+                .unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).await.unwrap();
+            let (head, body) = response.split_once("\r\n\r\n").unwrap();
+            let status_line = head.lines().next().unwrap().to_string();
+            (
+                status_line,
+                serde_json::from_str::<serde_json::Value>(body).unwrap(),
+            )
+        };
+
+        assert_eq!(get("/healthz").await.0, "HTTP/1.1 200 OK");
+        let (status_line, body) = get("/readyz").await;
+        assert_eq!(status_line, "HTTP/1.1 503 Service Unavailable");
+        assert_eq!(
+            body,
+            serde_json::json!({ "ready": false, "not_ready": ["operator_membership"] })
+        );
+
+        readiness.set_ready(Subsystem::OperatorMembership, true);
+        let (status_line, body) = get("/readyz").await;
+        assert_eq!(status_line, "HTTP/1.1 200 OK");
+        assert_eq!(body, serde_json::json!({ "ready": true, "not_ready": [] }));
+        assert_eq!(get("/metrics").await.0, "HTTP/1.1 404 Not Found");
+        cancel.cancel();
+    }
+}
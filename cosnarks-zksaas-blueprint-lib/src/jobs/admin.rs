@@ -0,0 +1,110 @@
+// Admin job handlers for operator introspection and recovery
+
+use crate::context::CosnarksContext;
+use crate::error::{Error, Result};
+use crate::types::{CircuitSyncReport, SessionStatus};
+use blueprint_sdk::crypto::KeyType;
+use blueprint_sdk::extract::Context;
+use blueprint_sdk::tangle::extract::{CallId, Caller, TangleArg, TangleResult};
+use tracing::{info, warn};
+
+/// Lists the MPC sessions currently cached by this operator.
+///
+/// Useful for debugging stuck proofs: each entry reports the session's
+/// participant count and when it was established and last used. Only service
+/// operators and the service owner may call this job; other callers get
+/// `Error::Unauthorized`.
+pub async fn list_sessions<K: KeyType>(
+    Context(ctx): Context<CosnarksContext<K>>,
+    CallId(call_id): CallId,
+    Caller(caller): Caller,
+) -> Result<TangleResult<Vec<SessionStatus>>>
+where
+    K::Public: Unpin,
+{
+    if !ctx.is_service_admin(&caller).await? {
+        warn!(%call_id, caller = %hex::encode(caller), "Rejected session listing from non-operator");
+        return Err(Error::Unauthorized(
+            "Only service operators and the service owner may list MPC sessions".to_string(),
+        ));
+    }
+
+    let sessions = ctx.try_mpc_network_manager()?.active_sessions().await;
+    info!(%call_id, num_sessions = sessions.len(), "Listing MPC sessions");
+    Ok(TangleResult(sessions))
+}
+
+/// Force-closes a wedged MPC session without restarting the operator.
+///
+/// Removes the cached handler and aborts any proof job still running on the
+/// session (`MpcNetworkManager::force_close`). Only operators of the service may
+/// call this job; other callers get `Error::Unauthorized`. Returns whether a
+/// session with this ID was cached.
+pub async fn force_close_session<K: KeyType>(
+    Context(ctx): Context<CosnarksContext<K>>,
+    CallId(call_id): CallId,
+    Caller(caller): Caller,
+    TangleArg(session_id): TangleArg<String>,
+) -> Result<TangleResult<bool>>
+where
+    K::Public: Unpin,
+{
+    if !ctx.is_service_operator(&caller).await? {
+        warn!(%call_id, caller = %hex::encode(caller), %session_id, "Rejected force-close from non-operator");
+        return Err(Error::Unauthorized(
+            "Only service operators may force-close MPC sessions".to_string(),
+        ));
+    }
+
+    let closed = ctx
+        .try_mpc_network_manager()?
+        .force_close(&session_id)
+        .await;
+    info!(%call_id, %session_id, closed, "Processed force-close request");
+    Ok(TangleResult(closed))
+}
+
+/// Reconciles the local `CircuitStore` against the circuits registered on-chain.
+///
+/// Reports the circuits registered on-chain that this operator is missing,
+/// which need re-registering (e.g. after joining late or losing its data),
+/// and the local circuits that were never registered on-chain. Nothing is
+/// downloaded or removed; fixing the difference is left to the operator. Only
+/// service operators and the service owner may call this job; other callers
+/// get `Error::Unauthorized`.
+pub async fn sync_circuits<K: KeyType>(
+    Context(ctx): Context<CosnarksContext<K>>,
+    CallId(call_id): CallId,
+    Caller(caller): Caller,
+) -> Result<TangleResult<CircuitSyncReport>>
+where
+    K::Public: Unpin,
+{
+    if !ctx.is_service_admin(&caller).await? {
+        warn!(%call_id, caller = %hex::encode(caller), "Rejected circuit sync from non-operator");
+        return Err(Error::Unauthorized(
+            "Only service operators and the service owner may sync circuits".to_string(),
+        ));
+    }
+
+    let onchain = ctx.get_onchain_circuits().await?;
+    let circuit_store = ctx.circuit_store().clone();
+    let local = tokio::task::spawn_blocking(move || {
+        circuit_store.list_circuit_ids().collect::<Result<Vec<_>>>()
+    })
+    .await
+    .map_err(|e| Error::Internal(format!("Circuit listing task failed: {}", e)))??;
+
+    let report = CircuitSyncReport::diff(&onchain, &local);
+    if report.is_in_sync() {
+        info!(%call_id, num_circuits = report.in_sync, "Local circuits match the on-chain registry");
+    } else {
+        warn!(
+            %call_id,
+            missing_locally = report.missing_locally.len(),
+            missing_onchain = report.missing_onchain.len(),
+            "Local circuits differ from the on-chain registry"
+        );
+    }
+    Ok(TangleResult(report))
+}
@@ -0,0 +1,41 @@
+// cancel_proof job handler: removes a queued proof job or aborts a running one
+
+use crate::context::CosnarksContext;
+use crate::error::{Error, Result};
+use crate::queue::CancelOutcome;
+use blueprint_sdk::crypto::KeyType;
+use blueprint_sdk::extract::Context;
+use blueprint_sdk::tangle::extract::{CallId, Caller, TangleArg, TangleResult};
+use tracing::{info, warn};
+
+/// Cancels the `generate_proof` job submitted with call ID `proof_call_id`.
+///
+/// A job still waiting for a proving slot is removed from the `ProofQueue`
+/// before any proving work starts; a running job is aborted. Only the account
+/// that submitted the job and operators of the service may cancel it; other
+/// callers get `Error::Unauthorized`.
+pub async fn cancel_proof<K: KeyType>(
+    Context(ctx): Context<CosnarksContext<K>>,
+    CallId(call_id): CallId,
+    Caller(caller): Caller,
+    TangleArg(proof_call_id): TangleArg<u64>,
+) -> Result<TangleResult<CancelOutcome>>
+where
+    K::Public: Unpin,
+{
+    let queue = ctx.proof_queue();
+    let Some(submitter) = queue.caller_of(proof_call_id)? else {
+        info!(%call_id, %proof_call_id, "No queued or running proof job to cancel");
+        return Ok(TangleResult(CancelOutcome::NotFound));
+    };
+    if submitter != caller && !ctx.is_service_operator(&caller).await? {
+        warn!(%call_id, caller = %hex::encode(caller), %proof_call_id, "Rejected proof cancellation from unrelated caller");
+        return Err(Error::Unauthorized(
+            "Only the proof's caller or a service operator may cancel it".to_string(),
+        ));
+    }
+
+    let outcome = queue.cancel(proof_call_id)?;
+    info!(%call_id, %proof_call_id, ?outcome, "Processed proof cancellation");
+    Ok(TangleResult(outcome))
+}
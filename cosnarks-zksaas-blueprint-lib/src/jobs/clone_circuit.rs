@@ -0,0 +1,35 @@
+// clone_circuit job handler: copies a registered circuit under a new name
+
+use crate::context::CosnarksContext;
+use crate::error::{Error, Result};
+use crate::jobs::register_circuit::validate_circuit_name;
+use crate::types::CircuitId;
+use blueprint_sdk::crypto::KeyType;
+use blueprint_sdk::extract::Context;
+use blueprint_sdk::tangle::extract::{CallId, TangleArgs2, TangleResult};
+use tracing::info;
+
+/// Clones the circuit `source_id` under `new_name`, reusing its stored artifacts and keys.
+///
+/// Faster than registering the variant from its source URL: nothing is
+/// downloaded and no keys are generated. Returns the new circuit's ID.
+pub async fn clone_circuit<K: KeyType>(
+    Context(ctx): Context<CosnarksContext<K>>,
+    CallId(call_id): CallId,
+    TangleArgs2(source_id, new_name): TangleArgs2<[u8; 32], String>,
+) -> Result<TangleResult<[u8; 32]>>
+where
+    K::Public: Unpin,
+{
+    info!(%call_id, source_id = %hex::encode(source_id), %new_name, "Cloning circuit");
+    let new_name = validate_circuit_name(&new_name)?;
+
+    let circuit_store = ctx.circuit_store().clone();
+    let circuit_id: CircuitId =
+        tokio::task::spawn_blocking(move || circuit_store.clone_circuit(&source_id, &new_name))
+            .await
+            .map_err(|e| Error::Internal(format!("Clone task failed: {}", e)))??;
+
+    info!(%call_id, circuit_id = %hex::encode(circuit_id), "Circuit cloned successfully.");
+    Ok(TangleResult(circuit_id))
+}
@@ -0,0 +1,226 @@
+// export_circuit job handler: chunked export of a circuit's public data for mirroring
+
+use crate::context::CosnarksContext;
+use crate::error::{Error, Result};
+use crate::state::CircuitStore;
+use crate::types::{CircuitId, ExportChunk};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use blueprint_sdk::crypto::KeyType;
+use blueprint_sdk::extract::Context;
+use blueprint_sdk::tangle::extract::{CallId, TangleArgs2, TangleResult};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+use tracing::info;
+
+/// At most this many encoded exports are kept in an [`ExportCache`].
+const MAX_CACHED_EXPORTS: usize = 4;
+
+/// A circuit export, encoded once for all of its chunks.
+#[derive(Debug)]
+struct EncodedExport {
+    updated_at: u64,
+    encoded: Vec<u8>,
+    checksum: [u8; 32],
+}
+
+impl EncodedExport {
+    fn new(encoded: Vec<u8>, updated_at: u64) -> Self {
+        Self {
+            checksum: Sha256::digest(&encoded).into(),
+            updated_at,
+            encoded,
+        }
+    }
+}
+
+/// Encoded exports of recently exported circuits, keyed by circuit and valid
+/// while its `updated_at` is unchanged, so fetching every chunk of an export
+/// does not re-read and re-encode the whole circuit.
+#[derive(Debug, Clone, Default)]
+pub struct ExportCache {
+    exports: Arc<Mutex<HashMap<CircuitId, Arc<EncodedExport>>>>,
+}
+
+impl ExportCache {
+    /// Returns the encoded export of `circuit_id`, encoding it unless a cached
+    /// one is still current. Does blocking I/O; call it from `spawn_blocking`.
+    fn get_or_encode(
+        &self,
+        circuit_store: &CircuitStore,
+        circuit_id: &CircuitId,
+    ) -> Result<Option<Arc<EncodedExport>>> {
+        let Some(info) = circuit_store.get_circuit_info(&hex::encode(circuit_id))? else {
+            return Ok(None);
+        };
+        if let Some(cached) = self.lock_exports().get(circuit_id) {
+            if cached.updated_at == info.updated_at {
+                return Ok(Some(cached.clone()));
+            }
+        }
+
+        let Some(export) = circuit_store.export_circuit(circuit_id)? else {
+            return Ok(None);
+        };
+        let encoded = Arc::new(EncodedExport::new(
+            bincode::serialize(&export)?,
+            export.updated_at,
+        ));
+        let mut exports = self.lock_exports();
+        if exports.len() >= MAX_CACHED_EXPORTS && !exports.contains_key(circuit_id) {
+            // Evict the export of the least recently updated circuit
+            if let Some(oldest) = exports
+                .iter()
+                .min_by_key(|(_, cached)| cached.updated_at)
+                .map(|(id, _)| *id)
+            {
+                exports.remove(&oldest);
+            }
+        }
+        exports.insert(*circuit_id, encoded.clone());
+        Ok(Some(encoded))
+    }
+
+    fn lock_exports(&self) -> std::sync::MutexGuard<'_, HashMap<CircuitId, Arc<EncodedExport>>> {
+        // Entries are immutable once inserted, still valid after a panic
+        self.exports.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Returns chunk `index` of the bincode-encoded `CircuitExport` of a circuit:
+/// its artifacts and verification keys, never its proving keys.
+///
+/// Each chunk holds up to `EXPORT_CHUNK_SIZE_BYTES` of the export, base64
+/// encoded. Clients fetch chunks `0..chunk_count`, decode and concatenate them
+/// and check the result against `checksum`; a changed `updated_at` means the
+/// circuit was rekeyed in between and the export must be restarted. The export
+/// is encoded once per `updated_at` and cached for the following chunks.
+pub async fn export_circuit<K: KeyType>(
+    Context(ctx): Context<CosnarksContext<K>>,
+    CallId(call_id): CallId,
+    TangleArgs2(circuit_id, index): TangleArgs2<CircuitId, u32>,
+) -> Result<TangleResult<ExportChunk>>
+where
+    K::Public: Unpin,
+{
+    let circuit_id_hex = hex::encode(circuit_id);
+    let circuit_store = ctx.circuit_store().clone();
+    let export_cache = ctx.export_cache().clone();
+    let export = tokio::task::spawn_blocking(move || {
+        export_cache.get_or_encode(&circuit_store, &circuit_id)
+    })
+    .await
+    .map_err(|e| Error::Internal(format!("Export task failed: {}", e)))??
+    .ok_or_else(|| Error::InvalidInput(format!("Circuit ID not found: {}", circuit_id_hex)))?;
+
+    let chunk = export_chunk(&export, index, ctx.config().export_chunk_size)?;
+    info!(%call_id, %circuit_id_hex, index, chunk_count = chunk.chunk_count, "Exported circuit chunk");
+    Ok(TangleResult(chunk))
+}
+
+/// Cuts chunk `index` of `chunk_size` bytes out of an encoded export.
+fn export_chunk(export: &EncodedExport, index: u32, chunk_size: u64) -> Result<ExportChunk> {
+    let encoded = &export.encoded;
+    let chunk_size = usize::try_from(chunk_size.max(1)).unwrap_or(usize::MAX);
+    let chunk_count = u32::try_from(encoded.len().div_ceil(chunk_size).max(1)).map_err(|_| {
+        Error::InvalidInput("Export has too many chunks; raise EXPORT_CHUNK_SIZE_BYTES".to_string())
+    })?;
+    if index >= chunk_count {
+        return Err(Error::InvalidInput(format!(
+            "Chunk {} is out of range, the export has {} chunks",
+            index, chunk_count
+        )));
+    }
+    let data = encoded
+        .chunks(chunk_size)
+        .nth(index as usize)
+        .unwrap_or_default();
+    Ok(ExportChunk {
+        index,
+        chunk_count,
+        total_size: encoded.len() as u64,
+        checksum: export.checksum,
+        updated_at: export.updated_at,
+        data: BASE64.encode(data),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ArtifactRole, BackendKeys, CircuitInfo, CircuitType, ProvingBackend};
+    use std::collections::BTreeMap;
+    use std::path::{Path, PathBuf};
+
+    fn store_circuit(store: &CircuitStore, id: CircuitId, updated_at: u64) {
+        let info = CircuitInfo {
+            id,
+            name: "exported".to_string(),
+            circuit_type: CircuitType::Circom,
+            artifact_paths: BTreeMap::from([(ArtifactRole::R1cs, PathBuf::from("circuit.r1cs"))]),
+            artifact_blobs: BTreeMap::new(),
+            backends: vec![BackendKeys {
+                backend: ProvingBackend::Groth16,
+                proving_key_path: PathBuf::from("groth16/proving.key"),
+                verification_key_path: PathBuf::from("groth16/verification.key"),
+                verifier_address: None,
+            }],
+            pinned_operators: Vec::new(),
+            dependencies: Vec::new(),
+            num_public_inputs: None,
+            noir_abi: None,
+            updated_at,
+            source_url: None,
+            artifact_checksum: [0; 32],
+        };
+        store
+            .store_circuit(
+                &info,
+                &[(Path::new("circuit.r1cs"), b"r1cs".as_slice())],
+                &[(b"pk".as_slice(), b"vk".as_slice())],
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn export_cache_reencodes_only_updated_circuits() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = CircuitStore::new(dir.path().to_path_buf()).unwrap();
+        let cache = ExportCache::default();
+        let id = [0x21; 32];
+        store_circuit(&store, id, 1);
+
+        let first = cache.get_or_encode(&store, &id).unwrap().unwrap();
+        let again = cache.get_or_encode(&store, &id).unwrap().unwrap();
+        assert!(Arc::ptr_eq(&first, &again));
+
+        // A rekey changes updated_at and invalidates the cached export
+        store_circuit(&store, id, 2);
+        let rekeyed = cache.get_or_encode(&store, &id).unwrap().unwrap();
+        assert!(!Arc::ptr_eq(&first, &rekeyed));
+        assert_eq!(rekeyed.updated_at, 2);
+        assert!(cache.get_or_encode(&store, &[0x22; 32]).unwrap().is_none());
+    }
+
+    #[test]
+    fn export_chunks_reassemble_to_the_export() {
+        let encoded: Vec<u8> = (0..=255u8).cycle().take(1000).collect();
+        let export = EncodedExport::new(encoded.clone(), 7);
+        let first = export_chunk(&export, 0, 300).unwrap();
+        assert_eq!(first.chunk_count, 4);
+        assert_eq!(first.total_size, 1000);
+
+        let mut reassembled = Vec::new();
+        for index in 0..first.chunk_count {
+            let chunk = export_chunk(&export, index, 300).unwrap();
+            assert_eq!(chunk.checksum, first.checksum);
+            reassembled.extend(BASE64.decode(chunk.data).unwrap());
+        }
+        assert_eq!(reassembled, encoded);
+        assert!(matches!(
+            export_chunk(&export, 4, 300),
+            Err(Error::InvalidInput(_))
+        ));
+    }
+}
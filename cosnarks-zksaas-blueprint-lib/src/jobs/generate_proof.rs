@@ -1,43 +1,280 @@
 // Placeholder for generate_proof job handler
 
+use crate::artifact::parse_r1cs_header;
 use crate::context::CosnarksContext;
+use crate::discovery::PeerReputation;
 use crate::error::{Error, Result};
-use crate::state::CircuitStore;
-use crate::types::{CircuitId, ProofResult, WitnessInput};
-use blueprint_sdk::crypto::KeyType;
+use crate::jobs::register_circuit::validate_public_input_count;
+use crate::metrics::{CheatingIncident, CheatingMetrics};
+use crate::network::build_party_mapping;
+use crate::p2p::cache::ProofCacheAgreement;
+use crate::p2p::deadline::DeadlineAgreement;
+use crate::prover::ProvingRequest;
+use crate::state::unix_now;
+use crate::types::{
+    ArtifactRole, CircuitId, CircuitInfo, CircuitType, ProofFailure, ProofMetadata, ProofOutput,
+    ProofPhase, ProofPointer, ProofResult, ProofTimings, ProvingBackend, PublicKeyBytes,
+    WitnessInput,
+};
+use crate::verify::has_fast_verifier;
+use crate::witness::{
+    InputSignals, decode_data_uri, is_data_uri, normalize_witness, validate_witness,
+};
+use blueprint_sdk::crypto::{BytesEncoding, KeyType};
 use blueprint_sdk::extract::Context;
 use blueprint_sdk::std::{
     collections::hash_map::DefaultHasher,
     hash::{Hash, Hasher},
 };
-use blueprint_sdk::tangle::extract::{CallId, TangleArgs2, TangleResult};
+use blueprint_sdk::tangle::extract::{CallId, Caller, TangleArgs7, TangleResult};
 use blueprint_sdk::{debug, info, warn};
-use hex;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use std::time::Instant;
 
-/// Wrapper function that extracts arguments from TangleArgs2 and calls the main implementation
+/// Wrapper function that extracts arguments from TangleArgs7 and calls the main implementation.
+///
+/// `proving_backend` selects which of the circuit's backends to prove with;
+/// `None` uses the first backend it was registered with. Callers rejected by
+/// the context's `ProofAuthorizer` get `Error::Unauthorized` before any work is done.
+/// Proofs larger than `proof_pointer_threshold` are returned as a `ProofPointer`
+/// to a copy in the proof store. At most `max_concurrent_proofs` jobs prove at
+/// once; the rest wait in the context's `ProofQueue`, where `cancel_proof` can
+/// remove them.
+///
+/// With `benchmark` set, the proof is returned together with a `ProofTimings`
+/// breakdown of the job's phases; otherwise the timings are `None`.
+///
+/// `operators` lets the requester choose which operators run the proof, by
+/// public key; they must all be live, and include the circuit's pinned
+/// operators if it has any. `None` uses every live operator. Operators left
+/// out of the requested or pinned set return no result for the job.
+///
+/// `rng_seed` seeds the prover's randomness, so identical inputs produce
+/// identical proofs, for reproducible tests. It is rejected with
+/// `Error::InvalidInput` unless `ALLOW_PROOF_RNG_SEED` is set: seeded proofs
+/// are linkable, which defeats their zero-knowledge unlinkability, so never
+/// enable it in production. Seeded jobs bypass the proof cache.
+///
+/// `deadline` is a Unix timestamp (seconds) the proof must be done by. The
+/// participants agree on the earliest deadline any of them was given, and all
+/// abort with `Error::DeadlineExceeded` once it passes. Deadlines that have
+/// already passed are rejected with `Error::InvalidInput`.
 pub async fn generate_proof_job<K: KeyType>(
     Context(ctx): Context<CosnarksContext<K>>,
     CallId(call_id): CallId,
-    TangleArgs2(circuit_id_bytes, witness_input): TangleArgs2<[u8; 32], WitnessInput>,
-) -> Result<TangleResult<ProofResult>>
+    Caller(caller): Caller,
+    TangleArgs7(
+        circuit_id_bytes,
+        proving_backend,
+        witness_input,
+        benchmark,
+        operators,
+        rng_seed,
+        deadline,
+    ): TangleArgs7<
+        [u8; 32],
+        Option<ProvingBackend>,
+        WitnessInput,
+        bool,
+        Option<Vec<PublicKeyBytes>>,
+        Option<u64>,
+        Option<u64>,
+    >,
+) -> Result<Option<TangleResult<(ProofOutput, Option<ProofTimings>)>>>
 where
     K::Public: Unpin,
 {
+    let job_started = Instant::now();
     // Convert CircuitId bytes if needed, depends on how CircuitId is used internally
     // Assuming CircuitId is used directly as [u8; 32] internally now
     let circuit_id: CircuitId = circuit_id_bytes;
+    if !ctx.proof_authorizer().authorize(&caller, &circuit_id) {
+        warn!(%call_id, caller = %hex::encode(caller), circuit_id = %hex::encode(circuit_id), "Rejected unauthorized proof request");
+        return Err(Error::Unauthorized(format!(
+            "Caller {} may not request proofs for circuit {}",
+            hex::encode(caller),
+            hex::encode(circuit_id)
+        )));
+    }
+    if rng_seed.is_some() && !ctx.config().allow_proof_rng_seed {
+        return Err(Error::InvalidInput(
+            "rng_seed is only accepted with ALLOW_PROOF_RNG_SEED set".to_string(),
+        ));
+    }
+    if let Some(deadline) = deadline {
+        if deadline <= unix_now() {
+            return Err(Error::InvalidInput(format!(
+                "Proof deadline {} has already passed",
+                deadline
+            )));
+        }
+    }
+    if !is_locally_selected(&ctx, &circuit_id, operators.as_deref())? {
+        info!(%call_id, circuit_id = %hex::encode(circuit_id), "Local operator is not selected for this proof, skipping");
+        return Ok(None);
+    }
 
+    let witness_started = Instant::now();
     let witness_data_str = match witness_input {
         WitnessInput::Json(json_str) => json_str,
+        // Inline witnesses need no network round trip
+        WitnessInput::Uri(uri_str) if is_data_uri(&uri_str) => {
+            decode_data_uri(&uri_str, ctx.config().max_witness_size)?
+        }
         WitnessInput::Uri(uri_str) => {
             // TODO: Implement downloading witness data from URI
             warn!(uri = %uri_str, "Witness URI download not implemented, using empty witness.");
             "{}".to_string() // Placeholder
         }
+        WitnessInput::ProverToml { prover_toml } => {
+            witness_from_prover_toml(&ctx, &circuit_id, &prover_toml)?
+        }
+    };
+    let witness_load = witness_started.elapsed();
+
+    // Wait for a proving slot; the job can be cancelled from here on
+    let permit = ctx.proof_queue().acquire(call_id, caller).await?;
+    let (result, mut timings) = tokio::select! {
+        result = generate_proof_timed(
+            ctx.clone(),
+            call_id,
+            circuit_id,
+            proving_backend,
+            witness_data_str,
+            operators,
+            rng_seed,
+            deadline,
+        ) => result?,
+        _ = permit.cancelled() => {
+            info!(%call_id, "Proof job cancelled while running");
+            return Err(Error::Cancelled(format!("Proof job {} was cancelled", call_id)));
+        }
     };
+    drop(permit);
+    let output = into_proof_output(&ctx, &circuit_id, result).await?;
 
-    let result = generate_proof(ctx, call_id, circuit_id, witness_data_str).await?;
-    Ok(TangleResult(result))
+    let timings = benchmark.then(|| {
+        timings.witness_load_ms = witness_load.as_millis() as u64;
+        timings.total_ms = job_started.elapsed().as_millis() as u64;
+        info!(%call_id, ?timings, "Benchmarked proof job");
+        timings
+    });
+    Ok(Some(TangleResult((output, timings))))
+}
+
+/// Returns whether the local operator takes part in a proof for `circuit_id`:
+/// it must be among the `requested` operators if any were named, or else among
+/// the circuit's pinned operators if it has any. Unknown circuits count as
+/// selected, so the proof job reports them.
+fn is_locally_selected<K: KeyType>(
+    ctx: &CosnarksContext<K>,
+    circuit_id: &CircuitId,
+    requested: Option<&[PublicKeyBytes]>,
+) -> Result<bool>
+where
+    K::Public: Unpin,
+{
+    let mpc_network_manager = ctx.try_mpc_network_manager()?;
+    let pinned_operators = ctx
+        .circuit_store()
+        .get_circuit_info(&hex::encode(circuit_id))?
+        .map(|info| info.pinned_operators)
+        .unwrap_or_default();
+    Ok(selects_local_operator(
+        requested,
+        &pinned_operators,
+        |key| {
+            K::Public::from_bytes(key)
+                .is_ok_and(|public_key| mpc_network_manager.is_local_key(&public_key))
+        },
+    ))
+}
+
+/// Whether the operators chosen from `requested`, else from `pinned` (all live
+/// operators if both are empty), include the one `is_local` recognizes.
+fn selects_local_operator(
+    requested: Option<&[PublicKeyBytes]>,
+    pinned: &[PublicKeyBytes],
+    is_local: impl Fn(&PublicKeyBytes) -> bool,
+) -> bool {
+    match requested {
+        Some(requested) => requested.iter().any(&is_local),
+        None if !pinned.is_empty() => pinned.iter().any(&is_local),
+        None => true,
+    }
+}
+
+/// Wraps `proof` for the job result, storing it and returning a pointer if its
+/// encoding exceeds the configured pointer threshold.
+async fn into_proof_output<K: KeyType>(
+    ctx: &CosnarksContext<K>,
+    circuit_id: &CircuitId,
+    proof: ProofResult,
+) -> Result<ProofOutput>
+where
+    K::Public: Unpin,
+{
+    let Some(threshold) = ctx.config().proof_pointer_threshold else {
+        return Ok(ProofOutput::Proof(proof));
+    };
+    let size = bincode::serialized_size(&proof)?;
+    if size <= threshold {
+        return Ok(ProofOutput::Proof(proof));
+    }
+
+    let circuit_store = ctx.circuit_store().clone();
+    let circuit_id = *circuit_id;
+    let proof_hash =
+        tokio::task::spawn_blocking(move || circuit_store.store_proof(&circuit_id, &proof))
+            .await
+            .map_err(|e| Error::Internal(format!("Proof store task failed: {}", e)))??;
+    info!(proof_hash = %hex::encode(proof_hash), size, "Returning pointer to stored proof");
+    Ok(ProofOutput::Pointer(ProofPointer {
+        proof_hash,
+        size,
+        retrieval_endpoint: ctx.config().proof_retrieval_endpoint.clone(),
+    }))
+}
+
+/// Converts a Noir `Prover.toml` into a JSON witness using the circuit's ABI.
+fn witness_from_prover_toml<K: KeyType>(
+    ctx: &CosnarksContext<K>,
+    circuit_id: &CircuitId,
+    prover_toml: &str,
+) -> Result<String>
+where
+    K::Public: Unpin,
+{
+    let circuit_id_hex = hex::encode(circuit_id);
+    let info = ctx
+        .circuit_store()
+        .get_circuit_info(&circuit_id_hex)?
+        .ok_or_else(|| Error::InvalidInput(format!("Circuit ID not found: {}", circuit_id_hex)))?;
+    ensure_record_matches(circuit_id, &info)?;
+    let abi = info.noir_abi.as_ref().ok_or_else(|| {
+        Error::IncompatibleBackend(format!(
+            "Circuit {} has no Noir ABI to read a Prover.toml against",
+            circuit_id_hex
+        ))
+    })?;
+    Ok(abi.witness_from_prover_toml(prover_toml)?.to_string())
+}
+
+/// Checks that the record stored under `circuit_id` describes that circuit, so
+/// a storage-layer mix-up cannot produce a proof for the wrong circuit.
+fn ensure_record_matches(circuit_id: &CircuitId, info: &CircuitInfo) -> Result<()> {
+    if info.id != *circuit_id {
+        warn!(
+            requested = %hex::encode(circuit_id),
+            stored = %hex::encode(info.id),
+            "Circuit record does not match its key"
+        );
+        return Err(Error::Internal("circuit id/record mismatch".to_string()));
+    }
+    Ok(())
 }
 
 /// Core implementation of the proof generation logic
@@ -45,72 +282,695 @@ pub async fn generate_proof<K: KeyType>(
     ctx: CosnarksContext<K>,
     call_id: u64,
     circuit_id: CircuitId, // Use [u8; 32] type directly
+    proving_backend: Option<ProvingBackend>,
     witness_data_json: String,
 ) -> Result<ProofResult>
 // Return standard ProofResult
 where
     K::Public: Unpin,
 {
+    let (proof, _) = generate_proof_timed(
+        ctx,
+        call_id,
+        circuit_id,
+        proving_backend,
+        witness_data_json,
+        None,
+        None,
+        None,
+    )
+    .await?;
+    Ok(proof)
+}
+
+/// Like [`generate_proof`], also returning the time spent in the session setup
+/// and proving phases. The witness load and total times are left for the caller.
+///
+/// `operators` restricts the session to the given live operators; `None` uses
+/// all of them (or the circuit's pinned operators). `rng_seed` makes the proof
+/// reproducible; see [`generate_proof_job`]. `deadline` applies from the start
+/// of every attempt and is agreed on with the participants during its config
+/// exchange.
+///
+/// With `MPC_SESSION_RETRIES` set, a transient MPC failure evicts the session
+/// and retries on a fresh session with the same participants. If every
+/// attempt fails, the error is
+/// `Error::SessionRetriesExhausted` listing each attempt's error.
+pub async fn generate_proof_timed<K: KeyType>(
+    ctx: CosnarksContext<K>,
+    call_id: u64,
+    circuit_id: CircuitId,
+    proving_backend: Option<ProvingBackend>,
+    witness_data_json: String,
+    operators: Option<Vec<PublicKeyBytes>>,
+    rng_seed: Option<u64>,
+    deadline: Option<u64>,
+) -> Result<(ProofResult, ProofTimings)>
+where
+    K::Public: Unpin,
+{
+    let mut timings = ProofTimings::default();
     let circuit_id_hex = hex::encode(circuit_id);
     info!(%call_id, %circuit_id_hex, "Starting proof generation");
+    // Fails in register-only mode, before any work is done
+    let mpc_network_manager = ctx.try_mpc_network_manager()?.clone();
 
-    // 1. Get the circuit information
-    let circuit_info = ctx
-        .circuit_store()
-        .get_circuit_info(&circuit_id_hex)? // Use hex ID for lookup if keys are hex strings
-        .ok_or_else(|| Error::InvalidInput(format!("Circuit ID not found: {}", circuit_id_hex)))?;
-    debug!(?circuit_info, "Found circuit info");
+    // 1. Snapshot the circuit information, artifacts and the selected backend's
+    //    keys, so a concurrent rekey cannot swap files underneath the proof.
+    //    Fails fast with MissingProvingKey if the proving key was lost.
+    let snapshot = {
+        let circuit_store = ctx.circuit_store().clone();
+        let circuit_id_hex = circuit_id_hex.clone();
+        tokio::task::spawn_blocking(move || {
+            circuit_store.snapshot(&circuit_id_hex, proving_backend.as_ref())
+        })
+        .await
+        .map_err(|e| Error::Internal(format!("Snapshot task failed: {}", e)))??
+    }
+    .ok_or_else(|| Error::InvalidInput(format!("Circuit ID not found: {}", circuit_id_hex)))?;
+    ensure_record_matches(&circuit_id, &snapshot.info)?;
+    let circuit_info = snapshot.info.clone();
+    let proving_backend = snapshot.backend.clone();
+    debug!(?circuit_info, ?proving_backend, "Found circuit info");
+
+    // The limit may have been lowered since registration
+    let max_public_inputs = ctx.config().max_public_inputs;
+    if let Some(count) = circuit_info.num_public_inputs {
+        validate_public_input_count(count, max_public_inputs)?;
+    }
+    if let Some(signals) = InputSignals::of_circuit(&snapshot)? {
+        validate_witness(&signals, &witness_data_json)?;
+    }
+    // Circom provers read every witness value as a field element; give them one
+    // canonical form, so "0x10" and "16" prove (and cache) the same.
+    let witness_data_json = match (
+        &circuit_info.circuit_type,
+        snapshot.artifacts.get(&ArtifactRole::R1cs),
+    ) {
+        (CircuitType::Circom, Some(r1cs)) => normalize_witness(
+            &witness_data_json,
+            ctx.config().witness_field_encoding,
+            &parse_r1cs_header(r1cs)?.prime,
+        )?,
+        _ => witness_data_json,
+    };
+
+    // Look up repeated requests in the proof cache. A hit is only served if
+    // every participant hits too, which is agreed on in the config exchange.
+    // Proofs from different backends of the same circuit are cached separately,
+    // and the verification key is part of the cache key, so a rekey invalidates
+    // cached proofs however soon it follows them.
+    // Seeded proofs must come from their seed, so they skip the cache.
+    let cache_ttl = ctx.config().proof_cache_ttl.filter(|_| rng_seed.is_none());
+    let witness_hash: [u8; 32] = Sha256::new()
+        .chain_update(proving_backend.name().as_bytes())
+        .chain_update(witness_data_json.as_bytes())
+        .finalize()
+        .into();
+    let cache_key: [u8; 32] = Sha256::new()
+        .chain_update(witness_hash)
+        .chain_update(Sha256::digest(&snapshot.verification_key))
+        .finalize()
+        .into();
+    let cached = match cache_ttl {
+        Some(ttl) => {
+            let circuit_store = ctx.circuit_store().clone();
+            let circuit_info = circuit_info.clone();
+            tokio::task::spawn_blocking(move || {
+                circuit_store.get_cached_proof(&circuit_info, &cache_key, ttl)
+            })
+            .await
+            .map_err(|e| Error::Internal(format!("Proof cache task failed: {}", e)))??
+        }
+        None => None,
+    };
 
-    // 2. Get the ordered list of participants for this session
-    let mut participants = ctx.get_operators().await?;
-    if participants.is_empty() {
+    // 2. Get the live operators the participants are chosen from
+    let live_operators = ctx.get_operators().await?;
+    if live_operators.is_empty() {
         return Err(Error::ConfigError(
             "No operators found for the service/circuit".to_string(),
         ));
     }
-    participants.sort();
-    info!(num_participants = participants.len(), "Using participants");
+    let reputation = ctx.peer_reputation();
+    // Records failures from here on in the dead-letter store
+    let dead_letter = |participant_keys: &[PublicKeyBytes],
+                       session_id: &str,
+                       phase: ProofPhase,
+                       error: &Error| {
+        record_proof_failure(&ctx, ProofFailure {
+            call_id,
+            circuit_id,
+            proving_backend: proving_backend.clone(),
+            participants: participant_keys.to_vec(),
+            session_id: session_id.to_string(),
+            phase,
+            error: error.to_string(),
+            failed_at: unix_now(),
+        });
+    };
+
+    // Participants come only from data every operator shares: the job
+    // arguments, the circuit's pinned operators and the live operator set
+    let participants =
+        select_participants::<K>(&circuit_info, operators.as_deref(), live_operators)?;
+    // Party indices come from this mapping alone; the network manager checks it receives the same order
+    let party_mapping = build_party_mapping(participants)?;
+    info!(num_participants = party_mapping.len(), "Using participants");
+    // Starting before every listener is up would only time out in MPC-Net setup
+    if ctx.config().require_peer_announcements {
+        let unannounced = mpc_network_manager
+            .unannounced_peers(party_mapping.participants())
+            .await;
+        if !mpc_network_manager.is_ready() || !unannounced.is_empty() {
+            let unannounced: Vec<String> = unannounced
+                .iter()
+                .map(|pk| hex::encode(pk.to_bytes()))
+                .collect();
+            return Err(Error::InsufficientParticipants(format!(
+                "Participants have not announced readiness (local node ready: {}): {}",
+                mpc_network_manager.is_ready(),
+                unannounced.join(", ")
+            )));
+        }
+    }
+    let participant_keys: Vec<PublicKeyBytes> = party_mapping
+        .participants()
+        .iter()
+        .map(|pk| pk.to_bytes())
+        .collect();
+
+    // 3-5. Run the proof on an MPC session. A transient MPC failure evicts the
+    //      session and retries on a fresh one with the same participants: which
+    //      peers failed is each operator's local view, and operators leaving out
+    //      different peers would never meet in a session.
+    let max_attempts = ctx.config().mpc_session_retries.saturating_add(1);
+    let mut attempt_errors: Vec<String> = Vec::new();
+    let mut attempt = 0;
+    let (session_id, proof_bytes, public_inputs, proving_time) = loop {
+        attempt += 1;
+
+        // 3. Create a unique session ID; every attempt gets a fresh session
+        let session_id = generate_session_id(call_id, attempt, party_mapping.participants());
+        info!(%session_id, attempt, "Generated session ID");
 
-    // 3. Create a unique session ID
-    let session_id = generate_session_id(call_id, &participants);
-    info!(%session_id, "Generated session ID");
+        let cache_agreement = Arc::new(ProofCacheAgreement::new(cached.is_some()));
+        // Every participant aborts at the earliest deadline any of them was given
+        let deadline_agreement = Arc::new(DeadlineAgreement::new(deadline));
+        let result = async {
+            // 4. Establish the MPC session using the round-based protocol. Our
+            //    own deadline already applies while the session is set up.
+            let established = tokio::select! {
+                biased;
+                _ = wait_until(deadline) => {
+                    let e = Error::DeadlineExceeded(format!(
+                        "MPC session {} was not established before its deadline {:?}",
+                        session_id, deadline
+                    ));
+                    return Err((ProofPhase::SessionSetup, e));
+                }
+                established = mpc_network_manager.establish_mpc_session_timed(
+                    &session_id,
+                    party_mapping.participants().to_vec(),
+                    vec![cache_agreement.clone(), deadline_agreement.clone()],
+                ) => established,
+            };
+            let mpc_handler = match established {
+                Ok((handler, setup)) => {
+                    timings.session_reused = setup.reused;
+                    timings.config_exchange_ms = setup.config_exchange.as_millis() as u64;
+                    timings.mpc_establish_ms = setup.mpc_net.as_millis() as u64;
+                    handler
+                }
+                Err(e) => {
+                    record_failed_session(
+                        reputation,
+                        ctx.cheating_metrics(),
+                        &participant_keys,
+                        &e,
+                    );
+                    return Err((ProofPhase::SessionSetup, e));
+                }
+            };
+            // Every participant agreed on a cache hit in the config exchange, so
+            // no MPC-Net mesh was built
+            let Some(mpc_handler) = mpc_handler else {
+                return Ok(None);
+            };
+            // Abort if an operator force-closes the session while we are proving
+            let session_cancel = mpc_network_manager
+                .session_cancellation_token(&session_id)
+                .await
+                .unwrap_or_default();
+            let agreed_deadline = deadline_agreement.agreed();
+            mpc_network_manager
+                .record_deadline(&session_id, agreed_deadline)
+                .await;
 
-    // 4. Establish the MPC session using the round-based protocol
-    let mpc_handler = ctx
-        .mpc_network_manager()
-        .establish_mpc_session(&session_id, participants)
-        .await?;
+            // 5. Use the MPC handler to run the actual proof generation
+            info!(%session_id, prover = ctx.prover().name(), "MPC network established, running proof generation protocol...");
+            let proving_started = Instant::now();
+            // Every participant gets the same seed from the job arguments
+            let mut rng = match rng_seed {
+                Some(seed) => StdRng::seed_from_u64(seed),
+                None => StdRng::from_entropy(),
+            };
 
-    // 5. Use the MPC handler to run the actual proof generation
-    info!(%session_id, "MPC network established, running proof generation protocol...");
+            let request = ProvingRequest {
+                snapshot: &snapshot,
+                witness_json: &witness_data_json,
+                witness_hash: &witness_hash,
+                mpc_handler: &mpc_handler,
+            };
+            // Dropping the prover's future aborts the proof
+            let (proof_bytes, public_inputs) = tokio::select! {
+                biased;
+                _ = session_cancel.cancelled() => {
+                    let e = Error::Cancelled(format!("MPC session {} was force-closed", session_id));
+                    return Err((ProofPhase::Proving, e));
+                }
+                _ = wait_until(agreed_deadline) => {
+                    let e = Error::DeadlineExceeded(format!(
+                        "Proof in MPC session {} passed its deadline {:?}",
+                        session_id, agreed_deadline
+                    ));
+                    return Err((ProofPhase::Proving, e));
+                }
+                proved = ctx.prover().prove(&request, &mut rng) => {
+                    proved.map_err(|e| (ProofPhase::Proving, e))?
+                }
+            };
 
-    // TODO: Replace placeholder with actual co-circom/co-noir call
-    // let proof_result = co_lib::generate_proof(
-    //     circuit_info.artifact_path, // Assuming CircuitStore provides absolute paths
-    //     &witness_data_json,
-    //     mpc_handler
-    // ).await?;
+            Ok(Some((proof_bytes, public_inputs, proving_started.elapsed())))
+        }
+        .await;
 
-    // Placeholder: Simulate proof generation
-    tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
-    let proof_bytes = format!("proof_for_{}_{}", circuit_id_hex, call_id).into_bytes();
-    // Public inputs should be Vec<Vec<u8>>
-    let public_inputs: Vec<Vec<u8>> = vec![format!("input_for_{}", call_id).into_bytes()];
+        let (phase, e) = match result {
+            Ok(None) => {
+                // Every participant holds the proof; drop what the exchange set up
+                mpc_network_manager.force_close(&session_id).await;
+                if let Some(cached) = cached {
+                    info!(%call_id, %circuit_id_hex, "Serving proof from cache");
+                    deliver_to_webhook(&ctx, call_id, &circuit_id, &cached);
+                    timings.from_cache = true;
+                    return Ok((cached, timings));
+                }
+                return Err(Error::Internal(
+                    "Proof cache agreed on a hit without a cached proof".to_string(),
+                ));
+            }
+            Ok(Some((proof_bytes, public_inputs, proving_time))) => {
+                break (session_id, proof_bytes, public_inputs, proving_time);
+            }
+            Err(failure) => failure,
+        };
+        dead_letter(&participant_keys, &session_id, phase, &e);
+        if !e.is_transient_mpc_failure() || attempt >= max_attempts {
+            if attempt_errors.is_empty() {
+                return Err(e);
+            }
+            attempt_errors.push(format!("attempt {}: {}", attempt, e));
+            return Err(Error::SessionRetriesExhausted {
+                attempts: attempt_errors,
+            });
+        }
+        warn!(%call_id, %session_id, attempt, failed_parties = ?e.failed_parties(), error = %e, "MPC session failed, retrying on a fresh session");
+        attempt_errors.push(format!("attempt {}: {}", attempt, e));
+        mpc_network_manager.force_close(&session_id).await;
+    };
+    timings.proving_ms = proving_time.as_millis() as u64;
 
     // 6. Construct the proof result
+    validate_public_input_count(public_inputs.len() as u32, max_public_inputs)
+        .inspect_err(|e| dead_letter(&participant_keys, &session_id, ProofPhase::Proving, e))?;
+    let metadata = ctx.config().proof_metadata.then(|| ProofMetadata {
+        participants: participant_keys.clone(),
+        proving_backend: proving_backend.clone(),
+        proving_ms: proving_time.as_millis() as u64,
+        generated_at: unix_now(),
+    });
     let proof_result = ProofResult {
         proof_bytes,
         public_inputs,
+        metadata,
     };
 
+    // 7. Check the proof locally before it can reach the chain
+    if ctx.config().local_proof_verification && has_fast_verifier(&proving_backend) {
+        let verification_started = Instant::now();
+        if let Err(e) =
+            ctx.prover()
+                .verify(&proving_backend, &snapshot.verification_key, &proof_result)
+        {
+            warn!(%call_id, %circuit_id_hex, %session_id, error = %e, "Generated proof failed local verification");
+            reputation.record_session(&participant_keys, false);
+            dead_letter(&participant_keys, &session_id, ProofPhase::Verification, &e);
+            return Err(e);
+        }
+        timings.verification_ms = Some(verification_started.elapsed().as_millis() as u64);
+    }
+    reputation.record_session(&participant_keys, true);
+
     info!(%call_id, %circuit_id_hex, %session_id, "Generated proof successfully.");
-    Ok(proof_result)
+    deliver_to_webhook(&ctx, call_id, &circuit_id, &proof_result);
+
+    // Update proving statistics and the proof cache off the critical path
+    let circuit_store = ctx.circuit_store().clone();
+    let cache_enabled = cache_ttl.is_some();
+    let cached_result = proof_result.clone();
+    tokio::task::spawn_blocking(move || {
+        if let Err(e) = circuit_store.record_proof(&circuit_id, proving_time) {
+            warn!(circuit_id = %hex::encode(circuit_id), error = %e, "Failed to record proving stats");
+        }
+        if cache_enabled {
+            if let Err(e) = circuit_store.cache_proof(&circuit_info, &cache_key, &cached_result) {
+                warn!(circuit_id = %hex::encode(circuit_id), error = %e, "Failed to cache proof");
+            }
+        }
+    });
+
+    Ok((proof_result, timings))
+}
+
+/// Pushes `proof` to the operator's webhook in the background, if one is configured.
+/// Delivery failures are logged and never fail the job.
+fn deliver_to_webhook<K: KeyType>(
+    ctx: &CosnarksContext<K>,
+    call_id: u64,
+    circuit_id: &CircuitId,
+    proof: &ProofResult,
+) where
+    K::Public: Unpin,
+{
+    if let Some(webhook) = ctx.proof_webhook() {
+        webhook.spawn_delivery(
+            *circuit_id,
+            call_id,
+            proof.clone(),
+            ctx.cancellation_token().child_token(),
+        );
+    }
 }
 
-/// Generates a unique session ID based on the call ID and participant keys.
-fn generate_session_id<P: Hash>(call_id: u64, participants: &[P]) -> String {
+/// Selects the operators that take part in a proof for `circuit_info`.
+///
+/// A requester-chosen set of `requested` operators is used as-is; it must not
+/// contain duplicates and must include the circuit's pinned operators, if any.
+/// Otherwise circuits with pinned operators use exactly that set and all other
+/// circuits all live operators. Every chosen operator must be present in the
+/// live operator set, or the selection fails with `Error::InsufficientParticipants`.
+fn select_participants<K: KeyType>(
+    circuit_info: &CircuitInfo,
+    requested: Option<&[PublicKeyBytes]>,
+    live_operators: Vec<K::Public>,
+) -> Result<Vec<K::Public>> {
+    if let Some(requested) = requested {
+        let mut unique: Vec<&PublicKeyBytes> = requested.iter().collect();
+        unique.sort();
+        unique.dedup();
+        if unique.len() != requested.len() {
+            return Err(Error::InvalidInput(
+                "Requested operators contain duplicates".to_string(),
+            ));
+        }
+        if let Some(missing) = circuit_info
+            .pinned_operators
+            .iter()
+            .find(|pinned| !requested.contains(pinned))
+        {
+            return Err(Error::InvalidInput(format!(
+                "Requested operators omit pinned operator {}",
+                hex::encode(missing)
+            )));
+        }
+        return requested
+            .iter()
+            .map(|key| find_live_operator::<K>(key, "Requested", &live_operators))
+            .collect();
+    }
+
+    if circuit_info.pinned_operators.is_empty() {
+        return Ok(live_operators);
+    }
+
+    circuit_info
+        .pinned_operators
+        .iter()
+        .map(|pinned| find_live_operator::<K>(pinned, "Pinned", &live_operators))
+        .collect()
+}
+
+/// Looks up a `kind` ("Pinned", "Requested") operator in the live operator set.
+fn find_live_operator<K: KeyType>(
+    key: &PublicKeyBytes,
+    kind: &str,
+    live_operators: &[K::Public],
+) -> Result<K::Public> {
+    live_operators
+        .iter()
+        .find(|pk| &pk.to_bytes() == key)
+        .cloned()
+        .ok_or_else(|| {
+            Error::InsufficientParticipants(format!(
+                "{} operator {} is not in the live operator set",
+                kind,
+                hex::encode(key)
+            ))
+        })
+}
+
+/// Stores a failed proof job in the dead-letter store, off the critical path.
+fn record_proof_failure<K: KeyType>(ctx: &CosnarksContext<K>, failure: ProofFailure) {
+    let circuit_store = ctx.circuit_store().clone();
+    let max_entries = ctx.config().proof_failures_max_entries;
+    tokio::task::spawn_blocking(move || {
+        if let Err(e) = circuit_store.record_failure(&failure, max_entries) {
+            warn!(call_id = failure.call_id, error = %e, "Failed to record proof failure");
+        }
+    });
+}
+
+/// Updates peer reputation, and counts and alerts on any cheating, after a session could not be established.
+///
+/// If the config exchange blamed parties for cheating or flooding, only they
+/// are charged with the failure, and only provable cheating (see
+/// [`crate::p2p::BlameReason::is_provable`]) counts as an incident; otherwise,
+/// e.g. on a timeout, the failure counts against every participant and no
+/// incident is recorded.
+fn record_failed_session(
+    reputation: &PeerReputation,
+    cheating: &CheatingMetrics,
+    participant_keys: &[PublicKeyBytes],
+    error: &Error,
+) {
+    let guilty_parties = match error {
+        Error::CommitmentMismatch { guilty_parties } => guilty_parties,
+        Error::ExcessMessages { guilty_parties } => {
+            let mut flooders: Vec<PublicKeyBytes> = guilty_parties
+                .iter()
+                .filter_map(|blame| participant_keys.get(usize::from(blame.guilty_party)))
+                .cloned()
+                .collect();
+            flooders.sort();
+            flooders.dedup();
+            reputation.record_session(&flooders, false);
+            return;
+        }
+        _ => {
+            reputation.record_session(participant_keys, false);
+            return;
+        }
+    };
+    let mut guilty: Vec<PublicKeyBytes> = Vec::new();
+    let detected_at = unix_now();
+    for blame in guilty_parties {
+        let Some(peer) = participant_keys.get(usize::from(blame.guilty_party)) else {
+            continue;
+        };
+        if guilty.contains(peer) {
+            continue;
+        }
+        guilty.push(peer.clone());
+        if !blame.reason.is_provable() {
+            continue;
+        }
+        reputation.record_cheating(peer);
+        cheating.record(&CheatingIncident {
+            operator: peer.clone(),
+            reason: blame.reason.clone(),
+            detected_at,
+        });
+    }
+    guilty.sort();
+    reputation.record_session(&guilty, false);
+}
+
+/// Generates a unique session ID based on the call ID, the attempt and the
+/// participant keys, so a retry never reuses the session of a failed attempt.
+fn generate_session_id<P: Hash>(call_id: u64, attempt: u32, participants: &[P]) -> String {
     let mut hasher = DefaultHasher::new();
     call_id.hash(&mut hasher);
+    attempt.hash(&mut hasher);
     participants.hash(&mut hasher);
     format!("mpc-session-{}", hasher.finish())
 }
+
+/// Resolves once `deadline` (Unix seconds) has passed, or never without one.
+async fn wait_until(deadline: Option<u64>) {
+    match deadline {
+        Some(at) => {
+            tokio::time::sleep(tokio::time::Duration::from_secs(
+                at.saturating_sub(unix_now()),
+            ))
+            .await
+        }
+        None => std::future::pending().await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn circuit_info(id: CircuitId) -> CircuitInfo {
+        CircuitInfo {
+            id,
+            name: "circuit".to_string(),
+            circuit_type: CircuitType::Circom,
+            artifact_paths: BTreeMap::new(),
+            artifact_blobs: BTreeMap::new(),
+            backends: Vec::new(),
+            pinned_operators: Vec::new(),
+            dependencies: Vec::new(),
+            num_public_inputs: None,
+            noir_abi: None,
+            updated_at: 0,
+            source_url: None,
+            artifact_checksum: [0; 32],
+        }
+    }
+
+    #[test]
+    fn retries_run_on_fresh_sessions_with_the_same_participants() {
+        use blueprint_sdk::crypto::sp_core::SpEcdsa;
+
+        let operators: Vec<_> = (0..3)
+            .map(|_| SpEcdsa::public_from_secret(&SpEcdsa::generate_with_seed(None).unwrap()))
+            .collect();
+        let info = circuit_info([0x11; 32]);
+        // Operators see the live set in any order and select the same participants
+        let mut reordered = operators.clone();
+        reordered.reverse();
+        let mapping =
+            build_party_mapping(select_participants::<SpEcdsa>(&info, None, operators).unwrap())
+                .unwrap();
+        let other =
+            build_party_mapping(select_participants::<SpEcdsa>(&info, None, reordered).unwrap())
+                .unwrap();
+        assert_eq!(mapping.participants(), other.participants());
+
+        // Every attempt runs on its own session, which all operators derive alike
+        let session_id = |attempt| generate_session_id(7, attempt, mapping.participants());
+        assert_eq!(
+            session_id(1),
+            generate_session_id(7, 1, other.participants())
+        );
+        assert_ne!(session_id(1), session_id(2));
+        assert_ne!(session_id(2), session_id(3));
+    }
+
+    #[test]
+    fn only_chosen_operators_are_locally_selected() {
+        let (local, peer, other) = (vec![1u8], vec![2u8], vec![3u8]);
+        let is_local = |key: &PublicKeyBytes| key == &local;
+
+        // All live operators take part unless some are requested or pinned
+        assert!(selects_local_operator(None, &[], is_local));
+        assert!(selects_local_operator(
+            None,
+            &[local.clone(), peer.clone()],
+            is_local
+        ));
+        assert!(!selects_local_operator(
+            None,
+            &[peer.clone(), other.clone()],
+            is_local
+        ));
+        // A requested subset overrides the pinned set
+        let requested = [peer.clone(), other.clone()];
+        assert!(!selects_local_operator(
+            Some(&requested),
+            &[local.clone()],
+            is_local
+        ));
+        let requested = [other, local.clone()];
+        assert!(selects_local_operator(Some(&requested), &[peer], is_local));
+        assert!(!selects_local_operator(Some(&[]), &[], is_local));
+    }
+
+    #[test]
+    fn records_only_provable_cheating_as_incidents() {
+        use crate::p2p::{Blame, BlameReason};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let blame = |guilty_party, reason| Blame {
+            guilty_party,
+            commitment_msg: 0,
+            reveal_msg: 0,
+            reason,
+        };
+        let participants: Vec<PublicKeyBytes> =
+            vec![b"alice".to_vec(), b"bob".to_vec(), b"carol".to_vec()];
+        let reputation = PeerReputation::default();
+        let cheating = CheatingMetrics::default();
+        let fired = Arc::new(AtomicUsize::new(0));
+        {
+            // A hook may register further hooks without deadlocking
+            let cheating_clone = cheating.clone();
+            let fired = fired.clone();
+            cheating.on_incident(Arc::new(move |_| {
+                fired.fetch_add(1, Ordering::Relaxed);
+                cheating_clone.on_incident(Arc::new(|_| {}));
+            }));
+        }
+
+        // Timeouts and flooding blame nobody for cheating
+        let timeout = Error::MpcProtocolError("Config exchange timed out".to_string());
+        record_failed_session(&reputation, &cheating, &participants, &timeout);
+        let flooding = Error::ExcessMessages {
+            guilty_parties: vec![blame(1, BlameReason::ExcessMessages { round: 0 })],
+        };
+        record_failed_session(&reputation, &cheating, &participants, &flooding);
+        assert!(cheating.incidents().is_empty());
+        assert_eq!(fired.load(Ordering::Relaxed), 0);
+
+        let cheated = Error::CommitmentMismatch {
+            guilty_parties: vec![
+                blame(2, BlameReason::CertificateMismatch),
+                blame(0, BlameReason::ExcessMessages { round: 1 }),
+            ],
+        };
+        record_failed_session(&reputation, &cheating, &participants, &cheated);
+        let incidents = cheating.incidents();
+        assert_eq!(incidents.len(), 1);
+        assert_eq!(incidents.get(b"carol".as_slice()), Some(&1));
+        assert_eq!(fired.load(Ordering::Relaxed), 1);
+        assert_eq!(reputation.score(&participants[2]).cheating_incidents, 1);
+        assert_eq!(reputation.score(&participants[0]).cheating_incidents, 0);
+        // Every blamed party is charged with the failed session
+        assert_eq!(reputation.score(&participants[0]).sessions_failed, 2);
+        assert_eq!(reputation.score(&participants[1]).sessions_failed, 2);
+        assert_eq!(reputation.score(&participants[2]).sessions_failed, 2);
+    }
+
+    #[test]
+    fn ensure_record_matches_rejects_records_stored_under_another_id() {
+        let circuit_id = [0x11; 32];
+        assert!(ensure_record_matches(&circuit_id, &circuit_info(circuit_id)).is_ok());
+        assert!(matches!(
+            ensure_record_matches(&circuit_id, &circuit_info([0x22; 32])),
+            Err(Error::Internal(_))
+        ));
+    }
+}
@@ -0,0 +1,53 @@
+// get_proof job handler: serves proofs kept in the proof store
+
+use crate::context::CosnarksContext;
+use crate::error::{Error, Result};
+use crate::types::ProofResult;
+use blueprint_sdk::crypto::KeyType;
+use blueprint_sdk::extract::Context;
+use blueprint_sdk::tangle::extract::{CallId, Caller, TangleArg, TangleResult};
+use tracing::{info, warn};
+
+/// Returns the stored proof whose `ProofPointer::proof_hash` is `proof_hash`.
+///
+/// Serves proofs returned by pointer, and lets clients re-fetch a proof they
+/// lost without re-running the MPC. Unknown or expired hashes fail with
+/// `Error::InvalidInput`. The caller must be authorized to request proofs for
+/// the proof's circuit, as for `generate_proof`, or the job fails with
+/// `Error::Unauthorized`.
+pub async fn get_proof<K: KeyType>(
+    Context(ctx): Context<CosnarksContext<K>>,
+    CallId(call_id): CallId,
+    Caller(caller): Caller,
+    TangleArg(proof_hash): TangleArg<[u8; 32]>,
+) -> Result<TangleResult<ProofResult>>
+where
+    K::Public: Unpin,
+{
+    let proof_hash_hex = hex::encode(proof_hash);
+    info!(%call_id, proof_hash = %proof_hash_hex, "Fetching stored proof");
+
+    let circuit_store = ctx.circuit_store().clone();
+    let ttl = ctx.config().stored_proof_ttl;
+    let stored =
+        tokio::task::spawn_blocking(move || circuit_store.get_stored_proof(&proof_hash, ttl))
+            .await
+            .map_err(|e| Error::Internal(format!("Proof store task failed: {}", e)))??
+            .ok_or_else(|| {
+                Error::InvalidInput(format!("No stored proof with hash {}", proof_hash_hex))
+            })?;
+
+    if !ctx
+        .proof_authorizer()
+        .authorize(&caller, &stored.circuit_id)
+    {
+        warn!(%call_id, caller = %hex::encode(caller), proof_hash = %proof_hash_hex, "Rejected unauthorized stored proof request");
+        return Err(Error::Unauthorized(format!(
+            "Caller {} may not fetch proofs for circuit {}",
+            hex::encode(caller),
+            hex::encode(stored.circuit_id)
+        )));
+    }
+
+    Ok(TangleResult(stored.proof))
+}
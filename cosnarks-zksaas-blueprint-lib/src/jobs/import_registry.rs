@@ -0,0 +1,144 @@
+// import_registry job handler: bulk registration from a shared circuit registry
+
+use crate::context::CosnarksContext;
+use crate::download::validate_allowed_host;
+use crate::error::{Error, Result};
+use crate::jobs::register_circuit::{RegistrationRequest, register};
+use crate::types::{
+    ArtifactSource, BatchResult, CircuitId, CircuitType, ProvingBackend, PublicKeyBytes,
+};
+use blueprint_sdk::crypto::KeyType;
+use blueprint_sdk::extract::Context;
+use blueprint_sdk::tangle::extract::{CallId, Caller, TangleArg, TangleResult};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+use url::Url;
+
+/// A registry manifest listing circuits to import.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegistryManifest {
+    pub circuits: Vec<RegistryEntry>,
+}
+
+/// A single circuit published in a registry manifest.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegistryEntry {
+    pub name: String,
+    pub circuit_type: CircuitType,
+    /// Omitted to use the operator's default backend for the circuit type
+    #[serde(default)]
+    pub proving_backends: Vec<ProvingBackend>,
+    pub artifact_url: String,
+    /// Hex-encoded SHA-256 checksum of the artifact
+    #[serde(default)]
+    pub checksum: Option<String>,
+    #[serde(default)]
+    pub pinned_operators: Vec<PublicKeyBytes>,
+    /// Hex-encoded IDs of circuits this one depends on. They must be registered
+    /// already or appear earlier in the manifest.
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+}
+
+/// Imports every circuit listed in the registry manifest at `manifest_url`.
+///
+/// Each entry is registered independently. The result holds the circuit ID or
+/// error of every entry in manifest order; a failing entry does not stop the
+/// remaining imports.
+pub async fn import_registry<K: KeyType>(
+    Context(ctx): Context<CosnarksContext<K>>,
+    CallId(call_id): CallId,
+    Caller(caller): Caller,
+    TangleArg(manifest_url): TangleArg<String>,
+) -> Result<TangleResult<BatchResult<CircuitId>>>
+where
+    K::Public: Unpin,
+{
+    info!(%call_id, %manifest_url, "Importing circuits from registry");
+    let manifest = fetch_manifest(&ctx, &manifest_url).await?;
+    info!(
+        num_circuits = manifest.circuits.len(),
+        "Fetched registry manifest"
+    );
+
+    let mut results = Vec::with_capacity(manifest.circuits.len());
+    for entry in manifest.circuits {
+        let name = entry.name.clone();
+        let result = import_entry(&ctx, call_id, caller, entry).await;
+        if let Err(e) = &result {
+            warn!(%name, error = %e, "Failed to import circuit");
+        }
+        results.push((name, result));
+    }
+
+    let batch: BatchResult<CircuitId> = results.into_iter().collect();
+    info!(%call_id, imported = batch.succeeded, failed = batch.failed, "Registry import finished");
+    Ok(TangleResult(batch))
+}
+
+/// Downloads and parses the manifest JSON.
+async fn fetch_manifest<K: KeyType>(
+    ctx: &CosnarksContext<K>,
+    manifest_url: &str,
+) -> Result<RegistryManifest>
+where
+    K::Public: Unpin,
+{
+    let url = Url::parse(manifest_url)?;
+    validate_allowed_host(&url, ctx.config().allowed_artifact_hosts.as_deref())?;
+    let cancel = ctx.cancellation_token().child_token();
+    let spool = ctx
+        .downloader()
+        .download(&url, ctx.circuit_store().get_tmp_path(), &cancel)
+        .await?;
+    let manifest_bytes = std::fs::read(spool.path())?;
+    Ok(serde_json::from_slice(&manifest_bytes)?)
+}
+
+/// Registers a single manifest entry.
+async fn import_entry<K: KeyType>(
+    ctx: &CosnarksContext<K>,
+    call_id: u64,
+    caller: [u8; 32],
+    entry: RegistryEntry,
+) -> Result<CircuitId>
+where
+    K::Public: Unpin,
+{
+    let expected_checksum = entry.checksum.as_deref().map(parse_checksum).transpose()?;
+    let dependencies = entry
+        .dependencies
+        .iter()
+        .map(|id| parse_circuit_id(id))
+        .collect::<Result<Vec<_>>>()?;
+    let request = RegistrationRequest {
+        name: entry.name,
+        circuit_type: entry.circuit_type,
+        proving_backends: entry.proving_backends,
+        artifact_source: ArtifactSource::Url(entry.artifact_url),
+        pinned_operators: entry.pinned_operators,
+        dependencies,
+        expected_checksum,
+        pregenerated_keys: None,
+        caller,
+    };
+    Ok(register(ctx, call_id, request).await?.circuit_id)
+}
+
+/// Parses a hex-encoded 32-byte checksum.
+fn parse_checksum(checksum_hex: &str) -> Result<[u8; 32]> {
+    let bytes = hex::decode(checksum_hex)
+        .map_err(|e| Error::InvalidInput(format!("Invalid checksum hex: {}", e)))?;
+    bytes
+        .try_into()
+        .map_err(|_| Error::InvalidInput("Checksum must be a 32-byte SHA-256 digest".to_string()))
+}
+
+/// Parses a hex-encoded 32-byte circuit ID.
+fn parse_circuit_id(id_hex: &str) -> Result<CircuitId> {
+    let bytes = hex::decode(id_hex)
+        .map_err(|e| Error::InvalidInput(format!("Invalid circuit ID hex: {}", e)))?;
+    bytes
+        .try_into()
+        .map_err(|_| Error::InvalidInput("Circuit ID must be 32 bytes".to_string()))
+}
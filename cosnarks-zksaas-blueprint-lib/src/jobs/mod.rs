@@ -10,10 +10,85 @@ pub const REGISTER_CIRCUIT_JOB_ID: u8 = 0;
 // Output: Proof Bytes, Public Inputs
 pub const GENERATE_PROOF_JOB_ID: u8 = 1;
 
+// Job to bulk-import circuits published in a registry manifest.
+// Input: Manifest URL
+// Output: Per-circuit import outcomes
+pub const IMPORT_REGISTRY_JOB_ID: u8 = 2;
+
+// Admin job listing the MPC sessions cached by this operator.
+// Restricted to service operators and the service owner.
+// Input: None
+// Output: Session ID, participant count, established and last-used timestamps per session
+pub const LIST_SESSIONS_JOB_ID: u8 = 3;
+
+// Job to copy a registered circuit under a new name, reusing its artifacts and keys.
+// Input: Source Circuit ID, New Name
+// Output: Circuit ID of the copy
+pub const CLONE_CIRCUIT_JOB_ID: u8 = 4;
+
+// Admin job force-closing a cached MPC session and aborting jobs running on it.
+// Restricted to service operators.
+// Input: Session ID
+// Output: Whether the session was cached
+pub const FORCE_CLOSE_SESSION_JOB_ID: u8 = 5;
+
+// Job to fetch a proof kept in the operator's proof store.
+// Input: Proof Hash (from a ProofPointer)
+// Output: Proof Bytes, Public Inputs
+pub const GET_PROOF_JOB_ID: u8 = 6;
+
+// Job to cancel a proof job that is queued or running on this operator.
+// Restricted to the proof's caller and service operators.
+// Input: Call ID of the generate_proof job
+// Output: Whether the job was cancelled while queued, while running, or not found
+pub const CANCEL_PROOF_JOB_ID: u8 = 7;
+
+// Job to begin a chunked upload of an artifact too large for one job argument.
+// Input: Chunk Count, Total Size in bytes
+// Output: Upload ID (the call ID of this job)
+pub const BEGIN_ARTIFACT_UPLOAD_JOB_ID: u8 = 8;
+
+// Job to add one chunk to an upload. Chunks may be sent in any order.
+// Input: Upload ID, Chunk Index, Chunk Bytes
+// Output: Number of chunks still missing
+pub const ARTIFACT_CHUNK_JOB_ID: u8 = 9;
+
+// Job to assemble an upload once all chunks are present, for use as an
+// `ArtifactSource::Upload` in register_circuit.
+// Input: Upload ID, SHA-256 checksum of the artifact
+// Output: Size of the assembled artifact in bytes
+pub const FINALIZE_ARTIFACT_UPLOAD_JOB_ID: u8 = 10;
+
+// Job to export a circuit's artifacts and verification keys, in chunks, so it
+// can be mirrored elsewhere. Proving keys are never exported.
+// Input: Circuit ID, Chunk Index
+// Output: Base64 chunk of the encoded export, chunk count, total size and checksum
+pub const EXPORT_CIRCUIT_JOB_ID: u8 = 11;
+
+// Job to compare the locally stored circuits against those registered on-chain.
+// Nothing is downloaded; the report says which circuits need re-registering.
+// Input: None
+// Output: Circuit IDs missing locally, circuit IDs missing on-chain, in-sync count
+pub const SYNC_CIRCUITS_JOB_ID: u8 = 12;
+
 // --- Job Handler Modules ---
+pub mod admin;
+pub mod cancel_proof;
+pub mod clone_circuit;
+pub mod export_circuit;
 pub mod generate_proof;
+pub mod get_proof;
+pub mod import_registry;
 pub mod register_circuit;
+pub mod upload_artifact;
 
 // Re-export handlers
+pub use admin::{force_close_session, list_sessions, sync_circuits};
+pub use cancel_proof::cancel_proof;
+pub use clone_circuit::clone_circuit;
+pub use export_circuit::export_circuit;
 pub use generate_proof::generate_proof_job;
+pub use get_proof::get_proof;
+pub use import_registry::import_registry;
 pub use register_circuit::register_circuit;
+pub use upload_artifact::{artifact_chunk, begin_artifact_upload, finalize_artifact_upload};
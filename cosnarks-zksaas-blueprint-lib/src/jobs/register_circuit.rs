@@ -1,131 +1,643 @@
 // Placeholder for register_circuit job handler
 
+use crate::artifact::{
+    check_ptau_capacity, count_public_inputs, parse_r1cs_header, read_ptau_header,
+    validate_artifact_header, validate_pregenerated_keys,
+};
 use crate::context::CosnarksContext;
+use crate::download::{ArtifactDownloader, validate_allowed_host, validate_artifact_size};
 use crate::error::{Error, Result};
-use crate::state::CircuitStore;
-use crate::types::{CircuitId, CircuitInfo, CircuitType, OptionalJsonParams, ProvingBackend};
+use crate::noir::parse_program_abi;
+use crate::state::unix_now;
+use crate::types::{
+    ArtifactRole, ArtifactSource, BackendKeys, CircuitId, CircuitInfo, CircuitType, ProvingBackend,
+    PublicKeyBytes, VerifierAddress, generate_circuit_id,
+};
 // use blueprint_sdk::macros::debug_job; // Macro doesn't support generics yet
-use blueprint_sdk::crypto::KeyType;
-use blueprint_sdk::crypto::hashing::blake3_256;
+use blueprint_sdk::crypto::{BytesEncoding, KeyType};
 use blueprint_sdk::extract::Context;
-use blueprint_sdk::tangle::extract::{CallId, TangleArgs4, TangleResult};
-use reqwest;
+use blueprint_sdk::tangle::extract::{CallId, Caller, TangleArgs8, TangleResult};
+use futures::StreamExt;
+use futures::stream::FuturesUnordered;
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use std::path::PathBuf;
-use tracing::{debug, error, info};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+use unicode_normalization::UnicodeNormalization;
 use url::Url;
 
-const ARTIFACT_FILENAME: &str = "circuit_artifact"; // Generic name, extension added later
+const ARTIFACT_FILENAME: &str = "circuit_artifact"; // Generic name, extension derived from role
 const PROVING_KEY_FILENAME: &str = "proving.key";
 const VERIFICATION_KEY_FILENAME: &str = "verification.key";
+const MAX_CIRCUIT_NAME_LEN: usize = 128;
 
 // Example Input Arguments (adjust as needed):
 // - circuit_name: String
 // - circuit_description: Optional<String>
 // - circuit_type: CircuitType (enum Circom/Noir)
-// - proving_backend: ProvingBackend (enum Groth16/Plonk/UltraHonk)
-// - circuit_artifact_url: String (URL to download .r1cs, .acir, etc.)
+// - proving_backends: Vec<ProvingBackend> (enum Groth16/Plonk/UltraHonk; keys are generated for each, empty for the default)
+// - artifact_source: ArtifactSource (URL to download .r1cs, .acir, etc., one URL per bundle member, inline bytes, or a chunked upload)
+// - pinned_operators: Vec<PublicKeyBytes> (empty to use all operators)
+// - dependencies: Vec<CircuitId> (registered library circuits this one includes)
+// - pk_url / vk_url: Option<String> (pre-generated keys; both or neither)
 // - optional_setup_parameters: JSON (?) for backend-specific setup
 
+/// Arguments of a single circuit registration, independent of how the job was invoked.
+#[derive(Debug, Clone)]
+pub struct RegistrationRequest {
+    pub name: String,
+    pub circuit_type: CircuitType,
+    /// Backends to generate keys for; the first becomes the circuit's default.
+    /// Empty to use the configured default backend for the circuit type.
+    pub proving_backends: Vec<ProvingBackend>,
+    pub artifact_source: ArtifactSource,
+    pub pinned_operators: Vec<PublicKeyBytes>,
+    /// Registered circuits this circuit depends on
+    pub dependencies: Vec<CircuitId>,
+    /// Optional SHA-256 checksum the downloaded artifact must match
+    pub expected_checksum: Option<[u8; 32]>,
+    /// Externally generated keys to store instead of running key generation
+    pub pregenerated_keys: Option<PregeneratedKeys>,
+    /// Account that called the job; only it may register from its own uploads
+    pub caller: [u8; 32],
+}
+
+/// Locations of proving and verification keys generated outside the service,
+/// e.g. by a trusted setup ceremony.
+#[derive(Debug, Clone)]
+pub struct PregeneratedKeys {
+    pub pk_url: String,
+    pub vk_url: String,
+}
+
+/// Outcome of a successful registration.
+#[derive(Debug, Clone)]
+pub struct RegisteredCircuit {
+    pub circuit_id: CircuitId,
+    /// One entry per requested backend, in request order
+    pub backends: Vec<RegisteredBackend>,
+    /// Bytes downloaded for the artifact and any pre-generated keys
+    pub bytes_downloaded: u64,
+}
+
+/// Keys and verifier generated for one backend of a registered circuit.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RegisteredBackend {
+    pub backend: ProvingBackend,
+    /// Address of the deployed verifier contract; `None` if none was deployed
+    pub verifier_address: Option<VerifierAddress>,
+    pub verification_key: Vec<u8>,
+}
+
 /// Registers a new ZK circuit, downloads artifacts, generates keys, and stores metadata.
 // #[debug_job] // Cannot use with generics
 pub async fn register_circuit<K: KeyType>(
     Context(ctx): Context<CosnarksContext<K>>,
     CallId(call_id): CallId,
-    TangleArgs4(name, circuit_type, proving_backend, artifact_url_str): TangleArgs4<
+    Caller(caller): Caller,
+    TangleArgs8(
+        name,
+        circuit_type,
+        proving_backends,
+        artifact_source,
+        pinned_operators,
+        dependencies,
+        pk_url,
+        vk_url,
+    ): TangleArgs8<
         String,
         CircuitType,
-        ProvingBackend,
-        String, // artifact_url
-                // Add OptionalJsonParams here if TangleArgs5 is needed
+        Vec<ProvingBackend>, // proving_backends
+        ArtifactSource,      // artifact_source
+        Vec<PublicKeyBytes>, // pinned_operators
+        Vec<[u8; 32]>,       // dependencies
+        Option<String>,      // pk_url
+        Option<String>,      // vk_url
     >,
     // setup_params: OptionalJsonParams,
-) -> Result<TangleResult<([u8; 32], [u8; 20], Vec<u8>)>>
+) -> Result<TangleResult<([u8; 32], Vec<RegisteredBackend>)>>
 where
     K::Public: Ord + Unpin + std::hash::Hash + Send + Sync,
 {
-    // Return standard types
-    info!(%call_id, %name, ?circuit_type, ?proving_backend, %artifact_url_str, "Registering circuit");
+    let pregenerated_keys = match (pk_url, vk_url) {
+        (Some(pk_url), Some(vk_url)) => Some(PregeneratedKeys { pk_url, vk_url }),
+        (None, None) => None,
+        _ => {
+            return Err(Error::InvalidInput(
+                "pk_url and vk_url must be provided together".to_string(),
+            ));
+        }
+    };
+    let request = RegistrationRequest {
+        name,
+        circuit_type,
+        proving_backends,
+        artifact_source,
+        pinned_operators,
+        dependencies,
+        expected_checksum: None,
+        pregenerated_keys,
+        caller,
+    };
+    let registered = register(&ctx, call_id, request).await?;
+
+    Ok(TangleResult((registered.circuit_id, registered.backends)))
+}
+
+/// Core implementation of circuit registration, shared by all registration jobs.
+pub async fn register<K: KeyType>(
+    ctx: &CosnarksContext<K>,
+    call_id: u64,
+    request: RegistrationRequest,
+) -> Result<RegisteredCircuit>
+where
+    K::Public: Unpin,
+{
+    let RegistrationRequest {
+        name,
+        circuit_type,
+        proving_backends,
+        artifact_source,
+        pinned_operators,
+        dependencies,
+        expected_checksum,
+        pregenerated_keys,
+        caller,
+    } = request;
+    // Normalized before the ID is derived; an invalid name is reported by validate_request
+    let name = validate_circuit_name(&name).unwrap_or(name);
+    let pinned_operators = dedup_pinned_operators(pinned_operators);
+    let proving_backends = if proving_backends.is_empty() {
+        vec![ctx.config().default_backend(&circuit_type)]
+    } else {
+        proving_backends
+    };
+    info!(%call_id, %name, ?circuit_type, ?proving_backends, %artifact_source, "Registering circuit");
 
     // --- Validation ---
-    validate_backend_compatibility(&circuit_type, &proving_backend)?;
+    validate_request::<K>(
+        &name,
+        &circuit_type,
+        &proving_backends,
+        &artifact_source,
+        &pinned_operators,
+        pregenerated_keys.as_ref(),
+    )?;
+    validate_download_hosts(
+        &artifact_source,
+        pregenerated_keys.as_ref(),
+        ctx.config().allowed_artifact_hosts.as_deref(),
+    )?;
 
     // --- Circuit ID Generation ---
-    let circuit_id = generate_circuit_id(&name, &circuit_type, &proving_backend);
+    let circuit_id = generate_circuit_id(&name, &circuit_type, &proving_backends);
     let circuit_id_hex = hex::encode(circuit_id);
     info!(%circuit_id_hex, "Generated circuit ID");
+    ctx.circuit_store()
+        .validate_dependencies(&circuit_id, &dependencies)?;
 
     // --- Artifact Download ---
-    let artifact_url = Url::parse(&artifact_url_str).map_err(Error::UrlParseError)?;
-    debug!(url = %artifact_url, "Downloading artifact...");
-    let artifact_data = download_artifact(&artifact_url).await?;
-    debug!(
-        "Artifact downloaded successfully ({} bytes)",
-        artifact_data.len()
-    );
+    // Cancels every download of this registration at shutdown
+    let cancel = ctx.cancellation_token().child_token();
+    let primary_role = ArtifactRole::primary(&circuit_type);
+    let source_url = match &artifact_source {
+        ArtifactSource::Url(url) => Some(url.clone()),
+        ArtifactSource::Bundle(members) => members.get(&primary_role).cloned(),
+        ArtifactSource::Inline(_) | ArtifactSource::Upload { .. } => None,
+    };
+    // Consumed only once the circuit is stored, so a failed registration can be retried
+    let consumed_upload = match &artifact_source {
+        ArtifactSource::Upload { upload_id } => Some(*upload_id),
+        _ => None,
+    };
+    let mut bytes_downloaded: u64 = 0;
+    let artifact_bundle = match artifact_source {
+        ArtifactSource::Url(artifact_url_str) => {
+            let artifact_url = Url::parse(&artifact_url_str).map_err(Error::UrlParseError)?;
+            debug!(url = %artifact_url, "Downloading artifact...");
+            let artifact_spool = ctx
+                .downloader()
+                .download(&artifact_url, ctx.circuit_store().get_tmp_path(), &cancel)
+                .await?;
+            let artifact_data = tokio::fs::read(artifact_spool.path()).await?;
+            debug!(
+                "Artifact downloaded successfully ({} bytes)",
+                artifact_data.len()
+            );
+            bytes_downloaded += artifact_data.len() as u64;
+            BTreeMap::from([(primary_role, artifact_data)])
+        }
+        ArtifactSource::Bundle(members) => {
+            let required: Vec<ArtifactRole> = proving_backends
+                .iter()
+                .flat_map(|backend| required_artifact_roles(&circuit_type, backend))
+                .copied()
+                .collect();
+            let bundle = download_bundle(
+                ctx.downloader(),
+                ctx.circuit_store().get_tmp_path(),
+                ctx.config().bundle_download_concurrency,
+                &members,
+                &required,
+                &cancel,
+            )
+            .await?;
+            bytes_downloaded += bundle.values().map(|data| data.len() as u64).sum::<u64>();
+            bundle
+        }
+        ArtifactSource::Inline(artifact_data) => {
+            validate_artifact_size(artifact_data.len() as u64, ctx.downloader().max_size())?;
+            debug!("Using inline artifact ({} bytes)", artifact_data.len());
+            BTreeMap::from([(primary_role, artifact_data)])
+        }
+        ArtifactSource::Upload { upload_id } => {
+            let uploads = ctx.artifact_uploads().clone();
+            let artifact_data =
+                tokio::task::spawn_blocking(move || uploads.read(upload_id, &caller))
+                    .await
+                    .map_err(|e| {
+                        Error::Internal(format!("Artifact upload task failed: {}", e))
+                    })??;
+            debug!(
+                upload_id,
+                "Using uploaded artifact ({} bytes)",
+                artifact_data.len()
+            );
+            BTreeMap::from([(primary_role, artifact_data)])
+        }
+    };
+    for proving_backend in &proving_backends {
+        validate_artifact_bundle(&circuit_type, proving_backend, &artifact_bundle)?;
+    }
+    // The checksum and headers cover the primary member, which every backend requires
+    let artifact_data = &artifact_bundle[&primary_role];
+    let artifact_checksum: [u8; 32] = Sha256::digest(artifact_data).into();
+    if let Some(expected) = expected_checksum {
+        verify_artifact_checksum(&artifact_checksum, &expected)?;
+    }
+    for proving_backend in &proving_backends {
+        validate_artifact_header(artifact_data, &circuit_type, proving_backend)?;
+    }
+    // A circuit too large for the ptau would only fail midway through setup
+    if let Some(ptau_path) = &ctx.config().ptau_path {
+        let runs_groth16_setup = pregenerated_keys.is_none()
+            && circuit_type == CircuitType::Circom
+            && proving_backends.contains(&ProvingBackend::Groth16);
+        if runs_groth16_setup {
+            check_ptau_capacity(
+                &parse_r1cs_header(artifact_data)?,
+                &read_ptau_header(ptau_path)?,
+            )?;
+        }
+    }
+
+    // Bound the result size before spending any effort on setup
+    let num_public_inputs = count_public_inputs(&circuit_type, artifact_data)?;
+    if let Some(count) = num_public_inputs {
+        validate_public_input_count(count, ctx.config().max_public_inputs)?;
+    }
+    let noir_abi = match circuit_type {
+        CircuitType::Noir => parse_program_abi(artifact_data)?,
+        CircuitType::Circom => None,
+    };
+    if circuit_type == CircuitType::Noir && noir_abi.is_none() {
+        warn!(%circuit_id_hex, "Noir artifact carries no ABI; Prover.toml witnesses will be rejected");
+    }
 
     // --- Key Generation (Placeholder) ---
     // In a real implementation, this would call co-circom/co-noir based on type/backend
     // to generate PK and VK from the downloaded artifact_data.
-    info!(%circuit_id_hex, "Generating proving and verification keys (Placeholder)...", );
-    let (proving_key_data, verification_key_data, verifier_address) =
-        generate_keys_placeholder(&circuit_type, &proving_backend, &artifact_data)?;
-    debug!(
-        "Keys generated (PK: {} bytes, VK: {} bytes)",
-        proving_key_data.len(),
-        verification_key_data.len()
-    );
+    // Each backend gets its own keys (and time budget) under `<backend>/`.
+    let mut backend_keys = Vec::with_capacity(proving_backends.len());
+    let mut key_data = Vec::with_capacity(proving_backends.len());
+    let mut registered_backends = Vec::with_capacity(proving_backends.len());
+    for proving_backend in proving_backends {
+        let (proving_key_data, verification_key_data, verifier_address) = match &pregenerated_keys {
+            Some(keys) => {
+                info!(%circuit_id_hex, ?proving_backend, "Using pre-generated proving and verification keys");
+                let proving_key_data = download_key(ctx, &keys.pk_url, &cancel).await?;
+                let verification_key_data = download_key(ctx, &keys.vk_url, &cancel).await?;
+                bytes_downloaded += (proving_key_data.len() + verification_key_data.len()) as u64;
+                validate_pregenerated_keys(
+                    &circuit_type,
+                    &proving_backend,
+                    artifact_data,
+                    &proving_key_data,
+                    &verification_key_data,
+                )?;
+                (proving_key_data, verification_key_data, None)
+            }
+            None => {
+                info!(%circuit_id_hex, ?proving_backend, "Generating proving and verification keys (Placeholder)...");
+                generate_keys_within_budget(
+                    &circuit_id_hex,
+                    &circuit_type,
+                    &proving_backend,
+                    artifact_data,
+                    ctx.circuit_store().get_tmp_path(),
+                    ctx.config().keygen_timeout,
+                )
+                .await?
+            }
+        };
+        validate_verifier_address(verifier_address.as_ref())?;
+        debug!(
+            ?proving_backend,
+            "Keys generated (PK: {} bytes, VK: {} bytes)",
+            proving_key_data.len(),
+            verification_key_data.len()
+        );
+
+        // Relative paths for storing in CircuitInfo
+        let key_dir = PathBuf::from(proving_backend.name());
+        backend_keys.push(BackendKeys {
+            backend: proving_backend.clone(),
+            proving_key_path: key_dir.join(PROVING_KEY_FILENAME),
+            verification_key_path: key_dir.join(VERIFICATION_KEY_FILENAME),
+            verifier_address,
+        });
+        registered_backends.push(RegisteredBackend {
+            backend: proving_backend,
+            verifier_address,
+            verification_key: verification_key_data.clone(),
+        });
+        key_data.push((proving_key_data, verification_key_data));
+    }
 
     // --- Artifact Storage ---
     let artifact_store = ctx.circuit_store();
     let artifacts_base_path = artifact_store.get_artifacts_base_path();
     let circuit_artifact_dir = artifacts_base_path.join(&circuit_id_hex);
 
-    // Determine artifact file extension based on type
-    let artifact_ext = match circuit_type {
-        CircuitType::Circom => "r1cs", // Or .json, depends on compilation output
-        CircuitType::Noir => "acir",
-    };
-    let artifact_filename = format!("{}.{}", ARTIFACT_FILENAME, artifact_ext);
-
-    // Define relative paths for storing in CircuitInfo
-    let artifact_rel_path = PathBuf::from(&artifact_filename);
-    let pk_rel_path = PathBuf::from(PROVING_KEY_FILENAME);
-    let vk_rel_path = PathBuf::from(VERIFICATION_KEY_FILENAME);
+    // Define relative paths for storing in CircuitInfo, derived from each member's role
+    let artifact_paths: BTreeMap<ArtifactRole, PathBuf> = artifact_bundle
+        .keys()
+        .map(|role| (*role, PathBuf::from(artifact_filename(*role))))
+        .collect();
 
     let circuit_info = CircuitInfo {
         id: circuit_id,
         name: name.clone(),
         circuit_type,
-        proving_backend,
-        artifact_path: artifact_rel_path.clone(), // Store relative path
-        proving_key_path: pk_rel_path.clone(),    // Store relative path
-        verification_key_path: vk_rel_path.clone(), // Store relative path
-        verifier_address,                         // Store optional verifier address
+        artifact_paths: artifact_paths.clone(), // Store relative paths
+        artifact_blobs: BTreeMap::new(),        // Filled in by a content-addressed store
+        backends: backend_keys,                 // Store per-backend key paths
+        pinned_operators,
+        dependencies,
+        num_public_inputs,
+        noir_abi,
+        updated_at: unix_now(),
+        source_url,
+        artifact_checksum,
     };
 
     // Store artifacts and info
     debug!(dir = ?circuit_artifact_dir, "Storing artifacts...");
-    artifact_store.store_circuit_artifacts(
-        &circuit_id_hex,
-        &artifact_filename,
-        &artifact_data,
-        PROVING_KEY_FILENAME,
-        &proving_key_data,
-        VERIFICATION_KEY_FILENAME,
-        &verification_key_data,
-    )?;
-    artifact_store.store_circuit_info(&circuit_id_hex, &circuit_info)?;
-    info!(%circuit_id_hex, "Circuit artifacts and info stored successfully.");
-
-    // --- Prepare Result for Solidity ---
-    let result_verifier_addr_bytes = verifier_address.unwrap_or_default(); // Use default if None
+    let artifact_files: Vec<(&Path, &[u8])> = artifact_paths
+        .iter()
+        .map(|(role, rel_path)| (rel_path.as_path(), artifact_bundle[role].as_slice()))
+        .collect();
+    let key_files: Vec<(&[u8], &[u8])> = key_data
+        .iter()
+        .map(|(pk, vk)| (pk.as_slice(), vk.as_slice()))
+        .collect();
+    artifact_store.store_circuit(&circuit_info, &artifact_files, &key_files)?;
+    info!(%circuit_id_hex, bytes_downloaded, "Circuit artifacts and info stored successfully.");
+    if let Some(upload_id) = consumed_upload {
+        let uploads = ctx.artifact_uploads().clone();
+        match tokio::task::spawn_blocking(move || uploads.remove(upload_id, &caller)).await {
+            Ok(Ok(())) => {}
+            // The circuit is stored; a leftover upload only waits for the sweeper
+            Ok(Err(e)) => warn!(upload_id, error = %e, "Failed to remove consumed artifact upload"),
+            Err(e) => warn!(upload_id, error = %e, "Artifact upload removal task panicked"),
+        }
+    }
 
-    Ok(TangleResult((
+    Ok(RegisteredCircuit {
         circuit_id,
-        result_verifier_addr_bytes,
-        verification_key_data,
-    )))
+        backends: registered_backends,
+        bytes_downloaded,
+    })
+}
+
+/// Checks every registration argument up front, reporting all failures in one error.
+fn validate_request<K: KeyType>(
+    name: &str,
+    circuit_type: &CircuitType,
+    proving_backends: &[ProvingBackend],
+    artifact_source: &ArtifactSource,
+    pinned_operators: &[PublicKeyBytes],
+    pregenerated_keys: Option<&PregeneratedKeys>,
+) -> Result<()> {
+    let mut checks = vec![
+        validate_circuit_name(name).map(|_| ()),
+        validate_backend_list(proving_backends),
+    ];
+    checks.extend(
+        proving_backends
+            .iter()
+            .map(|backend| validate_backend_compatibility(circuit_type, backend)),
+    );
+    checks.push(validate_artifact_source(artifact_source));
+    checks.push(validate_pinned_operators::<K>(pinned_operators));
+    if let Some(keys) = pregenerated_keys {
+        checks.push(validate_download_url(&keys.pk_url));
+        checks.push(validate_download_url(&keys.vk_url));
+        if proving_backends.len() > 1 {
+            checks.push(Err(Error::InvalidInput(
+                "Pre-generated keys can only be supplied for a single proving backend".to_string(),
+            )));
+        }
+    }
+    let errors: Vec<String> = checks
+        .into_iter()
+        .filter_map(|check| check.err().map(|e| e.to_string()))
+        .collect();
+    if !errors.is_empty() {
+        return Err(Error::InvalidInput(format!(
+            "Invalid registration request ({} errors): {}",
+            errors.len(),
+            errors.join("; ")
+        )));
+    }
+    Ok(())
+}
+
+/// Normalizes a circuit name, trimming surrounding whitespace and converting
+/// it to Unicode NFC, so that names differing only in those respects derive the
+/// same `CircuitId`.
+///
+/// Rejects names that are empty after trimming, longer than
+/// `MAX_CIRCUIT_NAME_LEN` bytes once normalized, or contain control characters.
+pub(crate) fn validate_circuit_name(name: &str) -> Result<String> {
+    let name: String = name.trim().nfc().collect();
+    if name.is_empty() {
+        return Err(Error::InvalidInput(
+            "Circuit name must not be empty".to_string(),
+        ));
+    }
+    if name.len() > MAX_CIRCUIT_NAME_LEN {
+        return Err(Error::InvalidInput(format!(
+            "Circuit name is {} bytes, exceeding the maximum of {}",
+            name.len(),
+            MAX_CIRCUIT_NAME_LEN
+        )));
+    }
+    if name.chars().any(char::is_control) {
+        return Err(Error::InvalidInput(
+            "Circuit name must not contain control characters".to_string(),
+        ));
+    }
+    Ok(name)
+}
+
+/// Checks that a URL source is an HTTP(S) URL and an inline source is non-empty.
+fn validate_artifact_source(artifact_source: &ArtifactSource) -> Result<()> {
+    match artifact_source {
+        ArtifactSource::Url(url) => validate_download_url(url),
+        ArtifactSource::Inline(data) if data.is_empty() => Err(Error::InvalidInput(
+            "Inline artifact must not be empty".to_string(),
+        )),
+        ArtifactSource::Bundle(members) if members.is_empty() => Err(Error::InvalidInput(
+            "Artifact bundle must have at least one member".to_string(),
+        )),
+        ArtifactSource::Bundle(members) => members
+            .values()
+            .try_for_each(|url| validate_download_url(url)),
+        ArtifactSource::Inline(_) | ArtifactSource::Upload { .. } => Ok(()),
+    }
+}
+
+/// Checks that `url` is an HTTP(S) URL.
+fn validate_download_url(url: &str) -> Result<()> {
+    let url = Url::parse(url)?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(Error::InvalidInput(format!(
+            "Unsupported download URL scheme: {}",
+            url.scheme()
+        )));
+    }
+    Ok(())
+}
+
+/// Checks every URL the registration downloads from against the operator's host allow-list.
+fn validate_download_hosts(
+    artifact_source: &ArtifactSource,
+    pregenerated_keys: Option<&PregeneratedKeys>,
+    allowed_hosts: Option<&[String]>,
+) -> Result<()> {
+    let mut urls = Vec::new();
+    match artifact_source {
+        ArtifactSource::Url(url) => urls.push(url.as_str()),
+        ArtifactSource::Bundle(members) => urls.extend(members.values().map(String::as_str)),
+        ArtifactSource::Inline(_) | ArtifactSource::Upload { .. } => {}
+    }
+    if let Some(keys) = pregenerated_keys {
+        urls.extend([keys.pk_url.as_str(), keys.vk_url.as_str()]);
+    }
+    for url in urls {
+        validate_allowed_host(&Url::parse(url)?, allowed_hosts)?;
+    }
+    Ok(())
+}
+
+/// Downloads the members of a bundle from their URLs concurrently, at most
+/// `concurrency` at a time (within the downloader's own limit).
+///
+/// A member that failed is logged and left out of the bundle unless its role
+/// is `required`. The first required member to fail cancels the downloads
+/// still running and fails the registration with its error. Cancelling
+/// `cancel`, the registration's token, cancels every member download.
+async fn download_bundle(
+    downloader: &ArtifactDownloader,
+    spool_dir: &Path,
+    concurrency: usize,
+    members: &BTreeMap<ArtifactRole, String>,
+    required: &[ArtifactRole],
+    cancel: &CancellationToken,
+) -> Result<BTreeMap<ArtifactRole, Vec<u8>>> {
+    let permits = Semaphore::new(concurrency.max(1));
+    let bundle_cancel = cancel.child_token();
+    let mut downloads: FuturesUnordered<_> = members
+        .iter()
+        .map(|(role, url)| {
+            let permits = &permits;
+            let bundle_cancel = &bundle_cancel;
+            async move {
+                let result = async {
+                    let _permit = permits.acquire().await.map_err(|_| {
+                        Error::Internal("Bundle download semaphore closed".to_string())
+                    })?;
+                    let url = Url::parse(url)?;
+                    debug!(%url, ?role, "Downloading bundle member...");
+                    let spool = downloader.download(&url, spool_dir, bundle_cancel).await?;
+                    Ok(tokio::fs::read(spool.path()).await?)
+                }
+                .await;
+                (*role, result)
+            }
+        })
+        .collect();
+
+    let mut bundle = BTreeMap::new();
+    while let Some((role, result)) = downloads.next().await {
+        match result {
+            Ok(data) => {
+                debug!(?role, "Bundle member downloaded ({} bytes)", data.len());
+                bundle.insert(role, data);
+            }
+            Err(e) => {
+                let is_required = required.contains(&role);
+                warn!(?role, is_required, error = %e, "Failed to download bundle member");
+                if is_required {
+                    // The bundle is unusable without it; stop the other downloads
+                    bundle_cancel.cancel();
+                    return Err(e);
+                }
+            }
+        }
+    }
+    Ok(bundle)
+}
+
+/// Downloads a pre-generated key, bounded like any other artifact.
+async fn download_key<K: KeyType>(
+    ctx: &CosnarksContext<K>,
+    url: &str,
+    cancel: &CancellationToken,
+) -> Result<Vec<u8>>
+where
+    K::Public: Unpin,
+{
+    let url = Url::parse(url)?;
+    debug!(%url, "Downloading pre-generated key...");
+    let spool = ctx
+        .downloader()
+        .download(&url, ctx.circuit_store().get_tmp_path(), cancel)
+        .await?;
+    Ok(tokio::fs::read(spool.path()).await?)
+}
+
+/// Rejects an empty backend list or one naming the same backend twice.
+fn validate_backend_list(proving_backends: &[ProvingBackend]) -> Result<()> {
+    if proving_backends.is_empty() {
+        return Err(Error::InvalidInput(
+            "At least one proving backend is required".to_string(),
+        ));
+    }
+    for (idx, backend) in proving_backends.iter().enumerate() {
+        if proving_backends[..idx].contains(backend) {
+            return Err(Error::InvalidInput(format!(
+                "Proving backend {:?} is listed more than once",
+                backend
+            )));
+        }
+    }
+    Ok(())
 }
 
 /// Validates if the chosen proving backend is compatible with the circuit type.
@@ -144,49 +656,391 @@ fn validate_backend_compatibility(
     }
 }
 
-/// Generates a unique CircuitId based on metadata.
-fn generate_circuit_id(
-    name: &str,
+/// Artifact roles that must be present in a bundle for the given circuit type and backend.
+fn required_artifact_roles(
+    circuit_type: &CircuitType,
+    _proving_backend: &ProvingBackend,
+) -> &'static [ArtifactRole] {
+    match circuit_type {
+        CircuitType::Circom => &[ArtifactRole::R1cs],
+        CircuitType::Noir => &[ArtifactRole::Acir],
+    }
+}
+
+/// Rejects a bundle that lacks any role required by the circuit type and backend.
+fn validate_artifact_bundle(
     circuit_type: &CircuitType,
     proving_backend: &ProvingBackend,
-) -> CircuitId {
-    let mut hasher = Sha256::new();
-    hasher.update(name.as_bytes());
-    hasher.update(format!("{:?}", circuit_type).as_bytes());
-    hasher.update(format!("{:?}", proving_backend).as_bytes());
-    hasher.finalize().into()
-}
-
-/// Downloads artifact data from a given URL.
-async fn download_artifact(url: &Url) -> Result<Vec<u8>> {
-    let response = reqwest::get(url.clone()).await?;
-    if !response.status().is_success() {
-        return Err(Error::NetworkError(format!(
-            "Failed to download artifact from {}: Status {}",
-            url,
-            response.status()
+    bundle: &BTreeMap<ArtifactRole, Vec<u8>>,
+) -> Result<()> {
+    let missing: Vec<ArtifactRole> = required_artifact_roles(circuit_type, proving_backend)
+        .iter()
+        .filter(|role| !bundle.contains_key(role))
+        .copied()
+        .collect();
+    if !missing.is_empty() {
+        return Err(Error::InvalidInput(format!(
+            "Artifact bundle for {:?}/{:?} is missing required members: {:?}",
+            circuit_type, proving_backend, missing
         )));
     }
-    let bytes = response.bytes().await?.to_vec();
-    Ok(bytes)
+    Ok(())
+}
+
+/// Derives the stored filename of a bundle member from its role.
+fn artifact_filename(role: ArtifactRole) -> String {
+    format!("{}.{}", ARTIFACT_FILENAME, role.extension())
+}
+
+/// Rejects circuits declaring more public inputs than the operator allows.
+pub(crate) fn validate_public_input_count(count: u32, max: u32) -> Result<()> {
+    if count > max {
+        return Err(Error::ArtifactParseError(format!(
+            "Circuit declares {} public inputs, exceeding the maximum of {}",
+            count, max
+        )));
+    }
+    Ok(())
+}
+
+/// Validates that every pinned operator is a well-formed public key.
+fn validate_pinned_operators<K: KeyType>(pinned_operators: &[PublicKeyBytes]) -> Result<()> {
+    for (idx, key_bytes) in pinned_operators.iter().enumerate() {
+        K::Public::from_bytes(key_bytes).map_err(|e| {
+            Error::InvalidInput(format!(
+                "Pinned operator {} is not a valid key: {:?}",
+                idx, e
+            ))
+        })?;
+    }
+    Ok(())
+}
+
+/// Drops repeated pinned operators, keeping the first occurrence of each, so a
+/// key listed twice does not count as two session participants.
+fn dedup_pinned_operators(pinned_operators: Vec<PublicKeyBytes>) -> Vec<PublicKeyBytes> {
+    let mut unique: Vec<PublicKeyBytes> = Vec::with_capacity(pinned_operators.len());
+    for key in pinned_operators {
+        if !unique.contains(&key) {
+            unique.push(key);
+        }
+    }
+    unique
+}
+
+/// Rejects the zero address, which would be indistinguishable from "no verifier".
+fn validate_verifier_address(verifier_address: Option<&VerifierAddress>) -> Result<()> {
+    match verifier_address {
+        Some(address) if address.is_zero() => Err(Error::Internal(
+            "Key generation reported the zero address as verifier".to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Checks that the SHA-256 digest of the artifact, `actual`, matches `expected`.
+fn verify_artifact_checksum(actual: &[u8; 32], expected: &[u8; 32]) -> Result<()> {
+    if actual != expected {
+        return Err(Error::InvalidInput(format!(
+            "Artifact checksum mismatch: expected {}, got {}",
+            hex::encode(expected),
+            hex::encode(actual)
+        )));
+    }
+    Ok(())
+}
+
+/// Runs key generation on the blocking pool, aborting once `timeout` elapses.
+///
+/// Setup works inside a scratch directory under `work_dir`, which is removed
+/// whether key generation succeeds, fails or times out. A timed-out setup is
+/// cancelled through its token, which it checks between steps; the scratch
+/// directory is only removed once the setup thread has stopped.
+async fn generate_keys_within_budget(
+    circuit_id_hex: &str,
+    circuit_type: &CircuitType,
+    proving_backend: &ProvingBackend,
+    artifact_data: &[u8],
+    work_dir: &Path,
+    timeout: Option<Duration>,
+) -> Result<(Vec<u8>, Vec<u8>, Option<VerifierAddress>)> {
+    let scratch_dir = tempfile::Builder::new()
+        .prefix("keygen-")
+        .tempdir_in(work_dir)?;
+    let cancel = CancellationToken::new();
+    let mut keygen = {
+        let circuit_type = circuit_type.clone();
+        let proving_backend = proving_backend.clone();
+        let artifact_data = artifact_data.to_vec();
+        let scratch_path = scratch_dir.path().to_path_buf();
+        let cancel = cancel.clone();
+        tokio::task::spawn_blocking(move || {
+            generate_keys_placeholder(
+                &circuit_type,
+                &proving_backend,
+                &artifact_data,
+                &scratch_path,
+                &cancel,
+            )
+        })
+    };
+
+    let joined = match timeout {
+        Some(timeout) => match tokio::time::timeout(timeout, &mut keygen).await {
+            Ok(joined) => joined,
+            Err(_) => {
+                warn!(%circuit_id_hex, ?timeout, "Key generation exceeded its time budget, cancelling it");
+                cancel.cancel();
+                // Wait for the setup to stop before removing the files it works on
+                let _ = keygen.await;
+                if let Err(e) = scratch_dir.close() {
+                    warn!(%circuit_id_hex, error = %e, "Failed to remove key generation scratch directory");
+                }
+                return Err(Error::KeyGenerationTimeout {
+                    circuit_id: circuit_id_hex.to_string(),
+                    timeout,
+                });
+            }
+        },
+        None => keygen.await,
+    };
+    joined.map_err(|e| Error::Internal(format!("Key generation task failed: {}", e)))?
 }
 
 /// Placeholder function for generating keys.
-/// TODO: Replace with actual calls to co-circom/co-noir setup functions.
+/// TODO: Replace with actual calls to co-circom/co-noir setup functions, which
+/// must check `cancel` between their steps.
 fn generate_keys_placeholder(
     _circuit_type: &CircuitType,
     _proving_backend: &ProvingBackend,
     _artifact_data: &[u8],
-) -> Result<(Vec<u8>, Vec<u8>, Option<[u8; 20]>)> {
+    _scratch_dir: &Path,
+    cancel: &CancellationToken,
+) -> Result<(Vec<u8>, Vec<u8>, Option<VerifierAddress>)> {
+    if cancel.is_cancelled() {
+        return Err(Error::Cancelled("Key generation was cancelled".to_string()));
+    }
     // Simulate key generation
     info!("Simulating key generation...");
     let proving_key_data = b"fake_proving_key_data".to_vec();
     let verification_key_data = b"fake_verification_key_data".to_vec();
-    // Optionally simulate generating/finding a verifier contract address
-    let verifier_address = Some([0u8; 20]);
+    // No verifier contract is deployed by the placeholder setup
+    let verifier_address = None;
     Ok((proving_key_data, verification_key_data, verifier_address))
 }
 
 // Placeholder/Helper function signatures (implementations needed)
 // async fn download_artifact(url: &str) -> Result<Vec<u8>> { ... }
 // fn generate_keys(ct: &CircuitType, pb: &ProvingBackend, artifact: &[u8], params: Option<serde_json::Value>) -> Result<(Vec<u8>, Vec<u8>)> { ... }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CIRCUIT_TYPES: [CircuitType; 2] = [CircuitType::Circom, CircuitType::Noir];
+    const BACKENDS: [ProvingBackend; 3] = [
+        ProvingBackend::Groth16,
+        ProvingBackend::Plonk,
+        ProvingBackend::UltraHonk,
+    ];
+
+    #[test]
+    fn circuit_names_are_normalized() {
+        assert_eq!(validate_circuit_name("  Name \t").unwrap(), "Name");
+        // "é" precomposed and as "e" followed by a combining acute accent
+        assert_eq!(
+            validate_circuit_name("caf\u{e9}").unwrap(),
+            validate_circuit_name("cafe\u{301}").unwrap()
+        );
+        assert_eq!(
+            generate_circuit_id(
+                &validate_circuit_name("Name ").unwrap(),
+                &CircuitType::Circom,
+                &[ProvingBackend::Groth16]
+            ),
+            generate_circuit_id("Name", &CircuitType::Circom, &[ProvingBackend::Groth16])
+        );
+
+        for invalid in [
+            "",
+            "   ",
+            "bad\u{0}name",
+            &"x".repeat(MAX_CIRCUIT_NAME_LEN + 1),
+        ] {
+            assert!(matches!(
+                validate_circuit_name(invalid),
+                Err(Error::InvalidInput(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn pinned_operators_are_deduplicated_in_order() {
+        let (a, b, c) = (vec![1u8; 33], vec![2u8; 33], vec![3u8; 33]);
+        assert_eq!(
+            dedup_pinned_operators(vec![b.clone(), a.clone(), b.clone(), c.clone(), a.clone()]),
+            vec![b, a, c]
+        );
+        assert!(dedup_pinned_operators(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn cancelled_key_generation_stops() {
+        let dir = tempfile::tempdir().unwrap();
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        assert!(matches!(
+            generate_keys_placeholder(
+                &CircuitType::Circom,
+                &ProvingBackend::Groth16,
+                b"artifact",
+                dir.path(),
+                &cancel
+            ),
+            Err(Error::Cancelled(_))
+        ));
+    }
+
+    #[test]
+    fn artifact_filenames_match_every_backend_combination() {
+        // (circuit type, backend) => filenames of the required bundle members
+        let expected: [(CircuitType, ProvingBackend, &[&str]); 3] = [
+            (CircuitType::Circom, ProvingBackend::Groth16, &[
+                "circuit_artifact.r1cs",
+            ]),
+            // co-circom's Plonk prover consumes the same R1CS as Groth16
+            (CircuitType::Circom, ProvingBackend::Plonk, &[
+                "circuit_artifact.r1cs",
+            ]),
+            (CircuitType::Noir, ProvingBackend::UltraHonk, &[
+                "circuit_artifact.acir",
+            ]),
+        ];
+
+        for circuit_type in &CIRCUIT_TYPES {
+            for backend in &BACKENDS {
+                let entry = expected
+                    .iter()
+                    .find(|(ty, be, _)| ty == circuit_type && be == backend);
+                let Some((_, _, filenames)) = entry else {
+                    assert!(
+                        validate_backend_compatibility(circuit_type, backend).is_err(),
+                        "{:?}/{:?} should be rejected",
+                        circuit_type,
+                        backend
+                    );
+                    continue;
+                };
+
+                validate_backend_compatibility(circuit_type, backend).unwrap();
+                let stored: Vec<String> = required_artifact_roles(circuit_type, backend)
+                    .iter()
+                    .map(|role| artifact_filename(*role))
+                    .collect();
+                assert_eq!(
+                    stored, *filenames,
+                    "{:?}/{:?} stores unexpected filenames",
+                    circuit_type, backend
+                );
+                assert_eq!(
+                    artifact_filename(ArtifactRole::primary(circuit_type)),
+                    filenames[0]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn bundle_sources_check_every_member_url() {
+        let bundle = |urls: [(ArtifactRole, &str); 2]| {
+            ArtifactSource::Bundle(
+                urls.into_iter()
+                    .map(|(role, url)| (role, url.to_string()))
+                    .collect(),
+            )
+        };
+        let source = bundle([
+            (ArtifactRole::R1cs, "https://artifacts.example.com/c.r1cs"),
+            (ArtifactRole::Wasm, "https://cdn.example.com/c.wasm"),
+        ]);
+        validate_artifact_source(&source).unwrap();
+        let allowed = vec!["artifacts.example.com".to_string()];
+        assert!(matches!(
+            validate_download_hosts(&source, None, Some(&allowed)),
+            Err(Error::DisallowedUrl(_))
+        ));
+
+        let unsupported = bundle([
+            (ArtifactRole::R1cs, "https://artifacts.example.com/c.r1cs"),
+            (ArtifactRole::Sym, "ftp://artifacts.example.com/c.sym"),
+        ]);
+        assert!(validate_artifact_source(&unsupported).is_err());
+        assert!(validate_artifact_source(&ArtifactSource::Bundle(BTreeMap::new())).is_err());
+    }
+
+    #[tokio::test]
+    async fn required_bundle_member_failure_cancels_the_rest() {
+        use httpmock::prelude::*;
+
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/c.r1cs");
+            then.status(404);
+        });
+        server.mock(|when, then| {
+            when.method(GET).path("/c.wasm");
+            then.status(500).delay(Duration::from_secs(5));
+        });
+        server.mock(|when, then| {
+            when.method(GET).path("/c.sym");
+            then.status(200).body(b"symbols");
+        });
+        let members = |paths: &[(ArtifactRole, &str)]| -> BTreeMap<ArtifactRole, String> {
+            paths
+                .iter()
+                .map(|(role, path)| (*role, server.url(path)))
+                .collect()
+        };
+
+        let spool_dir = tempfile::tempdir().unwrap();
+        let downloader = ArtifactDownloader::new(4, 1024);
+        let cancel = CancellationToken::new();
+
+        // The required R1CS fails fast, cancelling the slow optional WASM download
+        let started = std::time::Instant::now();
+        let result = download_bundle(
+            &downloader,
+            spool_dir.path(),
+            4,
+            &members(&[
+                (ArtifactRole::R1cs, "/c.r1cs"),
+                (ArtifactRole::Wasm, "/c.wasm"),
+            ]),
+            &[ArtifactRole::R1cs],
+            &cancel,
+        )
+        .await;
+        assert!(matches!(result, Err(Error::InvalidInput(msg)) if msg.contains("404")));
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(!cancel.is_cancelled());
+
+        // A failed optional member is left out of the bundle
+        let bundle = download_bundle(
+            &downloader,
+            spool_dir.path(),
+            4,
+            &members(&[
+                (ArtifactRole::Sym, "/c.sym"),
+                (ArtifactRole::R1cs, "/c.r1cs"),
+            ]),
+            &[ArtifactRole::Sym],
+            &cancel,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            bundle,
+            BTreeMap::from([(ArtifactRole::Sym, b"symbols".to_vec())])
+        );
+    }
+}
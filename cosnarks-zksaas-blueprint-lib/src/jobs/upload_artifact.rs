@@ -0,0 +1,76 @@
+// upload_artifact job handlers: chunked uploads of artifacts too large for one job argument
+
+use crate::context::CosnarksContext;
+use crate::error::{Error, Result};
+use blueprint_sdk::crypto::KeyType;
+use blueprint_sdk::extract::Context;
+use blueprint_sdk::tangle::extract::{CallId, Caller, TangleArgs2, TangleArgs3, TangleResult};
+use tracing::info;
+
+/// Begins a chunked upload of a `total_size`-byte artifact in `chunk_count`
+/// chunks, bounded by `MAX_ARTIFACT_SIZE_BYTES`.
+///
+/// Returns the upload ID, which is this job's call ID. Only the caller may add
+/// chunks to the upload and finalize it.
+pub async fn begin_artifact_upload<K: KeyType>(
+    Context(ctx): Context<CosnarksContext<K>>,
+    CallId(call_id): CallId,
+    Caller(caller): Caller,
+    TangleArgs2(chunk_count, total_size): TangleArgs2<u32, u64>,
+) -> Result<TangleResult<u64>>
+where
+    K::Public: Unpin,
+{
+    let uploads = ctx.artifact_uploads().clone();
+    tokio::task::spawn_blocking(move || uploads.begin(call_id, caller, chunk_count, total_size))
+        .await
+        .map_err(upload_task_failed)??;
+    Ok(TangleResult(call_id))
+}
+
+/// Stores chunk `index` of an upload. Chunks may arrive in any order, and a
+/// re-sent chunk replaces the earlier one. Returns how many chunks are still
+/// missing.
+pub async fn artifact_chunk<K: KeyType>(
+    Context(ctx): Context<CosnarksContext<K>>,
+    CallId(call_id): CallId,
+    Caller(caller): Caller,
+    TangleArgs3(upload_id, index, data): TangleArgs3<u64, u32, Vec<u8>>,
+) -> Result<TangleResult<u32>>
+where
+    K::Public: Unpin,
+{
+    let uploads = ctx.artifact_uploads().clone();
+    let remaining =
+        tokio::task::spawn_blocking(move || uploads.add_chunk(upload_id, &caller, index, &data))
+            .await
+            .map_err(upload_task_failed)??;
+    info!(%call_id, upload_id, index, remaining, "Received artifact chunk");
+    Ok(TangleResult(remaining))
+}
+
+/// Assembles an upload once every chunk is present and checks it against the
+/// SHA-256 `checksum` of the whole artifact. Returns the artifact's size.
+///
+/// The finalized upload is registered by passing `ArtifactSource::Upload` to
+/// `register_circuit`, which consumes it once the circuit is stored.
+pub async fn finalize_artifact_upload<K: KeyType>(
+    Context(ctx): Context<CosnarksContext<K>>,
+    CallId(call_id): CallId,
+    Caller(caller): Caller,
+    TangleArgs2(upload_id, checksum): TangleArgs2<u64, [u8; 32]>,
+) -> Result<TangleResult<u64>>
+where
+    K::Public: Unpin,
+{
+    let uploads = ctx.artifact_uploads().clone();
+    let size = tokio::task::spawn_blocking(move || uploads.finalize(upload_id, &caller, &checksum))
+        .await
+        .map_err(upload_task_failed)??;
+    info!(%call_id, upload_id, size, "Finalized artifact upload");
+    Ok(TangleResult(size))
+}
+
+fn upload_task_failed(e: tokio::task::JoinError) -> Error {
+    Error::Internal(format!("Artifact upload task failed: {}", e))
+}
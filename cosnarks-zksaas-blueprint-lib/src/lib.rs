@@ -2,20 +2,56 @@
 //! Core library for the Collaborative zkSNARKs as a Service Blueprint.
 
 // Modules
+pub mod artifact;
+pub mod auth;
+pub mod certs;
+pub mod config;
 pub mod context;
+pub mod discovery;
+pub mod download;
 pub mod error;
+pub mod health;
 pub mod jobs;
+pub mod metrics;
 pub mod network;
+pub mod noir;
 pub mod p2p;
+pub mod prover;
+pub mod queue;
+pub mod redact;
 pub mod state;
 pub mod types;
+pub mod upload;
+pub mod verify;
+pub mod webhook;
+pub mod witness;
 
 // Re-exports for convenience
+pub use config::{OperatorSource, ServiceConfig, StaticOperator};
 pub use context::CosnarksContext;
+pub use discovery::{PeerReputation, PeerScore};
 pub use error::{Error, Result};
-pub use jobs::{GENERATE_PROOF_JOB_ID, REGISTER_CIRCUIT_JOB_ID};
-pub use state::CircuitStore;
-pub use types::{CircuitId, CircuitInfo, CircuitType, ProofResult, ProvingBackend, WitnessInput};
+pub use health::{Readiness, Subsystem};
+pub use jobs::{
+    ARTIFACT_CHUNK_JOB_ID, BEGIN_ARTIFACT_UPLOAD_JOB_ID, CANCEL_PROOF_JOB_ID, CLONE_CIRCUIT_JOB_ID,
+    EXPORT_CIRCUIT_JOB_ID, FINALIZE_ARTIFACT_UPLOAD_JOB_ID, FORCE_CLOSE_SESSION_JOB_ID,
+    GENERATE_PROOF_JOB_ID, GET_PROOF_JOB_ID, IMPORT_REGISTRY_JOB_ID, LIST_SESSIONS_JOB_ID,
+    REGISTER_CIRCUIT_JOB_ID, SYNC_CIRCUITS_JOB_ID,
+};
+pub use metrics::{CheatingHook, CheatingIncident, CheatingMetrics, TransferTotals};
+#[cfg(any(test, feature = "test-utils"))]
+pub use prover::MockProver;
+pub use prover::{PlaceholderProver, ProverBackend};
+pub use queue::{CancelOutcome, ProofQueue};
+pub use redact::Redacted;
+pub use state::{CircuitSnapshot, CircuitStore, CompactionReport, FileModes};
+pub use types::{
+    ArtifactRole, ArtifactSource, BackendKeys, BatchItem, BatchResult, CircuitExport, CircuitId,
+    CircuitInfo, CircuitStats, CircuitSyncReport, CircuitType, ExportChunk, ProofFailure,
+    ProofMetadata, ProofOutput, ProofPhase, ProofPointer, ProofResult, ProofTimings,
+    ProvingBackend, SessionStatus, VerifierAddress, WitnessInput,
+};
+pub use upload::ArtifactUploads;
 
 // Ensure blueprint_sdk is accessible
 pub use blueprint_sdk;
@@ -0,0 +1,177 @@
+//! Counters describing the work done by this operator since startup.
+
+use crate::p2p::BlameReason;
+use crate::types::PublicKeyBytes;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+use tracing::error;
+
+/// Running totals of artifact and key downloads, shared by every job.
+///
+/// The totals cover this process only: each operator runs its own node, so
+/// they are the local operator's transfers, not an aggregate across the
+/// service. Bytes are counted as they arrive, so aborted and rejected downloads
+/// still contribute the data they transferred.
+#[derive(Debug, Default)]
+pub struct TransferMetrics {
+    bytes_downloaded: AtomicU64,
+    downloads_completed: AtomicU64,
+    downloads_failed: AtomicU64,
+}
+
+/// A point-in-time copy of [`TransferMetrics`].
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TransferTotals {
+    /// Response body bytes received, after `Content-Encoding` decoding
+    pub bytes_downloaded: u64,
+    pub downloads_completed: u64,
+    pub downloads_failed: u64,
+}
+
+impl TransferMetrics {
+    pub(crate) fn record_bytes(&self, bytes: u64) {
+        self.bytes_downloaded.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_download(&self, succeeded: bool) {
+        let counter = if succeeded {
+            &self.downloads_completed
+        } else {
+            &self.downloads_failed
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns the current totals.
+    #[must_use]
+    pub fn totals(&self) -> TransferTotals {
+        TransferTotals {
+            bytes_downloaded: self.bytes_downloaded.load(Ordering::Relaxed),
+            downloads_completed: self.downloads_completed.load(Ordering::Relaxed),
+            downloads_failed: self.downloads_failed.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// A peer caught cheating during an MPC config exchange.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheatingIncident {
+    /// Public key of the guilty operator
+    pub operator: PublicKeyBytes,
+    /// Why the operator was blamed; the first blame if there were several
+    pub reason: BlameReason,
+    /// Unix timestamp (seconds) of the detection
+    pub detected_at: u64,
+}
+
+/// Callback fired on every [`CheatingIncident`], e.g. to post an alert to a
+/// webhook. Hooks run on the job's task, so slow alerts should be spawned.
+pub type CheatingHook = Arc<dyn Fn(&CheatingIncident) + Send + Sync>;
+
+/// Cheating incidents per guilty operator, with hooks for alerting.
+///
+/// Clones share the same counters and hooks.
+#[derive(Clone, Default)]
+pub struct CheatingMetrics {
+    incidents: Arc<Mutex<BTreeMap<PublicKeyBytes, u64>>>,
+    hooks: Arc<RwLock<Vec<CheatingHook>>>,
+}
+
+impl CheatingMetrics {
+    /// Registers `hook` to be called on every subsequent incident.
+    pub fn on_incident(&self, hook: CheatingHook) {
+        self.hooks
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(hook);
+    }
+
+    /// Counts `incident` against its operator and fires every hook.
+    pub(crate) fn record(&self, incident: &CheatingIncident) {
+        let total = {
+            let mut incidents = self
+                .incidents
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            let total = incidents.entry(incident.operator.clone()).or_default();
+            *total += 1;
+            *total
+        };
+        error!(
+            operator = %hex::encode(&incident.operator),
+            reason = ?incident.reason,
+            total,
+            "Cheating detected"
+        );
+        // Hooks run without the lock held, so they may register further hooks
+        let hooks: Vec<CheatingHook> = self
+            .hooks
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+        for hook in &hooks {
+            hook(incident);
+        }
+    }
+
+    /// Returns the number of incidents recorded against each operator.
+    #[must_use]
+    pub fn incidents(&self) -> BTreeMap<PublicKeyBytes, u64> {
+        self.incidents
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+}
+
+impl fmt::Debug for CheatingMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CheatingMetrics")
+            .field("incidents", &self.incidents())
+            .field(
+                "hooks",
+                &self
+                    .hooks
+                    .read()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .len(),
+            )
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    #[test]
+    fn counts_incidents_per_operator_and_fires_hooks() {
+        let metrics = CheatingMetrics::default();
+        let fired = Arc::new(AtomicUsize::new(0));
+        let seen = fired.clone();
+        metrics.on_incident(Arc::new(move |incident| {
+            assert_eq!(incident.reason, BlameReason::CommitmentMismatch);
+            seen.fetch_add(1, Ordering::Relaxed);
+        }));
+
+        let incident = |operator: &[u8]| CheatingIncident {
+            operator: operator.to_vec(),
+            reason: BlameReason::CommitmentMismatch,
+            detected_at: 0,
+        };
+        metrics.record(&incident(b"alice"));
+        metrics.record(&incident(b"bob"));
+        metrics.record(&incident(b"alice"));
+
+        assert_eq!(fired.load(Ordering::Relaxed), 3);
+        let incidents = metrics.incidents();
+        assert_eq!(incidents.get(b"alice".as_slice()), Some(&2));
+        assert_eq!(incidents.get(b"bob".as_slice()), Some(&1));
+        // Clones share the counters
+        assert_eq!(metrics.clone().incidents(), incidents);
+    }
+}
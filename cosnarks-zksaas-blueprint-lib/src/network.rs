@@ -1,38 +1,363 @@
+use crate::certs::{OperatorCa, cert_fingerprint, read_cert, read_cert_fingerprint};
+use crate::discovery::{DiscoveryState, announce_nonce_now};
 use crate::error::{Error, Result};
-use crate::p2p::{ConfigExchangeMsg, RevealMsg, mpc_config_exchange};
-use blueprint_sdk::crypto::KeyType;
+use crate::p2p::liveness::{LivenessMsg, mpc_liveness_check};
+use crate::p2p::{
+    CommitmentScheme, ConfigExchangeBuilder, ConfigExchangeMsg, ExchangeExtension, PartyRole,
+    RevealMsg, VerifiedPartyConfig, parse_dns_name,
+};
+use crate::redact::Redacted;
+use crate::state::unix_now;
+use crate::types::{MpcAnnounceMessage, MpcNetAnnounce, SessionStatus};
+use blueprint_sdk::crypto::hashing::blake3_256;
+use blueprint_sdk::crypto::{BytesEncoding, KeyType};
 use blueprint_sdk::networking::discovery::peers::VerificationIdentifierKey;
 use blueprint_sdk::networking::round_based_compat::RoundBasedNetworkAdapter;
-use blueprint_sdk::networking::service_handle::NetworkServiceHandle;
+use blueprint_sdk::networking::service_handle::{NetworkReceiver, NetworkServiceHandle};
+use blueprint_sdk::networking::types::{MessageRouting, ProtocolMessage};
 use mpc_net::MpcNetworkHandler;
 use mpc_net::config::{NetworkConfig, NetworkConfigFile, NetworkPartyConfig};
 use round_based::{MpcParty, PartyIndex};
-use std::collections::HashMap;
-use std::net::SocketAddr;
-use std::path::PathBuf;
-use std::sync::Arc;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
-use tracing::{debug, info};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+const DEFAULT_ANNOUNCE_MAX_SKEW: Duration = Duration::from_secs(30);
+const DEFAULT_MPC_NET_TIMEOUT: Duration = Duration::from_secs(60);
+/// How often the libp2p peer set is sampled while waiting for discovery to settle.
+const DISCOVERY_POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// Round number of discovery gossip, outside the rounds of any MPC protocol.
+const DISCOVERY_ROUND: u16 = u16::MAX;
+/// How often inbound libp2p messages are drained and routed.
+pub const GOSSIP_POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// Bijective assignment of MPC `PartyIndex`es to participant public keys.
+///
+/// Produced only by [`build_party_mapping`], so the networking layer and the
+/// MPC protocol always agree on which key sits behind which index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartyMapping<P> {
+    // Participants in party index order
+    parties: Vec<P>,
+}
+
+/// Builds the canonical party mapping for a set of participants.
+///
+/// Party indices follow the order of `blake3(key bytes)`, with the raw bytes as a
+/// tie-breaker. The order depends only on the key encoding, not on the key type's
+/// `Ord` impl, so every operator derives the same mapping whatever order it learned
+/// the participants in. Fails with `Error::ConfigError` on duplicate keys or more
+/// parties than `PartyIndex` can address.
+pub fn build_party_mapping<P: BytesEncoding>(participants: Vec<P>) -> Result<PartyMapping<P>> {
+    build_party_mapping_by(participants, P::to_bytes)
+}
+
+/// Like [`build_party_mapping`], for participants encoded by `encode`.
+pub fn build_party_mapping_by<P>(
+    participants: Vec<P>,
+    encode: impl Fn(&P) -> Vec<u8>,
+) -> Result<PartyMapping<P>> {
+    let mut keyed: Vec<([u8; 32], Vec<u8>, P)> = participants
+        .into_iter()
+        .map(|participant| {
+            let bytes = encode(&participant);
+            (blake3_256(&bytes), bytes, participant)
+        })
+        .collect();
+    keyed.sort_by(|(hash_a, bytes_a, _), (hash_b, bytes_b, _)| {
+        hash_a.cmp(hash_b).then_with(|| bytes_a.cmp(bytes_b))
+    });
+    if keyed.windows(2).any(|pair| pair[0].1 == pair[1].1) {
+        return Err(Error::ConfigError(
+            "Participant list contains duplicate keys".to_string(),
+        ));
+    }
+    if keyed.len() > usize::from(PartyIndex::MAX) {
+        return Err(Error::ConfigError(format!(
+            "{} participants exceed the maximum of {}",
+            keyed.len(),
+            PartyIndex::MAX
+        )));
+    }
+    Ok(PartyMapping {
+        parties: keyed
+            .into_iter()
+            .map(|(_, _, participant)| participant)
+            .collect(),
+    })
+}
+
+impl<P: PartialEq> PartyMapping<P> {
+    /// Participants in party index order.
+    pub fn participants(&self) -> &[P] {
+        &self.parties
+    }
+
+    /// Number of parties.
+    pub fn len(&self) -> u16 {
+        self.parties.len() as u16
+    }
+
+    /// Whether the mapping has no parties.
+    pub fn is_empty(&self) -> bool {
+        self.parties.is_empty()
+    }
+
+    /// The party index assigned to `participant`.
+    pub fn index_of(&self, participant: &P) -> Option<PartyIndex> {
+        self.parties
+            .iter()
+            .position(|p| p == participant)
+            .map(|idx| idx as PartyIndex)
+    }
+
+    /// The participant assigned to `index`.
+    pub fn participant(&self, index: PartyIndex) -> Option<&P> {
+        self.parties.get(usize::from(index))
+    }
+
+    /// Iterates over `(party index, participant)` pairs.
+    pub fn iter(&self) -> impl Iterator<Item = (PartyIndex, &P)> {
+        self.parties
+            .iter()
+            .enumerate()
+            .map(|(idx, p)| (idx as PartyIndex, p))
+    }
+}
+
+/// The local MPC-Net listener: the socket mpc-net binds and the `hostname:port`
+/// revealed to peers, who dial it.
+///
+/// The two may differ, e.g. a DNS name or public IP in front of a wildcard bind,
+/// but must agree where they overlap: the ports must match, and an advertised IP
+/// must be the bind IP unless the bind address is unspecified (`0.0.0.0` / `::`).
+/// The advertised host may not itself be unspecified, since peers cannot dial it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MpcListenAddress {
+    bind_addr: SocketAddr,
+    advertised: String,
+}
+
+impl MpcListenAddress {
+    /// Validates `advertised` (`hostname:port`) against `bind_addr`.
+    ///
+    /// Without a `bind_addr`, an advertised IP is bound directly and a DNS name
+    /// binds all IPv4 interfaces on the advertised port.
+    pub fn new(advertised: &str, bind_addr: Option<SocketAddr>) -> Result<Self> {
+        let address = parse_dns_name(advertised)?;
+        let advertised_ip = address.hostname.parse::<IpAddr>().ok();
+        if advertised_ip.is_some_and(|ip| ip.is_unspecified()) {
+            return Err(Error::ConfigError(format!(
+                "Advertised MPC-Net address {} is not reachable by peers",
+                advertised
+            )));
+        }
+
+        let bind_addr = match bind_addr {
+            Some(bind_addr) => bind_addr,
+            None => match advertised_ip {
+                Some(ip) => SocketAddr::new(ip, address.port),
+                None => SocketAddr::from(([0, 0, 0, 0], address.port)),
+            },
+        };
+        if bind_addr.port() != address.port {
+            return Err(Error::ConfigError(format!(
+                "MPC-Net bind port {} differs from advertised port {}",
+                bind_addr.port(),
+                address.port
+            )));
+        }
+        if let Some(ip) = advertised_ip {
+            if !bind_addr.ip().is_unspecified() && bind_addr.ip() != ip {
+                return Err(Error::ConfigError(format!(
+                    "MPC-Net binds {} but advertises {}",
+                    bind_addr, advertised
+                )));
+            }
+        }
+
+        Ok(Self {
+            bind_addr,
+            advertised: advertised.to_string(),
+        })
+    }
+
+    /// Socket the local mpc-net listener binds.
+    pub fn bind_addr(&self) -> SocketAddr {
+        self.bind_addr
+    }
+
+    /// `hostname:port` revealed to peers in the config exchange.
+    pub fn advertised(&self) -> &str {
+        &self.advertised
+    }
+}
+
+/// A participant of an MPC session, identified by its index in the ordered participant list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionParticipant<P> {
+    pub index: PartyIndex,
+    pub public_key: P,
+    pub role: PartyRole,
+    /// Certificate the participant revealed in the config exchange, as stored
+    /// in the local certificate directory, or the local party's own certificate
+    pub cert_path: PathBuf,
+    /// Fingerprint of that certificate, pinned for the lifetime of the session
+    pub cert_fingerprint: [u8; 32],
+}
+
+/// Time spent setting up an MPC session, from
+/// [`MpcNetworkManager::establish_mpc_session_timed`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SessionSetupTimings {
+    /// Whether a cached session was reused; both phases are zero then
+    pub reused: bool,
+    /// Config exchange, including the liveness check if enabled
+    pub config_exchange: Duration,
+    /// Establishing the MPC-Net mesh
+    pub mpc_net: Duration,
+}
+
+/// When idle cached sessions are evicted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionEviction {
+    /// How long an unused session stays cached after it was last handed out
+    pub idle_grace: Duration,
+}
+
+impl Default for SessionEviction {
+    fn default() -> Self {
+        Self {
+            idle_grace: Duration::from_secs(60),
+        }
+    }
+}
+
+impl SessionEviction {
+    /// Returns whether a session last handed out at `last_used_at` has
+    /// outlived its grace period at `now` (Unix seconds).
+    pub fn is_expired(&self, last_used_at: u64, now: u64) -> bool {
+        now.saturating_sub(last_used_at) > self.idle_grace.as_secs()
+    }
+}
+
+/// An established MPC session and the participants it connects.
+pub struct MpcSession<P> {
+    pub handler: Arc<MpcNetworkHandler>,
+    pub participants: Vec<SessionParticipant<P>>,
+    /// Unix timestamp (seconds) at which the session was established
+    pub established_at: u64,
+    /// Unix timestamp (seconds) at which the session was last handed out
+    last_used_at: AtomicU64,
+    /// Number of times the cached session was handed out again after being established
+    reuse_count: AtomicU64,
+    /// Cancelled when the session is force-closed, aborting jobs running on it
+    cancel: CancellationToken,
+    /// Proof deadline the participants last agreed on (see
+    /// [`MpcNetworkManager::record_deadline`])
+    deadline: Mutex<Option<u64>>,
+}
+
+impl<P> MpcSession<P> {
+    /// Returns a status snapshot of this session.
+    pub fn status(&self, session_id: &str) -> SessionStatus {
+        SessionStatus {
+            session_id: session_id.to_string(),
+            num_participants: self.participants.len() as u16,
+            established_at: self.established_at,
+            last_used_at: self.last_used_at.load(Ordering::Relaxed),
+            reuse_count: self.reuse_count.load(Ordering::Relaxed),
+            deadline: self
+                .deadline
+                .lock()
+                .map(|deadline| *deadline)
+                .unwrap_or(None),
+        }
+    }
+
+    fn touch(&self) {
+        self.last_used_at.store(unix_now(), Ordering::Relaxed);
+        self.reuse_count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns whether a job still holds the session's handler.
+    fn in_use(&self) -> bool {
+        Arc::strong_count(&self.handler) > 1
+    }
+
+    /// Checks that every participant still serves the certificate pinned at session start.
+    ///
+    /// Fails with `Error::CertificateError` naming the first participant whose
+    /// certificate was swapped or removed.
+    pub fn verify_pinned_certs(&self) -> Result<()> {
+        for participant in &self.participants {
+            let current = read_cert_fingerprint(&participant.cert_path).ok();
+            if current != Some(participant.cert_fingerprint) {
+                return Err(Error::CertificateError(format!(
+                    "Party {} switched certificates mid-session (pinned {})",
+                    participant.index,
+                    hex::encode(participant.cert_fingerprint)
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the participant acting as coordinator.
+    pub fn coordinator(&self) -> Option<&SessionParticipant<P>> {
+        self.participants
+            .iter()
+            .find(|p| p.role == PartyRole::Coordinator)
+    }
+}
 
 /// Manages the creation and lifecycle of MPC network sessions using round-based exchange.
 pub struct MpcNetworkManager<K: KeyType + 'static>
 where
     K::Public: Ord + Unpin,
 {
-    // Blueprint network handle for underlying p2p communication
+    // Blueprint network handle for underlying p2p communication. Its inbound
+    // messages are those `receive_gossip` routes to the MPC protocols.
     network_handle: NetworkServiceHandle<K>,
+    // Handle returned by libp2p, drained by `receive_gossip`
+    inbound: Arc<Mutex<NetworkServiceHandle<K>>>,
+    // Feeds the inbound messages of `network_handle`
+    protocol_messages: crossbeam_channel::Sender<ProtocolMessage>,
     // Local verification key for the current node
     local_verification_key: VerificationIdentifierKey<K>,
-    // Base socket address to bind MPC-Net listeners to (hostname:port)
-    // Use a publicly reachable address/DNS name in production
-    mpc_listen_dns: SocketAddr,
+    // Address MPC-Net listeners bind, and the publicly reachable name peers dial
+    listen_address: MpcListenAddress,
     // Path to MPC-Net private key
     key_path: PathBuf,
     // Path to MPC-Net certificate
     cert_path: PathBuf,
+    // Directory peer certificates received in the config exchange are stored under
+    cert_dir: PathBuf,
     // Cache for established MPC handlers, keyed by a unique session identifier
     // (e.g., derived from participants + job id)
-    established_handlers: Arc<RwLock<HashMap<String, Arc<MpcNetworkHandler>>>>,
+    established_handlers: Arc<RwLock<HashMap<String, MpcSession<K::Public>>>>,
+    // If set, a ping round with this timeout is run before the config exchange
+    liveness_timeout: Option<Duration>,
+    // MPC-Net connection timeout for sessions that do not set their own
+    mpc_net_timeout: Duration,
+    // Grace periods after which idle cached sessions are evicted
+    session_eviction: SessionEviction,
+    // Most recent round-trip times measured to each peer by the liveness check
+    peer_rtts: Arc<RwLock<BTreeMap<K::Public, Duration>>>,
+    // Hash function used for config exchange commitments; must match across peers
+    commitment_scheme: CommitmentScheme,
+    // If set, peer certificates must be issued by this CA
+    operator_ca: Option<OperatorCa>,
+    // Latest MPC-Net announcement accepted from each peer
+    discovery: Arc<RwLock<DiscoveryState<K>>>,
+    // Set once this node has announced its MPC-Net listener to the network
+    ready: Arc<AtomicBool>,
 }
 
 impl<K: KeyType + 'static> MpcNetworkManager<K>
@@ -40,23 +365,341 @@ where
     K::Public: Ord + Unpin,
 {
     /// Create a new MPC network manager
+    ///
+    /// Peer certificates received in the config exchange are stored in the
+    /// `peers` subdirectory of the directory of `cert_path`.
+    ///
+    /// Inbound messages of `network_handle` only reach the MPC protocols once
+    /// routed by [`Self::receive_gossip`], which must be polled.
     pub fn new(
         network_handle: NetworkServiceHandle<K>,
         local_verification_key: VerificationIdentifierKey<K>,
-        mpc_listen_dns: SocketAddr,
+        listen_address: MpcListenAddress,
         key_path: PathBuf,
         cert_path: PathBuf,
     ) -> Self {
+        let (protocol_messages, protocol_messages_rx) = crossbeam_channel::unbounded();
+        let mut protocol_handle = network_handle.clone();
+        protocol_handle.receiver = NetworkReceiver::new(protocol_messages_rx);
         Self {
-            network_handle,
+            network_handle: protocol_handle,
+            inbound: Arc::new(Mutex::new(network_handle)),
+            protocol_messages,
             local_verification_key,
-            mpc_listen_dns,
+            listen_address,
             key_path,
+            cert_dir: cert_path
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default(),
             cert_path,
             established_handlers: Arc::new(RwLock::new(HashMap::new())),
+            liveness_timeout: None,
+            mpc_net_timeout: DEFAULT_MPC_NET_TIMEOUT,
+            session_eviction: SessionEviction::default(),
+            peer_rtts: Arc::new(RwLock::new(BTreeMap::new())),
+            commitment_scheme: CommitmentScheme::default(),
+            operator_ca: None,
+            discovery: Arc::new(RwLock::new(DiscoveryState::new(DEFAULT_ANNOUNCE_MAX_SKEW))),
+            ready: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Sets the clock skew tolerated when validating peer announcements.
+    #[must_use]
+    pub fn with_announce_max_skew(mut self, max_skew: Duration) -> Self {
+        self.discovery = Arc::new(RwLock::new(DiscoveryState::new(max_skew)));
+        self
+    }
+
+    /// Consumes an `MpcNetAnnounce` gossiped by a peer.
+    ///
+    /// Replayed or out-of-window announcements are rejected with
+    /// `Error::StaleAnnouncement` and leave the recorded state untouched.
+    pub async fn handle_announce(&self, announce: MpcNetAnnounce<K>) -> Result<()> {
+        let peer = announce.public_key.clone();
+        let nonce = announce.nonce;
+        let result = self
+            .discovery
+            .write()
+            .await
+            .accept(announce, announce_nonce_now());
+        match &result {
+            Ok(()) => debug!(?peer, nonce, "Accepted MPC-Net announcement"),
+            Err(e) => warn!(?peer, nonce, error = %e, "Rejected MPC-Net announcement"),
+        }
+        result
+    }
+
+    /// Drains the libp2p messages received so far. Discovery gossip is passed
+    /// to [`Self::handle_announce`]; everything else is forwarded to the MPC
+    /// protocols reading from the manager's network handle. Returns the number
+    /// of announcements accepted.
+    pub async fn receive_gossip(&self) -> usize {
+        let messages: Vec<ProtocolMessage> = {
+            let Ok(mut inbound) = self.inbound.lock() else {
+                return 0;
+            };
+            std::iter::from_fn(|| inbound.next_protocol_message()).collect()
+        };
+        let mut accepted = 0;
+        for message in messages {
+            if message.routing.round != DISCOVERY_ROUND {
+                // The receiver lives as long as the manager
+                let _ = self.protocol_messages.send(message);
+                continue;
+            }
+            match bincode::deserialize::<MpcAnnounceMessage<K>>(&message.payload) {
+                Ok(MpcAnnounceMessage::Announce(announce)) => {
+                    if self.handle_announce(announce).await.is_ok() {
+                        accepted += 1;
+                    }
+                }
+                Err(e) => {
+                    warn!(sender = %message.routing.sender, error = %e, "Dropping malformed discovery message")
+                }
+            }
+        }
+        accepted
+    }
+
+    /// Builds this node's MPC-Net announcement, stamped with the current time.
+    ///
+    /// The listen address is the advertised `MPC_LISTEN_DNS` peers dial, and
+    /// the certificate path is relative to the certificate directory, as in
+    /// the config exchange.
+    pub fn local_announce(&self) -> Result<MpcNetAnnounce<K>> {
+        let VerificationIdentifierKey::InstancePublicKey(public_key) = &self.local_verification_key
+        else {
+            return Err(Error::ConfigError(
+                "MPC-Net announcements require an instance public key".to_string(),
+            ));
+        };
+        let cert_path = self
+            .cert_path
+            .strip_prefix(&self.cert_dir)
+            .map_err(|_| {
+                Error::ConfigError(
+                    "MPC-Net certificate is outside the certificate directory".to_string(),
+                )
+            })?
+            .to_path_buf();
+        Ok(MpcNetAnnounce {
+            public_key: public_key.clone(),
+            listen_addr: self.listen_address.advertised().to_string(),
+            cert_path,
+            nonce: announce_nonce_now(),
+        })
+    }
+
+    /// Waits until the set of connected libp2p peers has not changed for
+    /// `settle`, or until `timeout` elapses. Returns the number of peers.
+    pub async fn wait_for_stable_peers(&self, settle: Duration, timeout: Duration) -> usize {
+        let started = Instant::now();
+        let mut peers = self.network_handle.peers().len();
+        let mut stable_since = Instant::now();
+        while stable_since.elapsed() < settle && started.elapsed() < timeout {
+            tokio::time::sleep(DISCOVERY_POLL_INTERVAL).await;
+            let current = self.network_handle.peers().len();
+            if current != peers {
+                debug!(peers = current, "libp2p peer set changed");
+                peers = current;
+                stable_since = Instant::now();
+            }
+        }
+        peers
+    }
+
+    /// Gossips this node's `MpcAnnounceMessage::Announce` and marks it ready
+    /// to take part in MPC sessions.
+    pub fn announce_ready(&self) -> Result<()> {
+        let announce = self.local_announce()?;
+        let routing = MessageRouting {
+            message_id: announce.nonce,
+            round: DISCOVERY_ROUND,
+            sender: self.network_handle.local_peer_id,
+            recipient: None,
+        };
+        let payload = bincode::serialize(&MpcAnnounceMessage::Announce(announce))?;
+        self.network_handle
+            .send(routing, payload)
+            .map_err(|e| Error::NetworkError(format!("Failed to gossip announcement: {}", e)))?;
+        self.ready.store(true, Ordering::Release);
+        info!(
+            advertised = %self.listen_address.advertised(),
+            "Announced MPC-Net listener; ready for MPC sessions"
+        );
+        Ok(())
+    }
+
+    /// Returns whether this node has announced its MPC-Net listener.
+    pub fn is_ready(&self) -> bool {
+        self.ready.load(Ordering::Acquire)
+    }
+
+    /// Returns the `participants`, other than this node, that have not
+    /// announced their MPC-Net listener.
+    pub async fn unannounced_peers(&self, participants: &[K::Public]) -> Vec<K::Public> {
+        let discovery = self.discovery.read().await;
+        participants
+            .iter()
+            .filter(|peer| !self.is_local_key(peer) && discovery.get(peer).is_none())
+            .cloned()
+            .collect()
+    }
+
+    /// Sets the hash function used for config exchange commitments.
+    #[must_use]
+    pub fn with_commitment_scheme(mut self, scheme: CommitmentScheme) -> Self {
+        self.commitment_scheme = scheme;
+        self
+    }
+
+    /// Address the MPC-Net listener binds and advertises.
+    pub fn listen_address(&self) -> &MpcListenAddress {
+        &self.listen_address
+    }
+
+    /// Only trusts peer certificates issued by `operator_ca` in the config
+    /// exchange, instead of any self-signed certificate a peer reveals.
+    #[must_use]
+    pub fn with_operator_ca(mut self, operator_ca: OperatorCa) -> Self {
+        self.operator_ca = Some(operator_ca);
+        self
+    }
+
+    /// Enables a liveness check (ping round) before each new session.
+    /// Sessions fail fast if any participant does not respond within `timeout`.
+    #[must_use]
+    pub fn with_liveness_check(mut self, timeout: Duration) -> Self {
+        self.liveness_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the default MPC-Net connection timeout (60 seconds unless set).
+    /// Individual sessions may override it via `establish_mpc_session_with_timeout`.
+    #[must_use]
+    pub fn with_mpc_net_timeout(mut self, timeout: Duration) -> Self {
+        self.mpc_net_timeout = timeout;
+        self
+    }
+
+    /// Sets when idle cached sessions are evicted by [`Self::evict_idle_sessions`].
+    #[must_use]
+    pub fn with_session_eviction(mut self, eviction: SessionEviction) -> Self {
+        self.session_eviction = eviction;
+        self
+    }
+
+    /// Returns the eviction policy of cached sessions.
+    pub fn session_eviction(&self) -> &SessionEviction {
+        &self.session_eviction
+    }
+
+    /// Drops cached sessions that have been idle for longer than the grace
+    /// period. Sessions a job is still running on are kept. Returns the number
+    /// of evicted sessions.
+    pub async fn evict_idle_sessions(&self) -> usize {
+        let now = unix_now();
+        let mut handlers = self.established_handlers.write().await;
+        let before = handlers.len();
+        handlers.retain(|session_id, session| {
+            let expired = !session.in_use()
+                && self
+                    .session_eviction
+                    .is_expired(session.last_used_at.load(Ordering::Relaxed), now);
+            if expired {
+                debug!(%session_id, "Evicting idle MPC session");
+            }
+            !expired
+        });
+        before - handlers.len()
+    }
+
+    /// Returns whether `key` is the local node's instance key.
+    pub fn is_local_key(&self, key: &K::Public) -> bool {
+        VerificationIdentifierKey::InstancePublicKey(key.clone()) == self.local_verification_key
+    }
+
+    /// Returns the status of every cached session, sorted by session ID.
+    pub async fn active_sessions(&self) -> Vec<SessionStatus> {
+        let mut sessions: Vec<SessionStatus> = self
+            .established_handlers
+            .read()
+            .await
+            .iter()
+            .map(|(session_id, session)| session.status(session_id))
+            .collect();
+        sessions.sort_by(|a, b| a.session_id.cmp(&b.session_id));
+        sessions
+    }
+
+    /// Returns a token that is cancelled if the session is force-closed.
+    ///
+    /// Jobs running on the session should abort once it fires.
+    pub async fn session_cancellation_token(
+        &self,
+        session_instance_id: &str,
+    ) -> Option<CancellationToken> {
+        self.established_handlers
+            .read()
+            .await
+            .get(session_instance_id)
+            .map(|session| session.cancel.clone())
+    }
+
+    /// Removes a cached session and signals any job running on it to abort.
+    ///
+    /// Returns `false` if no session with this ID was cached. The next job for
+    /// the same session ID establishes a fresh MPC-Net connection.
+    pub async fn force_close(&self, session_instance_id: &str) -> bool {
+        let removed = self
+            .established_handlers
+            .write()
+            .await
+            .remove(session_instance_id);
+        match removed {
+            Some(session) => {
+                session.cancel.cancel();
+                warn!(session_id = %session_instance_id, "Force-closed MPC session");
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Records the proof deadline the participants of a session agreed on
+    /// (see [`crate::p2p::deadline::DeadlineAgreement`]), reported in its status.
+    pub async fn record_deadline(&self, session_instance_id: &str, deadline: Option<u64>) {
+        if let Some(session) = self
+            .established_handlers
+            .read()
+            .await
+            .get(session_instance_id)
+        {
+            if let Ok(mut session_deadline) = session.deadline.lock() {
+                *session_deadline = deadline;
+            }
         }
     }
 
+    /// Returns the participants and their roles for an established session.
+    pub async fn session_participants(
+        &self,
+        session_instance_id: &str,
+    ) -> Option<Vec<SessionParticipant<K::Public>>> {
+        self.established_handlers
+            .read()
+            .await
+            .get(session_instance_id)
+            .map(|session| session.participants.clone())
+    }
+
+    /// Returns the most recently measured round-trip time to each peer.
+    pub async fn peer_rtts(&self) -> BTreeMap<K::Public, Duration> {
+        self.peer_rtts.read().await.clone()
+    }
+
     /// Establishes an MPC-Net session with a given set of participants for a specific job.
     ///
     /// This method orchestrates the secure exchange of MPC-Net configuration details
@@ -69,8 +712,10 @@ where
     ///                          (e.g., "proof_job_<job_id>_participants_hash").
     ///                          Used for namespacing the round-based protocol messages.
     /// * `ordered_participants`: A vector of the public keys of *all* participants
-    ///                           (including the local node) in a deterministic order.
-    ///                           This order determines the `PartyIndex` and MPC ID.
+    ///                           (including the local node) in the order of their
+    ///                           [`build_party_mapping`]. This order determines the
+    ///                           `PartyIndex`, MPC ID and `PartyRole` (the first
+    ///                           participant coordinates); any other order is rejected.
     ///
     /// # Returns
     ///
@@ -80,63 +725,170 @@ where
         session_instance_id: &str,
         ordered_participants: Vec<K::Public>,
     ) -> Result<Arc<MpcNetworkHandler>> {
-        // Check cache first
-        if let Some(handler) = self
+        self.establish_mpc_session_with_timeout(
+            session_instance_id,
+            ordered_participants,
+            self.mpc_net_timeout,
+        )
+        .await
+    }
+
+    /// Like [`Self::establish_mpc_session`], also reporting how long the config
+    /// exchange and the MPC-Net setup took.
+    ///
+    /// `extensions` are added to the config exchange of a new session, in order;
+    /// every participant must pass the same ones. They are not run when a cached
+    /// session is returned. If an extension finds the session unnecessary
+    /// ([`ExchangeExtension::session_needed`]), no MPC-Net mesh is built and
+    /// no handler is returned.
+    pub async fn establish_mpc_session_timed(
+        &self,
+        session_instance_id: &str,
+        ordered_participants: Vec<K::Public>,
+        extensions: Vec<Arc<dyn ExchangeExtension>>,
+    ) -> Result<(Option<Arc<MpcNetworkHandler>>, SessionSetupTimings)> {
+        self.establish_session(
+            session_instance_id,
+            ordered_participants,
+            self.mpc_net_timeout,
+            extensions,
+        )
+        .await
+    }
+
+    /// Like [`Self::establish_mpc_session`], with an MPC-Net connection timeout
+    /// for this session instead of the manager's default.
+    ///
+    /// The timeout only applies when a new connection is established; cached
+    /// sessions are returned as-is.
+    pub async fn establish_mpc_session_with_timeout(
+        &self,
+        session_instance_id: &str,
+        ordered_participants: Vec<K::Public>,
+        mpc_net_timeout: Duration,
+    ) -> Result<Arc<MpcNetworkHandler>> {
+        let (handler, _) = self
+            .establish_session(
+                session_instance_id,
+                ordered_participants,
+                mpc_net_timeout,
+                Vec::new(),
+            )
+            .await?;
+        // Only an extension can make the session unnecessary
+        handler.ok_or_else(|| {
+            Error::Internal("MPC session was skipped without extensions".to_string())
+        })
+    }
+
+    async fn establish_session(
+        &self,
+        session_instance_id: &str,
+        ordered_participants: Vec<K::Public>,
+        mpc_net_timeout: Duration,
+        extensions: Vec<Arc<dyn ExchangeExtension>>,
+    ) -> Result<(Option<Arc<MpcNetworkHandler>>, SessionSetupTimings)> {
+        // Check cache first. A cached session is only reused while every
+        // participant still serves the certificate pinned when it was established.
+        let cached = self
             .established_handlers
             .read()
             .await
             .get(session_instance_id)
-        {
-            info!(session_id = %session_instance_id, "Returning cached MPC handler");
-            return Ok(handler.clone());
+            .map(|session| {
+                session.verify_pinned_certs().map(|()| {
+                    session.touch();
+                    session.handler.clone()
+                })
+            });
+        match cached {
+            Some(Ok(handler)) => {
+                info!(session_id = %session_instance_id, "Returning cached MPC handler");
+                let timings = SessionSetupTimings {
+                    reused: true,
+                    ..SessionSetupTimings::default()
+                };
+                return Ok((Some(handler), timings));
+            }
+            Some(Err(e)) => {
+                warn!(session_id = %session_instance_id, error = %e, "Evicting MPC session with swapped certificate");
+                self.established_handlers
+                    .write()
+                    .await
+                    .remove(session_instance_id);
+                return Err(e);
+            }
+            None => {}
         }
 
         info!(session_id = %session_instance_id, num_participants = ordered_participants.len(), "Establishing new MPC session");
 
-        // 1. Determine local party index and total number of parties
-        let n = ordered_participants.len() as u16;
+        // 1. Determine local party index and total number of parties.
+        // The caller's order must be the canonical mapping, or our indices would
+        // disagree with the ones the networking layer routes messages by.
+        let mapping = build_party_mapping(ordered_participants.clone())?;
+        if mapping.participants() != ordered_participants.as_slice() {
+            return Err(Error::ConfigError(
+                "Participants are not in canonical party index order".to_string(),
+            ));
+        }
+        let n = mapping.len();
         if n < 2 {
             return Err(Error::ConfigError(
                 "MPC requires at least 2 participants".to_string(),
             ));
         }
 
-        let local_public_key = self.local_verification_key.clone();
-
-        let local_party_index = ordered_participants
+        let local_party_index = mapping
             .iter()
-            .position(|pk| {
-                VerificationIdentifierKey::InstancePublicKey(pk.clone()) == local_public_key
-            })
+            .find(|(_, pk)| self.is_local_key(pk))
+            .map(|(idx, _)| idx)
             .ok_or_else(|| {
                 Error::ConfigError("Local node not found in participant list".to_string())
-            })? as PartyIndex;
+            })?;
 
+        let local_role = PartyRole::for_index(local_party_index);
         debug!(
             my_index = local_party_index,
             total_parties = n,
+            ?local_role,
             "Determined party info"
         );
 
         // 2. Prepare local configuration reveal message
+        let local_cert_der = read_cert(&self.cert_path)?;
         let local_reveal_msg = RevealMsg {
-            dns_name: self.mpc_listen_dns.to_string(),
-            cert_path: self.cert_path.clone(),
+            dns_name: self.listen_address.advertised().to_string(),
+            cert_fingerprint: cert_fingerprint(&local_cert_der),
+            cert_der: local_cert_der,
+            role: local_role,
         };
 
         // 3. Setup round-based network adapter
         // Map PartyIndex (0..n-1) to VerificationIdentifierKey for the adapter
-        let party_mapping: HashMap<PartyIndex, VerificationIdentifierKey<K>> = ordered_participants
-            .into_iter()
-            .enumerate()
+        let party_mapping: HashMap<PartyIndex, VerificationIdentifierKey<K>> = mapping
+            .iter()
             .map(|(idx, pub_key)| {
                 (
-                    idx as PartyIndex,
-                    VerificationIdentifierKey::InstancePublicKey(pub_key),
+                    idx,
+                    VerificationIdentifierKey::InstancePublicKey(pub_key.clone()),
                 )
             })
             .collect();
 
+        // 3a. Optionally confirm every peer is alive before committing to the session
+        let exchange_started = Instant::now();
+        if let Some(timeout) = self.liveness_timeout {
+            self.run_liveness_check(
+                session_instance_id,
+                local_party_index,
+                n,
+                &party_mapping,
+                timeout,
+            )
+            .await?;
+        }
+
         let network_adapter = RoundBasedNetworkAdapter::new(
             self.network_handle.clone(),
             local_party_index,
@@ -147,59 +899,671 @@ where
 
         // 4. Execute the configuration exchange protocol
         info!(session_id = %session_instance_id, "Starting MPC config exchange protocol...");
-        let verified_configs =
-            mpc_config_exchange(mpc_party, local_party_index, n, local_reveal_msg)
-                .await
-                .map_err(|e| Error::MpcProtocolError(format!("Config exchange failed: {:?}", e)))?;
+        let mut exchange = ConfigExchangeBuilder::new(
+            local_party_index,
+            n,
+            local_reveal_msg,
+            self.cert_path.clone(),
+            self.cert_dir.clone(),
+        )
+        .with_commitment_scheme(self.commitment_scheme);
+        if let Some(operator_ca) = &self.operator_ca {
+            exchange = exchange.with_operator_ca(operator_ca.clone());
+        }
+        for extension in &extensions {
+            exchange = exchange.with_extension(extension.clone());
+        }
+        let verified_configs = exchange.run(mpc_party).await.map_err(|e| match e {
+            // Kept intact so callers can hold the blamed parties to account
+            e @ (Error::CommitmentMismatch { .. } | Error::ExcessMessages { .. }) => e,
+            e => Error::MpcProtocolError(format!("Config exchange failed: {:?}", e)),
+        })?;
         info!(session_id = %session_instance_id, "MPC config exchange complete.");
+        let config_exchange = exchange_started.elapsed();
+        if !extensions
+            .iter()
+            .all(|extension| extension.session_needed())
+        {
+            info!(session_id = %session_instance_id, "Config exchange made the MPC session unnecessary, skipping MPC-Net");
+            let timings = SessionSetupTimings {
+                reused: false,
+                config_exchange,
+                mpc_net: Duration::ZERO,
+            };
+            return Ok((None, timings));
+        }
 
-        // 5. Build the final NetworkConfigFile for mpc-net
-        let mut parties: Vec<NetworkPartyConfig> = verified_configs
-            .into_values()
-            .map(|config| config)
+        // 5. Record the pinned certificates and build the final NetworkConfigFile for mpc-net
+        //    Every participant must take part until a protocol runs on a
+        //    threshold subset, so the threshold is the full set.
+        let (parties, my_id) = mpc_net_parties(&verified_configs, n, n, local_party_index)?;
+        let participants: Vec<SessionParticipant<K::Public>> = mapping
+            .iter()
+            .filter_map(|(index, public_key)| {
+                let config = verified_configs.get(&index)?;
+                Some(SessionParticipant {
+                    index,
+                    public_key: public_key.clone(),
+                    role: PartyRole::for_index(index),
+                    cert_path: config.network.cert_path.clone(),
+                    cert_fingerprint: config.cert_fingerprint,
+                })
+            })
             .collect();
-
-        // Ensure parties are sorted by ID (which is the PartyIndex)
-        parties.sort_by_key(|p| p.id);
+        let num_parties = parties.len();
 
         let mpc_net_config_file = NetworkConfigFile {
-            my_id: local_party_index as usize,
-            bind_addr: self.mpc_listen_dns.clone(),
+            my_id,
+            bind_addr: self.listen_address.bind_addr(),
             key_path: self.key_path.clone(),
             parties,
-            timeout_secs: Some(60), // Increased timeout for potentially slower networks/setup
+            // mpc-net works in whole seconds; round up so short timeouts stay non-zero
+            timeout_secs: Some(mpc_net_timeout.as_secs_f64().ceil() as u64),
         };
 
-        debug!(config = ?mpc_net_config_file, "Constructed MPC-Net config file");
+        debug!(
+            my_id = mpc_net_config_file.my_id,
+            bind_addr = %mpc_net_config_file.bind_addr,
+            key_path = %Redacted(&mpc_net_config_file.key_path),
+            parties = mpc_net_config_file.parties.len(),
+            timeout_secs = ?mpc_net_config_file.timeout_secs,
+            "Constructed MPC-Net config file"
+        );
 
-        // 6. Establish the actual MPC-Net connection
-        let handler = Self::establish_mpc_network_internal(mpc_net_config_file).await?;
+        // 6. Establish the actual MPC-Net connection.
+        //    A partially connected mesh is dropped rather than cached, so the next
+        //    attempt starts from scratch instead of failing deep inside the proof.
+        let mpc_net_started = Instant::now();
+        let (handler, connections) =
+            Self::establish_mpc_network_internal(mpc_net_config_file).await?;
+        let expected_connections = num_parties - 1;
+        if connections < expected_connections {
+            warn!(
+                session_id = %session_instance_id,
+                connections,
+                expected_connections,
+                "MPC-Net mesh is incomplete, discarding handler"
+            );
+            return Err(Error::NetworkError(format!(
+                "MPC-Net established {} of {} expected connections",
+                connections, expected_connections
+            )));
+        }
         let handler_arc = Arc::new(handler);
+        let timings = SessionSetupTimings {
+            reused: false,
+            config_exchange,
+            mpc_net: mpc_net_started.elapsed(),
+        };
 
         // 7. Cache the handler
-        self.established_handlers
-            .write()
-            .await
-            .insert(session_instance_id.to_string(), handler_arc.clone());
+        let established_at = unix_now();
+        self.established_handlers.write().await.insert(
+            session_instance_id.to_string(),
+            MpcSession {
+                handler: handler_arc.clone(),
+                participants,
+                established_at,
+                last_used_at: AtomicU64::new(established_at),
+                reuse_count: AtomicU64::new(0),
+                cancel: CancellationToken::new(),
+                deadline: Mutex::new(None),
+            },
+        );
 
         info!(session_id = %session_instance_id, "Successfully established and cached MPC handler");
-        Ok(handler_arc)
+        Ok((Some(handler_arc), timings))
+    }
+
+    /// Runs the ping protocol over a dedicated instance and records the measured RTTs.
+    async fn run_liveness_check(
+        &self,
+        session_instance_id: &str,
+        local_party_index: PartyIndex,
+        n: u16,
+        party_mapping: &HashMap<PartyIndex, VerificationIdentifierKey<K>>,
+        timeout: Duration,
+    ) -> Result<()> {
+        info!(session_id = %session_instance_id, ?timeout, "Running MPC liveness check...");
+        let ping_instance_id = format!("{}-liveness", session_instance_id);
+        let network_adapter = RoundBasedNetworkAdapter::new(
+            self.network_handle.clone(),
+            local_party_index,
+            party_mapping.clone(),
+            &ping_instance_id,
+        );
+        let mpc_party: MpcParty<LivenessMsg, _, _> = MpcParty::connected(network_adapter);
+        let rtts = mpc_liveness_check(mpc_party, local_party_index, n, timeout).await?;
+
+        let mut peer_rtts = self.peer_rtts.write().await;
+        for (party_idx, rtt) in rtts {
+            if let Some(VerificationIdentifierKey::InstancePublicKey(pk)) =
+                party_mapping.get(&party_idx)
+            {
+                peer_rtts.insert(pk.clone(), rtt);
+            }
+        }
+        Ok(())
     }
 
     /// Internal helper to establish the MPC network connection.
+    ///
+    /// Returns the handler with the number of peers it is connected to.
+    /// `MpcNetworkHandler::establish` connects to every other party in the
+    /// config and fails if any of them cannot be reached, so these are the
+    /// distinct peer IDs the handler was established with.
     async fn establish_mpc_network_internal(
         config: NetworkConfigFile,
-    ) -> Result<MpcNetworkHandler> {
+    ) -> Result<(MpcNetworkHandler, usize)> {
         debug!("Converting NetworkConfigFile to NetworkConfig...");
+        let my_id = config.my_id;
         let network_config = NetworkConfig::try_from(config).map_err(|e| {
             Error::ConfigError(format!("Failed to create MPC network config: {}", e))
         })?;
+        let connections = network_config
+            .parties
+            .iter()
+            .map(|party| party.id)
+            .filter(|id| *id != my_id)
+            .collect::<BTreeSet<_>>()
+            .len();
 
         info!("Establishing MPC-Net connection...");
         let handler = MpcNetworkHandler::establish(network_config)
             .await
-            .map_err(|e| Error::NetworkError(format!("Failed to establish MPC network: {}", e)))?;
-        info!("MPC-Net connection established.");
-        Ok(handler)
+            .map_err(|e| classify_mpc_net_error(e.as_ref()))?;
+        info!(connections, "MPC-Net connection established.");
+        Ok((handler, connections))
+    }
+}
+
+/// Maps an mpc-net connection error onto the `Error` variant telling the
+/// operator what to fix.
+///
+/// Certificate rejections and other TLS failures point at the MPC-Net
+/// certificates, refused connections and timeouts at reachability of the peers'
+/// `MPC_LISTEN_DNS`. The error chain is searched for the underlying I/O and
+/// rustls errors; since mpc-net may wrap them in its own types, the messages are
+/// checked as a fallback. Anything else stays an `Error::NetworkError`.
+fn classify_mpc_net_error(error: &(dyn std::error::Error + 'static)) -> Error {
+    let message = format!("Failed to establish MPC network: {}", error);
+    let mut source = Some(error);
+    while let Some(current) = source {
+        if let Some(io) = current.downcast_ref::<std::io::Error>() {
+            match io.kind() {
+                std::io::ErrorKind::ConnectionRefused => {
+                    return Error::MpcNetConnectionRefused(message);
+                }
+                std::io::ErrorKind::TimedOut => return Error::MpcNetTimeout(message),
+                _ => {}
+            }
+        }
+        if current
+            .downcast_ref::<tokio::time::error::Elapsed>()
+            .is_some()
+        {
+            return Error::MpcNetTimeout(message);
+        }
+        if let Some(tls) = current.downcast_ref::<rustls::Error>() {
+            return match tls {
+                rustls::Error::InvalidCertificate(_) | rustls::Error::NoCertificatesPresented => {
+                    Error::MpcNetCertificate(message)
+                }
+                _ => Error::MpcNetTlsHandshake(message),
+            };
+        }
+
+        let text = current.to_string().to_ascii_lowercase();
+        if text.contains("certificate") {
+            return Error::MpcNetCertificate(message);
+        }
+        if text.contains("handshake") || text.contains("tls") {
+            return Error::MpcNetTlsHandshake(message);
+        }
+        if text.contains("connection refused") {
+            return Error::MpcNetConnectionRefused(message);
+        }
+        if text.contains("timed out") || text.contains("timeout") {
+            return Error::MpcNetTimeout(message);
+        }
+        source = current.source();
+    }
+    Error::NetworkError(message)
+}
+
+/// Removes the MPC-Net TLS material at the configured `key_path` and
+/// `cert_path` (`MPC_KEY_PATH`, `MPC_CERT_PATH`), plus the PEM copy
+/// `certs::generate_mpc_cert` writes next to the certificate, returning the
+/// removed paths. Missing files and directories are skipped.
+pub fn cleanup_mpc_material(key_path: &Path, cert_path: &Path) -> Result<Vec<PathBuf>> {
+    let mut removed = Vec::new();
+    for path in [
+        key_path.to_path_buf(),
+        cert_path.to_path_buf(),
+        cert_path.with_extension("pem"),
+    ] {
+        if removed.contains(&path) || !path.is_file() {
+            continue;
+        }
+        std::fs::remove_file(&path)?;
+        removed.push(path);
+    }
+    if !removed.is_empty() {
+        info!(cert_path = %Redacted(cert_path), count = removed.len(), "Removed MPC-Net certificate material");
+    }
+    Ok(removed)
+}
+
+/// Builds the mpc-net party list from the configs verified in the config
+/// exchange, returning it with the local party's MPC-Net ID.
+///
+/// The exchange assigns every party its party index as MPC-Net ID. mpc-net
+/// expects IDs `0..m` for its `m` parties, so when only a subset of at least
+/// `threshold` of the `n` parties proceeds, the remaining parties are
+/// renumbered contiguously in party index order. Fails with
+/// `Error::ConfigError` if fewer than `threshold` parties were verified, a
+/// party carries an ID other than its index, or the local party is not among
+/// them.
+fn mpc_net_parties(
+    verified_configs: &HashMap<PartyIndex, VerifiedPartyConfig>,
+    n: u16,
+    threshold: u16,
+    local_party_index: PartyIndex,
+) -> Result<(Vec<NetworkPartyConfig>, usize)> {
+    if verified_configs.len() > usize::from(n) || verified_configs.len() < usize::from(threshold) {
+        return Err(Error::ConfigError(format!(
+            "Config exchange verified {} of {} parties, {} required",
+            verified_configs.len(),
+            n,
+            threshold
+        )));
+    }
+    let mut verified: Vec<(&PartyIndex, &VerifiedPartyConfig)> = verified_configs.iter().collect();
+    verified.sort_by_key(|(index, _)| **index);
+    let mut parties = Vec::with_capacity(verified.len());
+    let mut my_id = None;
+    for (id, (index, config)) in verified.into_iter().enumerate() {
+        // The exchange assigns every party its own index as MPC-Net ID
+        if *index >= n || config.network.id != usize::from(*index) {
+            return Err(Error::ConfigError(format!(
+                "Party {} of {} was assigned MPC-Net ID {}",
+                index, n, config.network.id
+            )));
+        }
+        if *index == local_party_index {
+            my_id = Some(id);
+        }
+        parties.push(NetworkPartyConfig {
+            id,
+            ..config.network.clone()
+        });
+    }
+    let my_id = my_id.ok_or_else(|| {
+        Error::ConfigError(format!(
+            "Local party {} is not among the verified parties",
+            local_party_index
+        ))
+    })?;
+    if parties.len() < usize::from(n) {
+        info!(
+            num_parties = parties.len(),
+            n, "Renumbering the verified parties contiguously for mpc-net"
+        );
+    }
+    Ok((parties, my_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blueprint_sdk::crypto::sp_core::SpEcdsa;
+    use blueprint_sdk::networking::AllowedKeys;
+    use blueprint_sdk::networking::test_utils::TestNode;
+
+    fn mapping(keys: Vec<[u8; 4]>) -> Result<PartyMapping<[u8; 4]>> {
+        build_party_mapping_by(keys, |key| key.to_vec())
+    }
+
+    #[test]
+    fn party_mapping_is_independent_of_input_order() {
+        let keys = vec![[3u8; 4], [1u8; 4], [4u8; 4], [2u8; 4]];
+        let mapping = mapping(keys.clone()).unwrap();
+
+        // Index and key lookups are inverse to each other
+        for (index, key) in mapping.iter() {
+            assert_eq!(mapping.index_of(key), Some(index));
+            assert_eq!(mapping.participant(index), Some(key));
+        }
+        assert_eq!(mapping.participant(4), None);
+
+        // Indices follow the hash of the key bytes, not the keys' own order
+        let hashes: Vec<_> = mapping
+            .participants()
+            .iter()
+            .map(|key| blake3_256(key))
+            .collect();
+        assert!(hashes.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn parties_agree_on_mapping_whatever_order_they_learn_keys() {
+        let keys: Vec<[u8; 4]> = (0u8..8).map(|i| [i, i.wrapping_mul(37), 0xA5, i]).collect();
+        let expected = mapping(keys.clone()).unwrap();
+
+        // Each party sees the operator set in its own order, e.g. from differently
+        // ordered chain queries or discovery responses
+        for party in 0..keys.len() {
+            let mut view = keys.clone();
+            view.rotate_left(party);
+            if party % 2 == 1 {
+                view.reverse();
+            }
+            assert_eq!(
+                mapping(view).unwrap(),
+                expected,
+                "party {} disagrees",
+                party
+            );
+        }
+    }
+
+    #[test]
+    fn party_mapping_rejects_duplicate_keys() {
+        let result = mapping(vec![[1u8; 4], [2u8; 4], [1u8; 4]]);
+        assert!(matches!(result, Err(Error::ConfigError(_))));
+    }
+
+    #[test]
+    fn listen_address_binds_consistently_with_advertised_address() {
+        // An advertised IP is bound directly, a DNS name binds every interface
+        let ip = MpcListenAddress::new("10.0.0.5:9001", None).unwrap();
+        assert_eq!(ip.bind_addr(), "10.0.0.5:9001".parse().unwrap());
+        let dns = MpcListenAddress::new("operator.example.com:9001", None).unwrap();
+        assert_eq!(dns.bind_addr(), "0.0.0.0:9001".parse().unwrap());
+        assert_eq!(dns.advertised(), "operator.example.com:9001");
+
+        // A public IP or name in front of a wildcard bind is fine
+        let wildcard = Some("0.0.0.0:9001".parse().unwrap());
+        assert!(MpcListenAddress::new("203.0.113.7:9001", wildcard).is_ok());
+        assert!(MpcListenAddress::new("operator.example.com:9001", wildcard).is_ok());
+
+        let mismatches = [
+            (
+                "operator.example.com:9001",
+                Some("0.0.0.0:9002".parse().unwrap()),
+            ),
+            ("10.0.0.5:9001", Some("10.0.0.6:9001".parse().unwrap())),
+            ("0.0.0.0:9001", None),
+        ];
+        for (advertised, bind_addr) in mismatches {
+            assert!(
+                matches!(
+                    MpcListenAddress::new(advertised, bind_addr),
+                    Err(Error::ConfigError(_))
+                ),
+                "{} / {:?} should be rejected",
+                advertised,
+                bind_addr
+            );
+        }
+        assert!(matches!(
+            MpcListenAddress::new("no-port.example.com", None),
+            Err(Error::InvalidDnsName(_))
+        ));
+    }
+
+    /// An mpc-net style error wrapping its cause
+    #[derive(Debug)]
+    struct Wrapped(&'static str, Box<dyn std::error::Error + 'static>);
+
+    impl std::fmt::Display for Wrapped {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str(self.0)
+        }
+    }
+
+    impl std::error::Error for Wrapped {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            Some(self.1.as_ref())
+        }
+    }
+
+    #[test]
+    fn classifies_mpc_net_failures() {
+        let refused = Wrapped(
+            "connecting to party 1",
+            Box::new(std::io::Error::from(std::io::ErrorKind::ConnectionRefused)),
+        );
+        assert!(matches!(
+            classify_mpc_net_error(&refused),
+            Error::MpcNetConnectionRefused(_)
+        ));
+
+        let timeout = Wrapped(
+            "connecting to party 1",
+            Box::new(std::io::Error::from(std::io::ErrorKind::TimedOut)),
+        );
+        assert!(matches!(
+            classify_mpc_net_error(&timeout),
+            Error::MpcNetTimeout(_)
+        ));
+
+        let bad_cert = Wrapped(
+            "connecting to party 1",
+            Box::new(rustls::Error::InvalidCertificate(
+                rustls::CertificateError::UnknownIssuer,
+            )),
+        );
+        assert!(matches!(
+            classify_mpc_net_error(&bad_cert),
+            Error::MpcNetCertificate(_)
+        ));
+
+        let handshake = Wrapped(
+            "connecting to party 1",
+            Box::new(rustls::Error::HandshakeNotComplete),
+        );
+        assert!(matches!(
+            classify_mpc_net_error(&handshake),
+            Error::MpcNetTlsHandshake(_)
+        ));
+
+        let other = std::io::Error::other("party list is empty");
+        assert!(matches!(
+            classify_mpc_net_error(&other),
+            Error::NetworkError(_)
+        ));
+    }
+
+    // An MPC-Net handler of a single-party network, which needs no peers
+    async fn single_party_handler(dir: &Path, port: u16) -> Arc<MpcNetworkHandler> {
+        let key_path = dir.join(format!("key_{}.der", port));
+        let cert_path = dir.join(format!("cert_{}.der", port));
+        crate::certs::generate_mpc_cert(&cert_path, &key_path).unwrap();
+        let config = NetworkConfigFile {
+            my_id: 0,
+            bind_addr: SocketAddr::from(([127, 0, 0, 1], port)),
+            key_path,
+            parties: vec![NetworkPartyConfig {
+                id: 0,
+                dns_name: parse_dns_name(&format!("127.0.0.1:{}", port)).unwrap(),
+                cert_path,
+            }],
+            timeout_secs: Some(5),
+        };
+        let (handler, connections) =
+            MpcNetworkManager::<SpEcdsa>::establish_mpc_network_internal(config)
+                .await
+                .unwrap();
+        assert_eq!(connections, 0);
+        Arc::new(handler)
+    }
+
+    #[tokio::test]
+    async fn evicts_only_idle_sessions_no_job_holds() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut node = TestNode::<SpEcdsa>::new(
+            "session-eviction-test",
+            "instance-eviction-1",
+            AllowedKeys::InstancePublicKeys(Default::default()),
+            vec![],
+            false,
+        );
+        let handle = node.start().await.unwrap();
+        let manager = MpcNetworkManager::new(
+            handle,
+            VerificationIdentifierKey::InstancePublicKey(node.instance_key_pair.public()),
+            MpcListenAddress::new("127.0.0.1:39100", None).unwrap(),
+            dir.path().join("mpc_key.der"),
+            dir.path().join("mpc_cert.der"),
+        )
+        .with_session_eviction(SessionEviction {
+            idle_grace: Duration::from_secs(10),
+        });
+
+        let now = unix_now();
+        let session = |handler: Arc<MpcNetworkHandler>, last_used_at: u64| MpcSession {
+            handler,
+            participants: Vec::new(),
+            established_at: last_used_at,
+            last_used_at: AtomicU64::new(last_used_at),
+            reuse_count: AtomicU64::new(0),
+            cancel: CancellationToken::new(),
+            deadline: Mutex::new(None),
+        };
+        let busy_handler = single_party_handler(dir.path(), 39101).await;
+        {
+            let mut handlers = manager.established_handlers.write().await;
+            handlers.insert(
+                "idle".to_string(),
+                session(single_party_handler(dir.path(), 39102).await, now - 60),
+            );
+            handlers.insert(
+                "recent".to_string(),
+                session(single_party_handler(dir.path(), 39103).await, now),
+            );
+            handlers.insert("busy".to_string(), session(busy_handler.clone(), now - 60));
+        }
+
+        assert_eq!(manager.evict_idle_sessions().await, 1);
+        let remaining: Vec<String> = manager
+            .active_sessions()
+            .await
+            .into_iter()
+            .map(|status| status.session_id)
+            .collect();
+        assert_eq!(remaining, vec!["busy".to_string(), "recent".to_string()]);
+
+        // Once the job releases the handler, the idle session goes too
+        drop(busy_handler);
+        assert_eq!(manager.evict_idle_sessions().await, 1);
+        assert_eq!(manager.active_sessions().await.len(), 1);
+    }
+
+    #[test]
+    fn sessions_expire_after_the_idle_grace_period() {
+        let eviction = SessionEviction {
+            idle_grace: Duration::from_secs(10),
+        };
+        assert!(!eviction.is_expired(1_000, 1_010));
+        assert!(eviction.is_expired(1_000, 1_011));
+        // A clock that went backwards never evicts
+        assert!(!eviction.is_expired(1_000, 900));
+    }
+
+    #[test]
+    fn cleanup_removes_only_the_configured_mpc_material() {
+        let dir = tempfile::tempdir().unwrap();
+        let key_path = dir.path().join("operator_key.der");
+        let cert_path = dir.path().join("operator_cert.der");
+        crate::certs::generate_mpc_cert(&cert_path, &key_path).unwrap();
+        let kept = [
+            "mpc_key.der",
+            "mpc_cert.der",
+            "operator_key.pem",
+            "other.der",
+        ];
+        for name in kept {
+            std::fs::write(dir.path().join(name), b"material").unwrap();
+        }
+
+        let mut removed = cleanup_mpc_material(&key_path, &cert_path).unwrap();
+        removed.sort();
+        let mut expected = vec![
+            key_path.clone(),
+            cert_path.clone(),
+            dir.path().join("operator_cert.pem"),
+        ];
+        expected.sort();
+        assert_eq!(removed, expected);
+        for name in kept {
+            assert!(dir.path().join(name).is_file());
+        }
+
+        // Already removed material is skipped
+        assert!(
+            cleanup_mpc_material(&key_path, &cert_path)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn mpc_net_parties_renumber_threshold_subsets_contiguously() {
+        let config = |id: usize| VerifiedPartyConfig {
+            network: NetworkPartyConfig {
+                id,
+                dns_name: parse_dns_name(&format!("party{}:900{}", id, id)).unwrap(),
+                cert_path: PathBuf::from(format!("party{}.der", id)),
+            },
+            cert_fingerprint: [id as u8; 32],
+        };
+        let ids_and_certs = |parties: &[NetworkPartyConfig]| -> Vec<(usize, PathBuf)> {
+            parties
+                .iter()
+                .map(|party| (party.id, party.cert_path.clone()))
+                .collect()
+        };
+        let configs: HashMap<PartyIndex, VerifiedPartyConfig> =
+            (0..3).map(|i| (i, config(usize::from(i)))).collect();
+        let (parties, my_id) = mpc_net_parties(&configs, 3, 3, 1).unwrap();
+        assert_eq!(ids_and_certs(&parties), vec![
+            (0, PathBuf::from("party0.der")),
+            (1, PathBuf::from("party1.der")),
+            (2, PathBuf::from("party2.der")),
+        ]);
+        assert_eq!(my_id, 1);
+
+        // Without a threshold below n, a party dropped from the exchange leaves
+        // the set incomplete
+        let mut partial = configs.clone();
+        partial.remove(&1);
+        assert!(matches!(
+            mpc_net_parties(&partial, 3, 3, 0),
+            Err(Error::ConfigError(_))
+        ));
+
+        // A threshold subset proceeds with contiguous IDs in party index order
+        let (parties, my_id) = mpc_net_parties(&partial, 3, 2, 2).unwrap();
+        assert_eq!(ids_and_certs(&parties), vec![
+            (0, PathBuf::from("party0.der")),
+            (1, PathBuf::from("party2.der")),
+        ]);
+        assert_eq!(my_id, 1);
+
+        // The local party must be among the parties that proceed
+        assert!(matches!(
+            mpc_net_parties(&partial, 3, 2, 1),
+            Err(Error::ConfigError(_))
+        ));
+
+        // Parties must carry the ID the exchange assigned them
+        let mut misnumbered = partial.clone();
+        misnumbered.insert(1, config(2));
+        misnumbered.remove(&2);
+        assert!(matches!(
+            mpc_net_parties(&misnumbered, 3, 2, 0),
+            Err(Error::ConfigError(_))
+        ));
     }
 }
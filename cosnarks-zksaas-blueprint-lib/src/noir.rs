@@ -0,0 +1,390 @@
+//! Noir program ABIs and witness construction from `Prover.toml` files.
+//!
+//! The ABI is taken from the `abi` field of a compiled Noir program (the JSON
+//! `nargo compile` writes next to the ACIR it embeds as `bytecode`).
+
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+/// Inputs and return value of a Noir program's `main`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NoirAbi {
+    pub parameters: Vec<AbiParameter>,
+    /// Type of the (always public) return value, if `main` returns one
+    pub return_type: Option<AbiType>,
+}
+
+/// A single parameter of `main`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AbiParameter {
+    pub name: String,
+    pub typ: AbiType,
+    pub visibility: AbiVisibility,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum AbiVisibility {
+    Public,
+    Private,
+    DataBus,
+}
+
+/// Type of an ABI parameter, mirroring Noir's `AbiType`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum AbiType {
+    Field,
+    Boolean,
+    Integer {
+        signed: bool,
+        width: u32,
+    },
+    Array {
+        length: u32,
+        typ: Box<AbiType>,
+    },
+    String {
+        length: u32,
+    },
+    Struct {
+        path: String,
+        fields: Vec<(String, AbiType)>,
+    },
+    Tuple {
+        fields: Vec<AbiType>,
+    },
+}
+
+impl AbiType {
+    /// Number of field elements the type occupies in the witness.
+    #[must_use]
+    pub fn field_count(&self) -> u32 {
+        match self {
+            AbiType::Field | AbiType::Boolean | AbiType::Integer { .. } => 1,
+            AbiType::Array { length, typ } => length.saturating_mul(typ.field_count()),
+            AbiType::String { length } => *length,
+            AbiType::Struct { fields, .. } => fields.iter().map(|(_, typ)| typ.field_count()).sum(),
+            AbiType::Tuple { fields } => fields.iter().map(AbiType::field_count).sum(),
+        }
+    }
+
+    fn from_json(value: &JsonValue) -> Result<Self> {
+        let kind = value
+            .get("kind")
+            .and_then(JsonValue::as_str)
+            .unwrap_or_default();
+        let typ = match kind {
+            "field" => AbiType::Field,
+            "boolean" => AbiType::Boolean,
+            "integer" => AbiType::Integer {
+                signed: json_str(value, "sign")? == "signed",
+                width: json_u32(value, "width")?,
+            },
+            "array" => AbiType::Array {
+                length: json_u32(value, "length")?,
+                typ: Box::new(AbiType::from_json(json_field(value, "type")?)?),
+            },
+            "string" => AbiType::String {
+                length: json_u32(value, "length")?,
+            },
+            "struct" => AbiType::Struct {
+                path: json_str(value, "path")?.to_string(),
+                fields: json_array(value, "fields")?
+                    .iter()
+                    .map(|field| {
+                        Ok((
+                            json_str(field, "name")?.to_string(),
+                            AbiType::from_json(json_field(field, "type")?)?,
+                        ))
+                    })
+                    .collect::<Result<_>>()?,
+            },
+            "tuple" => AbiType::Tuple {
+                fields: json_array(value, "fields")?
+                    .iter()
+                    .map(AbiType::from_json)
+                    .collect::<Result<_>>()?,
+            },
+            other => {
+                return Err(Error::ArtifactParseError(format!(
+                    "Unsupported Noir ABI type kind '{}'",
+                    other
+                )));
+            }
+        };
+        Ok(typ)
+    }
+
+    /// Checks that `value` from a `Prover.toml` has this type; `path` names it in errors.
+    fn check(&self, value: &toml::Value, path: &str) -> Result<()> {
+        let ok = match (self, value) {
+            (AbiType::Field, toml::Value::Integer(_)) => true,
+            (AbiType::Field, toml::Value::String(s)) => is_field_literal(s),
+            (AbiType::Boolean, toml::Value::Boolean(_)) => true,
+            (AbiType::Integer { signed, width }, toml::Value::Integer(i)) => {
+                integer_fits(*i, *signed, *width)
+            }
+            (AbiType::Integer { .. }, toml::Value::String(s)) => is_field_literal(s),
+            (AbiType::String { length }, toml::Value::String(s)) => s.len() == *length as usize,
+            (AbiType::Array { length, typ }, toml::Value::Array(items)) => {
+                if items.len() != *length as usize {
+                    return Err(witness_error(
+                        path,
+                        &format!("expected {} elements, got {}", length, items.len()),
+                    ));
+                }
+                for (idx, item) in items.iter().enumerate() {
+                    typ.check(item, &format!("{}[{}]", path, idx))?;
+                }
+                true
+            }
+            (AbiType::Tuple { fields }, toml::Value::Array(items)) => {
+                if items.len() != fields.len() {
+                    return Err(witness_error(
+                        path,
+                        &format!(
+                            "expected {} tuple elements, got {}",
+                            fields.len(),
+                            items.len()
+                        ),
+                    ));
+                }
+                for (idx, (typ, item)) in fields.iter().zip(items).enumerate() {
+                    typ.check(item, &format!("{}.{}", path, idx))?;
+                }
+                true
+            }
+            (AbiType::Struct { fields, .. }, toml::Value::Table(table)) => {
+                check_table(
+                    fields.iter().map(|(name, typ)| (name.as_str(), typ)),
+                    table,
+                    &format!("{}.", path),
+                )?;
+                true
+            }
+            _ => false,
+        };
+        if ok {
+            Ok(())
+        } else {
+            Err(witness_error(path, &format!("expected {:?}", self)))
+        }
+    }
+}
+
+impl NoirAbi {
+    /// Parameters visible to the verifier, in declaration order.
+    pub fn public_parameters(&self) -> impl Iterator<Item = &AbiParameter> {
+        self.parameters
+            .iter()
+            .filter(|p| p.visibility != AbiVisibility::Private)
+    }
+
+    /// Parameters known only to the prover, in declaration order.
+    pub fn private_parameters(&self) -> impl Iterator<Item = &AbiParameter> {
+        self.parameters
+            .iter()
+            .filter(|p| p.visibility == AbiVisibility::Private)
+    }
+
+    /// Number of public field elements: public parameters plus the return value.
+    #[must_use]
+    pub fn num_public_inputs(&self) -> u32 {
+        self.public_parameters()
+            .map(|p| p.typ.field_count())
+            .chain(self.return_type.iter().map(AbiType::field_count))
+            .sum()
+    }
+
+    /// Builds a JSON witness from the contents of a `Prover.toml`.
+    ///
+    /// Every parameter must be present with a value of its ABI type, and no
+    /// other keys may appear; otherwise this fails with `Error::InvalidInput`
+    /// naming the offending input. The result maps parameter names to values.
+    pub fn witness_from_prover_toml(&self, prover_toml: &str) -> Result<JsonValue> {
+        let table: toml::Table = prover_toml
+            .parse()
+            .map_err(|e| Error::InvalidInput(format!("Invalid Prover.toml: {}", e)))?;
+        check_table(
+            self.parameters.iter().map(|p| (p.name.as_str(), &p.typ)),
+            &table,
+            "",
+        )?;
+        Ok(serde_json::to_value(&table)?)
+    }
+}
+
+/// Extracts the ABI of a compiled Noir program JSON.
+///
+/// Returns `None` for other artifacts (e.g. gzipped ACIR bytecode), which carry no ABI.
+pub fn parse_program_abi(data: &[u8]) -> Result<Option<NoirAbi>> {
+    let Ok(program) = serde_json::from_slice::<JsonValue>(data) else {
+        return Ok(None);
+    };
+    let Some(abi) = program.get("abi") else {
+        return Ok(None);
+    };
+
+    let parameters = json_array(abi, "parameters")?
+        .iter()
+        .map(|param| {
+            let visibility = match json_str(param, "visibility")? {
+                "public" => AbiVisibility::Public,
+                "private" => AbiVisibility::Private,
+                "databus" => AbiVisibility::DataBus,
+                other => {
+                    return Err(Error::ArtifactParseError(format!(
+                        "Unknown Noir ABI visibility '{}'",
+                        other
+                    )));
+                }
+            };
+            Ok(AbiParameter {
+                name: json_str(param, "name")?.to_string(),
+                typ: AbiType::from_json(json_field(param, "type")?)?,
+                visibility,
+            })
+        })
+        .collect::<Result<_>>()?;
+    let return_type = match abi.get("return_type") {
+        None | Some(JsonValue::Null) => None,
+        Some(ret) => Some(AbiType::from_json(json_field(ret, "abi_type")?)?),
+    };
+    Ok(Some(NoirAbi {
+        parameters,
+        return_type,
+    }))
+}
+
+/// Checks a TOML table against named fields, rejecting missing and unknown keys.
+fn check_table<'a>(
+    fields: impl Iterator<Item = (&'a str, &'a AbiType)>,
+    table: &toml::Table,
+    prefix: &str,
+) -> Result<()> {
+    let fields: Vec<(&str, &AbiType)> = fields.collect();
+    for (name, typ) in &fields {
+        let path = format!("{}{}", prefix, name);
+        let value = table
+            .get(*name)
+            .ok_or_else(|| witness_error(&path, "missing"))?;
+        typ.check(value, &path)?;
+    }
+    if let Some(unknown) = table
+        .keys()
+        .find(|key| !fields.iter().any(|(name, _)| name == key))
+    {
+        return Err(witness_error(
+            &format!("{}{}", prefix, unknown),
+            "not declared in the ABI",
+        ));
+    }
+    Ok(())
+}
+
+/// Whether `s` is a decimal or `0x`-prefixed hex field element literal.
+fn is_field_literal(s: &str) -> bool {
+    let s = s.strip_prefix('-').unwrap_or(s);
+    match s.strip_prefix("0x") {
+        Some(hex) => !hex.is_empty() && hex.bytes().all(|b| b.is_ascii_hexdigit()),
+        None => !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit()),
+    }
+}
+
+/// Whether `value` fits an integer of `width` bits with the given signedness.
+fn integer_fits(value: i64, signed: bool, width: u32) -> bool {
+    if width >= 64 {
+        return signed || value >= 0;
+    }
+    if signed {
+        let bound = 1i64 << (width - 1);
+        (-bound..bound).contains(&value)
+    } else {
+        (0..(1i64 << width)).contains(&value)
+    }
+}
+
+fn witness_error(path: &str, reason: &str) -> Error {
+    Error::InvalidInput(format!("Prover.toml input `{}`: {}", path, reason))
+}
+
+fn json_field<'a>(value: &'a JsonValue, key: &str) -> Result<&'a JsonValue> {
+    value
+        .get(key)
+        .ok_or_else(|| Error::ArtifactParseError(format!("Noir ABI entry is missing '{}'", key)))
+}
+
+fn json_str<'a>(value: &'a JsonValue, key: &str) -> Result<&'a str> {
+    json_field(value, key)?
+        .as_str()
+        .ok_or_else(|| Error::ArtifactParseError(format!("Noir ABI '{}' must be a string", key)))
+}
+
+fn json_u32(value: &JsonValue, key: &str) -> Result<u32> {
+    json_field(value, key)?
+        .as_u64()
+        .and_then(|n| u32::try_from(n).ok())
+        .ok_or_else(|| Error::ArtifactParseError(format!("Noir ABI '{}' must be a u32", key)))
+}
+
+fn json_array<'a>(value: &'a JsonValue, key: &str) -> Result<&'a Vec<JsonValue>> {
+    json_field(value, key)?
+        .as_array()
+        .ok_or_else(|| Error::ArtifactParseError(format!("Noir ABI '{}' must be an array", key)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROGRAM: &str = r#"{
+        "bytecode": "H4sIAAAAAAAA/w==",
+        "abi": {
+            "parameters": [
+                { "name": "x", "type": { "kind": "field" }, "visibility": "private" },
+                { "name": "y", "type": { "kind": "array", "length": 2,
+                    "type": { "kind": "integer", "sign": "unsigned", "width": 8 } },
+                  "visibility": "public" },
+                { "name": "p", "type": { "kind": "struct", "path": "Point", "fields": [
+                    { "name": "a", "type": { "kind": "boolean" } }
+                  ] }, "visibility": "private" }
+            ],
+            "return_type": { "abi_type": { "kind": "field" }, "visibility": "public" },
+            "error_types": {}
+        }
+    }"#;
+
+    #[test]
+    fn parses_program_abi() {
+        let abi = parse_program_abi(PROGRAM.as_bytes()).unwrap().unwrap();
+        let public: Vec<&str> = abi.public_parameters().map(|p| p.name.as_str()).collect();
+        let private: Vec<&str> = abi.private_parameters().map(|p| p.name.as_str()).collect();
+        assert_eq!(public, ["y"]);
+        assert_eq!(private, ["x", "p"]);
+        assert_eq!(abi.num_public_inputs(), 3);
+        assert_eq!(parse_program_abi(b"\x1f\x8b\x08\x00").unwrap(), None);
+    }
+
+    #[test]
+    fn builds_witness_from_prover_toml() {
+        let abi = parse_program_abi(PROGRAM.as_bytes()).unwrap().unwrap();
+        let witness = abi
+            .witness_from_prover_toml("x = \"0x2a\"\ny = [1, 255]\n[p]\na = true\n")
+            .unwrap();
+        assert_eq!(witness["y"], serde_json::json!([1, 255]));
+
+        for (toml, offending) in [
+            ("y = [1, 2]\n[p]\na = true\n", "`x`"),
+            ("x = 1\ny = [1, 256]\n[p]\na = true\n", "`y[1]`"),
+            ("x = 1\ny = [1]\n[p]\na = true\n", "`y`"),
+            ("x = 1\ny = [1, 2]\n[p]\na = 1\n", "`p.a`"),
+            ("x = 1\ny = [1, 2]\nz = 3\n[p]\na = true\n", "`z`"),
+        ] {
+            let err = abi.witness_from_prover_toml(toml).unwrap_err();
+            assert!(
+                matches!(&err, Error::InvalidInput(msg) if msg.contains(offending)),
+                "{toml:?}: {err}"
+            );
+        }
+    }
+}
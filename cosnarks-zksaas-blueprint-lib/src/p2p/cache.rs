@@ -0,0 +1,124 @@
+// Agreement on serving a proof from the proof cache, run as part of the config exchange.
+
+use super::{ExchangeExtension, VerifiedPartyConfig};
+use crate::error::{Error as CoSnarksError, Result};
+use round_based::PartyIndex;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+/// Config exchange extension deciding whether every participant holds a cached
+/// proof for the request.
+///
+/// Each party states whether its own cache hit. The proof is only served from
+/// the cache if all of them did; otherwise every party proves, so no operator
+/// returns early while its peers wait for it in the MPC session.
+#[derive(Debug, Default)]
+pub struct ProofCacheAgreement {
+    local_hit: bool,
+    all_hit: Mutex<Option<bool>>,
+}
+
+impl ProofCacheAgreement {
+    /// Creates the extension for a party whose own cache did (`true`) or did not hit.
+    #[must_use]
+    pub fn new(local_hit: bool) -> Self {
+        Self {
+            local_hit,
+            all_hit: Mutex::new(None),
+        }
+    }
+
+    /// Whether every party reported a cache hit. `false` until the exchange
+    /// has verified the payloads.
+    pub fn all_hit(&self) -> bool {
+        self.all_hit
+            .lock()
+            .map(|all_hit| all_hit.unwrap_or(false))
+            .unwrap_or(false)
+    }
+}
+
+impl ExchangeExtension for ProofCacheAgreement {
+    fn name(&self) -> &'static str {
+        "proof-cache"
+    }
+
+    fn payload(
+        &self,
+        _i: PartyIndex,
+        _configs: &HashMap<PartyIndex, VerifiedPartyConfig>,
+    ) -> Result<Vec<u8>> {
+        Ok(vec![u8::from(self.local_hit)])
+    }
+
+    fn verify(
+        &self,
+        _configs: &HashMap<PartyIndex, VerifiedPartyConfig>,
+        payloads: &BTreeMap<PartyIndex, Vec<u8>>,
+    ) -> Result<()> {
+        let mut all_hit = true;
+        for (party_idx, payload) in payloads {
+            match payload.as_slice() {
+                [0] => all_hit = false,
+                [1] => {}
+                _ => {
+                    return Err(CoSnarksError::MpcProtocolError(format!(
+                        "Party {} sent a malformed proof cache payload",
+                        party_idx
+                    )));
+                }
+            }
+        }
+        if let Ok(mut agreed) = self.all_hit.lock() {
+            *agreed = Some(all_hit);
+        }
+        Ok(())
+    }
+
+    fn session_needed(&self) -> bool {
+        // Every participant serves the proof from its cache
+        !self.all_hit()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payloads(hits: &[&[u8]]) -> BTreeMap<PartyIndex, Vec<u8>> {
+        hits.iter()
+            .enumerate()
+            .map(|(i, payload)| (i as PartyIndex, payload.to_vec()))
+            .collect()
+    }
+
+    #[test]
+    fn serves_from_cache_only_if_every_party_hit() {
+        let configs = HashMap::new();
+        let agreement = ProofCacheAgreement::new(true);
+        assert!(!agreement.all_hit());
+        assert!(agreement.session_needed());
+        assert_eq!(agreement.payload(0, &configs).unwrap(), vec![1]);
+
+        // A full hit makes the MPC session unnecessary
+        agreement
+            .verify(&configs, &payloads(&[&[1], &[1], &[1]]))
+            .unwrap();
+        assert!(agreement.all_hit());
+        assert!(!agreement.session_needed());
+
+        let agreement = ProofCacheAgreement::new(true);
+        agreement
+            .verify(&configs, &payloads(&[&[1], &[0], &[1]]))
+            .unwrap();
+        assert!(!agreement.all_hit());
+        assert!(agreement.session_needed());
+
+        let agreement = ProofCacheAgreement::new(true);
+        assert!(matches!(
+            agreement.verify(&configs, &payloads(&[&[1], &[2]])),
+            Err(CoSnarksError::MpcProtocolError(_))
+        ));
+        assert!(!agreement.all_hit());
+    }
+}
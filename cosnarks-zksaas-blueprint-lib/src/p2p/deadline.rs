@@ -0,0 +1,116 @@
+// Agreement on a per-proof deadline, run as part of the config exchange, so every MPC party aborts at the same time.
+
+use super::{ExchangeExtension, VerifiedPartyConfig};
+use crate::error::{Error as CoSnarksError, Result};
+use round_based::PartyIndex;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+/// Config exchange extension agreeing on the deadline of a proof.
+///
+/// Each party states the deadline (Unix seconds) it was asked to meet, if any.
+/// Every party then abides by the earliest deadline any of them proposed.
+#[derive(Debug, Default)]
+pub struct DeadlineAgreement {
+    local_deadline: Option<u64>,
+    agreed: Mutex<Option<Option<u64>>>,
+}
+
+impl DeadlineAgreement {
+    /// Creates the extension for a party asked to finish by `deadline`.
+    #[must_use]
+    pub fn new(deadline: Option<u64>) -> Self {
+        Self {
+            local_deadline: deadline,
+            agreed: Mutex::new(None),
+        }
+    }
+
+    /// The earliest deadline any party proposed, or `None` if none did. The
+    /// local deadline until the exchange has verified the payloads.
+    pub fn agreed(&self) -> Option<u64> {
+        self.agreed
+            .lock()
+            .ok()
+            .and_then(|agreed| *agreed)
+            .unwrap_or(self.local_deadline)
+    }
+}
+
+impl ExchangeExtension for DeadlineAgreement {
+    fn name(&self) -> &'static str {
+        "deadline"
+    }
+
+    fn payload(
+        &self,
+        _i: PartyIndex,
+        _configs: &HashMap<PartyIndex, VerifiedPartyConfig>,
+    ) -> Result<Vec<u8>> {
+        Ok(bincode::serialize(&self.local_deadline)?)
+    }
+
+    fn verify(
+        &self,
+        _configs: &HashMap<PartyIndex, VerifiedPartyConfig>,
+        payloads: &BTreeMap<PartyIndex, Vec<u8>>,
+    ) -> Result<()> {
+        let mut proposed = Vec::with_capacity(payloads.len());
+        for (party_idx, payload) in payloads {
+            let deadline: Option<u64> = bincode::deserialize(payload).map_err(|_| {
+                CoSnarksError::MpcProtocolError(format!(
+                    "Party {} sent a malformed deadline payload",
+                    party_idx
+                ))
+            })?;
+            proposed.push(deadline);
+        }
+        let agreed = proposed.iter().copied().flatten().min();
+        if agreed != self.local_deadline {
+            tracing::warn!(local = ?self.local_deadline, ?agreed, ?proposed, "Peers proposed an earlier proof deadline");
+        }
+        if let Ok(mut slot) = self.agreed.lock() {
+            *slot = Some(agreed);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payloads(deadlines: &[Option<u64>]) -> BTreeMap<PartyIndex, Vec<u8>> {
+        deadlines
+            .iter()
+            .enumerate()
+            .map(|(i, deadline)| (i as PartyIndex, bincode::serialize(deadline).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn parties_agree_on_the_earliest_deadline() {
+        let configs = HashMap::new();
+        let agreement = DeadlineAgreement::new(Some(300));
+        assert_eq!(agreement.agreed(), Some(300));
+        agreement
+            .verify(&configs, &payloads(&[Some(300), None, Some(200)]))
+            .unwrap();
+        assert_eq!(agreement.agreed(), Some(200));
+
+        let agreement = DeadlineAgreement::new(None);
+        agreement
+            .verify(&configs, &payloads(&[None, None]))
+            .unwrap();
+        assert_eq!(agreement.agreed(), None);
+
+        let agreement = DeadlineAgreement::new(Some(300));
+        let mut malformed = payloads(&[Some(300)]);
+        malformed.insert(1, vec![7]);
+        assert!(matches!(
+            agreement.verify(&configs, &malformed),
+            Err(CoSnarksError::MpcProtocolError(_))
+        ));
+        assert_eq!(agreement.agreed(), Some(300));
+    }
+}
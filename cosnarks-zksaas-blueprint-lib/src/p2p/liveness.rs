@@ -0,0 +1,148 @@
+// Lightweight ping protocol for checking MPC peer liveness and latency.
+
+use crate::error::{Error as CoSnarksError, Result};
+use futures::StreamExt;
+use round_based::{Delivery, Mpc, MpcParty, Outgoing, PartyIndex, ProtocolMessage, SinkExt};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Protocol messages for the liveness check
+#[derive(Clone, Debug, PartialEq, ProtocolMessage, Serialize, Deserialize)]
+pub enum LivenessMsg {
+    /// Broadcast by every party to start measuring round-trip times
+    Ping(PingMsg),
+    /// Sent back point-to-point in response to a peer's ping
+    Pong(PongMsg),
+}
+
+/// Ping carrying a random nonce that must be echoed back
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PingMsg {
+    pub nonce: u64,
+}
+
+/// Pong echoing the nonce of the ping it answers
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PongMsg {
+    pub nonce: u64,
+}
+
+/// Pings every other party and waits for their pongs, answering their pings in turn.
+///
+/// Returns the measured round-trip time to each peer. Fails with
+/// `Error::UnresponsiveParties` naming the unresponsive parties if not every
+/// peer has answered (and pinged us) within `timeout`.
+#[tracing::instrument(skip(party))]
+pub async fn mpc_liveness_check<M>(
+    party: M,
+    i: PartyIndex,
+    n: u16,
+    timeout: Duration,
+) -> Result<HashMap<PartyIndex, Duration>>
+where
+    M: Mpc<ProtocolMessage = LivenessMsg>,
+{
+    let MpcParty { delivery, .. } = party.into_party();
+    let (mut incoming, mut outgoing) = delivery.split();
+
+    let nonce: u64 = rand::random();
+    let deadline = Instant::now() + timeout;
+    let mut awaiting_pong: BTreeSet<PartyIndex> = (0..n).filter(|&j| j != i).collect();
+    let mut awaiting_ping = awaiting_pong.clone();
+    let mut rtts = HashMap::with_capacity(awaiting_pong.len());
+
+    let sent_at = Instant::now();
+    outgoing
+        .send(Outgoing::broadcast(LivenessMsg::Ping(PingMsg { nonce })))
+        .await
+        .map_err(|e| CoSnarksError::ExchangeRoundBasedError(e.to_string()))?;
+
+    while !awaiting_pong.is_empty() || !awaiting_ping.is_empty() {
+        let next = match tokio::time::timeout_at(deadline, incoming.next()).await {
+            Ok(Some(next)) => next,
+            Ok(None) => {
+                return Err(CoSnarksError::ExchangeRoundBasedError(
+                    "Incoming stream closed during liveness check".to_string(),
+                ));
+            }
+            Err(_) => {
+                let unresponsive: BTreeSet<_> = awaiting_pong.union(&awaiting_ping).collect();
+                return Err(CoSnarksError::UnresponsiveParties {
+                    parties: unresponsive.into_iter().copied().collect(),
+                    timeout,
+                });
+            }
+        };
+        let msg = next.map_err(|e| CoSnarksError::ExchangeRoundBasedError(e.to_string()))?;
+
+        match msg.msg {
+            LivenessMsg::Ping(ping) => {
+                if awaiting_ping.remove(&msg.sender) {
+                    outgoing
+                        .send(Outgoing::p2p(
+                            msg.sender,
+                            LivenessMsg::Pong(PongMsg { nonce: ping.nonce }),
+                        ))
+                        .await
+                        .map_err(|e| CoSnarksError::ExchangeRoundBasedError(e.to_string()))?;
+                }
+            }
+            LivenessMsg::Pong(pong) => {
+                if pong.nonce != nonce {
+                    tracing::warn!(party = msg.sender, "Ignoring pong with unexpected nonce");
+                    continue;
+                }
+                if awaiting_pong.remove(&msg.sender) {
+                    let rtt = sent_at.elapsed();
+                    tracing::debug!(party = msg.sender, ?rtt, "Received pong");
+                    rtts.insert(msg.sender, rtt);
+                }
+            }
+        }
+    }
+
+    tracing::info!(?rtts, "Liveness check completed");
+    Ok(rtts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn every_party_measures_every_peer() {
+        let n: u16 = 3;
+        let results = round_based::sim::run_with_setup(vec![(); n as usize], |i, party, ()| {
+            mpc_liveness_check(party, i, n, Duration::from_secs(5))
+        })
+        .unwrap()
+        .0;
+        for (i, result) in results.into_iter().enumerate() {
+            let rtts = result.unwrap();
+            let mut peers: Vec<PartyIndex> = rtts.keys().copied().collect();
+            peers.sort();
+            let expected: Vec<PartyIndex> = (0..n).filter(|&j| usize::from(j) != i).collect();
+            assert_eq!(peers, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn silent_parties_are_reported_unresponsive() {
+        // Party 2 of 3 never joins the simulation
+        let results = round_based::sim::run_with_setup(vec![(); 2], |i, party, ()| {
+            mpc_liveness_check(party, i, 3, Duration::from_millis(200))
+        })
+        .unwrap()
+        .0;
+        for result in results {
+            match result {
+                Err(CoSnarksError::UnresponsiveParties { parties, .. }) => {
+                    assert_eq!(parties, vec![2]);
+                }
+                other => panic!("expected UnresponsiveParties, got {:?}", other),
+            }
+        }
+    }
+}
@@ -1,13 +1,96 @@
 // Protocol for exchanging MPC-Net configuration details securely.
 
+pub mod cache;
+pub mod deadline;
+pub mod liveness;
+
+use crate::certs::{MAX_CERT_SIZE, OperatorCa, cert_fingerprint, store_peer_cert};
 use crate::error::{Error as CoSnarksError, Result};
+use blueprint_sdk::crypto::hashing::blake3_256;
+use futures::StreamExt;
 use mpc_net::config::{Address, NetworkPartyConfig};
-use round_based::rounds_router::{RoundsRouter, simple_store::RoundInput};
-use round_based::{Delivery, Mpc, MpcParty, MsgId, Outgoing, PartyIndex, ProtocolMessage, SinkExt};
+use round_based::rounds_router::RoundsRouter;
+use round_based::rounds_router::simple_store::{RoundInput, RoundMsgs};
+use round_based::{
+    Delivery, Incoming, Mpc, MpcParty, MsgId, Outgoing, PartyIndex, ProtocolMessage, SinkExt,
+};
+use serde::de::{self, Deserializer, Visitor};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+/// Longest revealed `dns_name` accepted: a 253-byte hostname, `:` and a 5-digit port.
+pub const MAX_REVEALED_DNS_NAME_LEN: usize = 253 + 1 + 5;
+/// Largest revealed certificate accepted, in bytes.
+pub const MAX_REVEALED_CERT_LEN: usize = MAX_CERT_SIZE as usize;
+/// Messages a party may send per round of the config exchange; every round is
+/// a single broadcast. Further messages are dropped and the sender is blamed.
+pub const MAX_MESSAGES_PER_ROUND: u32 = 1;
+
+/// Hash function used to commit to the revealed configuration.
+///
+/// Every party states its scheme alongside its commitment; the exchange
+/// aborts if the participants have not agreed on the same one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CommitmentScheme {
+    #[default]
+    Sha256,
+    Blake3,
+}
+
+impl CommitmentScheme {
+    /// Computes the 32-byte commitment to `data`.
+    pub fn commit(&self, data: &[u8]) -> [u8; 32] {
+        match self {
+            CommitmentScheme::Sha256 => Sha256::digest(data).into(),
+            CommitmentScheme::Blake3 => blake3_256(data),
+        }
+    }
+}
+
+impl FromStr for CommitmentScheme {
+    type Err = CoSnarksError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "sha256" => Ok(CommitmentScheme::Sha256),
+            "blake3" => Ok(CommitmentScheme::Blake3),
+            other => Err(CoSnarksError::ConfigError(format!(
+                "Unknown commitment scheme: {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// Role a party plays within an MPC session.
+///
+/// Roles are a pure function of the party index, so every participant derives
+/// the same assignment; the config exchange additionally checks that each peer
+/// claims the role it was assigned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PartyRole {
+    /// Drives coordinator-based flows (e.g. proof aggregation). Held by index 0.
+    Coordinator,
+    /// Every other participant
+    Worker,
+}
+
+impl PartyRole {
+    /// Returns the role assigned to the party at `index`.
+    #[must_use]
+    pub fn for_index(index: PartyIndex) -> Self {
+        if index == 0 {
+            PartyRole::Coordinator
+        } else {
+            PartyRole::Worker
+        }
+    }
+}
 
 /// Protocol messages for MPC configuration exchange
 #[derive(Clone, Debug, PartialEq, ProtocolMessage, Serialize, Deserialize)]
@@ -16,98 +99,520 @@ pub enum ConfigExchangeMsg {
     Commit(CommitMsg),
     /// Round 2: Reveal the configuration details
     Reveal(RevealMsg),
+    /// Round 3: Payloads of the configured `ExchangeExtension`s, if any
+    Extension(ExtensionMsg),
 }
 
 /// Round 1: Commitment message
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct CommitMsg {
+    pub scheme: CommitmentScheme,
     pub commitment: [u8; 32],
 }
 
 /// Round 2: Reveal message containing the actual configuration part
+///
+/// Peer-supplied fields are bounded while the message is decoded: a
+/// `dns_name` longer than [`MAX_REVEALED_DNS_NAME_LEN`] or a `cert_der`
+/// longer than [`MAX_REVEALED_CERT_LEN`] bytes fails to deserialize.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct RevealMsg {
+    #[serde(deserialize_with = "deserialize_dns_name")]
     pub dns_name: String,
-    pub cert_path: PathBuf,
+    /// The sender's DER-encoded certificate. Peers store it in their own
+    /// certificate directory (see [`store_peer_cert`]), so no party depends on
+    /// where another keeps its certificate.
+    #[serde(deserialize_with = "deserialize_cert_der")]
+    pub cert_der: Vec<u8>,
+    /// SHA-256 fingerprint of `cert_der`. Identifies which certificate the
+    /// sender uses while rotating and is pinned for the session.
+    pub cert_fingerprint: [u8; 32],
+    /// Role the sender assumes in the session
+    pub role: PartyRole,
+}
+
+fn deserialize_dns_name<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<String, D::Error> {
+    deserializer.deserialize_str(BoundedStr {
+        field: "dns_name",
+        max_len: MAX_REVEALED_DNS_NAME_LEN,
+    })
+}
+
+fn deserialize_cert_der<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Vec<u8>, D::Error> {
+    deserializer.deserialize_bytes(BoundedBytes {
+        field: "cert_der",
+        max_len: MAX_REVEALED_CERT_LEN,
+    })
+}
+
+/// Visits a string field, rejecting it before it is copied if it is longer
+/// than `max_len` bytes.
+struct BoundedStr {
+    field: &'static str,
+    max_len: usize,
+}
+
+impl Visitor<'_> for BoundedStr {
+    type Value = String;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a {} of at most {} bytes", self.field, self.max_len)
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> std::result::Result<String, E> {
+        if v.len() > self.max_len {
+            return Err(E::invalid_length(v.len(), &self));
+        }
+        Ok(v.to_string())
+    }
+}
+
+/// Visits a byte field, rejecting it before it is copied if it is longer
+/// than `max_len` bytes.
+struct BoundedBytes {
+    field: &'static str,
+    max_len: usize,
+}
+
+impl<'de> Visitor<'de> for BoundedBytes {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a {} of at most {} bytes", self.field, self.max_len)
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> std::result::Result<Vec<u8>, E> {
+        if v.len() > self.max_len {
+            return Err(E::invalid_length(v.len(), &self));
+        }
+        Ok(v.to_vec())
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(
+        self,
+        mut seq: A,
+    ) -> std::result::Result<Vec<u8>, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(self.max_len));
+        while let Some(byte) = seq.next_element()? {
+            if bytes.len() == self.max_len {
+                return Err(de::Error::invalid_length(bytes.len() + 1, &self));
+            }
+            bytes.push(byte);
+        }
+        Ok(bytes)
+    }
+}
+
+/// Round 3: one payload per configured extension, in configuration order
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ExtensionMsg {
+    pub payloads: Vec<ExtensionPayload>,
+}
+
+/// Payload of a single `ExchangeExtension`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ExtensionPayload {
+    /// `ExchangeExtension::name` of the extension that produced the payload
+    pub name: String,
+    pub payload: Vec<u8>,
+}
+
+/// A party's MPC-Net configuration as verified by the config exchange.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VerifiedPartyConfig {
+    pub network: NetworkPartyConfig,
+    /// Fingerprint of the certificate the party committed to
+    pub cert_fingerprint: [u8; 32],
 }
 
 /// Executes the secure MPC configuration exchange protocol.
 ///
-/// Each party commits to their `NetworkPartyConfig` info (excluding ID),
-/// then reveals it. The protocol verifies consistency and returns a map
-/// of `PartyIndex` to the verified `(ParticipantId, NetworkPartyConfig)`.
+/// Each party commits to their `NetworkPartyConfig` info (excluding ID) using
+/// `scheme`, then reveals it. The protocol verifies consistency, checks that every
+/// party claims the role `PartyRole::for_index` assigns it and that each revealed
+/// certificate matches the revealed fingerprint. Peer certificates are stored under
+/// `cert_dir` (see [`store_peer_cert`]); the local party keeps its own `cert_path`.
+/// Returns a map of `PartyIndex` to the verified configuration.
+///
+/// Shorthand for the default [`ConfigExchangeBuilder`], which runs exactly the
+/// commit and reveal rounds.
 #[tracing::instrument(skip(party, reveal_msg))]
 pub async fn mpc_config_exchange<M>(
     party: M,
     i: PartyIndex,
     n: u16,
     reveal_msg: RevealMsg,
-) -> Result<HashMap<PartyIndex, NetworkPartyConfig>>
+    scheme: CommitmentScheme,
+    cert_path: PathBuf,
+    cert_dir: PathBuf,
+) -> Result<HashMap<PartyIndex, VerifiedPartyConfig>>
 where
     M: Mpc<ProtocolMessage = ConfigExchangeMsg>,
 {
-    let MpcParty { delivery, .. } = party.into_party();
-    let (incoming, mut outgoing) = delivery.split();
-
-    // Define rounds
-    let mut rounds = RoundsRouter::<ConfigExchangeMsg>::builder();
-    let round1 = rounds.add_round(RoundInput::<CommitMsg>::broadcast(i, n));
-    let round2 = rounds.add_round(RoundInput::<RevealMsg>::broadcast(i, n));
-    let mut rounds = rounds.listen(incoming);
-
-    // --- The Protocol ---
-
-    // 1. Serialize local config for commitment
-    let local_config_bytes = bincode::serialize(&reveal_msg)?;
-
-    // 2. Commit to the config (hash of serialized RevealMsg)
-    let commitment = Sha256::digest(&local_config_bytes);
-    tracing::debug!(commitment = %hex::encode(commitment), "Committed local config");
-    outgoing
-        .send(Outgoing::broadcast(ConfigExchangeMsg::Commit(CommitMsg {
-            commitment: commitment.into(),
-        })))
+    ConfigExchangeBuilder::new(i, n, reveal_msg, cert_path, cert_dir)
+        .with_commitment_scheme(scheme)
+        .run(party)
         .await
-        .map_err(|e| CoSnarksError::ExchangeRoundBasedError(e.to_string()))?;
+}
 
-    tracing::debug!("Sent commitment, waiting for others...");
+/// An additional step of the config exchange, run after the reveal.
+///
+/// Each extension broadcasts one payload computed from the verified configs and
+/// then checks the payloads of every party, e.g. to agree on a circuit ID or to
+/// exchange signatures over the configs.
+pub trait ExchangeExtension: Send + Sync {
+    /// Identifies the extension; parties must run the same extensions in the same order.
+    fn name(&self) -> &'static str;
 
-    // 3. Receive commitments from other parties
-    let commitments = rounds
-        .complete(round1)
-        .await
-        .map_err(|e| CoSnarksError::ExchangeRoundBasedError(e.to_string()))?;
-    tracing::debug!("Received all commitments");
-
-    // 4. Reveal local config
-    tracing::debug!("Revealing local config");
-    outgoing
-        .send(Outgoing::broadcast(ConfigExchangeMsg::Reveal(
-            reveal_msg.clone(),
-        )))
-        .await
-        .map_err(|e| CoSnarksError::ExchangeRoundBasedError(e.to_string()))?;
-    tracing::debug!("Sent revealed config, waiting for others...");
+    /// Payload the local party `i` broadcasts.
+    fn payload(
+        &self,
+        i: PartyIndex,
+        configs: &HashMap<PartyIndex, VerifiedPartyConfig>,
+    ) -> Result<Vec<u8>>;
 
-    // 5. Receive revealed configs, verify against commitments
-    let revealed_configs = rounds
-        .complete(round2)
-        .await
-        .map_err(|e| CoSnarksError::ExchangeRoundBasedError(e.to_string()))?;
-    tracing::debug!("Received all revealed configs");
+    /// Checks the payloads of all parties, including the local one, by party index.
+    fn verify(
+        &self,
+        configs: &HashMap<PartyIndex, VerifiedPartyConfig>,
+        payloads: &BTreeMap<PartyIndex, Vec<u8>>,
+    ) -> Result<()>;
+
+    /// Whether the session is still needed once the payloads are verified.
+    /// If any extension says no, the MPC-Net mesh is not built.
+    fn session_needed(&self) -> bool {
+        true
+    }
+}
+
+/// Assembles the rounds of the MPC config exchange and runs them.
+///
+/// The default exchange is the two-round commit-reveal protocol. Extensions added
+/// with [`with_extension`](Self::with_extension) share one further broadcast round,
+/// in which every party sends the payloads of all extensions in the order they were
+/// added. All parties must configure the same commitment scheme and extensions.
+pub struct ConfigExchangeBuilder {
+    i: PartyIndex,
+    n: u16,
+    reveal_msg: RevealMsg,
+    cert_path: PathBuf,
+    cert_dir: PathBuf,
+    scheme: CommitmentScheme,
+    operator_ca: Option<OperatorCa>,
+    extensions: Vec<Arc<dyn ExchangeExtension>>,
+}
+
+impl ConfigExchangeBuilder {
+    /// Creates the default exchange for party `i` of `n`, revealing `reveal_msg`.
+    ///
+    /// The local party's certificate is the one at `cert_path`; the certificates
+    /// peers reveal are stored under `cert_dir` (see [`store_peer_cert`]).
+    pub fn new(
+        i: PartyIndex,
+        n: u16,
+        reveal_msg: RevealMsg,
+        cert_path: PathBuf,
+        cert_dir: PathBuf,
+    ) -> Self {
+        Self {
+            i,
+            n,
+            reveal_msg,
+            cert_path,
+            cert_dir,
+            scheme: CommitmentScheme::default(),
+            operator_ca: None,
+            extensions: Vec::new(),
+        }
+    }
+
+    /// Sets the hash function used for commitments.
+    #[must_use]
+    pub fn with_commitment_scheme(mut self, scheme: CommitmentScheme) -> Self {
+        self.scheme = scheme;
+        self
+    }
 
+    /// Only accepts peer certificates issued by `operator_ca`; peers revealing
+    /// any other certificate are blamed with `BlameReason::UntrustedCertificate`.
+    #[must_use]
+    pub fn with_operator_ca(mut self, operator_ca: OperatorCa) -> Self {
+        self.operator_ca = Some(operator_ca);
+        self
+    }
+
+    /// Appends an extension, run after the reveal round.
+    #[must_use]
+    pub fn with_extension(mut self, extension: Arc<dyn ExchangeExtension>) -> Self {
+        self.extensions.push(extension);
+        self
+    }
+
+    /// Runs the assembled rounds over `party`.
+    pub async fn run<M>(self, party: M) -> Result<HashMap<PartyIndex, VerifiedPartyConfig>>
+    where
+        M: Mpc<ProtocolMessage = ConfigExchangeMsg>,
+    {
+        let Self {
+            i,
+            n,
+            reveal_msg,
+            cert_path,
+            cert_dir,
+            scheme,
+            operator_ca,
+            extensions,
+        } = self;
+        let MpcParty { delivery, .. } = party.into_party();
+        let (incoming, mut outgoing) = delivery.split();
+
+        // Bound what each peer can make us buffer before the rounds router sees it
+        let flood_guard = Arc::new(Mutex::new(FloodGuard::new(i, n)));
+        let incoming = {
+            let flood_guard = flood_guard.clone();
+            incoming.filter(move |incoming| {
+                let admit = match incoming {
+                    Ok(incoming) => flood_guard
+                        .lock()
+                        .map(|mut guard| guard.admit(incoming))
+                        .unwrap_or(false),
+                    Err(_) => true,
+                };
+                std::future::ready(admit)
+            })
+        };
+
+        // Define rounds
+        let mut rounds = RoundsRouter::<ConfigExchangeMsg>::builder();
+        let round1 = rounds.add_round(RoundInput::<CommitMsg>::broadcast(i, n));
+        let round2 = rounds.add_round(RoundInput::<RevealMsg>::broadcast(i, n));
+        let extension_round = rounds.add_round(RoundInput::<ExtensionMsg>::broadcast(i, n));
+        let mut rounds = rounds.listen(incoming);
+
+        // --- The Protocol ---
+
+        // 1. Serialize local config for commitment
+        let local_config_bytes = bincode::serialize(&reveal_msg)?;
+
+        // 2. Commit to the config (hash of serialized RevealMsg)
+        let commitment = scheme.commit(&local_config_bytes);
+        tracing::debug!(commitment = %hex::encode(commitment), ?scheme, "Committed local config");
+        outgoing
+            .send(Outgoing::broadcast(ConfigExchangeMsg::Commit(CommitMsg {
+                scheme,
+                commitment,
+            })))
+            .await
+            .map_err(|e| CoSnarksError::ExchangeRoundBasedError(e.to_string()))?;
+
+        tracing::debug!("Sent commitment, waiting for others...");
+
+        // 3. Receive commitments from other parties
+        let commitments = rounds.complete(round1).await;
+        check_flooding(&flood_guard)?;
+        let commitments =
+            commitments.map_err(|e| CoSnarksError::ExchangeRoundBasedError(e.to_string()))?;
+        tracing::debug!("Received all commitments");
+
+        // All parties must commit with the same hash function
+        let mismatched_schemes: Vec<(PartyIndex, CommitmentScheme)> = commitments
+            .iter_indexed()
+            .filter(|(_, _, commit)| commit.scheme != scheme)
+            .map(|(party_idx, _, commit)| (party_idx, commit.scheme))
+            .collect();
+        if !mismatched_schemes.is_empty() {
+            return Err(CoSnarksError::MpcProtocolError(format!(
+                "Commitment scheme mismatch: local {:?}, peers {:?}",
+                scheme, mismatched_schemes
+            )));
+        }
+
+        // 4. Reveal local config
+        tracing::debug!("Revealing local config");
+        outgoing
+            .send(Outgoing::broadcast(ConfigExchangeMsg::Reveal(
+                reveal_msg.clone(),
+            )))
+            .await
+            .map_err(|e| CoSnarksError::ExchangeRoundBasedError(e.to_string()))?;
+        tracing::debug!("Sent revealed config, waiting for others...");
+
+        // 5. Receive revealed configs, verify against commitments
+        let revealed_configs = rounds.complete(round2).await;
+        check_flooding(&flood_guard)?;
+        let revealed_configs =
+            revealed_configs.map_err(|e| CoSnarksError::ExchangeRoundBasedError(e.to_string()))?;
+        tracing::debug!("Received all revealed configs");
+
+        let local_config = VerifiedPartyConfig {
+            network: NetworkPartyConfig {
+                id: i as usize,
+                dns_name: parse_dns_name(&reveal_msg.dns_name)?,
+                cert_path,
+            },
+            cert_fingerprint: reveal_msg.cert_fingerprint,
+        };
+        let party_configs = verify_reveals(
+            i,
+            n,
+            local_config,
+            &cert_dir,
+            scheme,
+            operator_ca.as_ref(),
+            commitments,
+            revealed_configs,
+        )?;
+        if extensions.is_empty() {
+            tracing::info!("MPC Config Exchange protocol completed successfully.");
+            return Ok(party_configs);
+        }
+
+        // 6. Run the extensions in one further broadcast round
+        let mut local_payloads = Vec::with_capacity(extensions.len());
+        for extension in &extensions {
+            local_payloads.push(ExtensionPayload {
+                name: extension.name().to_string(),
+                payload: extension.payload(i, &party_configs)?,
+            });
+        }
+        outgoing
+            .send(Outgoing::broadcast(ConfigExchangeMsg::Extension(
+                ExtensionMsg {
+                    payloads: local_payloads.clone(),
+                },
+            )))
+            .await
+            .map_err(|e| CoSnarksError::ExchangeRoundBasedError(e.to_string()))?;
+        let extension_msgs = rounds.complete(extension_round).await;
+        check_flooding(&flood_guard)?;
+        let extension_msgs =
+            extension_msgs.map_err(|e| CoSnarksError::ExchangeRoundBasedError(e.to_string()))?;
+
+        let mut received: BTreeMap<PartyIndex, Vec<ExtensionPayload>> = BTreeMap::new();
+        received.insert(i, local_payloads);
+        for (party_idx, _, msg) in extension_msgs.into_iter_indexed() {
+            received.insert(party_idx, msg.payloads);
+        }
+        for (position, extension) in extensions.iter().enumerate() {
+            let mut payloads = BTreeMap::new();
+            for (&party_idx, party_payloads) in &received {
+                match party_payloads.get(position) {
+                    Some(entry) if entry.name == extension.name() => {
+                        payloads.insert(party_idx, entry.payload.clone());
+                    }
+                    other => {
+                        return Err(CoSnarksError::MpcProtocolError(format!(
+                            "Party {} sent {:?} where extension {} was expected",
+                            party_idx,
+                            other.map(|entry| &entry.name),
+                            extension.name()
+                        )));
+                    }
+                }
+            }
+            extension.verify(&party_configs, &payloads)?;
+            tracing::debug!(
+                extension = extension.name(),
+                "Config exchange extension verified"
+            );
+        }
+
+        tracing::info!("MPC Config Exchange protocol completed successfully.");
+        Ok(party_configs)
+    }
+}
+
+/// Counts the messages each peer sends per round, admitting at most
+/// [`MAX_MESSAGES_PER_ROUND`] and blaming parties that send more.
+struct FloodGuard {
+    i: PartyIndex,
+    n: u16,
+    received: HashMap<(PartyIndex, u16), u32>,
+    flooders: BTreeMap<PartyIndex, Blame>,
+}
+
+impl FloodGuard {
+    fn new(i: PartyIndex, n: u16) -> Self {
+        Self {
+            i,
+            n,
+            received: HashMap::new(),
+            flooders: BTreeMap::new(),
+        }
+    }
+
+    /// Returns whether `incoming` may be passed on to the rounds router.
+    fn admit(&mut self, incoming: &Incoming<ConfigExchangeMsg>) -> bool {
+        let sender = incoming.sender;
+        if sender >= self.n || sender == self.i {
+            tracing::warn!(
+                party = sender,
+                "Dropping config exchange message from unexpected sender"
+            );
+            return false;
+        }
+        let round = incoming.msg.round();
+        let count = self.received.entry((sender, round)).or_default();
+        *count = count.saturating_add(1);
+        if *count <= MAX_MESSAGES_PER_ROUND {
+            return true;
+        }
+        if !self.flooders.contains_key(&sender) {
+            tracing::warn!(
+                party = sender,
+                round,
+                "Party sent more config exchange messages than expected"
+            );
+            self.flooders.insert(sender, Blame {
+                guilty_party: sender,
+                commitment_msg: incoming.id,
+                reveal_msg: incoming.id,
+                reason: BlameReason::ExcessMessages { round },
+            });
+        }
+        false
+    }
+}
+
+/// Fails with `Error::ExcessMessages` blaming every party that flooded a round.
+fn check_flooding(flood_guard: &Mutex<FloodGuard>) -> Result<()> {
+    let mut guard = flood_guard
+        .lock()
+        .map_err(|_| CoSnarksError::Internal("Config exchange flood guard poisoned".to_string()))?;
+    if guard.flooders.is_empty() {
+        return Ok(());
+    }
+    let guilty_parties: Vec<Blame> = std::mem::take(&mut guard.flooders).into_values().collect();
+    tracing::error!(
+        ?guilty_parties,
+        "MPC Config Exchange failed due to flooding parties."
+    );
+    Err(CoSnarksError::ExcessMessages { guilty_parties })
+}
+
+/// Checks every peer's reveal against its commitment, claimed role and
+/// certificate, storing the certificates of verified peers under `cert_dir`.
+/// Returns the verified configs of all parties, `local_config` among them.
+fn verify_reveals(
+    i: PartyIndex,
+    n: u16,
+    local_config: VerifiedPartyConfig,
+    cert_dir: &Path,
+    scheme: CommitmentScheme,
+    operator_ca: Option<&OperatorCa>,
+    commitments: RoundMsgs<CommitMsg>,
+    revealed_configs: RoundMsgs<RevealMsg>,
+) -> Result<HashMap<PartyIndex, VerifiedPartyConfig>> {
     let mut guilty_parties = vec![];
+    let mut misassigned_roles = vec![];
     let mut party_configs = HashMap::with_capacity(n as usize);
 
-    // Parse local dns_name into Address struct expected by NetworkPartyConfig
-    let local_address = parse_dns_name(&reveal_msg.dns_name)?;
-
     // Add self to the map first
-    party_configs.insert(i, NetworkPartyConfig {
-        id: i as usize,
-        dns_name: local_address,
-        cert_path: reveal_msg.cert_path.clone(),
-    });
+    party_configs.insert(i, local_config);
 
     for ((party_idx, commit_msg_id, commit), (_, reveal_msg_id, revealed)) in commitments
         .into_iter_indexed()
@@ -120,14 +625,45 @@ where
 
         // Verify commitment
         let revealed_bytes = bincode::serialize(&revealed)?;
-        let commitment_expected = Sha256::digest(&revealed_bytes);
+        let commitment_expected = scheme.commit(&revealed_bytes);
 
-        if commit.commitment.to_vec() != commitment_expected.to_vec() {
+        if commit.commitment != commitment_expected {
             tracing::warn!(%party_idx, "Commitment mismatch");
             guilty_parties.push(Blame {
                 guilty_party: party_idx,
                 commitment_msg: commit_msg_id,
                 reveal_msg: reveal_msg_id,
+                reason: BlameReason::CommitmentMismatch,
+            });
+            continue;
+        }
+
+        if revealed.role != PartyRole::for_index(party_idx) {
+            tracing::warn!(%party_idx, role = ?revealed.role, "Party claimed an unassigned role");
+            misassigned_roles.push((party_idx, revealed.role));
+            continue;
+        }
+
+        // The revealed certificate must be the one the party committed to
+        if cert_fingerprint(&revealed.cert_der) != revealed.cert_fingerprint {
+            tracing::warn!(%party_idx, "Revealed certificate does not match its fingerprint");
+            guilty_parties.push(Blame {
+                guilty_party: party_idx,
+                commitment_msg: commit_msg_id,
+                reveal_msg: reveal_msg_id,
+                reason: BlameReason::CertificateMismatch,
+            });
+            continue;
+        }
+
+        // With an operator CA, the certificate must also be issued by it
+        if let Some(Err(e)) = operator_ca.map(|ca| ca.verify(&revealed.cert_der)) {
+            tracing::warn!(%party_idx, error = %e, "Revealed certificate is not trusted by the operator CA");
+            guilty_parties.push(Blame {
+                guilty_party: party_idx,
+                commitment_msg: commit_msg_id,
+                reveal_msg: reveal_msg_id,
+                reason: BlameReason::UntrustedCertificate,
             });
             continue;
         }
@@ -135,29 +671,39 @@ where
         // Parse revealed dns_name into Address struct
         let revealed_address = parse_dns_name(&revealed.dns_name)?;
 
+        // mpc-net reads peer certificates from disk
+        let cert_path = store_peer_cert(cert_dir, &revealed.cert_der)?;
+
         // Store verified config
-        let party_config = NetworkPartyConfig {
-            id: party_idx as usize,
-            dns_name: revealed_address,
-            cert_path: revealed.cert_path,
+        let party_config = VerifiedPartyConfig {
+            network: NetworkPartyConfig {
+                id: party_idx as usize,
+                dns_name: revealed_address,
+                cert_path,
+            },
+            cert_fingerprint: revealed.cert_fingerprint,
         };
         party_configs.insert(party_idx, party_config);
     }
 
-    if guilty_parties.is_empty() {
-        tracing::info!("MPC Config Exchange protocol completed successfully.");
-        Ok(party_configs)
-    } else {
+    if !guilty_parties.is_empty() {
         tracing::error!(
             ?guilty_parties,
             "MPC Config Exchange failed due to cheating parties."
         );
         Err(CoSnarksError::CommitmentMismatch { guilty_parties })
+    } else if !misassigned_roles.is_empty() {
+        Err(CoSnarksError::MpcProtocolError(format!(
+            "Parties claimed roles they were not assigned: {:?}",
+            misassigned_roles
+        )))
+    } else {
+        Ok(party_configs)
     }
 }
 
 /// Helper to parse "hostname:port" string into mpc_net::config::Address
-fn parse_dns_name(dns_name: &str) -> Result<Address> {
+pub(crate) fn parse_dns_name(dns_name: &str) -> Result<Address> {
     let parts: Vec<&str> = dns_name.split(':').collect();
     if parts.len() != 2 {
         return Err(CoSnarksError::InvalidDnsName(format!(
@@ -178,12 +724,38 @@ pub struct Blame {
     pub guilty_party: PartyIndex,
     pub commitment_msg: MsgId,
     pub reveal_msg: MsgId,
+    pub reason: BlameReason,
+}
+
+/// Why a party was blamed during the config exchange
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BlameReason {
+    /// The revealed config does not match the party's commitment
+    CommitmentMismatch,
+    /// The revealed certificate does not match the revealed fingerprint
+    CertificateMismatch,
+    /// The revealed certificate was not issued by the operator CA
+    UntrustedCertificate,
+    /// The party sent more messages in the round than the protocol allows;
+    /// both message IDs of the blame name the first excess message
+    ExcessMessages { round: u16 },
+}
+
+impl BlameReason {
+    /// Returns whether the blame proves misbehavior from the party's own
+    /// committed reveal. Excess messages may be duplicates from a flaky
+    /// transport, so they are not held against the party as cheating.
+    #[must_use]
+    pub fn is_provable(&self) -> bool {
+        !matches!(self, BlameReason::ExcessMessages { .. })
+    }
 }
 
 // --- Tests ---
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::certs::read_cert_fingerprint;
     use blueprint_sdk::crypto::KeyType;
     use blueprint_sdk::crypto::sp_core::SpEcdsa;
     use blueprint_sdk::networking::AllowedKeys;
@@ -200,7 +772,7 @@ mod tests {
     // Helper to create dummy cert/key paths for testing
     async fn create_dummy_certs(dir: &Path, party_idx: usize) -> (PathBuf, PathBuf) {
         let key_path = dir.join(format!("key_{}.der", party_idx));
-        let cert_path = dir.join(format!("cert_{}.der", party_idx));
+        let cert_path = dummy_cert_path(dir, party_idx as u16);
         fs::write(&key_path, format!("key_data_{}", party_idx))
             .await
             .unwrap();
@@ -210,6 +782,32 @@ mod tests {
         (key_path, cert_path)
     }
 
+    fn dummy_cert_path(dir: &Path, i: u16) -> PathBuf {
+        dir.join(format!("cert_{}.der", i))
+    }
+
+    // Helper to build a reveal for party `i` backed by a dummy cert in `dir`
+    async fn dummy_reveal(dir: &Path, i: u16, dns_name: String) -> RevealMsg {
+        let (_key_path, cert_path) = create_dummy_certs(dir, i as usize).await;
+        reveal_cert(&cert_path, dns_name, i)
+    }
+
+    fn reveal_cert(cert_path: &Path, dns_name: String, i: u16) -> RevealMsg {
+        let cert_der = std::fs::read(cert_path).unwrap();
+        RevealMsg {
+            dns_name,
+            cert_fingerprint: cert_fingerprint(&cert_der),
+            cert_der,
+            role: PartyRole::for_index(i),
+        }
+    }
+
+    fn peer_cert_path(cert_dir: &Path, reveal: &RevealMsg) -> PathBuf {
+        cert_dir
+            .join(crate::certs::PEER_CERT_DIR)
+            .join(format!("{}.der", hex::encode(reveal.cert_fingerprint)))
+    }
+
     #[tokio::test]
     async fn test_config_exchange_simulation() {
         setup_log();
@@ -217,20 +815,26 @@ mod tests {
         let temp_dir = tempdir().unwrap();
         let base_path = temp_dir.path().to_path_buf();
 
-        let configs: Vec<_> = (0..n)
-            .map(|i| {
-                let keypair = SpEcdsa::generate_with_seed(None).unwrap();
-                RevealMsg {
-                    dns_name: format!("party_{}.example.com:900{}", i, i),
-                    cert_path: base_path.join(format!("cert_{}.der", i)),
-                }
-            })
-            .collect();
+        let mut configs = Vec::new();
+        for i in 0..n {
+            let dns_name = format!("party_{}.example.com:900{}", i, i);
+            configs.push(dummy_reveal(&base_path, i, dns_name).await);
+        }
 
-        let results: Vec<Result<HashMap<u16, NetworkPartyConfig>>> =
+        let results: Vec<Result<HashMap<u16, VerifiedPartyConfig>>> =
             round_based::sim::run_with_setup(
                 configs.clone(), // Each party gets its own config to reveal
-                |i, party, config| mpc_config_exchange(party, i, n, config),
+                |i, party, config| {
+                    mpc_config_exchange(
+                        party,
+                        i,
+                        n,
+                        config,
+                        CommitmentScheme::default(),
+                        dummy_cert_path(&base_path, i),
+                        base_path.clone(),
+                    )
+                },
             )
             .unwrap()
             .0;
@@ -239,16 +843,244 @@ mod tests {
         for i in 0..n {
             let party_conf = results[i as usize].as_ref().unwrap().clone();
             let expected_address = parse_dns_name(&configs[i as usize].dns_name).unwrap();
-            assert_eq!(party_conf[&i].id, i as usize);
-            assert_eq!(party_conf[&i].dns_name, expected_address);
+            assert_eq!(party_conf[&i].network.id, i as usize);
+            assert_eq!(party_conf[&i].network.dns_name, expected_address);
             assert_eq!(
-                party_conf.get(&i).unwrap().cert_path,
-                configs[i as usize].cert_path
+                party_conf.get(&i).unwrap().network.cert_path,
+                dummy_cert_path(&base_path, i)
             );
+            for j in 0..n {
+                assert_eq!(
+                    party_conf[&j].cert_fingerprint,
+                    configs[j as usize].cert_fingerprint
+                );
+                if j != i {
+                    assert_eq!(
+                        party_conf[&j].network.cert_path,
+                        peer_cert_path(&base_path, &configs[j as usize])
+                    );
+                }
+            }
         }
         tracing::info!("Simulation test passed.");
     }
 
+    #[tokio::test]
+    async fn test_config_exchange_rejects_claimed_coordinator() {
+        setup_log();
+        let n: u16 = 3;
+        let temp_dir = tempdir().unwrap();
+        let mut configs = Vec::new();
+        for i in 0..n {
+            let dns_name = format!("party_{}.example.com:900{}", i, i);
+            configs.push(dummy_reveal(temp_dir.path(), i, dns_name).await);
+        }
+        // Party 2 tries to take over coordination
+        configs[2].role = PartyRole::Coordinator;
+
+        let results = round_based::sim::run_with_setup(configs, |i, party, config| {
+            mpc_config_exchange(
+                party,
+                i,
+                n,
+                config,
+                CommitmentScheme::default(),
+                dummy_cert_path(temp_dir.path(), i),
+                temp_dir.path().to_path_buf(),
+            )
+        })
+        .unwrap()
+        .0;
+
+        for (i, result) in results.iter().enumerate() {
+            if i == 2 {
+                continue;
+            }
+            assert!(
+                matches!(result, Err(CoSnarksError::MpcProtocolError(_))),
+                "Party {} accepted a misassigned role",
+                i
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn reveal_rejects_oversized_fields_when_decoding() {
+        let temp_dir = tempdir().unwrap();
+        let reveal = dummy_reveal(temp_dir.path(), 1, "party_1.example.com:9001".to_string()).await;
+        let decoded: RevealMsg =
+            bincode::deserialize(&bincode::serialize(&reveal).unwrap()).unwrap();
+        assert_eq!(decoded, reveal);
+
+        let mut oversized = reveal.clone();
+        oversized.dns_name = format!("{}:9001", "a".repeat(MAX_REVEALED_DNS_NAME_LEN));
+        let encoded = bincode::serialize(&ConfigExchangeMsg::Reveal(oversized)).unwrap();
+        assert!(bincode::deserialize::<ConfigExchangeMsg>(&encoded).is_err());
+
+        let mut oversized = reveal;
+        oversized.cert_der = vec![0xAA; MAX_REVEALED_CERT_LEN + 1];
+        let encoded = bincode::serialize(&oversized).unwrap();
+        assert!(bincode::deserialize::<RevealMsg>(&encoded).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_config_exchange_blames_swapped_certificate() {
+        setup_log();
+        let n: u16 = 3;
+        let temp_dir = tempdir().unwrap();
+        let mut configs = Vec::new();
+        for i in 0..n {
+            let dns_name = format!("party_{}.example.com:900{}", i, i);
+            configs.push(dummy_reveal(temp_dir.path(), i, dns_name).await);
+        }
+        // Party 2 commits to a fingerprint that is not its certificate's
+        configs[2].cert_fingerprint = [0xAA; 32];
+
+        let results = round_based::sim::run_with_setup(configs, |i, party, config| {
+            mpc_config_exchange(
+                party,
+                i,
+                n,
+                config,
+                CommitmentScheme::default(),
+                dummy_cert_path(temp_dir.path(), i),
+                temp_dir.path().to_path_buf(),
+            )
+        })
+        .unwrap()
+        .0;
+
+        for (i, result) in results.iter().enumerate() {
+            if i == 2 {
+                continue;
+            }
+            match result {
+                Err(CoSnarksError::CommitmentMismatch { guilty_parties }) => {
+                    assert_eq!(guilty_parties.len(), 1);
+                    assert_eq!(guilty_parties[0].guilty_party, 2);
+                    assert_eq!(guilty_parties[0].reason, BlameReason::CertificateMismatch);
+                }
+                other => panic!("Party {} accepted a swapped certificate: {:?}", i, other),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_config_exchange_blames_certificate_not_issued_by_operator_ca() {
+        setup_log();
+        let n: u16 = 3;
+        let temp_dir = tempdir().unwrap();
+        let mut ca_params = rcgen::CertificateParams::new(vec!["operator-ca".to_string()]);
+        ca_params.is_ca = rcgen::IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+        let ca = rcgen::Certificate::from_params(ca_params).unwrap();
+        let operator_ca = OperatorCa::from_der(vec![ca.serialize_der().unwrap()]).unwrap();
+
+        let mut configs = Vec::new();
+        for i in 0..n {
+            let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+            // Party 2 keeps its self-signed certificate
+            let cert_der = if i == 2 {
+                cert.serialize_der().unwrap()
+            } else {
+                cert.serialize_der_with_signer(&ca).unwrap()
+            };
+            fs::write(dummy_cert_path(temp_dir.path(), i), &cert_der)
+                .await
+                .unwrap();
+            configs.push(RevealMsg {
+                dns_name: format!("party_{}.example.com:900{}", i, i),
+                cert_fingerprint: cert_fingerprint(&cert_der),
+                cert_der,
+                role: PartyRole::for_index(i),
+            });
+        }
+
+        let results = round_based::sim::run_with_setup(configs, |i, party, config| {
+            ConfigExchangeBuilder::new(
+                i,
+                n,
+                config,
+                dummy_cert_path(temp_dir.path(), i),
+                temp_dir.path().to_path_buf(),
+            )
+            .with_operator_ca(operator_ca.clone())
+            .run(party)
+        })
+        .unwrap()
+        .0;
+
+        for (i, result) in results.iter().enumerate() {
+            if i == 2 {
+                continue;
+            }
+            match result {
+                Err(CoSnarksError::CommitmentMismatch { guilty_parties }) => {
+                    assert_eq!(guilty_parties.len(), 1);
+                    assert_eq!(guilty_parties[0].guilty_party, 2);
+                    assert_eq!(guilty_parties[0].reason, BlameReason::UntrustedCertificate);
+                }
+                other => panic!("Party {} accepted an untrusted certificate: {:?}", i, other),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_config_exchange_stores_peer_certs_with_default_paths() {
+        setup_log();
+        let n: u16 = 3;
+        let temp_dir = tempdir().unwrap();
+        // Every operator keeps its certificate at the default path in its own data directory
+        let mut cert_dirs = Vec::new();
+        let mut inputs = Vec::new();
+        for i in 0..n {
+            let data_dir = temp_dir.path().join(format!("operator_{}", i));
+            let cert_path = data_dir.join("mpc_certs/mpc_cert.der");
+            let key_path = data_dir.join("mpc_certs/mpc_key.der");
+            crate::certs::generate_mpc_cert(&cert_path, &key_path).unwrap();
+            let dns_name = format!("party_{}.example.com:900{}", i, i);
+            let reveal = reveal_cert(&cert_path, dns_name, i);
+            let cert_dir = cert_path.parent().unwrap().to_path_buf();
+            cert_dirs.push(cert_dir.clone());
+            inputs.push((reveal, cert_path, cert_dir));
+        }
+        let reveals: Vec<RevealMsg> = inputs.iter().map(|(reveal, ..)| reveal.clone()).collect();
+
+        let results = round_based::sim::run_with_setup(
+            inputs.clone(),
+            |i, party, (reveal, cert_path, cert_dir)| {
+                mpc_config_exchange(
+                    party,
+                    i,
+                    n,
+                    reveal,
+                    CommitmentScheme::default(),
+                    cert_path,
+                    cert_dir,
+                )
+            },
+        )
+        .unwrap()
+        .0;
+
+        for (i, result) in results.into_iter().enumerate() {
+            let configs = result.unwrap();
+            let cert_dir = &cert_dirs[i];
+            for (j, reveal) in reveals.iter().enumerate() {
+                let cert_path = &configs[&(j as u16)].network.cert_path;
+                if i == j {
+                    assert_eq!(cert_path, &inputs[i].1);
+                } else {
+                    // Each peer's own certificate is stored, not the local one at the same path
+                    assert_eq!(cert_path, &peer_cert_path(cert_dir, reveal));
+                }
+                assert_eq!(
+                    read_cert_fingerprint(cert_path).unwrap(),
+                    reveal.cert_fingerprint
+                );
+            }
+        }
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn test_config_exchange_p2p() {
         setup_log();
@@ -262,6 +1094,7 @@ mod tests {
         let mut nodes = Vec::new();
         let mut handles = Vec::new();
         let mut configs = Vec::new();
+        let mut cert_paths = Vec::new();
 
         for i in 0..n {
             let node_dir = base_path.join(format!("node_{}", i));
@@ -274,10 +1107,8 @@ mod tests {
                 vec![],
                 false,
             );
-            configs.push(RevealMsg {
-                dns_name: format!("127.0.0.1:900{}", i),
-                cert_path,
-            });
+            configs.push(reveal_cert(&cert_path, format!("127.0.0.1:900{}", i), i));
+            cert_paths.push(cert_path);
             nodes.push(node);
         }
 
@@ -311,11 +1142,22 @@ mod tests {
             let handle = handles[i as usize].clone();
             let parties_clone = parties.clone();
             let config_clone = configs[i as usize].clone();
+            let cert_path = cert_paths[i as usize].clone();
+            let cert_dir = base_path.join(format!("node_{}", i));
             let task = tokio::spawn(async move {
                 tracing::info!(party_index = i, "Spawning protocol task");
                 let network = RoundBasedNetworkAdapter::new(handle, i, parties_clone, instance_id);
                 let mpc_party = MpcParty::connected(network);
-                mpc_config_exchange(mpc_party, i, n, config_clone).await
+                mpc_config_exchange(
+                    mpc_party,
+                    i,
+                    n,
+                    config_clone,
+                    CommitmentScheme::default(),
+                    cert_path,
+                    cert_dir,
+                )
+                .await
             });
             tasks.push(task);
         }
@@ -337,47 +1179,155 @@ mod tests {
             }
         }
 
-        // Verify all parties have the same final config map
+        // Verify all parties agree on every party's config; certificate paths
+        // are local to each party
         assert!(
             !final_configs.is_empty(),
             "No configurations were collected"
         );
-        let first_map = final_configs[0].clone();
-        assert_eq!(
-            first_map.len(),
-            n as usize,
-            "Expected {} parties in config map, found {}",
-            n,
-            first_map.len()
-        );
-
-        for (idx, other_map) in final_configs.iter().enumerate().skip(1) {
+        for (idx, config_map) in final_configs.iter().enumerate() {
             assert_eq!(
-                &first_map, other_map,
-                "Config maps differ between party 0 and party {}",
-                idx
+                config_map.len(),
+                n as usize,
+                "Expected {} parties in party {}'s config map, found {}",
+                n,
+                idx,
+                config_map.len()
             );
+            for i in 0..n {
+                let party_conf = config_map
+                    .get(&i)
+                    .unwrap_or_else(|| panic!("Missing config for party {}", i));
+                let expected_address = parse_dns_name(&configs[i as usize].dns_name).unwrap();
+                assert_eq!(
+                    party_conf.network.id, i as usize,
+                    "MPC ID mismatch for party {}",
+                    i
+                );
+                assert_eq!(
+                    party_conf.network.dns_name, expected_address,
+                    "DNS name mismatch for party {}",
+                    i
+                );
+                assert_eq!(
+                    party_conf.cert_fingerprint, configs[i as usize].cert_fingerprint,
+                    "Fingerprint mismatch for party {}",
+                    i
+                );
+                assert_eq!(
+                    read_cert_fingerprint(&party_conf.network.cert_path).unwrap(),
+                    configs[i as usize].cert_fingerprint,
+                    "Cert mismatch for party {}",
+                    i
+                );
+            }
+        }
+
+        tracing::info!("P2P Config Exchange test passed.");
+    }
+
+    /// Agrees on a value that every party must hold identically
+    struct AgreeOn(Vec<u8>);
+
+    impl ExchangeExtension for AgreeOn {
+        fn name(&self) -> &'static str {
+            "agree-on"
+        }
+
+        fn payload(
+            &self,
+            _i: PartyIndex,
+            _configs: &HashMap<PartyIndex, VerifiedPartyConfig>,
+        ) -> Result<Vec<u8>> {
+            Ok(self.0.clone())
+        }
+
+        fn verify(
+            &self,
+            _configs: &HashMap<PartyIndex, VerifiedPartyConfig>,
+            payloads: &BTreeMap<PartyIndex, Vec<u8>>,
+        ) -> Result<()> {
+            match payloads.iter().find(|(_, payload)| **payload != self.0) {
+                Some((party_idx, _)) => Err(CoSnarksError::MpcProtocolError(format!(
+                    "Party {} disagrees",
+                    party_idx
+                ))),
+                None => Ok(()),
+            }
         }
+    }
 
-        // Basic check on the content
+    #[tokio::test]
+    async fn test_config_exchange_runs_extensions() {
+        setup_log();
+        let n: u16 = 3;
+        let temp_dir = tempdir().unwrap();
+        let mut inputs = Vec::new();
         for i in 0..n {
-            let party_conf = first_map
-                .get(&i)
-                .unwrap_or_else(|| panic!("Missing config for party {}", i));
-            let expected_address = parse_dns_name(&configs[i as usize].dns_name).unwrap();
-            assert_eq!(party_conf.id, i as usize, "MPC ID mismatch for party {}", i);
-            assert_eq!(
-                party_conf.dns_name, expected_address,
-                "DNS name mismatch for party {}",
-                i
-            );
-            assert_eq!(
-                party_conf.cert_path, configs[i as usize].cert_path,
-                "Cert path mismatch for party {}",
-                i
-            );
+            let dns_name = format!("party_{}.example.com:900{}", i, i);
+            let reveal = dummy_reveal(temp_dir.path(), i, dns_name).await;
+            // Party 1 holds a different value than the others
+            let value = if i == 1 {
+                b"other".to_vec()
+            } else {
+                b"value".to_vec()
+            };
+            inputs.push((reveal, value));
         }
 
-        tracing::info!("P2P Config Exchange test passed.");
+        let results = round_based::sim::run_with_setup(inputs, |i, party, (reveal, value)| {
+            ConfigExchangeBuilder::new(
+                i,
+                n,
+                reveal,
+                dummy_cert_path(temp_dir.path(), i),
+                temp_dir.path().to_path_buf(),
+            )
+            .with_extension(Arc::new(AgreeOn(value)))
+            .run(party)
+        })
+        .unwrap()
+        .0;
+
+        for result in &results {
+            assert!(matches!(result, Err(CoSnarksError::MpcProtocolError(_))));
+        }
+    }
+
+    #[test]
+    fn flood_guard_blames_parties_sending_excess_messages() {
+        let commit = |id: MsgId, sender: PartyIndex| Incoming {
+            id,
+            sender,
+            msg_type: round_based::MessageType::Broadcast,
+            msg: ConfigExchangeMsg::Commit(CommitMsg {
+                scheme: CommitmentScheme::default(),
+                commitment: [0u8; 32],
+            }),
+        };
+        let guard = Mutex::new(FloodGuard::new(0, 3));
+        assert!(check_flooding(&guard).is_ok());
+        {
+            let mut guard = guard.lock().unwrap();
+            assert!(guard.admit(&commit(1, 1)));
+            assert!(guard.admit(&commit(2, 2)));
+            // Duplicates, messages claiming to be from us and out-of-range senders are dropped
+            assert!(!guard.admit(&commit(3, 1)));
+            assert!(!guard.admit(&commit(4, 1)));
+            assert!(!guard.admit(&commit(5, 0)));
+            assert!(!guard.admit(&commit(6, 3)));
+        }
+
+        match check_flooding(&guard) {
+            Err(CoSnarksError::ExcessMessages { guilty_parties }) => {
+                assert_eq!(guilty_parties.len(), 1);
+                assert_eq!(guilty_parties[0].guilty_party, 1);
+                assert_eq!(guilty_parties[0].commitment_msg, 3);
+                assert_eq!(guilty_parties[0].reason, BlameReason::ExcessMessages {
+                    round: commit(0, 1).msg.round()
+                });
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
     }
 }
@@ -0,0 +1,190 @@
+//! Proving backends that turn a witness into a proof on an established MPC session.
+
+#[cfg(any(test, feature = "test-utils"))]
+use crate::error::Error;
+use crate::error::Result;
+use crate::state::CircuitSnapshot;
+use crate::types::{ProofResult, ProvingBackend};
+use crate::verify::verify_proof;
+use futures::future::BoxFuture;
+use mpc_net::MpcNetworkHandler;
+use rand::Rng;
+use rand::rngs::StdRng;
+#[cfg(any(test, feature = "test-utils"))]
+use sha2::{Digest, Sha256};
+
+/// Domain separator of [`MockProver`] proofs.
+#[cfg(any(test, feature = "test-utils"))]
+const MOCK_PROOF_DOMAIN: &[u8] = b"cosnarks-mock-proof-v1";
+
+/// Inputs of a single proof.
+pub struct ProvingRequest<'a> {
+    /// Artifacts and keys of the circuit, with the backend to prove with
+    pub snapshot: &'a CircuitSnapshot,
+    /// JSON witness, already validated and normalized
+    pub witness_json: &'a str,
+    /// Hash of the backend name and the witness, as used by the proof cache
+    pub witness_hash: &'a [u8; 32],
+    /// MPC-Net session shared with the other participants
+    pub mpc_handler: &'a MpcNetworkHandler,
+}
+
+/// Produces proofs for `generate_proof` and verifies them before they are returned.
+///
+/// Every participant of a session must use the same prover. Install one with
+/// `CosnarksContext::with_prover`; with the `test-utils` feature,
+/// `MOCK_PROVER` selects `MockProver`.
+pub trait ProverBackend: Send + Sync {
+    /// Name of the prover, for logs.
+    fn name(&self) -> &'static str;
+
+    /// Proves `request`, returning the proof bytes and the public inputs.
+    ///
+    /// The MPC rounds run on the returned future, which `generate_proof`
+    /// drops to abort the proof when its session is force-closed or its
+    /// deadline passes.
+    fn prove<'a>(
+        &'a self,
+        request: &'a ProvingRequest<'_>,
+        rng: &'a mut StdRng,
+    ) -> BoxFuture<'a, Result<(Vec<u8>, Vec<Vec<u8>>)>>;
+
+    /// Verifies a proof from [`prove`](Self::prove) against the verification key `vk`.
+    ///
+    /// Fails with `Error::ProofSelfCheckFailed` if the proof does not verify.
+    fn verify(&self, backend: &ProvingBackend, vk: &[u8], proof: &ProofResult) -> Result<()>;
+}
+
+/// The default prover, returning placeholder proofs until co-circom and
+/// co-noir proving is wired in.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PlaceholderProver;
+
+impl ProverBackend for PlaceholderProver {
+    fn name(&self) -> &'static str {
+        "placeholder"
+    }
+
+    fn prove<'a>(
+        &'a self,
+        request: &'a ProvingRequest<'_>,
+        rng: &'a mut StdRng,
+    ) -> BoxFuture<'a, Result<(Vec<u8>, Vec<Vec<u8>>)>> {
+        Box::pin(async move {
+            // TODO: Replace placeholder with actual co-circom/co-noir call
+            // let proof_result = co_lib::generate_proof(
+            //     request.snapshot.primary_artifact()?,
+            //     &request.snapshot.proving_key,
+            //     request.witness_json,
+            //     request.mpc_handler,
+            //     rng
+            // ).await?;
+            let blinding: [u8; 16] = rng.r#gen();
+            let proof_bytes = format!(
+                "proof_for_{}_{}",
+                hex::encode(request.snapshot.info.id),
+                hex::encode(blinding)
+            )
+            .into_bytes();
+            let public_inputs =
+                vec![format!("input_for_{}", hex::encode(&request.witness_hash[..8])).into_bytes()];
+            Ok((proof_bytes, public_inputs))
+        })
+    }
+
+    fn verify(&self, backend: &ProvingBackend, vk: &[u8], proof: &ProofResult) -> Result<()> {
+        verify_proof(backend, vk, proof)
+    }
+}
+
+/// A deterministic prover for tests, never for production.
+///
+/// The public input is the SHA-256 of the witness, and the proof is a SHA-256
+/// tag binding it to the backend and the verification key, which
+/// [`verify`](ProverBackend::verify) recomputes. Identical witnesses always
+/// produce identical proofs, and the proof proves nothing about the witness.
+/// Only built with the `test-utils` feature.
+#[cfg(any(test, feature = "test-utils"))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MockProver;
+
+#[cfg(any(test, feature = "test-utils"))]
+impl MockProver {
+    fn tag(backend: &ProvingBackend, vk: &[u8], public_inputs: &[Vec<u8>]) -> Vec<u8> {
+        let mut hasher = Sha256::new()
+            .chain_update(MOCK_PROOF_DOMAIN)
+            .chain_update(backend.name().as_bytes())
+            .chain_update((vk.len() as u64).to_be_bytes())
+            .chain_update(vk);
+        for input in public_inputs {
+            hasher.update((input.len() as u64).to_be_bytes());
+            hasher.update(input);
+        }
+        hasher.finalize().to_vec()
+    }
+}
+
+#[cfg(any(test, feature = "test-utils"))]
+impl ProverBackend for MockProver {
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+
+    fn prove<'a>(
+        &'a self,
+        request: &'a ProvingRequest<'_>,
+        _rng: &'a mut StdRng,
+    ) -> BoxFuture<'a, Result<(Vec<u8>, Vec<Vec<u8>>)>> {
+        let snapshot = request.snapshot;
+        let public_inputs = vec![Sha256::digest(request.witness_json.as_bytes()).to_vec()];
+        let proof_bytes = Self::tag(
+            &snapshot.backend,
+            &snapshot.verification_key,
+            &public_inputs,
+        );
+        Box::pin(std::future::ready(Ok((proof_bytes, public_inputs))))
+    }
+
+    fn verify(&self, backend: &ProvingBackend, vk: &[u8], proof: &ProofResult) -> Result<()> {
+        verify_proof(backend, vk, proof)?;
+        if proof.proof_bytes != Self::tag(backend, vk, &proof.public_inputs) {
+            return Err(Error::ProofSelfCheckFailed(format!(
+                "Mock {:?} proof does not match its public inputs and verification key",
+                backend
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mock_proofs_only_verify_against_their_inputs() {
+        let backend = ProvingBackend::Groth16;
+        let public_inputs = vec![Sha256::digest(b"{\"a\":1}").to_vec()];
+        let proof = ProofResult {
+            proof_bytes: MockProver::tag(&backend, b"vk", &public_inputs),
+            public_inputs,
+            metadata: None,
+        };
+        MockProver.verify(&backend, b"vk", &proof).unwrap();
+
+        let other_input = ProofResult {
+            public_inputs: vec![Sha256::digest(b"{\"a\":2}").to_vec()],
+            ..proof.clone()
+        };
+        for (backend, vk, proof) in [
+            (&backend, &b"other vk"[..], &proof),
+            (&ProvingBackend::Plonk, b"vk", &proof),
+            (&backend, b"vk", &other_input),
+        ] {
+            assert!(matches!(
+                MockProver.verify(backend, vk, proof),
+                Err(Error::ProofSelfCheckFailed(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,213 @@
+//! Admission of proof jobs, bounding how many prove at once.
+
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio_util::sync::CancellationToken;
+use tracing::debug;
+
+/// Where a proof job was when it was cancelled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CancelOutcome {
+    /// Removed while waiting for a permit; no proving work was started
+    Queued,
+    /// Aborted while proving
+    Running,
+    /// No queued or running proof job has this call ID
+    NotFound,
+}
+
+#[derive(Debug)]
+struct QueuedJob {
+    caller: [u8; 32],
+    cancel: CancellationToken,
+    running: bool,
+}
+
+/// Queues proof jobs behind a shared semaphore and tracks them by call ID,
+/// so a job can be cancelled whether it is still waiting or already running.
+///
+/// Jobs beyond the limit wait in FIFO order for a permit.
+#[derive(Debug, Clone)]
+pub struct ProofQueue {
+    permits: Arc<Semaphore>,
+    jobs: Arc<Mutex<HashMap<u64, QueuedJob>>>,
+}
+
+/// A proof job's slot in the [`ProofQueue`]; releases the permit when dropped.
+#[derive(Debug)]
+pub struct ProofPermit {
+    call_id: u64,
+    cancel: CancellationToken,
+    jobs: Arc<Mutex<HashMap<u64, QueuedJob>>>,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl ProofQueue {
+    /// Creates a queue letting `max_concurrent` proof jobs run at once.
+    pub fn new(max_concurrent: usize) -> Self {
+        Self {
+            permits: Arc::new(Semaphore::new(max_concurrent.max(1))),
+            jobs: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Waits for a permit for the job `call_id`, submitted by `caller`.
+    ///
+    /// Fails with `Error::Cancelled` if the job is cancelled while queued and
+    /// with `Error::InvalidInput` if a job with this call ID is already queued.
+    pub async fn acquire(&self, call_id: u64, caller: [u8; 32]) -> Result<ProofPermit> {
+        let cancel = CancellationToken::new();
+        {
+            let mut jobs = self.lock_jobs()?;
+            if jobs.contains_key(&call_id) {
+                return Err(Error::InvalidInput(format!(
+                    "Proof job {} is already queued",
+                    call_id
+                )));
+            }
+            jobs.insert(call_id, QueuedJob {
+                caller,
+                cancel: cancel.clone(),
+                running: false,
+            });
+        }
+
+        // Deregisters the job unless it is admitted, also if this future is dropped
+        let mut pending = PendingJob {
+            jobs: &self.jobs,
+            call_id,
+            admitted: false,
+        };
+
+        let permit = tokio::select! {
+            permit = self.permits.clone().acquire_owned() => permit,
+            _ = cancel.cancelled() => {
+                return Err(Error::Cancelled(format!(
+                    "Proof job {} was cancelled while queued",
+                    call_id
+                )));
+            }
+        };
+        let permit = permit.map_err(|_| Error::Internal("Proof queue closed".to_string()))?;
+
+        let mut jobs = self.lock_jobs()?;
+        match jobs.get_mut(&call_id) {
+            // Cancelled between the permit being granted and being recorded
+            None => Err(Error::Cancelled(format!(
+                "Proof job {} was cancelled while queued",
+                call_id
+            ))),
+            Some(job) => {
+                job.running = true;
+                pending.admitted = true;
+                debug!(call_id, "Proof job admitted");
+                Ok(ProofPermit {
+                    call_id,
+                    cancel,
+                    jobs: self.jobs.clone(),
+                    _permit: permit,
+                })
+            }
+        }
+    }
+
+    /// Cancels the job `call_id`: a queued job is removed before it acquires a
+    /// permit, a running job is aborted.
+    pub fn cancel(&self, call_id: u64) -> Result<CancelOutcome> {
+        let mut jobs = self.lock_jobs()?;
+        let outcome = match jobs.get(&call_id) {
+            None => CancelOutcome::NotFound,
+            Some(job) if job.running => {
+                job.cancel.cancel();
+                CancelOutcome::Running
+            }
+            Some(job) => {
+                job.cancel.cancel();
+                jobs.remove(&call_id);
+                CancelOutcome::Queued
+            }
+        };
+        Ok(outcome)
+    }
+
+    /// Returns the account that submitted the queued or running job `call_id`.
+    pub fn caller_of(&self, call_id: u64) -> Result<Option<[u8; 32]>> {
+        Ok(self.lock_jobs()?.get(&call_id).map(|job| job.caller))
+    }
+
+    fn lock_jobs(&self) -> Result<std::sync::MutexGuard<'_, HashMap<u64, QueuedJob>>> {
+        self.jobs
+            .lock()
+            .map_err(|_| Error::Internal("Proof queue lock poisoned".to_string()))
+    }
+}
+
+struct PendingJob<'a> {
+    jobs: &'a Mutex<HashMap<u64, QueuedJob>>,
+    call_id: u64,
+    admitted: bool,
+}
+
+impl Drop for PendingJob<'_> {
+    fn drop(&mut self) {
+        if !self.admitted {
+            if let Ok(mut jobs) = self.jobs.lock() {
+                jobs.remove(&self.call_id);
+            }
+        }
+    }
+}
+
+impl ProofPermit {
+    /// Completes once the job has been cancelled while running.
+    pub async fn cancelled(&self) {
+        self.cancel.cancelled().await;
+    }
+}
+
+impl Drop for ProofPermit {
+    fn drop(&mut self) {
+        if let Ok(mut jobs) = self.jobs.lock() {
+            jobs.remove(&self.call_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn cancels_queued_and_running_jobs() {
+        let queue = ProofQueue::new(1);
+        let running = queue.acquire(1, [1u8; 32]).await.unwrap();
+
+        let waiting = {
+            let queue = queue.clone();
+            tokio::spawn(async move { queue.acquire(2, [2u8; 32]).await })
+        };
+        // Let the second job enqueue behind the first
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(queue.caller_of(2).unwrap(), Some([2u8; 32]));
+
+        assert_eq!(queue.cancel(2).unwrap(), CancelOutcome::Queued);
+        assert!(matches!(waiting.await.unwrap(), Err(Error::Cancelled(_))));
+
+        assert_eq!(queue.cancel(1).unwrap(), CancelOutcome::Running);
+        tokio::time::timeout(Duration::from_secs(1), running.cancelled())
+            .await
+            .unwrap();
+        drop(running);
+        assert_eq!(queue.cancel(1).unwrap(), CancelOutcome::NotFound);
+
+        // The freed permit admits the next job
+        let next = tokio::time::timeout(Duration::from_secs(1), queue.acquire(3, [3u8; 32]))
+            .await
+            .unwrap();
+        assert!(next.is_ok());
+    }
+}
@@ -0,0 +1,80 @@
+//! Redaction of sensitive values in logs and error messages.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Formats a sensitive value without revealing it in full.
+///
+/// Paths are abbreviated to their file name, so logs and errors still say
+/// which file is meant without exposing the operator's directory layout.
+/// Byte strings, such as key material, are reduced to their length.
+pub struct Redacted<'a, T: ?Sized>(pub &'a T);
+
+/// Values with a redacted representation, formatted through [`Redacted`].
+pub trait Redact {
+    /// Writes the redacted form of `self`.
+    fn fmt_redacted(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result;
+}
+
+impl Redact for Path {
+    fn fmt_redacted(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let has_parent = self
+            .parent()
+            .is_some_and(|parent| !parent.as_os_str().is_empty());
+        match (has_parent, self.file_name()) {
+            (false, Some(name)) => write!(f, "{}", name.to_string_lossy()),
+            (true, Some(name)) => write!(f, ".../{}", name.to_string_lossy()),
+            (_, None) => f.write_str("<path>"),
+        }
+    }
+}
+
+impl Redact for PathBuf {
+    fn fmt_redacted(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_path().fmt_redacted(f)
+    }
+}
+
+impl Redact for [u8] {
+    fn fmt_redacted(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<{} bytes redacted>", self.len())
+    }
+}
+
+impl Redact for Vec<u8> {
+    fn fmt_redacted(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_slice().fmt_redacted(f)
+    }
+}
+
+impl<T: Redact + ?Sized> fmt::Display for Redacted<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt_redacted(f)
+    }
+}
+
+impl<T: Redact + ?Sized> fmt::Debug for Redacted<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt_redacted(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_directories_and_key_material() {
+        let path = Path::new("/var/lib/operator/mpc_certs/mpc_key.der");
+        assert_eq!(Redacted(path).to_string(), ".../mpc_key.der");
+        assert_eq!(format!("{:?}", Redacted(path)), ".../mpc_key.der");
+        assert_eq!(
+            Redacted(Path::new("mpc_key.der")).to_string(),
+            "mpc_key.der"
+        );
+        assert_eq!(Redacted(Path::new("/")).to_string(), "<path>");
+
+        let key = vec![0xAB; 48];
+        assert_eq!(Redacted(&key).to_string(), "<48 bytes redacted>");
+    }
+}
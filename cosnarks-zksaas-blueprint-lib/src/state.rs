@@ -1,14 +1,42 @@
 use crate::error::{Error, Result};
-use crate::types::{CircuitId, CircuitInfo};
-use hex;
+use crate::noir::NoirAbi;
+use crate::redact::Redacted;
+use crate::types::{
+    ArtifactRole, BackendKeys, CachedProof, CircuitExport, CircuitId, CircuitInfo, CircuitStats,
+    CircuitType, ProofFailure, ProofResult, ProvingBackend, PublicKeyBytes, StoredProof,
+    generate_circuit_id,
+};
+use blueprint_sdk::crypto::hashing::blake3_256;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use sled::Db;
+use sled::transaction::{ConflictableTransactionError, TransactionError, Transactional};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
+use std::io::Write;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 const DB_SUBDIR: &str = "circuit_db";
 const ARTIFACTS_SUBDIR: &str = "artifacts";
+const BLOBS_SUBDIR: &str = "blobs";
+const TMP_SUBDIR: &str = "tmp";
 const INFO_TREE_NAME: &[u8] = b"circuit_info";
+const STATS_TREE_NAME: &[u8] = b"circuit_stats";
+const PROOF_CACHE_TREE_NAME: &[u8] = b"proof_cache";
+const PROOF_STORE_TREE_NAME: &[u8] = b"stored_proofs";
+const BLOB_REFS_TREE_NAME: &[u8] = b"blob_refs";
+const FAILURES_TREE_NAME: &[u8] = b"proof_failures";
+/// Key in the default tree holding the schema version (big-endian `u32`)
+const SCHEMA_VERSION_KEY: &[u8] = b"__schema_version";
+/// Version of the record layout written by this build.
+/// Bump whenever a stored type changes its bincode encoding.
+pub const SCHEMA_VERSION: u32 = 5;
+/// Attempts of an info tree operation before a transient sled error is reported
+const SLED_MAX_ATTEMPTS: u32 = 3;
+/// Circuit infos kept in memory unless set otherwise with `with_info_cache_capacity`
+const DEFAULT_INFO_CACHE_CAPACITY: usize = 128;
 
 /// Manages persistent storage for circuit information and artifacts.
 #[derive(Debug, Clone)]
@@ -16,64 +44,489 @@ pub struct CircuitStore {
     db: Db,
     base_path: PathBuf,
     artifacts_path: PathBuf,
+    /// Content-addressed artifacts shared between circuits, named by their BLAKE3 hash
+    blobs_path: PathBuf,
+    tmp_path: PathBuf,
     info_tree: sled::Tree,
+    stats_tree: sled::Tree,
+    /// Cached proofs, keyed by circuit ID followed by the witness hash
+    proof_cache_tree: sled::Tree,
+    /// Proofs returned by pointer, keyed by the hash of their encoding
+    proof_store_tree: sled::Tree,
+    /// Number of circuits referencing each blob, keyed by its hash (big-endian `u64`)
+    blob_refs_tree: sled::Tree,
+    /// Dead-letter records of failed proof jobs, keyed by failure time followed
+    /// by call ID (both big-endian `u64`), so iteration runs oldest first
+    failures_tree: sled::Tree,
+    /// Whether new artifacts are stored as deduplicated blobs instead of per circuit
+    content_addressed: bool,
+    /// Unix modes applied to written keys and artifacts
+    file_modes: FileModes,
+    /// Whether written keys and artifacts are fsync'd before their metadata is stored
+    sync_files: bool,
+    /// Recently read circuit infos, saving the sled read and decoding for hot circuits
+    info_cache: Arc<Mutex<InfoCache>>,
+    /// Held for reading while taking a snapshot and for writing while a circuit's
+    /// info and files are replaced, so snapshots never mix two key generations
+    keys_lock: Arc<RwLock<()>>,
+}
+
+/// Unix permission modes of the files written by a [`CircuitStore`].
+///
+/// Applied to each file before it is renamed into place. Ignored on non-unix targets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileModes {
+    /// Mode of proving and verification keys
+    pub key: u32,
+    /// Mode of circuit artifacts
+    pub artifact: u32,
+}
+
+impl Default for FileModes {
+    fn default() -> Self {
+        Self {
+            key: 0o600,
+            artifact: 0o644,
+        }
+    }
+}
+
+/// A consistent, in-memory view of a circuit's metadata, artifacts and the keys
+/// of one of its backends.
+///
+/// Proofs run against a snapshot so that a concurrent rekey, which replaces the
+/// files on disk, cannot pair a new proving key with an old artifact.
+#[derive(Debug, Clone)]
+pub struct CircuitSnapshot {
+    pub info: CircuitInfo,
+    pub artifacts: BTreeMap<ArtifactRole, Vec<u8>>,
+    /// Backend the keys below belong to
+    pub backend: ProvingBackend,
+    pub proving_key: Vec<u8>,
+    pub verification_key: Vec<u8>,
+}
+
+impl CircuitSnapshot {
+    /// Returns the primary artifact (R1CS / ACIR) of the snapshot.
+    pub fn primary_artifact(&self) -> Result<&[u8]> {
+        let role = ArtifactRole::primary(&self.info.circuit_type);
+        self.artifacts.get(&role).map(Vec::as_slice).ok_or_else(|| {
+            Error::StateError(format!(
+                "Circuit {} has no {:?} artifact",
+                hex::encode(self.info.id),
+                role
+            ))
+        })
+    }
+}
+
+/// Contents of a key file, memory-mapped when the `mmap` feature is enabled.
+///
+/// The store only ever replaces files by renaming a new file over the old one,
+/// so a mapped key keeps the contents it was opened with even if the circuit is
+/// rekeyed or removed; the mapping lives exactly as long as this value.
+#[derive(Debug)]
+pub enum KeyBytes {
+    #[cfg(feature = "mmap")]
+    Mapped(memmap2::Mmap),
+    Buffered(Vec<u8>),
+}
+
+impl std::ops::Deref for KeyBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            #[cfg(feature = "mmap")]
+            KeyBytes::Mapped(map) => map,
+            KeyBytes::Buffered(data) => data,
+        }
+    }
+}
+
+impl AsRef<[u8]> for KeyBytes {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+/// Space reclaimed by [`CircuitStore::compact`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CompactionReport {
+    /// On-disk size of the sled database before and after compaction, in bytes
+    pub db_bytes_before: u64,
+    pub db_bytes_after: u64,
+    /// Artifact directories removed because no registered circuit owns them
+    pub orphaned_dirs_removed: usize,
+    /// Blobs removed because no registered circuit references them
+    pub orphaned_blobs_removed: usize,
+    /// Total size of the removed artifact directories and blobs, in bytes
+    pub artifact_bytes_reclaimed: u64,
+    /// Stats and proof cache entries removed because their circuit is gone
+    pub orphaned_entries_removed: usize,
 }
 
 impl CircuitStore {
     /// Creates or opens a `CircuitStore` rooted at the given base path.
+    /// In-progress downloads use the `tmp` subdirectory of the base path.
     pub fn new(base_path: PathBuf) -> Result<Self> {
+        let tmp_path = base_path.join(TMP_SUBDIR);
+        Self::with_work_dir(base_path, tmp_path)
+    }
+
+    /// Creates or opens a `CircuitStore` rooted at `base_path`, using `work_dir`
+    /// as scratch space for in-progress downloads.
+    pub fn with_work_dir(base_path: PathBuf, work_dir: PathBuf) -> Result<Self> {
         let db_path = base_path.join(DB_SUBDIR);
         let artifacts_path = base_path.join(ARTIFACTS_SUBDIR);
+        let blobs_path = base_path.join(BLOBS_SUBDIR);
+        let tmp_path = work_dir;
 
         fs::create_dir_all(&db_path)?;
         fs::create_dir_all(&artifacts_path)?;
+        fs::create_dir_all(&blobs_path)?;
+        fs::create_dir_all(&tmp_path)?;
 
         let db = sled::open(&db_path)
             .map_err(|e| Error::StateError(format!("Failed to open sled DB: {}", e)))?;
+        check_schema_version(&db)?;
         let info_tree = db
             .open_tree(INFO_TREE_NAME)
             .map_err(|e| Error::StateError(format!("Failed to open sled tree: {}", e)))?;
+        let stats_tree = db
+            .open_tree(STATS_TREE_NAME)
+            .map_err(|e| Error::StateError(format!("Failed to open sled tree: {}", e)))?;
+        let proof_cache_tree = db
+            .open_tree(PROOF_CACHE_TREE_NAME)
+            .map_err(|e| Error::StateError(format!("Failed to open sled tree: {}", e)))?;
+        let proof_store_tree = db
+            .open_tree(PROOF_STORE_TREE_NAME)
+            .map_err(|e| Error::StateError(format!("Failed to open sled tree: {}", e)))?;
+        let blob_refs_tree = db
+            .open_tree(BLOB_REFS_TREE_NAME)
+            .map_err(|e| Error::StateError(format!("Failed to open sled tree: {}", e)))?;
+        let failures_tree = db
+            .open_tree(FAILURES_TREE_NAME)
+            .map_err(|e| Error::StateError(format!("Failed to open sled tree: {}", e)))?;
 
         Ok(Self {
             db,
             base_path,
             artifacts_path,
+            blobs_path,
+            tmp_path,
             info_tree,
+            stats_tree,
+            proof_cache_tree,
+            proof_store_tree,
+            blob_refs_tree,
+            failures_tree,
+            content_addressed: false,
+            file_modes: FileModes::default(),
+            sync_files: true,
+            info_cache: Arc::new(Mutex::new(InfoCache::new(DEFAULT_INFO_CACHE_CAPACITY))),
+            keys_lock: Arc::new(RwLock::new(())),
         })
     }
 
+    /// Sets the permission modes of newly written keys and artifacts.
+    #[must_use]
+    pub fn with_file_modes(mut self, file_modes: FileModes) -> Self {
+        self.file_modes = file_modes;
+        self
+    }
+
+    /// Sets whether newly written keys and artifacts are fsync'd, together with
+    /// their directory, before the circuit's metadata is stored. Disabling it
+    /// speeds up registration at the risk of truncated files after a crash.
+    #[must_use]
+    pub fn with_sync_files(mut self, sync_files: bool) -> Self {
+        self.sync_files = sync_files;
+        self
+    }
+
+    /// Sets how many circuit infos are cached in memory; 0 disables the cache.
+    #[must_use]
+    pub fn with_info_cache_capacity(mut self, capacity: usize) -> Self {
+        self.info_cache = Arc::new(Mutex::new(InfoCache::new(capacity)));
+        self
+    }
+
+    /// Sets whether newly stored artifacts are content-addressed: written once
+    /// under their BLAKE3 hash and shared by every circuit with the same
+    /// contents. Circuits stored before keep reading their own directory.
+    #[must_use]
+    pub fn with_content_addressed(mut self, content_addressed: bool) -> Self {
+        self.content_addressed = content_addressed;
+        self
+    }
+
     /// Returns the base path where artifacts are stored.
+    #[must_use]
     pub fn get_artifacts_base_path(&self) -> &Path {
         &self.artifacts_path
     }
 
+    /// Returns the scratch directory used for in-progress downloads.
+    #[must_use]
+    pub fn get_tmp_path(&self) -> &Path {
+        &self.tmp_path
+    }
+
+    /// Stores a circuit's artifact files, keys and info as one unit.
+    ///
+    /// No snapshot taken concurrently observes a mix of the previous and the new
+    /// files, which makes this the entry point for registration and rekeying.
+    /// `key_data` holds the `(proving key, verification key)` of each entry of
+    /// `info.backends`, in the same order; they are written to the recorded paths.
+    ///
+    /// A content-addressed store writes the artifacts as blobs instead and records
+    /// their hashes in the stored info's `artifact_blobs`. Blobs only referenced by
+    /// the info being replaced are deleted.
+    pub fn store_circuit(
+        &self,
+        info: &CircuitInfo,
+        artifact_files: &[(&Path, &[u8])],
+        key_data: &[(&[u8], &[u8])],
+    ) -> Result<()> {
+        if key_data.len() != info.backends.len() {
+            return Err(Error::Internal(format!(
+                "Got keys for {} backends, but the circuit declares {}",
+                key_data.len(),
+                info.backends.len()
+            )));
+        }
+        let mut key_files = Vec::with_capacity(key_data.len() * 2);
+        for (keys, &(proving_key_data, verification_key_data)) in info.backends.iter().zip(key_data)
+        {
+            key_files.push((keys.proving_key_path.as_path(), proving_key_data));
+            key_files.push((keys.verification_key_path.as_path(), verification_key_data));
+        }
+
+        let circuit_id_hex = hex::encode(info.id);
+        let _guard = self.write_keys()?;
+        self.check_dependencies(&info.id, &info.dependencies)?;
+        let previous = self.get_circuit_info(&circuit_id_hex)?;
+        if self.content_addressed {
+            let mut info = info.clone();
+            info.artifact_blobs = self.write_blobs(&info, artifact_files)?;
+            self.write_circuit_files(&circuit_id_hex, &[], &key_files)?;
+            self.write_info_referencing_blobs(&circuit_id_hex, &info)?;
+        } else {
+            self.write_circuit_files(&circuit_id_hex, artifact_files, &key_files)?;
+            self.write_circuit_info(&circuit_id_hex, info)?;
+        }
+        match previous {
+            Some(previous) => self.release_blobs(previous.artifact_blobs.values()),
+            None => Ok(()),
+        }
+    }
+
+    /// Writes each artifact as a blob named by its hash, unless already present,
+    /// and returns the hashes by role.
+    fn write_blobs(
+        &self,
+        info: &CircuitInfo,
+        artifact_files: &[(&Path, &[u8])],
+    ) -> Result<BTreeMap<ArtifactRole, [u8; 32]>> {
+        let mut blobs = BTreeMap::new();
+        for (rel_path, data) in artifact_files {
+            let role = info
+                .artifact_paths
+                .iter()
+                .find(|(_, path)| path.as_path() == *rel_path)
+                .map(|(role, _)| *role)
+                .ok_or_else(|| {
+                    Error::Internal(format!(
+                        "Artifact {} is not part of the circuit's bundle",
+                        rel_path.display()
+                    ))
+                })?;
+            let hash = blake3_256(data);
+            let path = self.blob_path(&hash);
+            if !path.exists() {
+                write_atomic(&path, data, self.file_modes.artifact, self.sync_files)?;
+            }
+            blobs.insert(role, hash);
+        }
+        if self.sync_files && !blobs.is_empty() {
+            sync_dir(&self.blobs_path)?;
+        }
+        Ok(blobs)
+    }
+
+    fn blob_path(&self, hash: &[u8; 32]) -> PathBuf {
+        self.blobs_path.join(hex::encode(hash))
+    }
+
+    /// Returns how many circuits reference the blob with the given hash.
+    pub fn blob_ref_count(&self, hash: &[u8; 32]) -> Result<u64> {
+        let count = retry_transient(|| self.blob_refs_tree.get(hash))
+            .map_err(|e| Error::StateError(format!("Sled get failed: {}", e)))?;
+        match count {
+            Some(bytes) => {
+                let bytes: [u8; 8] = bytes.as_ref().try_into().map_err(|_| {
+                    Error::StateError(format!(
+                        "Corrupt blob reference count ({} bytes)",
+                        bytes.len()
+                    ))
+                })?;
+                Ok(u64::from_be_bytes(bytes))
+            }
+            None => Ok(0),
+        }
+    }
+
+    /// Stores `info` after adding a reference to each of its blobs, dropping
+    /// the references again if the info cannot be stored. Blobs are referenced
+    /// first, so a crash in between leaks a blob at worst. Callers hold the
+    /// keys lock for writing.
+    fn write_info_referencing_blobs(&self, circuit_id_hex: &str, info: &CircuitInfo) -> Result<()> {
+        self.acquire_blobs(info.artifact_blobs.values())?;
+        if let Err(e) = self.write_circuit_info(circuit_id_hex, info) {
+            if let Err(release_err) = self.release_blobs(info.artifact_blobs.values()) {
+                tracing::warn!(%circuit_id_hex, error = %release_err, "Failed to drop blob references of an unstored circuit");
+            }
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Adds a reference to each blob. Callers hold the keys lock for writing.
+    fn acquire_blobs<'a>(&self, hashes: impl IntoIterator<Item = &'a [u8; 32]>) -> Result<()> {
+        for hash in hashes {
+            let count = self.blob_ref_count(hash)? + 1;
+            retry_transient(|| self.blob_refs_tree.insert(hash, &count.to_be_bytes()))
+                .map_err(|e| Error::StateError(format!("Sled insert failed: {}", e)))?;
+        }
+        Ok(())
+    }
+
+    /// Drops a reference to each blob, deleting blobs no circuit references
+    /// anymore. Callers hold the keys lock for writing.
+    fn release_blobs<'a>(&self, hashes: impl IntoIterator<Item = &'a [u8; 32]>) -> Result<()> {
+        for hash in hashes {
+            let count = self.blob_ref_count(hash)?.saturating_sub(1);
+            if count > 0 {
+                retry_transient(|| self.blob_refs_tree.insert(hash, &count.to_be_bytes()))
+                    .map_err(|e| Error::StateError(format!("Sled insert failed: {}", e)))?;
+                continue;
+            }
+            retry_transient(|| self.blob_refs_tree.remove(hash))
+                .map_err(|e| Error::StateError(format!("Sled remove failed: {}", e)))?;
+            let path = self.blob_path(hash);
+            if path.exists() {
+                fs::remove_file(&path)?;
+            }
+        }
+        Ok(())
+    }
+
     /// Stores circuit artifact files in a dedicated directory.
+    /// `artifact_files` pairs each bundle member's relative path with its contents.
+    /// Each file is written to a temp file in the circuit directory and renamed into
+    /// place, so readers never observe a partially written artifact. Keys and
+    /// artifacts receive the store's [`FileModes`].
     pub fn store_circuit_artifacts(
         &self,
         circuit_id_hex: &str,
-        artifact_filename: &str,
-        artifact_data: &[u8],
+        artifact_files: &[(&Path, &[u8])],
         pk_filename: &str,
         proving_key_data: &[u8],
         vk_filename: &str,
         verification_key_data: &[u8],
+    ) -> Result<()> {
+        let key_files = [
+            (Path::new(pk_filename), proving_key_data),
+            (Path::new(vk_filename), verification_key_data),
+        ];
+        let _guard = self.write_keys()?;
+        self.write_circuit_files(circuit_id_hex, artifact_files, &key_files)
+    }
+
+    /// Writes each `(relative path, contents)` pair under the circuit's directory.
+    fn write_circuit_files(
+        &self,
+        circuit_id_hex: &str,
+        artifact_files: &[(&Path, &[u8])],
+        key_files: &[(&Path, &[u8])],
     ) -> Result<()> {
         let circuit_artifact_dir = self.artifacts_path.join(circuit_id_hex);
         fs::create_dir_all(&circuit_artifact_dir)?;
 
-        fs::write(circuit_artifact_dir.join(artifact_filename), artifact_data)?;
-        fs::write(circuit_artifact_dir.join(pk_filename), proving_key_data)?;
-        fs::write(
-            circuit_artifact_dir.join(vk_filename),
-            verification_key_data,
-        )?;
+        let artifacts = artifact_files
+            .iter()
+            .map(|file| (file, self.file_modes.artifact));
+        let keys = key_files.iter().map(|file| (file, self.file_modes.key));
+        for ((rel_path, data), mode) in artifacts.chain(keys) {
+            let full_path = circuit_artifact_dir.join(rel_path);
+            // Per-backend keys live in subdirectories of the circuit directory
+            if let Some(parent) = full_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            write_atomic(&full_path, data, mode, self.sync_files)?;
+        }
+        if self.sync_files {
+            // Persist the entries of newly created (sub)directories too
+            sync_dir(&circuit_artifact_dir)?;
+            sync_dir(&self.artifacts_path)?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks that every circuit in `dependencies` is registered.
+    ///
+    /// Fails with `Error::InvalidInput` naming the first missing dependency, or
+    /// if the circuit `id` lists itself.
+    pub fn validate_dependencies(&self, id: &CircuitId, dependencies: &[CircuitId]) -> Result<()> {
+        let _guard = self.read_keys()?;
+        self.check_dependencies(id, dependencies)
+    }
 
+    fn check_dependencies(&self, id: &CircuitId, dependencies: &[CircuitId]) -> Result<()> {
+        for dependency in dependencies {
+            let dependency_hex = hex::encode(dependency);
+            if dependency == id {
+                return Err(Error::InvalidInput(format!(
+                    "Circuit {} cannot depend on itself",
+                    dependency_hex
+                )));
+            }
+            if !retry_transient(|| self.info_tree.contains_key(dependency_hex.as_bytes()))
+                .map_err(|e| Error::StateError(format!("Failed to read from sled tree: {}", e)))?
+            {
+                return Err(Error::InvalidInput(format!(
+                    "Dependency circuit {} is not registered",
+                    dependency_hex
+                )));
+            }
+        }
         Ok(())
     }
 
+    /// Returns the IDs of the registered circuits that directly depend on `id`.
+    pub fn dependents(&self, id: &CircuitId) -> Result<Vec<CircuitId>> {
+        let mut dependents = Vec::new();
+        for entry in self.info_tree.iter() {
+            let (key, info_bytes) =
+                entry.map_err(|e| Error::StateError(format!("Sled iteration failed: {}", e)))?;
+            let info: CircuitInfo = bincode::deserialize(&info_bytes)?;
+            if info.dependencies.contains(id) {
+                dependents.push(decode_circuit_id_key(&key)?);
+            }
+        }
+        Ok(dependents)
+    }
+
     /// Stores circuit information (metadata) in the database.
     /// Uses the hex representation of the CircuitId as the key.
     pub fn store_circuit_info(&self, circuit_id_hex: &str, info: &CircuitInfo) -> Result<()> {
+        let _guard = self.write_keys()?;
+        self.write_circuit_info(circuit_id_hex, info)
+    }
+
+    fn write_circuit_info(&self, circuit_id_hex: &str, info: &CircuitInfo) -> Result<()> {
         // Ensure the ID in the info matches the key being used
         if hex::encode(info.id) != circuit_id_hex {
             return Err(Error::Internal(
@@ -82,9 +535,12 @@ impl CircuitStore {
         }
 
         let info_bytes = bincode::serialize(info)?;
-        self.info_tree
-            .insert(circuit_id_hex.as_bytes(), info_bytes)
-            .map_err(|e| Error::StateError(format!("Failed to insert into sled tree: {}", e)))?;
+        retry_transient(|| {
+            self.info_tree
+                .insert(circuit_id_hex.as_bytes(), info_bytes.as_slice())
+        })
+        .map_err(|e| Error::StateError(format!("Failed to insert into sled tree: {}", e)))?;
+        self.invalidate_cached_info(&info.id);
 
         self.db
             .flush()
@@ -92,87 +548,725 @@ impl CircuitStore {
         Ok(())
     }
 
+    fn lock_info_cache(&self) -> std::sync::MutexGuard<'_, InfoCache> {
+        // The cache holds no invariants a panicking holder could break
+        self.info_cache
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Drops a circuit's cached info; called after every change to its record.
+    fn invalidate_cached_info(&self, id: &CircuitId) {
+        self.lock_info_cache().invalidate(id);
+    }
+
     /// Retrieves circuit information by its ID (hex representation) from Sled.
+    ///
+    /// Served from the in-memory cache when the circuit was read recently.
+    /// IDs are matched case-insensitively, as records are keyed by lowercase hex.
     pub fn get_circuit_info(&self, id_hex: &str) -> Result<Option<CircuitInfo>> {
-        let info_bytes_opt = self
-            .info_tree
-            .get(id_hex.as_bytes())
+        let mut id = [0u8; 32];
+        let cache_key = hex::decode_to_slice(id_hex, &mut id).ok().map(|()| id);
+        // The cache and the tree must agree on which spellings of an ID they find
+        let id_hex = match &cache_key {
+            Some(id) => hex::encode(id),
+            None => id_hex.to_string(),
+        };
+        let generation = match &cache_key {
+            Some(id) => {
+                let mut cache = self.lock_info_cache();
+                if let Some(info) = cache.get(id) {
+                    return Ok(Some(info));
+                }
+                cache.generation
+            }
+            None => 0,
+        };
+
+        let info_bytes_opt = retry_transient(|| self.info_tree.get(id_hex.as_bytes()))
             .map_err(|e| Error::StateError(format!("Failed to read from sled tree: {}", e)))?;
 
         match info_bytes_opt {
             Some(info_bytes) => {
                 let info: CircuitInfo = bincode::deserialize(&info_bytes)?;
-                // Optional: Verify info.id matches id_hex if paranoid
+                if let Some(id) = cache_key {
+                    self.lock_info_cache().insert(generation, id, &info);
+                }
                 Ok(Some(info))
             }
             None => Ok(None),
         }
     }
 
-    /// Retrieves the artifact data for a given circuit.
+    /// Loads the info, artifacts and the keys for `backend` (the circuit's
+    /// default backend if `None`) into a consistent snapshot.
+    ///
+    /// Returns `Ok(None)` if the circuit is not registered,
+    /// `Error::IncompatibleBackend` if it has no keys for `backend` and
+    /// `Error::MissingProvingKey` if the proving key is gone from disk.
+    pub fn snapshot(
+        &self,
+        id_hex: &str,
+        backend: Option<&ProvingBackend>,
+    ) -> Result<Option<CircuitSnapshot>> {
+        let _guard = self.read_keys()?;
+        let Some(info) = self.get_circuit_info(id_hex)? else {
+            return Ok(None);
+        };
+        let keys = info.backend_keys(backend)?.clone();
+        if !self.proving_key_exists(&info, &keys) {
+            return Err(Error::MissingProvingKey {
+                circuit_id: id_hex.to_string(),
+            });
+        }
+
+        let artifacts = info
+            .artifact_paths
+            .keys()
+            .map(|role| Ok((*role, self.get_artifact_member_data(&info, *role)?)))
+            .collect::<Result<BTreeMap<_, _>>>()?;
+        let proving_key = self.get_proving_key_data(&info, &keys)?;
+        let verification_key = self.get_verification_key_data(&info, &keys)?;
+        Ok(Some(CircuitSnapshot {
+            info,
+            artifacts,
+            backend: keys.backend,
+            proving_key,
+            verification_key,
+        }))
+    }
+
+    /// Reads a circuit's artifacts and verification keys for export.
+    ///
+    /// Proving keys are left out. Returns `Ok(None)` if the circuit is not registered.
+    pub fn export_circuit(&self, id: &CircuitId) -> Result<Option<CircuitExport>> {
+        let _guard = self.read_keys()?;
+        let Some(info) = self.get_circuit_info(&hex::encode(id))? else {
+            return Ok(None);
+        };
+        let artifacts = info
+            .artifact_paths
+            .keys()
+            .map(|role| Ok((*role, self.get_artifact_member_data(&info, *role)?)))
+            .collect::<Result<BTreeMap<_, _>>>()?;
+        let verification_keys = info
+            .backends
+            .iter()
+            .map(|keys| {
+                Ok((
+                    keys.backend.clone(),
+                    self.get_verification_key_data(&info, keys)?,
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Some(CircuitExport {
+            circuit_id: info.id,
+            name: info.name,
+            circuit_type: info.circuit_type,
+            artifacts,
+            verification_keys,
+            num_public_inputs: info.num_public_inputs,
+            noir_abi: info.noir_abi,
+            updated_at: info.updated_at,
+        }))
+    }
+
+    fn read_keys(&self) -> Result<RwLockReadGuard<'_, ()>> {
+        self.keys_lock
+            .read()
+            .map_err(|_| Error::Internal("Circuit keys lock poisoned".to_string()))
+    }
+
+    fn write_keys(&self) -> Result<RwLockWriteGuard<'_, ()>> {
+        self.keys_lock
+            .write()
+            .map_err(|_| Error::Internal("Circuit keys lock poisoned".to_string()))
+    }
+
+    /// Retrieves the primary artifact data (R1CS / ACIR) for a given circuit.
     pub fn get_artifact_data(&self, info: &CircuitInfo) -> Result<Vec<u8>> {
+        self.get_artifact_member_data(info, ArtifactRole::primary(&info.circuit_type))
+    }
+
+    /// Retrieves the data of the bundle member with the given role.
+    pub fn get_artifact_member_data(
+        &self,
+        info: &CircuitInfo,
+        role: ArtifactRole,
+    ) -> Result<Vec<u8>> {
+        if let Some(hash) = info.artifact_blobs.get(&role) {
+            return fs::read(self.blob_path(hash)).map_err(Error::IoError);
+        }
+        let rel_path = info.artifact_paths.get(&role).ok_or_else(|| {
+            Error::StateError(format!(
+                "Circuit {} has no {:?} artifact",
+                hex::encode(info.id),
+                role
+            ))
+        })?;
         let full_path = self
             .artifacts_path
             .join(hex::encode(info.id))
-            .join(&info.artifact_path);
+            .join(rel_path);
         fs::read(&full_path).map_err(Error::IoError)
     }
 
-    /// Retrieves the proving key data.
-    pub fn get_proving_key_data(&self, info: &CircuitInfo) -> Result<Vec<u8>> {
+    /// Retrieves the proving key data for one of the circuit's backends.
+    pub fn get_proving_key_data(&self, info: &CircuitInfo, keys: &BackendKeys) -> Result<Vec<u8>> {
         let full_path = self
             .artifacts_path
             .join(hex::encode(info.id))
-            .join(&info.proving_key_path);
+            .join(&keys.proving_key_path);
         fs::read(&full_path).map_err(Error::IoError)
     }
 
-    /// Retrieves the verification key data.
-    pub fn get_verification_key_data(&self, info: &CircuitInfo) -> Result<Vec<u8>> {
+    /// Returns whether the proving key file for one of the circuit's backends is present on disk.
+    #[must_use]
+    pub fn proving_key_exists(&self, info: &CircuitInfo, keys: &BackendKeys) -> bool {
+        self.artifacts_path
+            .join(hex::encode(info.id))
+            .join(&keys.proving_key_path)
+            .is_file()
+    }
+
+    /// Retrieves the verification key data for one of the circuit's backends.
+    pub fn get_verification_key_data(
+        &self,
+        info: &CircuitInfo,
+        keys: &BackendKeys,
+    ) -> Result<Vec<u8>> {
         let full_path = self
             .artifacts_path
             .join(hex::encode(info.id))
-            .join(&info.verification_key_path);
+            .join(&keys.verification_key_path);
         fs::read(&full_path).map_err(Error::IoError)
     }
 
-    // Optional: Add methods for listing circuits (iterating over the tree), removing circuits, etc.
+    /// Opens the verification key of one of the circuit's backends without
+    /// copying it, for callers that read the same key repeatedly.
+    ///
+    /// With the `mmap` feature the file is memory-mapped; if mapping fails (or
+    /// without the feature) it is read into a buffer instead.
+    pub fn read_verification_key(
+        &self,
+        info: &CircuitInfo,
+        keys: &BackendKeys,
+    ) -> Result<KeyBytes> {
+        let full_path = self
+            .artifacts_path
+            .join(hex::encode(info.id))
+            .join(&keys.verification_key_path);
+        #[cfg(feature = "mmap")]
+        {
+            let file = fs::File::open(&full_path)?;
+            // SAFETY: key files are never modified in place (see `write_atomic`),
+            // so the mapped contents cannot change while the map is alive.
+            match unsafe { memmap2::Mmap::map(&file) } {
+                Ok(map) => return Ok(KeyBytes::Mapped(map)),
+                Err(e) => {
+                    tracing::debug!(path = %Redacted(&full_path), error = %e, "Falling back to buffered key read");
+                }
+            }
+        }
+        Ok(KeyBytes::Buffered(fs::read(&full_path)?))
+    }
+
+    /// Lists the IDs of all registered circuits.
+    ///
+    /// Keys are stored as the lowercase hex encoding of the `CircuitId`; each one
+    /// is decoded back into the raw 32-byte ID. Keys that are not valid UTF-8 or
+    /// not exactly 64 lowercase hex characters yield a `StateError`.
     pub fn list_circuit_ids(&self) -> impl Iterator<Item = Result<CircuitId>> + '_ {
         self.info_tree.iter().keys().map(|key_result| {
             key_result
                 .map_err(|e| Error::StateError(format!("Sled key iteration failed: {}", e)))
-                .and_then(|key_bytes| {
-                    // Decode the hex string retrieved from the DB
-                    let hex_key = String::from_utf8(key_bytes.to_vec())
-                        .map_err(|e| Error::StateError(format!("Invalid UTF8 key in DB: {}", e)))?;
-                    let bytes = hex::decode(&hex_key).map_err(|e| {
-                        Error::StateError(format!("Invalid hex key in DB '{}': {}", hex_key, e))
-                    })?;
-                    // Try to convert Vec<u8> to [u8; 32]
-                    bytes.try_into().map_err(|_| {
-                        Error::StateError(format!(
-                            "Invalid key length in DB, expected 32 bytes, found {}",
-                            hex_key.len() / 2 // Approximation for error message
-                        ))
-                    })
-                })
+                .and_then(|key_bytes| decode_circuit_id_key(&key_bytes))
         })
     }
 
-    pub fn remove_circuit(&self, id: &CircuitId) -> Result<Option<CircuitInfo>> {
-        match self
-            .info_tree
-            .remove(hex::encode(id).as_bytes())
-            .map_err(|e| Error::StateError(format!("Sled remove failed: {}", e)))?
-        {
+    /// Audits every stored record, re-deriving its ID with `generate_circuit_id`
+    /// from the record's name, type and backends.
+    ///
+    /// Returns the IDs, as stored under their keys, of records whose ID differs
+    /// from its derivation or from the key it is stored under: signs of
+    /// tampering with the database or of a change to how IDs are derived.
+    pub fn verify_ids(&self) -> Result<Vec<CircuitId>> {
+        let _guard = self.read_keys()?;
+        let mut inconsistent = Vec::new();
+        for entry in self.info_tree.iter() {
+            let (key, info_bytes) =
+                entry.map_err(|e| Error::StateError(format!("Sled iteration failed: {}", e)))?;
+            let id = decode_circuit_id_key(&key)?;
+            let info: CircuitInfo = bincode::deserialize(&info_bytes)?;
+            let backends: Vec<ProvingBackend> = info
+                .backends
+                .iter()
+                .map(|keys| keys.backend.clone())
+                .collect();
+            let derived = generate_circuit_id(&info.name, &info.circuit_type, &backends);
+            if info.id != derived || info.id != id {
+                tracing::warn!(
+                    circuit_id = %hex::encode(id),
+                    stored = %hex::encode(info.id),
+                    derived = %hex::encode(derived),
+                    "Circuit record does not match its derived ID"
+                );
+                inconsistent.push(id);
+            }
+        }
+        Ok(inconsistent)
+    }
+
+    /// Records a successful proof for a circuit, updating its statistics atomically.
+    pub fn record_proof(&self, id: &CircuitId, proving_time: Duration) -> Result<CircuitStats> {
+        let now = unix_now();
+        let proving_ms = proving_time.as_millis() as u64;
+        let mut update_error = None;
+
+        let updated = self
+            .stats_tree
+            .update_and_fetch(hex::encode(id).as_bytes(), |old| {
+                let mut stats = match old.map(bincode::deserialize::<CircuitStats>) {
+                    Some(Ok(stats)) => stats,
+                    Some(Err(e)) => {
+                        // Leave the unreadable entry as it is
+                        update_error = Some(e);
+                        return old.map(<[u8]>::to_vec);
+                    }
+                    None => CircuitStats::default(),
+                };
+                stats.proof_count += 1;
+                stats.last_proven_at = now;
+                stats.total_proving_ms = stats.total_proving_ms.saturating_add(proving_ms);
+                bincode::serialize(&stats).ok()
+            })
+            .map_err(|e| Error::StateError(format!("Failed to update circuit stats: {}", e)))?;
+
+        if let Some(e) = update_error {
+            return Err(Error::BincodeError(e));
+        }
+        let stats_bytes = updated
+            .ok_or_else(|| Error::StateError("Circuit stats vanished during update".to_string()))?;
+        Ok(bincode::deserialize(&stats_bytes)?)
+    }
+
+    /// Retrieves the proving statistics of a circuit, if it has been proven before.
+    pub fn get_stats(&self, id: &CircuitId) -> Result<Option<CircuitStats>> {
+        self.stats_tree
+            .get(hex::encode(id).as_bytes())
+            .map_err(|e| Error::StateError(format!("Failed to read circuit stats: {}", e)))?
+            .map(|stats_bytes| bincode::deserialize(&stats_bytes).map_err(Error::BincodeError))
+            .transpose()
+    }
+
+    /// Caches a proof generated for `info` from the witness with hash `witness_hash`.
+    pub fn cache_proof(
+        &self,
+        info: &CircuitInfo,
+        witness_hash: &[u8; 32],
+        proof: &ProofResult,
+    ) -> Result<()> {
+        let entry = CachedProof {
+            proof: proof.clone(),
+            cached_at: unix_now(),
+            circuit_updated_at: info.updated_at,
+        };
+        self.proof_cache_tree
+            .insert(
+                proof_cache_key(&info.id, witness_hash),
+                bincode::serialize(&entry)?,
+            )
+            .map_err(|e| Error::StateError(format!("Failed to cache proof: {}", e)))?;
+        Ok(())
+    }
+
+    /// Returns the cached proof of `info` for the witness with hash `witness_hash`.
+    ///
+    /// Entries older than `ttl`, or generated before the circuit was last rekeyed,
+    /// are removed and reported as a miss.
+    pub fn get_cached_proof(
+        &self,
+        info: &CircuitInfo,
+        witness_hash: &[u8; 32],
+        ttl: Duration,
+    ) -> Result<Option<ProofResult>> {
+        let key = proof_cache_key(&info.id, witness_hash);
+        let Some(entry_bytes) = self
+            .proof_cache_tree
+            .get(key)
+            .map_err(|e| Error::StateError(format!("Failed to read proof cache: {}", e)))?
+        else {
+            return Ok(None);
+        };
+
+        let entry: CachedProof = bincode::deserialize(&entry_bytes)?;
+        if entry.circuit_updated_at != info.updated_at || is_expired(&entry, unix_now(), ttl) {
+            self.proof_cache_tree
+                .remove(key)
+                .map_err(|e| Error::StateError(format!("Sled remove failed: {}", e)))?;
+            return Ok(None);
+        }
+        Ok(Some(entry))
+    }
+
+    /// Evicts expired and stale cached proofs, then the oldest entries beyond `max_entries`.
+    /// Returns the number of evicted entries.
+    pub fn sweep_proof_cache(&self, ttl: Duration, max_entries: usize) -> Result<usize> {
+        let now = unix_now();
+        let mut updated_at: HashMap<CircuitId, Option<u64>> = HashMap::new();
+        let mut retained = Vec::new();
+        let mut evicted = 0;
+
+        for item in self.proof_cache_tree.iter() {
+            let (key, entry_bytes) =
+                item.map_err(|e| Error::StateError(format!("Sled iteration failed: {}", e)))?;
+            let entry = bincode::deserialize::<CachedProof>(&entry_bytes).ok();
+            let circuit_updated_at = match <[u8; 32]>::try_from(&key[..key.len().min(32)]) {
+                Ok(circuit_id) => match updated_at.get(&circuit_id) {
+                    Some(cached) => *cached,
+                    None => {
+                        let current = self
+                            .get_circuit_info(&hex::encode(circuit_id))?
+                            .map(|info| info.updated_at);
+                        updated_at.insert(circuit_id, current);
+                        current
+                    }
+                },
+                Err(_) => None,
+            };
+
+            match entry {
+                Some(entry)
+                    if Some(entry.circuit_updated_at) == circuit_updated_at
+                        && !is_expired(&entry, now, ttl) =>
+                {
+                    retained.push((entry.cached_at, key));
+                }
+                // Corrupt, expired, or belonging to a rekeyed or removed circuit
+                _ => {
+                    self.proof_cache_tree
+                        .remove(&key)
+                        .map_err(|e| Error::StateError(format!("Sled remove failed: {}", e)))?;
+                    evicted += 1;
+                }
+            }
+        }
+
+        if retained.len() > max_entries {
+            retained.sort_by_key(|(cached_at, _)| *cached_at);
+            let excess = retained.len() - max_entries;
+            for (_, key) in retained.into_iter().take(excess) {
+                self.proof_cache_tree
+                    .remove(&key)
+                    .map_err(|e| Error::StateError(format!("Sled remove failed: {}", e)))?;
+                evicted += 1;
+            }
+        }
+        Ok(evicted)
+    }
+
+    /// Stores `proof`, generated for `circuit_id`, under the SHA-256 of its
+    /// bincode encoding and returns the hash.
+    ///
+    /// Storing the same proof twice keeps a single entry.
+    pub fn store_proof(&self, circuit_id: &CircuitId, proof: &ProofResult) -> Result<[u8; 32]> {
+        let proof_hash = proof_hash(proof)?;
+        let entry = StoredProof {
+            circuit_id: *circuit_id,
+            proof: proof.clone(),
+            stored_at: unix_now(),
+        };
+        self.proof_store_tree
+            .insert(proof_hash, bincode::serialize(&entry)?)
+            .map_err(|e| Error::StateError(format!("Failed to store proof: {}", e)))?;
+        Ok(proof_hash)
+    }
+
+    /// Returns the stored proof with hash `proof_hash`.
+    ///
+    /// Entries stored more than `ttl` ago are removed and reported as missing.
+    pub fn get_stored_proof(
+        &self,
+        proof_hash: &[u8; 32],
+        ttl: Duration,
+    ) -> Result<Option<StoredProof>> {
+        let Some(entry_bytes) = self
+            .proof_store_tree
+            .get(proof_hash)
+            .map_err(|e| Error::StateError(format!("Failed to read stored proof: {}", e)))?
+        else {
+            return Ok(None);
+        };
+
+        let entry: StoredProof = bincode::deserialize(&entry_bytes)?;
+        if unix_now().saturating_sub(entry.stored_at) >= ttl.as_secs() {
+            self.proof_store_tree
+                .remove(proof_hash)
+                .map_err(|e| Error::StateError(format!("Sled remove failed: {}", e)))?;
+            return Ok(None);
+        }
+        Ok(Some(entry.proof))
+    }
+
+    /// Removes stored proofs older than `ttl`. Returns the number of removed entries.
+    pub fn sweep_stored_proofs(&self, ttl: Duration) -> Result<usize> {
+        let now = unix_now();
+        let mut removed = 0;
+        for item in self.proof_store_tree.iter() {
+            let (key, entry_bytes) =
+                item.map_err(|e| Error::StateError(format!("Sled iteration failed: {}", e)))?;
+            let expired = match bincode::deserialize::<StoredProof>(&entry_bytes) {
+                Ok(entry) => now.saturating_sub(entry.stored_at) >= ttl.as_secs(),
+                // Corrupt entries can never be served
+                Err(_) => true,
+            };
+            if expired {
+                self.proof_store_tree
+                    .remove(&key)
+                    .map_err(|e| Error::StateError(format!("Sled remove failed: {}", e)))?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Records a failed proof job in the dead-letter store, keeping at most
+    /// `max_entries` records; the oldest are evicted first.
+    pub fn record_failure(&self, failure: &ProofFailure, max_entries: usize) -> Result<()> {
+        if max_entries == 0 {
+            return Ok(());
+        }
+        let mut key = [0u8; 16];
+        key[..8].copy_from_slice(&failure.failed_at.to_be_bytes());
+        key[8..].copy_from_slice(&failure.call_id.to_be_bytes());
+        self.failures_tree
+            .insert(key, bincode::serialize(failure)?)
+            .map_err(|e| Error::StateError(format!("Failed to record proof failure: {}", e)))?;
+        while self.failures_tree.len() > max_entries {
+            self.failures_tree
+                .pop_min()
+                .map_err(|e| Error::StateError(format!("Sled remove failed: {}", e)))?;
+        }
+        Ok(())
+    }
+
+    /// Returns the recorded proof failures, oldest first.
+    pub fn list_failures(&self) -> Result<Vec<ProofFailure>> {
+        self.failures_tree
+            .iter()
+            .values()
+            .map(|value| {
+                let bytes = value
+                    .map_err(|e| Error::StateError(format!("Sled iteration failed: {}", e)))?;
+                Ok(bincode::deserialize(&bytes)?)
+            })
+            .collect()
+    }
+
+    /// Removes every cached proof. Returns the number of removed entries.
+    pub fn clear_proof_cache(&self) -> Result<usize> {
+        let count = self.proof_cache_tree.len();
+        self.proof_cache_tree
+            .clear()
+            .map_err(|e| Error::StateError(format!("Failed to clear proof cache: {}", e)))?;
+        Ok(count)
+    }
+
+    /// Copies a registered circuit's artifacts and keys under `new_name`.
+    ///
+    /// The copy gets the ID registration would derive for `new_name` with the
+    /// source's circuit type and backends, a fresh `updated_at` and no stats.
+    /// Files are copied before the info is stored, so an interrupted clone only
+    /// leaves an orphaned directory behind for `compact` to reclaim.
+    pub fn clone_circuit(&self, src_id: &CircuitId, new_name: &str) -> Result<CircuitId> {
+        let _guard = self.write_keys()?;
+        let src_id_hex = hex::encode(src_id);
+        let src_info = self
+            .get_circuit_info(&src_id_hex)?
+            .ok_or_else(|| Error::InvalidInput(format!("Circuit ID not found: {}", src_id_hex)))?;
+
+        let backends: Vec<ProvingBackend> = src_info
+            .backends
+            .iter()
+            .map(|keys| keys.backend.clone())
+            .collect();
+        let new_id = generate_circuit_id(new_name, &src_info.circuit_type, &backends);
+        let new_id_hex = hex::encode(new_id);
+        if self.get_circuit_info(&new_id_hex)?.is_some() {
+            return Err(Error::InvalidInput(format!(
+                "Circuit {} is already registered",
+                new_id_hex
+            )));
+        }
+
+        let new_dir = self.artifacts_path.join(&new_id_hex);
+        if new_dir.exists() {
+            fs::remove_dir_all(&new_dir)?;
+        }
+        copy_dir_all(&self.artifacts_path.join(&src_id_hex), &new_dir)?;
+
+        let info = CircuitInfo {
+            id: new_id,
+            name: new_name.to_string(),
+            updated_at: unix_now(),
+            ..src_info
+        };
+        self.write_info_referencing_blobs(&new_id_hex, &info)?;
+        Ok(new_id)
+    }
+
+    /// Reclaims space left behind by rekeys and removals.
+    ///
+    /// Deletes artifact directories, blobs, stats and cached proofs that no registered
+    /// circuit owns (e.g. after a crash between writing files and storing info),
+    /// then flushes the database so sled's segment cleaner can reuse the space
+    /// of rewritten pages. sled offers no explicit compaction call, so the
+    /// database file may shrink less than the freed data.
+    pub fn compact(&self) -> Result<CompactionReport> {
+        let mut report = CompactionReport {
+            db_bytes_before: self.size_on_disk()?,
+            ..CompactionReport::default()
+        };
+
+        let _guard = self.write_keys()?;
+        let registered: HashSet<String> = self
+            .list_circuit_ids()
+            .map(|id| id.map(hex::encode))
+            .collect::<Result<_>>()?;
+
+        for entry in fs::read_dir(&self.artifacts_path)? {
+            let entry = entry?;
+            let name = entry.file_name();
+            if registered.contains(name.to_string_lossy().as_ref()) {
+                continue;
+            }
+            let size = dir_size(&entry.path())?;
+            if entry.file_type()?.is_dir() {
+                fs::remove_dir_all(entry.path())?;
+            } else {
+                fs::remove_file(entry.path())?;
+            }
+            report.orphaned_dirs_removed += 1;
+            report.artifact_bytes_reclaimed += size;
+        }
+
+        for entry in fs::read_dir(&self.blobs_path)? {
+            let entry = entry?;
+            let referenced = hex::decode(entry.file_name().to_string_lossy().as_bytes())
+                .ok()
+                .and_then(|hash| <[u8; 32]>::try_from(hash).ok())
+                .map(|hash| self.blob_ref_count(&hash))
+                .transpose()?
+                .is_some_and(|count| count > 0);
+            if referenced {
+                continue;
+            }
+            let size = entry.metadata()?.len();
+            fs::remove_file(entry.path())?;
+            report.orphaned_blobs_removed += 1;
+            report.artifact_bytes_reclaimed += size;
+        }
+
+        for key in self.stats_tree.iter().keys() {
+            let key =
+                key.map_err(|e| Error::StateError(format!("Sled iteration failed: {}", e)))?;
+            if !registered.contains(String::from_utf8_lossy(&key).as_ref()) {
+                self.stats_tree
+                    .remove(key)
+                    .map_err(|e| Error::StateError(format!("Sled remove failed: {}", e)))?;
+                report.orphaned_entries_removed += 1;
+            }
+        }
+        for key in self.proof_cache_tree.iter().keys() {
+            let key =
+                key.map_err(|e| Error::StateError(format!("Sled iteration failed: {}", e)))?;
+            let owner = key.get(..32).map(hex::encode).unwrap_or_default();
+            if !registered.contains(&owner) {
+                self.proof_cache_tree
+                    .remove(key)
+                    .map_err(|e| Error::StateError(format!("Sled remove failed: {}", e)))?;
+                report.orphaned_entries_removed += 1;
+            }
+        }
+
+        self.db
+            .flush()
+            .map_err(|e| Error::StateError(format!("Failed to flush sled DB: {}", e)))?;
+        report.db_bytes_after = self.size_on_disk()?;
+        Ok(report)
+    }
+
+    /// Checks that the database is still readable, for readiness probes.
+    pub fn check_health(&self) -> Result<()> {
+        self.db
+            .get(SCHEMA_VERSION_KEY)
+            .map(|_| ())
+            .map_err(|e| Error::StateError(format!("Sled DB is not readable: {}", e)))
+    }
+
+    fn size_on_disk(&self) -> Result<u64> {
+        self.db
+            .size_on_disk()
+            .map_err(|e| Error::StateError(format!("Failed to read sled DB size: {}", e)))
+    }
+
+    /// Removes a circuit together with its files, stats and cached proofs.
+    ///
+    /// Circuits that other circuits depend on are only removed with `cascade`,
+    /// which also removes every (transitive) dependent; otherwise this fails with
+    /// `Error::InvalidInput` naming the dependents. Returns the removed circuits,
+    /// `id` first, or nothing if `id` is not registered.
+    pub fn remove_circuit(&self, id: &CircuitId, cascade: bool) -> Result<Vec<CircuitInfo>> {
+        let _guard = self.write_keys()?;
+        let dependents = self.dependents(id)?;
+        if !dependents.is_empty() && !cascade {
+            let dependents: Vec<String> = dependents.iter().map(hex::encode).collect();
+            return Err(Error::InvalidInput(format!(
+                "Circuit {} is a dependency of {}",
+                hex::encode(id),
+                dependents.join(", ")
+            )));
+        }
+
+        let mut removed = Vec::new();
+        let mut pending = vec![*id];
+        let mut visited = HashSet::new();
+        while let Some(next) = pending.pop() {
+            if !visited.insert(next) {
+                continue;
+            }
+            pending.extend(self.dependents(&next)?);
+            if let Some(info) = self.remove_circuit_entry(&next)? {
+                removed.push(info);
+            }
+        }
+        Ok(removed)
+    }
+
+    fn remove_circuit_entry(&self, id: &CircuitId) -> Result<Option<CircuitInfo>> {
+        let key = hex::encode(id);
+        let removed = retry_transient(|| self.info_tree.remove(key.as_bytes()))
+            .map_err(|e| Error::StateError(format!("Sled remove failed: {}", e)))?;
+        self.invalidate_cached_info(id);
+        match removed {
             Some(info_bytes) => {
-                let info: CircuitInfo =
-                    bincode::deserialize(&info_bytes).map_err(|e| Error::BincodeError(e))?;
+                let info: CircuitInfo = bincode::deserialize(&info_bytes)?;
                 // Remove associated artifact files
                 let circuit_artifact_dir = self.artifacts_path.join(hex::encode(id));
                 if circuit_artifact_dir.exists() {
                     fs::remove_dir_all(&circuit_artifact_dir)?;
                 }
+                self.release_blobs(info.artifact_blobs.values())?;
+                self.stats_tree
+                    .remove(hex::encode(id).as_bytes())
+                    .map_err(|e| Error::StateError(format!("Sled remove failed: {}", e)))?;
+                for key in self.proof_cache_tree.scan_prefix(id).keys() {
+                    let key = key
+                        .map_err(|e| Error::StateError(format!("Sled iteration failed: {}", e)))?;
+                    self.proof_cache_tree
+                        .remove(key)
+                        .map_err(|e| Error::StateError(format!("Sled remove failed: {}", e)))?;
+                }
                 self.db
                     .flush()
                     .map_err(|e| Error::StateError(format!("Failed to flush sled DB: {}", e)))?;
@@ -182,3 +1276,1268 @@ impl CircuitStore {
         }
     }
 }
+
+/// A bounded cache of decoded circuit infos, evicting the least recently used.
+#[derive(Debug)]
+struct InfoCache {
+    capacity: usize,
+    /// Info and the tick of its last use, by circuit ID
+    entries: HashMap<CircuitId, (CircuitInfo, u64)>,
+    tick: u64,
+    /// Bumped by every invalidation. A reader only caches what it read from sled
+    /// if no invalidation happened since it started, so a write racing the read
+    /// can never leave a stale info behind.
+    generation: u64,
+}
+
+impl InfoCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            tick: 0,
+            generation: 0,
+        }
+    }
+
+    fn get(&mut self, id: &CircuitId) -> Option<CircuitInfo> {
+        self.tick += 1;
+        let (info, last_used) = self.entries.get_mut(id)?;
+        *last_used = self.tick;
+        Some(info.clone())
+    }
+
+    /// Caches `info` if nothing was invalidated since `generation` was read.
+    fn insert(&mut self, generation: u64, id: CircuitId, info: &CircuitInfo) {
+        if self.capacity == 0 || generation != self.generation {
+            return;
+        }
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(&id) {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(id, _)| *id);
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.tick += 1;
+        self.entries.insert(id, (info.clone(), self.tick));
+    }
+
+    fn invalidate(&mut self, id: &CircuitId) {
+        self.generation += 1;
+        self.entries.remove(id);
+    }
+}
+
+/// Runs a sled operation, retrying errors that are known to be transient.
+///
+/// Interrupted or timed-out I/O (e.g. under flush contention) is retried up to
+/// `SLED_MAX_ATTEMPTS` times; corruption, bugs and other I/O errors are
+/// returned immediately. Store methods run on async job paths, so retries only
+/// yield the thread instead of sleeping on it.
+fn retry_transient<T>(mut op: impl FnMut() -> sled::Result<T>) -> sled::Result<T> {
+    let mut attempt = 1;
+    loop {
+        match op() {
+            Err(e) if attempt < SLED_MAX_ATTEMPTS && is_transient_sled_error(&e) => {
+                tracing::debug!(attempt, error = %e, "Retrying transient sled error");
+                std::thread::yield_now();
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+fn is_transient_sled_error(error: &sled::Error) -> bool {
+    matches!(
+        error,
+        sled::Error::Io(io) if matches!(
+            io.kind(),
+            std::io::ErrorKind::Interrupted
+                | std::io::ErrorKind::WouldBlock
+                | std::io::ErrorKind::TimedOut
+        )
+    )
+}
+
+/// Stamps a new database with [`SCHEMA_VERSION`], or checks that an existing one matches it.
+///
+/// Only an empty database is stamped; one holding records but no version
+/// predates versioning and is rejected, since its layout is unknown. v2, v3
+/// and v4 databases are migrated in place, one version at a time (see
+/// [`migrate_infos`] and [`migrate_v4_stored_proofs`]).
+fn check_schema_version(db: &Db) -> Result<()> {
+    let stored = db
+        .get(SCHEMA_VERSION_KEY)
+        .map_err(|e| Error::StateError(format!("Failed to read schema version: {}", e)))?;
+    match stored {
+        Some(bytes) => {
+            let bytes: [u8; 4] = bytes.as_ref().try_into().map_err(|_| {
+                Error::StateError(format!(
+                    "Corrupt schema version in DB ({} bytes)",
+                    bytes.len()
+                ))
+            })?;
+            let mut version = u32::from_be_bytes(bytes);
+            if version == 2 {
+                migrate_infos::<CircuitInfoV2, CircuitInfoV3>(db, 2)?;
+                version = 3;
+            }
+            if version == 3 {
+                migrate_infos::<CircuitInfoV3, CircuitInfo>(db, 3)?;
+                version = 4;
+            }
+            if version == 4 {
+                migrate_v4_stored_proofs(db)?;
+                version = 5;
+            }
+            if version != SCHEMA_VERSION {
+                return Err(Error::StateError(format!(
+                    "DB schema v{}, this build expects v{}",
+                    version, SCHEMA_VERSION
+                )));
+            }
+        }
+        None => {
+            if !is_empty_db(db)? {
+                return Err(Error::StateError(format!(
+                    "DB has records but no schema version, this build expects v{}",
+                    SCHEMA_VERSION
+                )));
+            }
+            db.insert(SCHEMA_VERSION_KEY, &SCHEMA_VERSION.to_be_bytes())
+                .map_err(|e| Error::StateError(format!("Failed to write schema version: {}", e)))?;
+            db.flush()
+                .map_err(|e| Error::StateError(format!("Failed to flush sled DB: {}", e)))?;
+        }
+    }
+    Ok(())
+}
+
+/// Whether no tree of `db`, including the default one, holds a record.
+fn is_empty_db(db: &Db) -> Result<bool> {
+    for name in db.tree_names() {
+        let tree = db
+            .open_tree(&name)
+            .map_err(|e| Error::StateError(format!("Failed to open sled tree: {}", e)))?;
+        if !tree.is_empty() {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// `CircuitInfo` as written by schema v2, before artifacts could be stored as blobs.
+#[derive(Serialize, Deserialize)]
+struct CircuitInfoV2 {
+    id: CircuitId,
+    name: String,
+    circuit_type: CircuitType,
+    artifact_paths: BTreeMap<ArtifactRole, PathBuf>,
+    backends: Vec<BackendKeys>,
+    pinned_operators: Vec<PublicKeyBytes>,
+    dependencies: Vec<CircuitId>,
+    num_public_inputs: Option<u32>,
+    noir_abi: Option<NoirAbi>,
+    updated_at: u64,
+}
+
+impl From<CircuitInfoV2> for CircuitInfoV3 {
+    fn from(info: CircuitInfoV2) -> Self {
+        CircuitInfoV3 {
+            id: info.id,
+            name: info.name,
+            circuit_type: info.circuit_type,
+            artifact_paths: info.artifact_paths,
+            // v2 stores kept every artifact in its circuit's directory
+            artifact_blobs: BTreeMap::new(),
+            backends: info.backends,
+            pinned_operators: info.pinned_operators,
+            dependencies: info.dependencies,
+            num_public_inputs: info.num_public_inputs,
+            noir_abi: info.noir_abi,
+            updated_at: info.updated_at,
+        }
+    }
+}
+
+/// `CircuitInfo` as written by schema v3, before artifact provenance was recorded.
+#[derive(Serialize, Deserialize)]
+struct CircuitInfoV3 {
+    id: CircuitId,
+    name: String,
+    circuit_type: CircuitType,
+    artifact_paths: BTreeMap<ArtifactRole, PathBuf>,
+    artifact_blobs: BTreeMap<ArtifactRole, [u8; 32]>,
+    backends: Vec<BackendKeys>,
+    pinned_operators: Vec<PublicKeyBytes>,
+    dependencies: Vec<CircuitId>,
+    num_public_inputs: Option<u32>,
+    noir_abi: Option<NoirAbi>,
+    updated_at: u64,
+}
+
+impl From<CircuitInfoV3> for CircuitInfo {
+    fn from(info: CircuitInfoV3) -> Self {
+        CircuitInfo {
+            id: info.id,
+            name: info.name,
+            circuit_type: info.circuit_type,
+            artifact_paths: info.artifact_paths,
+            artifact_blobs: info.artifact_blobs,
+            backends: info.backends,
+            pinned_operators: info.pinned_operators,
+            dependencies: info.dependencies,
+            num_public_inputs: info.num_public_inputs,
+            noir_abi: info.noir_abi,
+            updated_at: info.updated_at,
+            source_url: None,
+            artifact_checksum: [0; 32],
+        }
+    }
+}
+
+/// Rewrites every circuit info written by schema `from` in the layout of the
+/// next schema and stamps the database with it, all in one transaction.
+fn migrate_infos<Old, New>(db: &Db, from: u32) -> Result<()>
+where
+    Old: serde::de::DeserializeOwned + Into<New>,
+    New: Serialize,
+{
+    let info_tree = db
+        .open_tree(INFO_TREE_NAME)
+        .map_err(|e| Error::StateError(format!("Failed to open sled tree: {}", e)))?;
+    let mut migrated = Vec::new();
+    for entry in info_tree.iter() {
+        let (key, value) =
+            entry.map_err(|e| Error::StateError(format!("Sled iteration failed: {}", e)))?;
+        let info: New = bincode::deserialize::<Old>(&value)?.into();
+        migrated.push((key, bincode::serialize(&info)?));
+    }
+
+    let default_tree: &sled::Tree = db;
+    (&info_tree, default_tree)
+        .transaction(|(info_tx, default_tx)| {
+            for (key, value) in &migrated {
+                info_tx.insert(key, value.as_slice())?;
+            }
+            default_tx.insert(SCHEMA_VERSION_KEY, &(from + 1).to_be_bytes())?;
+            Ok::<_, ConflictableTransactionError<()>>(())
+        })
+        .map_err(|e: TransactionError<()>| {
+            Error::StateError(format!(
+                "Failed to migrate DB from schema v{}: {:?}",
+                from, e
+            ))
+        })?;
+    db.flush()
+        .map_err(|e| Error::StateError(format!("Failed to flush sled DB: {}", e)))?;
+    tracing::info!(
+        circuits = migrated.len(),
+        "Migrated circuit DB from schema v{} to v{}",
+        from,
+        from + 1
+    );
+    Ok(())
+}
+
+/// Drops the v4 stored proofs, which do not record their circuit, and stamps
+/// the database v5, all in one transaction. Stored proofs expire anyway, and
+/// their owners can no longer be authorized to fetch them.
+fn migrate_v4_stored_proofs(db: &Db) -> Result<()> {
+    let proof_store_tree = db
+        .open_tree(PROOF_STORE_TREE_NAME)
+        .map_err(|e| Error::StateError(format!("Failed to open sled tree: {}", e)))?;
+    let keys = proof_store_tree
+        .iter()
+        .keys()
+        .collect::<sled::Result<Vec<_>>>()
+        .map_err(|e| Error::StateError(format!("Sled iteration failed: {}", e)))?;
+
+    let default_tree: &sled::Tree = db;
+    (&proof_store_tree, default_tree)
+        .transaction(|(proofs_tx, default_tx)| {
+            for key in &keys {
+                proofs_tx.remove(key)?;
+            }
+            default_tx.insert(SCHEMA_VERSION_KEY, &5u32.to_be_bytes())?;
+            Ok::<_, ConflictableTransactionError<()>>(())
+        })
+        .map_err(|e: TransactionError<()>| {
+            Error::StateError(format!("Failed to migrate DB from schema v4: {:?}", e))
+        })?;
+    db.flush()
+        .map_err(|e| Error::StateError(format!("Failed to flush sled DB: {}", e)))?;
+    tracing::info!(
+        dropped_proofs = keys.len(),
+        "Migrated circuit DB from schema v4 to v5"
+    );
+    Ok(())
+}
+
+/// Decodes an info tree key (lowercase hex of the `CircuitId`) into the raw ID.
+fn decode_circuit_id_key(key_bytes: &[u8]) -> Result<CircuitId> {
+    let hex_key = std::str::from_utf8(key_bytes)
+        .map_err(|e| Error::StateError(format!("Invalid UTF8 key in DB: {}", e)))?;
+    if hex_key.len() != 64 || hex_key.bytes().any(|b| b.is_ascii_uppercase()) {
+        return Err(Error::StateError(format!(
+            "Invalid key in DB '{}', expected 64 lowercase hex characters",
+            hex_key
+        )));
+    }
+    let mut id = [0u8; 32];
+    hex::decode_to_slice(hex_key, &mut id)
+        .map_err(|e| Error::StateError(format!("Invalid hex key in DB '{}': {}", hex_key, e)))?;
+    Ok(id)
+}
+
+/// Recursively copies the directory `src` to `dst`.
+fn copy_dir_all(src: &Path, dst: &Path) -> Result<()> {
+    fs::create_dir_all(dst)?;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let target = dst.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir_all(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Total size of the files under `path` (or of `path` itself if it is a file).
+fn dir_size(path: &Path) -> Result<u64> {
+    let metadata = fs::symlink_metadata(path)?;
+    if !metadata.is_dir() {
+        return Ok(metadata.len());
+    }
+    let mut size = 0;
+    for entry in fs::read_dir(path)? {
+        size += dir_size(&entry?.path())?;
+    }
+    Ok(size)
+}
+
+/// Current Unix time in seconds.
+pub(crate) fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Content hash identifying a stored proof.
+pub fn proof_hash(proof: &ProofResult) -> Result<[u8; 32]> {
+    Ok(Sha256::digest(bincode::serialize(proof)?).into())
+}
+
+fn proof_cache_key(id: &CircuitId, witness_hash: &[u8; 32]) -> [u8; 64] {
+    let mut key = [0u8; 64];
+    key[..32].copy_from_slice(id);
+    key[32..].copy_from_slice(witness_hash);
+    key
+}
+
+fn is_expired(entry: &CachedProof, now: u64, ttl: Duration) -> bool {
+    now.saturating_sub(entry.cached_at) >= ttl.as_secs()
+}
+
+/// Writes `data` to a temp file next to `path`, sets its permission `mode` and
+/// atomically renames it into place.
+///
+/// With `sync`, the file contents are fsync'd before the rename and the
+/// directory after it, so both the data and its name survive a crash.
+fn write_atomic(path: &Path, data: &[u8], mode: u32, sync: bool) -> Result<()> {
+    let dir = path.parent().ok_or_else(|| {
+        Error::Internal(format!("Artifact path has no parent: {}", Redacted(path)))
+    })?;
+    let mut staged = tempfile::NamedTempFile::new_in(dir)?;
+    staged.write_all(data)?;
+    set_file_mode(staged.as_file(), mode)?;
+    if sync {
+        staged.as_file().sync_all()?;
+    }
+    staged.persist(path).map_err(|e| Error::IoError(e.error))?;
+    if sync {
+        sync_dir(dir)?;
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> Result<()> {
+    fs::File::open(dir)?.sync_all()?;
+    Ok(())
+}
+
+// Directories cannot be opened as files on other targets
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(unix)]
+fn set_file_mode(file: &fs::File, mode: u32) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    file.set_permissions(fs::Permissions::from_mode(mode))?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn set_file_mode(_file: &fs::File, _mode: u32) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{CircuitType, ProvingBackend};
+    use std::collections::BTreeSet;
+
+    fn circuit_info(id: CircuitId) -> CircuitInfo {
+        CircuitInfo {
+            id,
+            name: format!("circuit-{}", id[0]),
+            circuit_type: CircuitType::Circom,
+            artifact_paths: BTreeMap::new(),
+            artifact_blobs: BTreeMap::new(),
+            backends: vec![backend_keys(ProvingBackend::Groth16)],
+            pinned_operators: Vec::new(),
+            dependencies: Vec::new(),
+            num_public_inputs: None,
+            noir_abi: None,
+            updated_at: 0,
+            source_url: None,
+            artifact_checksum: [0; 32],
+        }
+    }
+
+    fn backend_keys(backend: ProvingBackend) -> BackendKeys {
+        BackendKeys {
+            proving_key_path: PathBuf::from(backend.name()).join("proving.key"),
+            verification_key_path: PathBuf::from(backend.name()).join("verification.key"),
+            verifier_address: None,
+            backend,
+        }
+    }
+
+    #[test]
+    fn circuit_info_lookup_ignores_hex_case() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = CircuitStore::new(dir.path().to_path_buf()).unwrap();
+        let info = circuit_info([0xAB; 32]);
+        store
+            .store_circuit_info(&hex::encode(info.id), &info)
+            .unwrap();
+
+        let upper = hex::encode_upper(info.id);
+        // Same answer before and after the lowercase lookup fills the cache
+        assert_eq!(store.get_circuit_info(&upper).unwrap(), Some(info.clone()));
+        assert_eq!(
+            store.get_circuit_info(&hex::encode(info.id)).unwrap(),
+            Some(info.clone())
+        );
+        assert_eq!(store.get_circuit_info(&upper).unwrap(), Some(info));
+    }
+
+    #[test]
+    fn list_circuit_ids_round_trips_stored_ids() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = CircuitStore::new(dir.path().to_path_buf()).unwrap();
+
+        let ids: BTreeSet<CircuitId> = [[0x00; 32], [0xAB; 32], {
+            let mut id = [0u8; 32];
+            id[31] = 0x7F;
+            id
+        }]
+        .into_iter()
+        .collect();
+        for id in &ids {
+            store
+                .store_circuit_info(&hex::encode(id), &circuit_info(*id))
+                .unwrap();
+        }
+
+        let listed: BTreeSet<CircuitId> = store.list_circuit_ids().collect::<Result<_>>().unwrap();
+        assert_eq!(listed, ids);
+    }
+
+    #[test]
+    fn record_proof_accumulates_stats() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = CircuitStore::new(dir.path().to_path_buf()).unwrap();
+        let id = [0x22; 32];
+        assert!(store.get_stats(&id).unwrap().is_none());
+
+        let first = store.record_proof(&id, Duration::from_millis(150)).unwrap();
+        assert_eq!(first.proof_count, 1);
+        assert_eq!(first.total_proving_ms, 150);
+        assert!(first.last_proven_at > 0);
+
+        let second = store.record_proof(&id, Duration::from_millis(50)).unwrap();
+        assert_eq!(second.proof_count, 2);
+        assert_eq!(second.total_proving_ms, 200);
+        assert!(second.last_proven_at >= first.last_proven_at);
+        assert_eq!(store.get_stats(&id).unwrap(), Some(second));
+
+        // Stats are kept per circuit
+        assert!(store.get_stats(&[0x23; 32]).unwrap().is_none());
+    }
+
+    #[test]
+    fn record_proof_rejects_corrupt_stats() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = CircuitStore::new(dir.path().to_path_buf()).unwrap();
+        let id = [0x24; 32];
+        store
+            .stats_tree
+            .insert(hex::encode(id).as_bytes(), &[0xFF][..])
+            .unwrap();
+
+        assert!(matches!(
+            store.record_proof(&id, Duration::from_millis(10)),
+            Err(Error::BincodeError(_))
+        ));
+        assert!(store.get_stats(&id).is_err());
+    }
+
+    #[test]
+    fn snapshot_loads_stored_circuit() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = CircuitStore::new(dir.path().to_path_buf()).unwrap();
+        let mut info = circuit_info([0x11; 32]);
+        info.artifact_paths
+            .insert(ArtifactRole::R1cs, PathBuf::from("circuit_artifact.r1cs"));
+        info.backends.push(backend_keys(ProvingBackend::Plonk));
+        store
+            .store_circuit(
+                &info,
+                &[(Path::new("circuit_artifact.r1cs"), b"r1cs".as_slice())],
+                &[
+                    (b"groth16-pk".as_slice(), b"groth16-vk".as_slice()),
+                    (b"plonk-pk".as_slice(), b"plonk-vk".as_slice()),
+                ],
+            )
+            .unwrap();
+        let id_hex = hex::encode(info.id);
+
+        let snapshot = store.snapshot(&id_hex, None).unwrap().unwrap();
+        assert_eq!(snapshot.info, info);
+        assert_eq!(snapshot.primary_artifact().unwrap(), b"r1cs");
+        assert_eq!(snapshot.backend, ProvingBackend::Groth16);
+        assert_eq!(snapshot.proving_key, b"groth16-pk");
+        assert_eq!(snapshot.verification_key, b"groth16-vk");
+        let vk = store
+            .read_verification_key(&snapshot.info, &snapshot.info.backends[0])
+            .unwrap();
+        assert_eq!(&*vk, b"groth16-vk");
+
+        let snapshot = store
+            .snapshot(&id_hex, Some(&ProvingBackend::Plonk))
+            .unwrap()
+            .unwrap();
+        assert_eq!(snapshot.backend, ProvingBackend::Plonk);
+        assert_eq!(snapshot.proving_key, b"plonk-pk");
+        assert_eq!(snapshot.verification_key, b"plonk-vk");
+
+        assert!(matches!(
+            store.snapshot(&id_hex, Some(&ProvingBackend::UltraHonk)),
+            Err(Error::IncompatibleBackend(_))
+        ));
+        assert!(
+            store
+                .snapshot(&hex::encode([0x22; 32]), None)
+                .unwrap()
+                .is_none()
+        );
+
+        fs::remove_file(
+            store
+                .get_artifacts_base_path()
+                .join(&id_hex)
+                .join(&info.backends[0].proving_key_path),
+        )
+        .unwrap();
+        assert!(matches!(
+            store.snapshot(&id_hex, None),
+            Err(Error::MissingProvingKey { .. })
+        ));
+    }
+
+    #[test]
+    fn export_circuit_leaves_out_proving_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = CircuitStore::new(dir.path().to_path_buf()).unwrap();
+        let mut info = circuit_info([0x12; 32]);
+        info.artifact_paths
+            .insert(ArtifactRole::R1cs, PathBuf::from("circuit_artifact.r1cs"));
+        store
+            .store_circuit(
+                &info,
+                &[(Path::new("circuit_artifact.r1cs"), b"r1cs".as_slice())],
+                &[(b"groth16-pk".as_slice(), b"groth16-vk".as_slice())],
+            )
+            .unwrap();
+
+        let export = store.export_circuit(&info.id).unwrap().unwrap();
+        assert_eq!(export.circuit_id, info.id);
+        assert_eq!(export.artifacts[&ArtifactRole::R1cs], b"r1cs");
+        assert_eq!(export.verification_keys, [(
+            ProvingBackend::Groth16,
+            b"groth16-vk".to_vec()
+        )]);
+        let encoded = bincode::serialize(&export).unwrap();
+        assert!(!encoded.windows(10).any(|window| window == b"groth16-pk"));
+        assert!(store.export_circuit(&[0x13; 32]).unwrap().is_none());
+    }
+
+    #[test]
+    fn compact_removes_orphaned_data() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = CircuitStore::new(dir.path().to_path_buf()).unwrap();
+        let info = circuit_info([0x33; 32]);
+        store
+            .store_circuit(&info, &[], &[(b"pk".as_slice(), b"vk".as_slice())])
+            .unwrap();
+        store
+            .record_proof(&info.id, Duration::from_millis(5))
+            .unwrap();
+
+        let orphan = [0x44; 32];
+        let orphan_dir = store.get_artifacts_base_path().join(hex::encode(orphan));
+        fs::create_dir_all(&orphan_dir).unwrap();
+        fs::write(orphan_dir.join("proving.key"), [0u8; 100]).unwrap();
+        store
+            .record_proof(&orphan, Duration::from_millis(5))
+            .unwrap();
+
+        let report = store.compact().unwrap();
+        assert_eq!(report.orphaned_dirs_removed, 1);
+        assert_eq!(report.artifact_bytes_reclaimed, 100);
+        assert_eq!(report.orphaned_entries_removed, 1);
+        assert!(!orphan_dir.exists());
+        assert!(store.get_stats(&orphan).unwrap().is_none());
+
+        // The registered circuit is untouched
+        assert!(
+            store
+                .snapshot(&hex::encode(info.id), None)
+                .unwrap()
+                .is_some()
+        );
+        assert!(store.get_stats(&info.id).unwrap().is_some());
+        assert_eq!(store.compact().unwrap().orphaned_dirs_removed, 0);
+    }
+
+    #[test]
+    fn clone_circuit_copies_files_under_new_id() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = CircuitStore::new(dir.path().to_path_buf()).unwrap();
+        let mut info = circuit_info([0x55; 32]);
+        info.artifact_paths
+            .insert(ArtifactRole::R1cs, PathBuf::from("circuit_artifact.r1cs"));
+        store
+            .store_circuit(
+                &info,
+                &[(Path::new("circuit_artifact.r1cs"), b"r1cs".as_slice())],
+                &[(b"pk".as_slice(), b"vk".as_slice())],
+            )
+            .unwrap();
+
+        let clone_id = store.clone_circuit(&info.id, "variant").unwrap();
+        assert_eq!(
+            clone_id,
+            generate_circuit_id("variant", &info.circuit_type, &[ProvingBackend::Groth16])
+        );
+        let snapshot = store
+            .snapshot(&hex::encode(clone_id), None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(snapshot.info.name, "variant");
+        assert_eq!(snapshot.primary_artifact().unwrap(), b"r1cs");
+        assert_eq!(snapshot.proving_key, b"pk");
+
+        // The source is untouched and a second clone under the same name is rejected
+        assert!(
+            store
+                .snapshot(&hex::encode(info.id), None)
+                .unwrap()
+                .is_some()
+        );
+        assert!(matches!(
+            store.clone_circuit(&info.id, "variant"),
+            Err(Error::InvalidInput(_))
+        ));
+        assert!(matches!(
+            store.clone_circuit(&[0x66; 32], "other"),
+            Err(Error::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn content_addressed_artifacts_are_shared_until_unreferenced() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = CircuitStore::new(dir.path().to_path_buf())
+            .unwrap()
+            .with_content_addressed(true);
+        let artifact = [(Path::new("circuit_artifact.r1cs"), b"r1cs".as_slice())];
+        let keys = [(b"pk".as_slice(), b"vk".as_slice())];
+        let hash = blake3_256(b"r1cs");
+        let blob = dir.path().join(BLOBS_SUBDIR).join(hex::encode(hash));
+
+        let mut first = circuit_info([0x71; 32]);
+        first
+            .artifact_paths
+            .insert(ArtifactRole::R1cs, PathBuf::from("circuit_artifact.r1cs"));
+        let second = CircuitInfo {
+            id: [0x72; 32],
+            ..first.clone()
+        };
+        store.store_circuit(&first, &artifact, &keys).unwrap();
+        store.store_circuit(&second, &artifact, &keys).unwrap();
+        let clone_id = store.clone_circuit(&first.id, "variant").unwrap();
+        assert_eq!(store.blob_ref_count(&hash).unwrap(), 3);
+
+        let snapshot = store
+            .snapshot(&hex::encode(second.id), None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(snapshot.info.artifact_blobs[&ArtifactRole::R1cs], hash);
+        assert_eq!(snapshot.primary_artifact().unwrap(), b"r1cs");
+        assert!(
+            !store
+                .get_artifacts_base_path()
+                .join(hex::encode(second.id))
+                .join("circuit_artifact.r1cs")
+                .exists()
+        );
+
+        store.remove_circuit(&first.id, false).unwrap();
+        store.remove_circuit(&clone_id, false).unwrap();
+        assert!(blob.exists());
+        assert_eq!(store.compact().unwrap().orphaned_blobs_removed, 0);
+        store.remove_circuit(&second.id, false).unwrap();
+        assert!(!blob.exists());
+        assert_eq!(store.blob_ref_count(&hash).unwrap(), 0);
+    }
+
+    #[test]
+    fn list_circuit_ids_rejects_malformed_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = CircuitStore::new(dir.path().to_path_buf()).unwrap();
+
+        for key in [&b"\xff\xfe"[..], b"abcd", "AB".repeat(32).as_bytes()] {
+            store.info_tree.clear().unwrap();
+            store.info_tree.insert(key, b"".as_slice()).unwrap();
+            let listed: Vec<_> = store.list_circuit_ids().collect();
+            assert!(matches!(listed.as_slice(), [Err(Error::StateError(_))]));
+        }
+    }
+
+    #[test]
+    fn verify_ids_flags_records_not_matching_their_derivation() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = CircuitStore::new(dir.path().to_path_buf()).unwrap();
+        let derived = |name: &str| {
+            let mut info = circuit_info([0; 32]);
+            info.name = name.to_string();
+            info.id = generate_circuit_id(name, &info.circuit_type, &[ProvingBackend::Groth16]);
+            info
+        };
+        let honest = derived("honest");
+        let mut renamed = derived("renamed");
+        renamed.name = "tampered".to_string();
+        let arbitrary = circuit_info([0x33; 32]);
+        for info in [&honest, &renamed, &arbitrary] {
+            store
+                .store_circuit_info(&hex::encode(info.id), info)
+                .unwrap();
+        }
+        // A record moved under another circuit's key
+        let moved = derived("moved");
+        let moved_key = [0x44; 32];
+        store
+            .info_tree
+            .insert(
+                hex::encode(moved_key).as_bytes(),
+                bincode::serialize(&moved).unwrap(),
+            )
+            .unwrap();
+
+        let mut inconsistent = store.verify_ids().unwrap();
+        inconsistent.sort();
+        let mut expected = vec![renamed.id, arbitrary.id, moved_key];
+        expected.sort();
+        assert_eq!(inconsistent, expected);
+    }
+
+    #[test]
+    fn info_cache_never_serves_stale_infos() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = CircuitStore::new(dir.path().to_path_buf())
+            .unwrap()
+            .with_info_cache_capacity(1);
+        let first = circuit_info([0x01; 32]);
+        let second = circuit_info([0x02; 32]);
+        store.store_circuit(&first, &[], &[(b"pk", b"vk")]).unwrap();
+        store
+            .store_circuit(&second, &[], &[(b"pk", b"vk")])
+            .unwrap();
+        let first_hex = hex::encode(first.id);
+
+        // Cache the info, then rekey the circuit
+        assert_eq!(
+            store
+                .get_circuit_info(&first_hex)
+                .unwrap()
+                .unwrap()
+                .updated_at,
+            first.updated_at
+        );
+        let mut rekeyed = first.clone();
+        rekeyed.updated_at += 1;
+        store
+            .store_circuit(&rekeyed, &[], &[(b"pk2", b"vk2")])
+            .unwrap();
+        assert_eq!(
+            store
+                .get_circuit_info(&first_hex)
+                .unwrap()
+                .unwrap()
+                .updated_at,
+            rekeyed.updated_at
+        );
+
+        // Reading another circuit evicts the first from the single-entry cache
+        store.get_circuit_info(&hex::encode(second.id)).unwrap();
+        assert!(store.get_circuit_info(&first_hex).unwrap().is_some());
+
+        store.remove_circuit(&first.id, false).unwrap();
+        assert!(store.get_circuit_info(&first_hex).unwrap().is_none());
+    }
+
+    #[test]
+    fn remove_circuit_respects_dependencies() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = CircuitStore::new(dir.path().to_path_buf()).unwrap();
+        let library = circuit_info([0x01; 32]);
+        let mut composite = circuit_info([0x02; 32]);
+        composite.dependencies = vec![library.id];
+        let mut outer = circuit_info([0x03; 32]);
+        outer.dependencies = vec![composite.id];
+
+        // Dependencies must be registered first
+        assert!(matches!(
+            store.store_circuit(&composite, &[], &[(b"pk", b"vk")]),
+            Err(Error::InvalidInput(_))
+        ));
+        for info in [&library, &composite, &outer] {
+            store.store_circuit(info, &[], &[(b"pk", b"vk")]).unwrap();
+        }
+        assert_eq!(store.dependents(&library.id).unwrap(), vec![composite.id]);
+
+        assert!(matches!(
+            store.remove_circuit(&library.id, false),
+            Err(Error::InvalidInput(_))
+        ));
+        assert!(
+            store
+                .get_circuit_info(&hex::encode(library.id))
+                .unwrap()
+                .is_some()
+        );
+
+        let removed: Vec<CircuitId> = store
+            .remove_circuit(&library.id, true)
+            .unwrap()
+            .into_iter()
+            .map(|info| info.id)
+            .collect();
+        assert_eq!(removed, vec![library.id, composite.id, outer.id]);
+        assert_eq!(store.list_circuit_ids().count(), 0);
+        assert!(store.remove_circuit(&library.id, false).unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_db_with_other_schema_version() {
+        let dir = tempfile::tempdir().unwrap();
+        drop(CircuitStore::new(dir.path().to_path_buf()).unwrap());
+        // Reopening a DB stamped by this build succeeds
+        let store = CircuitStore::new(dir.path().to_path_buf()).unwrap();
+        store
+            .db
+            .insert(SCHEMA_VERSION_KEY, &(SCHEMA_VERSION + 1).to_be_bytes())
+            .unwrap();
+        store.db.flush().unwrap();
+        drop(store);
+
+        let err = CircuitStore::new(dir.path().to_path_buf()).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::StateError(msg)
+                if msg == format!("DB schema v{}, this build expects v{}", SCHEMA_VERSION + 1, SCHEMA_VERSION)
+        ));
+    }
+
+    #[test]
+    fn rejects_unversioned_db_with_records() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let store = CircuitStore::new(dir.path().to_path_buf()).unwrap();
+            store.db.remove(SCHEMA_VERSION_KEY).unwrap();
+            store.db.flush().unwrap();
+        }
+        // An empty unversioned DB is stamped
+        drop(CircuitStore::new(dir.path().to_path_buf()).unwrap());
+
+        {
+            let store = CircuitStore::new(dir.path().to_path_buf()).unwrap();
+            store
+                .info_tree
+                .insert(b"legacy", b"record".as_slice())
+                .unwrap();
+            store.db.remove(SCHEMA_VERSION_KEY).unwrap();
+            store.db.flush().unwrap();
+        }
+        let err = CircuitStore::new(dir.path().to_path_buf()).unwrap_err();
+        assert!(matches!(err, Error::StateError(msg) if msg.contains("no schema version")));
+    }
+
+    #[test]
+    fn migrating_v4_drops_stored_proofs() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let store = CircuitStore::new(dir.path().to_path_buf()).unwrap();
+            store
+                .proof_store_tree
+                .insert([0x55; 32], b"v4 stored proof".as_slice())
+                .unwrap();
+            store
+                .db
+                .insert(SCHEMA_VERSION_KEY, &4u32.to_be_bytes())
+                .unwrap();
+            store.db.flush().unwrap();
+        }
+
+        let store = CircuitStore::new(dir.path().to_path_buf()).unwrap();
+        assert!(store.proof_store_tree.is_empty());
+        assert_eq!(
+            store.db.get(SCHEMA_VERSION_KEY).unwrap().unwrap().as_ref(),
+            SCHEMA_VERSION.to_be_bytes()
+        );
+    }
+
+    #[test]
+    fn migrates_v3_infos_with_empty_provenance() {
+        let dir = tempfile::tempdir().unwrap();
+        let info = CircuitInfo {
+            source_url: Some("https://example.com/circuit.r1cs".to_string()),
+            artifact_checksum: [0xC5; 32],
+            ..circuit_info([0x33; 32])
+        };
+        {
+            let store = CircuitStore::new(dir.path().to_path_buf()).unwrap();
+            let legacy = CircuitInfoV3 {
+                id: info.id,
+                name: info.name.clone(),
+                circuit_type: info.circuit_type.clone(),
+                artifact_paths: info.artifact_paths.clone(),
+                artifact_blobs: info.artifact_blobs.clone(),
+                backends: info.backends.clone(),
+                pinned_operators: info.pinned_operators.clone(),
+                dependencies: info.dependencies.clone(),
+                num_public_inputs: info.num_public_inputs,
+                noir_abi: info.noir_abi.clone(),
+                updated_at: info.updated_at,
+            };
+            store
+                .info_tree
+                .insert(hex::encode(info.id), bincode::serialize(&legacy).unwrap())
+                .unwrap();
+            store
+                .db
+                .insert(SCHEMA_VERSION_KEY, &3u32.to_be_bytes())
+                .unwrap();
+            store.db.flush().unwrap();
+        }
+
+        let store = CircuitStore::new(dir.path().to_path_buf()).unwrap();
+        let migrated = store
+            .get_circuit_info(&hex::encode(info.id))
+            .unwrap()
+            .unwrap();
+        assert_eq!(migrated, CircuitInfo {
+            source_url: None,
+            artifact_checksum: [0; 32],
+            ..info
+        });
+        assert_eq!(
+            store.db.get(SCHEMA_VERSION_KEY).unwrap().unwrap().as_ref(),
+            SCHEMA_VERSION.to_be_bytes()
+        );
+    }
+
+    #[test]
+    fn migrates_v2_infos_through_every_schema() {
+        let dir = tempfile::tempdir().unwrap();
+        let info = circuit_info([0x22; 32]);
+        {
+            let store = CircuitStore::new(dir.path().to_path_buf()).unwrap();
+            let legacy = CircuitInfoV2 {
+                id: info.id,
+                name: info.name.clone(),
+                circuit_type: info.circuit_type.clone(),
+                artifact_paths: info.artifact_paths.clone(),
+                backends: info.backends.clone(),
+                pinned_operators: info.pinned_operators.clone(),
+                dependencies: info.dependencies.clone(),
+                num_public_inputs: info.num_public_inputs,
+                noir_abi: info.noir_abi.clone(),
+                updated_at: info.updated_at,
+            };
+            store
+                .info_tree
+                .insert(hex::encode(info.id), bincode::serialize(&legacy).unwrap())
+                .unwrap();
+            store
+                .db
+                .insert(SCHEMA_VERSION_KEY, &2u32.to_be_bytes())
+                .unwrap();
+            store.db.flush().unwrap();
+        }
+
+        let store = CircuitStore::new(dir.path().to_path_buf()).unwrap();
+        let migrated = store
+            .get_circuit_info(&hex::encode(info.id))
+            .unwrap()
+            .unwrap();
+        assert_eq!(migrated, info);
+        assert_eq!(
+            store.db.get(SCHEMA_VERSION_KEY).unwrap().unwrap().as_ref(),
+            SCHEMA_VERSION.to_be_bytes()
+        );
+    }
+
+    #[test]
+    fn retries_only_transient_sled_errors() {
+        let mut calls = 0;
+        let result = retry_transient(|| {
+            calls += 1;
+            if calls < SLED_MAX_ATTEMPTS {
+                Err(sled::Error::Io(std::io::ErrorKind::Interrupted.into()))
+            } else {
+                Ok(calls)
+            }
+        });
+        assert_eq!(result.unwrap(), SLED_MAX_ATTEMPTS);
+
+        let mut calls = 0;
+        let result: sled::Result<()> = retry_transient(|| {
+            calls += 1;
+            Err(sled::Error::Unsupported("corrupt".to_string()))
+        });
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn applies_file_modes_to_keys_and_artifacts() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let store = CircuitStore::new(dir.path().to_path_buf())
+            .unwrap()
+            .with_file_modes(FileModes {
+                key: 0o600,
+                artifact: 0o640,
+            });
+        let info = circuit_info([0x31; 32]);
+        store
+            .store_circuit(
+                &info,
+                &[(Path::new("circuit.r1cs"), b"r1cs".as_slice())],
+                &[(b"pk".as_slice(), b"vk".as_slice())],
+            )
+            .unwrap();
+
+        let circuit_dir = store.get_artifacts_base_path().join(hex::encode(info.id));
+        let mode = |path: &Path| {
+            fs::metadata(circuit_dir.join(path))
+                .unwrap()
+                .permissions()
+                .mode()
+                & 0o777
+        };
+        assert_eq!(mode(Path::new("circuit.r1cs")), 0o640);
+        assert_eq!(mode(&info.backends[0].proving_key_path), 0o600);
+        assert_eq!(mode(&info.backends[0].verification_key_path), 0o600);
+    }
+
+    #[test]
+    fn stored_proofs_are_keyed_by_content_hash() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = CircuitStore::new(dir.path().to_path_buf()).unwrap();
+        let proof = ProofResult {
+            proof_bytes: vec![0xAB; 4096],
+            public_inputs: vec![b"input".to_vec()],
+            metadata: None,
+        };
+
+        let circuit_id = [0x44; 32];
+        let hash = store.store_proof(&circuit_id, &proof).unwrap();
+        assert_eq!(hash, proof_hash(&proof).unwrap());
+        assert_eq!(store.store_proof(&circuit_id, &proof).unwrap(), hash);
+        let ttl = Duration::from_secs(60);
+        let stored = store.get_stored_proof(&hash, ttl).unwrap().unwrap();
+        assert_eq!(stored.circuit_id, circuit_id);
+        assert_eq!(stored.proof, proof);
+        assert_eq!(store.get_stored_proof(&[0u8; 32], ttl).unwrap(), None);
+
+        // A zero TTL expires the entry, which is then gone for good
+        assert_eq!(store.sweep_stored_proofs(ttl).unwrap(), 0);
+        assert_eq!(store.get_stored_proof(&hash, Duration::ZERO).unwrap(), None);
+        assert_eq!(store.get_stored_proof(&hash, ttl).unwrap(), None);
+    }
+
+    #[test]
+    fn opened_verification_key_survives_rekey() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = CircuitStore::new(dir.path().to_path_buf()).unwrap();
+        let info = circuit_info([0x41; 32]);
+        store
+            .store_circuit(&info, &[], &[(b"pk".as_slice(), b"old-vk".as_slice())])
+            .unwrap();
+        let vk = store
+            .read_verification_key(&info, &info.backends[0])
+            .unwrap();
+
+        store
+            .store_circuit(&info, &[], &[(b"pk".as_slice(), b"new-vk".as_slice())])
+            .unwrap();
+        assert_eq!(&*vk, b"old-vk");
+        let reopened = store
+            .read_verification_key(&info, &info.backends[0])
+            .unwrap();
+        assert_eq!(&*reopened, b"new-vk");
+    }
+
+    #[test]
+    fn failure_store_keeps_the_newest_records() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = CircuitStore::new(dir.path().to_path_buf()).unwrap();
+        let failure = |call_id: u64, failed_at: u64| ProofFailure {
+            call_id,
+            circuit_id: [0x0F; 32],
+            proving_backend: ProvingBackend::Groth16,
+            participants: vec![vec![1; 33], vec![2; 33]],
+            session_id: format!("mpc-session-{}", call_id),
+            phase: crate::types::ProofPhase::SessionSetup,
+            error: "peer unreachable".to_string(),
+            failed_at,
+        };
+
+        // Recorded out of order, listed oldest first, bounded to the newest two
+        store.record_failure(&failure(3, 300), 2).unwrap();
+        store.record_failure(&failure(1, 100), 2).unwrap();
+        store.record_failure(&failure(2, 200), 2).unwrap();
+        assert_eq!(store.list_failures().unwrap(), vec![
+            failure(2, 200),
+            failure(3, 300)
+        ]);
+
+        // A bound of zero disables the store
+        store.record_failure(&failure(4, 400), 0).unwrap();
+        assert_eq!(store.list_failures().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn concurrent_registrations_of_one_circuit_leave_one_winner() {
+        const RACERS: usize = 8;
+        let dir = tempfile::tempdir().unwrap();
+        let store = CircuitStore::new(dir.path().to_path_buf()).unwrap();
+        let id = [0x51; 32];
+        let id_hex = hex::encode(id);
+        let racer_info = |racer: usize| {
+            let mut info = circuit_info(id);
+            info.name = format!("racer-{}", racer);
+            info.artifact_paths
+                .insert(ArtifactRole::R1cs, PathBuf::from("circuit_artifact.r1cs"));
+            info
+        };
+        // Every file of a consistent circuit names the same racer as its info
+        let assert_consistent = |snapshot: &CircuitSnapshot| {
+            let racer = snapshot.info.name.strip_prefix("racer-").unwrap();
+            assert_eq!(
+                snapshot.primary_artifact().unwrap(),
+                format!("r1cs-{}", racer).as_bytes()
+            );
+            assert_eq!(snapshot.proving_key, format!("pk-{}", racer).as_bytes());
+            assert_eq!(
+                snapshot.verification_key,
+                format!("vk-{}", racer).as_bytes()
+            );
+        };
+
+        let registered = std::sync::atomic::AtomicUsize::new(0);
+        std::thread::scope(|scope| {
+            for racer in 0..RACERS {
+                let (store, registered) = (&store, &registered);
+                scope.spawn(move || {
+                    let info = racer_info(racer);
+                    let r1cs = format!("r1cs-{}", racer);
+                    let (pk, vk) = (format!("pk-{}", racer), format!("vk-{}", racer));
+                    store
+                        .store_circuit(
+                            &info,
+                            &[(Path::new("circuit_artifact.r1cs"), r1cs.as_bytes())],
+                            &[(pk.as_bytes(), vk.as_bytes())],
+                        )
+                        .unwrap();
+                    registered.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                });
+            }
+            // Snapshots taken mid-race never mix two registrations
+            let (store, registered, id_hex) = (&store, &registered, &id_hex);
+            scope.spawn(move || {
+                while registered.load(std::sync::atomic::Ordering::SeqCst) < RACERS {
+                    if let Some(snapshot) = store.snapshot(id_hex, None).unwrap() {
+                        assert_consistent(&snapshot);
+                    }
+                }
+            });
+        });
+
+        let snapshot = store.snapshot(&id_hex, None).unwrap().unwrap();
+        assert_consistent(&snapshot);
+        assert_eq!(store.list_circuit_ids().count(), 1);
+        // No staged temp files are left next to the artifacts
+        let circuit_dir = store.get_artifacts_base_path().join(&id_hex);
+        let mut files: Vec<PathBuf> = Vec::new();
+        let mut dirs = vec![circuit_dir.clone()];
+        while let Some(dir) = dirs.pop() {
+            for entry in fs::read_dir(dir).unwrap() {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    dirs.push(path);
+                } else {
+                    files.push(path.strip_prefix(&circuit_dir).unwrap().to_path_buf());
+                }
+            }
+        }
+        files.sort();
+        let info = circuit_info(id);
+        let mut expected = vec![
+            PathBuf::from("circuit_artifact.r1cs"),
+            info.backends[0].proving_key_path.clone(),
+            info.backends[0].verification_key_path.clone(),
+        ];
+        expected.sort();
+        assert_eq!(files, expected);
+    }
+}
@@ -1,7 +1,10 @@
+use crate::error::{Error, Result};
+use crate::noir::NoirAbi;
 use blueprint_sdk::crypto::KeyType;
 use blueprint_sdk::networking::types::ParticipantId;
 use serde::{Deserialize, Serialize};
-use std::net::SocketAddr;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::PathBuf;
 
 // Represents the type of circuit (Circom or Noir)
@@ -24,26 +27,206 @@ pub enum ProvingBackend {
     UltraHonk,
 }
 
+impl ProvingBackend {
+    /// Stable lowercase name, matching the serialized form.
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProvingBackend::Groth16 => "groth16",
+            ProvingBackend::Plonk => "plonk",
+            ProvingBackend::UltraHonk => "ultrahonk",
+        }
+    }
+}
+
+impl std::str::FromStr for ProvingBackend {
+    type Err = Error;
+
+    /// Parses a backend from its name, case-insensitively.
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "groth16" => Ok(ProvingBackend::Groth16),
+            "plonk" => Ok(ProvingBackend::Plonk),
+            "ultrahonk" => Ok(ProvingBackend::UltraHonk),
+            other => Err(Error::InvalidInput(format!(
+                "Unknown proving backend: {}",
+                other
+            ))),
+        }
+    }
+}
+
+// Role of a single file within a circuit's artifact bundle.
+// Stored filenames are derived from the role, never from the uploader's name.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ArtifactRole {
+    #[serde(rename = "r1cs")]
+    R1cs,
+    #[serde(rename = "wasm")]
+    Wasm,
+    #[serde(rename = "sym")]
+    Sym,
+    #[serde(rename = "acir")]
+    Acir,
+}
+
+impl ArtifactRole {
+    /// The role holding the circuit's constraint system for the given circuit type.
+    #[must_use]
+    pub fn primary(circuit_type: &CircuitType) -> Self {
+        match circuit_type {
+            CircuitType::Circom => ArtifactRole::R1cs,
+            CircuitType::Noir => ArtifactRole::Acir,
+        }
+    }
+
+    /// File extension used when storing a member with this role.
+    #[must_use]
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ArtifactRole::R1cs => "r1cs",
+            ArtifactRole::Wasm => "wasm",
+            ArtifactRole::Sym => "sym",
+            ArtifactRole::Acir => "acir",
+        }
+    }
+}
+
 // Identifier for a registered circuit (hash of metadata)
 // Represented as bytes32 on the Solidity side.
 pub type CircuitId = [u8; 32];
 
+/// Generates a unique CircuitId based on metadata.
+pub fn generate_circuit_id(
+    name: &str,
+    circuit_type: &CircuitType,
+    proving_backends: &[ProvingBackend],
+) -> CircuitId {
+    let mut hasher = Sha256::new();
+    hasher.update(name.as_bytes());
+    hasher.update(format!("{:?}", circuit_type).as_bytes());
+    for proving_backend in proving_backends {
+        hasher.update(format!("{:?}", proving_backend).as_bytes());
+    }
+    hasher.finalize().into()
+}
+
+// Raw encoded operator public key, as produced by `BytesEncoding::to_bytes`
+pub type PublicKeyBytes = Vec<u8>;
+
 // Information stored about a registered circuit
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct CircuitInfo {
     pub id: CircuitId,
     pub name: String,
     pub circuit_type: CircuitType,
-    pub proving_backend: ProvingBackend,
-    // Path to the compiled circuit artifact (e.g., R1CS, ACIR bytecode)
+    // Paths to the compiled circuit artifacts (e.g., R1CS, ACIR bytecode), by role
     // Stored relative to the artifacts base directory
-    pub artifact_path: PathBuf,
-    // Path to the generated proving key (specific to the backend)
+    pub artifact_paths: BTreeMap<ArtifactRole, PathBuf>,
+    // BLAKE3 hashes of the artifacts kept in the store's shared blob directory, by role.
+    // Empty when the artifacts live in the circuit's own directory.
+    pub artifact_blobs: BTreeMap<ArtifactRole, [u8; 32]>,
+    // Keys for every backend the circuit was set up for, in registration order.
+    // The first entry is used when a proof request names no backend.
+    pub backends: Vec<BackendKeys>,
+    // Operators that must run every proof for this circuit.
+    // Empty means the full live operator set is used.
+    pub pinned_operators: Vec<PublicKeyBytes>,
+    // Library circuits this circuit includes; each must stay registered while this one is
+    pub dependencies: Vec<CircuitId>,
+    // Number of public inputs declared by the artifact, if the format exposes it
+    pub num_public_inputs: Option<u32>,
+    // ABI of a Noir circuit registered as a compiled program, used to build witnesses
+    pub noir_abi: Option<NoirAbi>,
+    // Unix timestamp (seconds) of the last time the circuit's keys were (re)generated
+    pub updated_at: u64,
+    // URL the primary artifact was downloaded from; None for inline and uploaded artifacts
+    pub source_url: Option<String>,
+    // SHA-256 of the primary artifact as registered.
+    // All zeros for circuits registered before provenance was recorded.
+    pub artifact_checksum: [u8; 32],
+}
+
+impl CircuitInfo {
+    /// Returns the keys for `backend`, or for the default backend if `None`.
+    pub fn backend_keys(&self, backend: Option<&ProvingBackend>) -> Result<&BackendKeys> {
+        let keys = match backend {
+            Some(backend) => self.backends.iter().find(|keys| &keys.backend == backend),
+            None => self.backends.first(),
+        };
+        keys.ok_or_else(|| {
+            Error::IncompatibleBackend(format!(
+                "Circuit {} has no keys for backend {:?}",
+                hex::encode(self.id),
+                backend
+            ))
+        })
+    }
+}
+
+// Keys generated for one proving backend of a circuit
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct BackendKeys {
+    pub backend: ProvingBackend,
+    // Path to the generated proving key, relative to the circuit's artifact directory
     pub proving_key_path: PathBuf,
     // Path to the verification key
     pub verification_key_path: PathBuf,
-    // Optional address of the verifier contract (bytes20)
-    pub verifier_address: Option<[u8; 20]>,
+    // Address of the deployed verifier contract, if one was deployed
+    pub verifier_address: Option<VerifierAddress>,
+}
+
+// Address of an on-chain verifier contract (Solidity `address`).
+// Absence is modelled as `Option::None`, never as the zero address.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(transparent)]
+pub struct VerifierAddress(pub [u8; 20]);
+
+impl VerifierAddress {
+    /// The all-zero address, which never hosts a verifier contract.
+    pub const ZERO: Self = Self([0u8; 20]);
+
+    /// Returns whether this is the all-zero address.
+    #[must_use]
+    pub fn is_zero(&self) -> bool {
+        *self == Self::ZERO
+    }
+}
+
+impl From<[u8; 20]> for VerifierAddress {
+    fn from(bytes: [u8; 20]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl std::fmt::Display for VerifierAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "0x{}", hex::encode(self.0))
+    }
+}
+
+impl std::str::FromStr for VerifierAddress {
+    type Err = Error;
+
+    /// Parses a 20-byte hex address, with or without the `0x` prefix.
+    fn from_str(s: &str) -> Result<Self> {
+        let hex_str = s.strip_prefix("0x").unwrap_or(s);
+        let mut bytes = [0u8; 20];
+        hex::decode_to_slice(hex_str, &mut bytes)
+            .map_err(|e| Error::InvalidInput(format!("Invalid verifier address '{}': {}", s, e)))?;
+        Ok(Self(bytes))
+    }
+}
+
+// Proving statistics tracked per circuit
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CircuitStats {
+    // Number of successfully generated proofs
+    pub proof_count: u64,
+    // Unix timestamp (seconds) of the most recent successful proof
+    pub last_proven_at: u64,
+    // Cumulative wall-clock proving time in milliseconds
+    pub total_proving_ms: u64,
 }
 
 // The generated proof and public inputs
@@ -52,6 +235,242 @@ pub struct ProofResult {
     pub proof_bytes: Vec<u8>,
     // Public inputs, each as a byte vector, matching Solidity's bytes[]
     pub public_inputs: Vec<Vec<u8>>,
+    // How the proof was produced; omitted if the operator disables proof metadata
+    pub metadata: Option<ProofMetadata>,
+}
+
+// What a proof job returns: the proof itself, or a pointer to a stored copy
+// if the proof is too large for a job result
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ProofOutput {
+    Proof(ProofResult),
+    Pointer(ProofPointer),
+}
+
+// Reference to a proof kept in the operator's proof store
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ProofPointer {
+    // SHA-256 of the bincode-encoded `ProofResult`
+    pub proof_hash: [u8; 32],
+    // Size of the encoded proof in bytes
+    pub size: u64,
+    // Where clients can fetch the proof, if the operator publishes one
+    pub retrieval_endpoint: Option<String>,
+}
+
+// Public data of a registered circuit, exported so it can be mirrored elsewhere.
+// Proving keys are never exported.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CircuitExport {
+    pub circuit_id: CircuitId,
+    pub name: String,
+    pub circuit_type: CircuitType,
+    // Bundle members, by role
+    pub artifacts: BTreeMap<ArtifactRole, Vec<u8>>,
+    // Verification key of each backend, in registration order
+    pub verification_keys: Vec<(ProvingBackend, Vec<u8>)>,
+    pub num_public_inputs: Option<u32>,
+    pub noir_abi: Option<NoirAbi>,
+    // `CircuitInfo::updated_at` of the exported keys; changes if the circuit is rekeyed
+    pub updated_at: u64,
+}
+
+// One chunk of a bincode-encoded `CircuitExport`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ExportChunk {
+    pub index: u32,
+    pub chunk_count: u32,
+    // Size of the whole encoded export in bytes
+    pub total_size: u64,
+    // SHA-256 of the whole encoded export
+    pub checksum: [u8; 32],
+    // `CircuitExport::updated_at`, so clients notice a rekey between chunks
+    pub updated_at: u64,
+    // Base64 of this chunk's bytes
+    pub data: String,
+}
+
+// A proof kept in the proof store
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StoredProof {
+    // Circuit the proof was generated for
+    pub circuit_id: CircuitId,
+    pub proof: ProofResult,
+    // Unix timestamp (seconds) at which the proof was stored
+    pub stored_at: u64,
+}
+
+// Phase of a proof job at which it failed
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ProofPhase {
+    // Establishing the MPC session (liveness check, config exchange, MPC-Net)
+    SessionSetup,
+    // Running the proving protocol on the established session
+    Proving,
+    // Checking the generated proof locally
+    Verification,
+}
+
+// A proof job that failed after its participants were chosen, kept in the
+// dead-letter store for diagnosis and retries
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ProofFailure {
+    pub call_id: u64,
+    pub circuit_id: CircuitId,
+    pub proving_backend: ProvingBackend,
+    // Public keys of the session's operators, in party index order
+    pub participants: Vec<PublicKeyBytes>,
+    pub session_id: String,
+    pub phase: ProofPhase,
+    pub error: String,
+    // Unix timestamp (seconds) of the failure
+    pub failed_at: u64,
+}
+
+// Provenance of a generated proof
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ProofMetadata {
+    // Public keys of the operators that took part, in party index order
+    pub participants: Vec<PublicKeyBytes>,
+    pub proving_backend: ProvingBackend,
+    // Wall-clock duration of the MPC proving phase in milliseconds
+    pub proving_ms: u64,
+    // Unix timestamp (seconds) at which the proof was generated
+    pub generated_at: u64,
+}
+
+// Phase-level timing breakdown of a proof job, returned in benchmark mode.
+// All durations are wall-clock milliseconds.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ProofTimings {
+    // Decoding or converting the witness input
+    pub witness_load_ms: u64,
+    // Whether the proof was served from the proof cache; no MPC phases ran then
+    pub from_cache: bool,
+    // Whether a cached MPC session was reused; config exchange and MPC-Net setup are zero then
+    pub session_reused: bool,
+    // Commit-reveal config exchange, including the liveness check if enabled
+    pub config_exchange_ms: u64,
+    // Establishing the MPC-Net mesh
+    pub mpc_establish_ms: u64,
+    pub proving_ms: u64,
+    // Local verification of the proof. Only set when local proof verification is
+    // enabled and the backend has a fast verifier; None for cached proofs and
+    // whenever the proof was not verified
+    pub verification_ms: Option<u64>,
+    // The whole job, end to end
+    pub total_ms: u64,
+}
+
+// Snapshot of an established MPC session, for introspection
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SessionStatus {
+    pub session_id: String,
+    pub num_participants: u16,
+    // Unix timestamp (seconds) at which the session was established
+    pub established_at: u64,
+    // Unix timestamp (seconds) at which the session was last handed out
+    pub last_used_at: u64,
+    // Number of jobs that reused the session after it was established
+    pub reuse_count: u64,
+    // Unix timestamp (seconds) of the proof deadline the participants last agreed on
+    pub deadline: Option<u64>,
+}
+
+// Difference between the circuits registered on-chain and those in the local
+// CircuitStore, from the sync_circuits job. Nothing is downloaded to fix it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CircuitSyncReport {
+    // Registered on-chain but missing locally; these need re-registering
+    pub missing_locally: Vec<CircuitId>,
+    // Stored locally but never registered on-chain
+    pub missing_onchain: Vec<CircuitId>,
+    // Number of circuits present in both
+    pub in_sync: u32,
+}
+
+impl CircuitSyncReport {
+    /// Compares the `onchain` circuit IDs against the `local` ones. Both lists
+    /// of the report are sorted and free of duplicates.
+    pub fn diff(onchain: &[CircuitId], local: &[CircuitId]) -> Self {
+        let onchain: BTreeSet<&CircuitId> = onchain.iter().collect();
+        let local: BTreeSet<&CircuitId> = local.iter().collect();
+        Self {
+            missing_locally: onchain.difference(&local).map(|id| **id).collect(),
+            missing_onchain: local.difference(&onchain).map(|id| **id).collect(),
+            in_sync: onchain.intersection(&local).count() as u32,
+        }
+    }
+
+    /// Returns whether the local store holds exactly the on-chain circuits.
+    pub fn is_in_sync(&self) -> bool {
+        self.missing_locally.is_empty() && self.missing_onchain.is_empty()
+    }
+}
+
+// A proof kept in the proof cache
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CachedProof {
+    pub proof: ProofResult,
+    // Unix timestamp (seconds) at which the proof was cached
+    pub cached_at: u64,
+    // `CircuitInfo::updated_at` when the proof was generated; a rekey invalidates the entry
+    pub circuit_updated_at: u64,
+}
+
+// Per-item outcomes of a batch job, in request order.
+// A failing item is recorded with its error and does not affect the others.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct BatchResult<T> {
+    // One entry per item
+    pub items: Vec<BatchItem<T>>,
+    pub succeeded: u32,
+    pub failed: u32,
+}
+
+// Outcome of one batch item, under the name the request gave it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct BatchItem<T> {
+    pub name: String,
+    // Errors are carried as their display string
+    pub result: std::result::Result<T, String>,
+}
+
+impl<T> BatchResult<T> {
+    /// Returns whether every item succeeded.
+    pub fn is_complete(&self) -> bool {
+        self.failed == 0
+    }
+}
+
+impl<T> Default for BatchResult<T> {
+    fn default() -> Self {
+        Self {
+            items: Vec::new(),
+            succeeded: 0,
+            failed: 0,
+        }
+    }
+}
+
+impl<T> FromIterator<(String, Result<T>)> for BatchResult<T> {
+    fn from_iter<I: IntoIterator<Item = (String, Result<T>)>>(results: I) -> Self {
+        let mut batch = Self::default();
+        for (name, result) in results {
+            let result = match result {
+                Ok(value) => {
+                    batch.succeeded += 1;
+                    Ok(value)
+                }
+                Err(e) => {
+                    batch.failed += 1;
+                    Err(e.to_string())
+                }
+            };
+            batch.items.push(BatchItem { name, result });
+        }
+        batch
+    }
 }
 
 // --- Helper for Job Arguments ---
@@ -64,7 +483,31 @@ pub type OptionalJsonParams = Option<serde_json::Value>;
 #[serde(untagged)] // Allows accepting either a direct JSON string or a URI
 pub enum WitnessInput {
     Json(String),
-    Uri(String), // Assume URI points to a JSON file
+    Uri(String), // Assume URI points to a JSON file, or inlines it as a data: URI
+    // Noir-style Prover.toml, checked against the circuit's ABI
+    ProverToml { prover_toml: String },
+}
+
+/// Source of the circuit artifact in register_circuit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)] // Allows accepting either a URL string or the raw artifact bytes
+pub enum ArtifactSource {
+    Url(String),
+    Inline(Vec<u8>),           // Small artifacts embedded directly in the job call
+    Upload { upload_id: u64 }, // A finalized chunked upload, consumed by the registration
+    // Bundle members fetched from separate URLs, by role; downloaded concurrently
+    Bundle(BTreeMap<ArtifactRole, String>),
+}
+
+impl std::fmt::Display for ArtifactSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArtifactSource::Url(url) => write!(f, "url:{}", url),
+            ArtifactSource::Inline(data) => write!(f, "inline:{} bytes", data.len()),
+            ArtifactSource::Upload { upload_id } => write!(f, "upload:{}", upload_id),
+            ArtifactSource::Bundle(members) => write!(f, "bundle:{} members", members.len()),
+        }
+    }
 }
 
 // Job ID constants (defined in lib.rs and jobs/mod.rs, but good to reference)
@@ -77,8 +520,8 @@ pub enum WitnessInput {
 pub struct MpcNetAnnounce<K: KeyType> {
     /// The public key of the announcing operator.
     pub public_key: K::Public,
-    /// The SocketAddr the operator's mpc-net instance is listening on.
-    pub listen_addr: SocketAddr,
+    /// The `hostname:port` peers dial to reach the operator's mpc-net listener.
+    pub listen_addr: String,
     /// The filesystem path to the operator's public certificate (DER format).
     /// Peers need this path *relative to their own environment* if they share
     /// a filesystem, or the *content* of the certificate if they don't.
@@ -86,10 +529,18 @@ pub struct MpcNetAnnounce<K: KeyType> {
     /// Sending content is more robust but increases message size.
     /// Let's send the path for now, assuming a standard deployment structure.
     pub cert_path: PathBuf,
-    /// A nonce or timestamp to ensure freshness
+    /// The sender's Unix time in milliseconds, used as a replay-protection nonce.
+    /// See `discovery::DiscoveryState` for how it is validated.
     pub nonce: u64,
 }
 
+/// Discovery messages gossiped over Blueprint's libp2p network.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum MpcAnnounceMessage<K: KeyType> {
+    /// The sender's MPC-Net listener is up and it is ready for sessions
+    Announce(MpcNetAnnounce<K>),
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MpcPeerInfo {
     pub id: u32,
@@ -151,3 +602,91 @@ pub enum MpcDiscoveryMessage {
         timestamp: u64,
     },
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verifier_address_round_trips_hex() {
+        let address: VerifierAddress = "0x00000000000000000000000000000000000000ab"
+            .parse()
+            .unwrap();
+        assert!(!address.is_zero());
+        assert_eq!(
+            address.to_string(),
+            "0x00000000000000000000000000000000000000ab"
+        );
+        assert_eq!(
+            "00000000000000000000000000000000000000ab"
+                .parse::<VerifierAddress>()
+                .unwrap(),
+            address
+        );
+        assert!(VerifierAddress::ZERO.is_zero());
+
+        for invalid in ["0x1234", "0xzz000000000000000000000000000000000000ab", ""] {
+            assert!(matches!(
+                invalid.parse::<VerifierAddress>(),
+                Err(Error::InvalidInput(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn proving_backend_parses_its_name() {
+        for backend in [
+            ProvingBackend::Groth16,
+            ProvingBackend::Plonk,
+            ProvingBackend::UltraHonk,
+        ] {
+            assert_eq!(backend.name().parse::<ProvingBackend>().unwrap(), backend);
+        }
+        assert_eq!(
+            "UltraHonk".parse::<ProvingBackend>().unwrap(),
+            ProvingBackend::UltraHonk
+        );
+        assert!(matches!(
+            "marlin".parse::<ProvingBackend>(),
+            Err(Error::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn batch_result_counts_outcomes() {
+        let batch: BatchResult<u32> = vec![
+            ("one".to_string(), Ok(1)),
+            (
+                "two".to_string(),
+                Err(Error::InvalidInput("bad item".to_string())),
+            ),
+            ("three".to_string(), Ok(3)),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(batch.succeeded, 2);
+        assert_eq!(batch.failed, 1);
+        assert!(!batch.is_complete());
+        assert_eq!(batch.items[0], BatchItem {
+            name: "one".to_string(),
+            result: Ok(1)
+        });
+        assert_eq!(batch.items[1], BatchItem {
+            name: "two".to_string(),
+            result: Err("Invalid Input: bad item".to_string())
+        });
+        assert_eq!(batch.items[2].name, "three");
+        assert_eq!(batch.items[2].result, Ok(3));
+    }
+
+    #[test]
+    fn circuit_sync_report_diffs_both_ways() {
+        let (a, b, c) = ([1u8; 32], [2u8; 32], [3u8; 32]);
+        let report = CircuitSyncReport::diff(&[c, a, a], &[b, a]);
+        assert_eq!(report.missing_locally, vec![c]);
+        assert_eq!(report.missing_onchain, vec![b]);
+        assert_eq!(report.in_sync, 1);
+        assert!(!report.is_in_sync());
+        assert!(CircuitSyncReport::diff(&[a, b], &[b, a]).is_in_sync());
+    }
+}
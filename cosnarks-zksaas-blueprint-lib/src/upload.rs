@@ -0,0 +1,478 @@
+//! Assembly of circuit artifacts uploaded in chunks over several job calls,
+//! for artifacts too large for a single job argument.
+
+use crate::download::validate_artifact_size;
+use crate::error::{Error, Result};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tracing::{debug, info};
+
+const ASSEMBLED_FILENAME: &str = "artifact";
+
+/// At most this many missing chunk indices are listed in an error.
+const MAX_REPORTED_MISSING: usize = 16;
+
+/// Default number of uploads one caller may have open at a time.
+pub const DEFAULT_MAX_UPLOADS_PER_CALLER: usize = 4;
+
+#[derive(Debug)]
+struct Upload {
+    owner: [u8; 32],
+    chunk_count: u32,
+    total_size: u64,
+    received: BTreeSet<u32>,
+    bytes_received: u64,
+    finalized: bool,
+    last_activity: Instant,
+}
+
+/// In-progress chunked uploads, keyed by upload ID.
+///
+/// An upload is begun with its chunk count and total size, receives chunks in
+/// any order, and is finalized once every chunk is present and the assembled
+/// bytes match the announced SHA-256 checksum. `register_circuit` then consumes
+/// it through `ArtifactSource::Upload` once the circuit is stored. Only the
+/// caller that began an upload may add to, finalize, read or remove it, and
+/// each caller may have a bounded number of uploads open. Chunks are
+/// spooled to disk under the store's directory; uploads idle for longer than
+/// the TTL are discarded by `sweep_expired`. Uploads do not survive a restart.
+///
+/// Every method does blocking file I/O under a lock; call them from
+/// `spawn_blocking` on async paths.
+#[derive(Debug, Clone)]
+pub struct ArtifactUploads {
+    dir: PathBuf,
+    max_size: u64,
+    ttl: Duration,
+    max_per_caller: usize,
+    uploads: Arc<Mutex<HashMap<u64, Upload>>>,
+}
+
+impl ArtifactUploads {
+    /// Creates a store spooling chunks under `dir`, accepting artifacts of up
+    /// to `max_size` bytes.
+    pub fn new(dir: PathBuf, max_size: u64, ttl: Duration) -> Self {
+        Self {
+            dir,
+            max_size,
+            ttl,
+            max_per_caller: DEFAULT_MAX_UPLOADS_PER_CALLER,
+            uploads: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Limits how many uploads, finalized ones included, one caller may have
+    /// open at a time. At least one is always allowed.
+    #[must_use]
+    pub fn with_max_per_caller(mut self, max_per_caller: usize) -> Self {
+        self.max_per_caller = max_per_caller.max(1);
+        self
+    }
+
+    /// Begins upload `upload_id` of `total_size` bytes in `chunk_count` chunks,
+    /// owned by `owner`. Only the owner may add chunks and finalize it.
+    ///
+    /// Fails with `Error::InvalidInput` if `owner` already has the maximum
+    /// number of uploads open.
+    pub fn begin(
+        &self,
+        upload_id: u64,
+        owner: [u8; 32],
+        chunk_count: u32,
+        total_size: u64,
+    ) -> Result<()> {
+        validate_artifact_size(total_size, self.max_size)?;
+        if chunk_count == 0 || u64::from(chunk_count) > total_size {
+            return Err(Error::InvalidInput(format!(
+                "An upload of {} bytes cannot have {} non-empty chunks",
+                total_size, chunk_count
+            )));
+        }
+
+        let mut uploads = self.lock_uploads()?;
+        self.discard_expired(&mut uploads);
+        if uploads.contains_key(&upload_id) {
+            return Err(Error::InvalidInput(format!(
+                "Upload {} already exists",
+                upload_id
+            )));
+        }
+        let open = uploads
+            .values()
+            .filter(|upload| upload.owner == owner)
+            .count();
+        if open >= self.max_per_caller {
+            return Err(Error::InvalidInput(format!(
+                "Caller already has {} open uploads, the maximum",
+                open
+            )));
+        }
+        fs::create_dir_all(self.upload_dir(upload_id))?;
+        uploads.insert(upload_id, Upload {
+            owner,
+            chunk_count,
+            total_size,
+            received: BTreeSet::new(),
+            bytes_received: 0,
+            finalized: false,
+            last_activity: Instant::now(),
+        });
+        info!(upload_id, chunk_count, total_size, "Began artifact upload");
+        Ok(())
+    }
+
+    /// Stores chunk `index` of upload `upload_id`. Chunks may arrive in any
+    /// order; re-sending a chunk replaces it. Returns how many chunks are still
+    /// missing.
+    pub fn add_chunk(
+        &self,
+        upload_id: u64,
+        caller: &[u8; 32],
+        index: u32,
+        data: &[u8],
+    ) -> Result<u32> {
+        let mut uploads = self.lock_uploads()?;
+        let upload = owned_upload(&mut uploads, upload_id, caller)?;
+        if upload.finalized {
+            return Err(Error::InvalidInput(format!(
+                "Upload {} is already finalized",
+                upload_id
+            )));
+        }
+        if index >= upload.chunk_count {
+            return Err(Error::InvalidInput(format!(
+                "Chunk index {} is out of range for an upload of {} chunks",
+                index, upload.chunk_count
+            )));
+        }
+        if data.is_empty() {
+            return Err(Error::InvalidInput("Chunks must not be empty".to_string()));
+        }
+
+        let chunk_path = self.upload_dir(upload_id).join(index.to_string());
+        let replaced = if upload.received.contains(&index) {
+            fs::metadata(&chunk_path)?.len()
+        } else {
+            0
+        };
+        let bytes_received = upload.bytes_received - replaced + data.len() as u64;
+        if bytes_received > upload.total_size {
+            return Err(Error::InvalidInput(format!(
+                "Upload {} would exceed its announced size of {} bytes",
+                upload_id, upload.total_size
+            )));
+        }
+
+        fs::write(&chunk_path, data)?;
+        upload.received.insert(index);
+        upload.bytes_received = bytes_received;
+        upload.last_activity = Instant::now();
+        let remaining = upload.chunk_count - upload.received.len() as u32;
+        debug!(upload_id, index, remaining, "Stored artifact chunk");
+        Ok(remaining)
+    }
+
+    /// Assembles upload `upload_id` in chunk order and checks it against
+    /// `checksum`, the SHA-256 of the whole artifact. Returns its size.
+    ///
+    /// Fails with `Error::InvalidInput` listing the missing chunks if any are
+    /// missing, or if the assembled size or checksum do not match; the upload
+    /// stays open so the offending chunks can be re-sent.
+    pub fn finalize(&self, upload_id: u64, caller: &[u8; 32], checksum: &[u8; 32]) -> Result<u64> {
+        let mut uploads = self.lock_uploads()?;
+        let upload = owned_upload(&mut uploads, upload_id, caller)?;
+        if upload.finalized {
+            return Ok(upload.total_size);
+        }
+
+        let missing: Vec<u32> = (0..upload.chunk_count)
+            .filter(|index| !upload.received.contains(index))
+            .collect();
+        if !missing.is_empty() {
+            let listed: Vec<String> = missing
+                .iter()
+                .take(MAX_REPORTED_MISSING)
+                .map(u32::to_string)
+                .collect();
+            return Err(Error::InvalidInput(format!(
+                "Upload {} is missing {} chunk(s): {}{}",
+                upload_id,
+                missing.len(),
+                listed.join(", "),
+                if missing.len() > MAX_REPORTED_MISSING {
+                    ", ..."
+                } else {
+                    ""
+                }
+            )));
+        }
+        if upload.bytes_received != upload.total_size {
+            return Err(Error::InvalidInput(format!(
+                "Upload {} has {} bytes, but {} were announced",
+                upload_id, upload.bytes_received, upload.total_size
+            )));
+        }
+
+        let dir = self.upload_dir(upload_id);
+        let assembled_path = dir.join(ASSEMBLED_FILENAME);
+        let actual = assemble(&dir, upload.chunk_count, &assembled_path)?;
+        if &actual != checksum {
+            fs::remove_file(&assembled_path)?;
+            return Err(Error::InvalidInput(format!(
+                "Upload {} checksum mismatch: expected {}, got {}",
+                upload_id,
+                hex::encode(checksum),
+                hex::encode(actual)
+            )));
+        }
+
+        upload.finalized = true;
+        upload.last_activity = Instant::now();
+        info!(
+            upload_id,
+            size = upload.total_size,
+            "Finalized artifact upload"
+        );
+        Ok(upload.total_size)
+    }
+
+    /// Returns the assembled artifact of finalized upload `upload_id`, keeping
+    /// the upload until `remove` so a failed registration can be retried.
+    /// Fails with `Error::Unauthorized` unless `caller` began the upload.
+    pub fn read(&self, upload_id: u64, caller: &[u8; 32]) -> Result<Vec<u8>> {
+        let mut uploads = self.lock_uploads()?;
+        let upload = owned_upload(&mut uploads, upload_id, caller)?;
+        if !upload.finalized {
+            return Err(Error::InvalidInput(format!(
+                "Upload {} has not been finalized",
+                upload_id
+            )));
+        }
+        upload.last_activity = Instant::now();
+        Ok(fs::read(
+            self.upload_dir(upload_id).join(ASSEMBLED_FILENAME),
+        )?)
+    }
+
+    /// Removes upload `upload_id` and its spooled chunks, once its artifact is
+    /// registered. Removing an unknown upload is a no-op; removing another
+    /// caller's upload fails with `Error::Unauthorized`.
+    pub fn remove(&self, upload_id: u64, caller: &[u8; 32]) -> Result<()> {
+        let mut uploads = self.lock_uploads()?;
+        if !uploads.contains_key(&upload_id) {
+            return Ok(());
+        }
+        owned_upload(&mut uploads, upload_id, caller)?;
+        uploads.remove(&upload_id);
+        fs::remove_dir_all(self.upload_dir(upload_id))?;
+        Ok(())
+    }
+
+    /// Discards every upload idle for longer than the TTL, returning how many
+    /// were discarded.
+    pub fn sweep_expired(&self) -> Result<usize> {
+        let mut uploads = self.lock_uploads()?;
+        Ok(self.discard_expired(&mut uploads))
+    }
+
+    /// Returns the TTL after which idle uploads are discarded.
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    fn discard_expired(&self, uploads: &mut HashMap<u64, Upload>) -> usize {
+        let before = uploads.len();
+        uploads.retain(|upload_id, upload| {
+            let expired = upload.last_activity.elapsed() > self.ttl;
+            if expired {
+                debug!(upload_id, "Discarding expired artifact upload");
+                // Best effort; a leftover directory is overwritten if the ID is reused
+                let _ = fs::remove_dir_all(self.upload_dir(*upload_id));
+            }
+            !expired
+        });
+        before - uploads.len()
+    }
+
+    fn upload_dir(&self, upload_id: u64) -> PathBuf {
+        self.dir.join(upload_id.to_string())
+    }
+
+    fn lock_uploads(&self) -> Result<MutexGuard<'_, HashMap<u64, Upload>>> {
+        self.uploads
+            .lock()
+            .map_err(|_| Error::Internal("Artifact upload lock poisoned".to_string()))
+    }
+}
+
+/// Returns upload `upload_id`, failing unless `caller` began it.
+fn owned_upload<'a>(
+    uploads: &'a mut HashMap<u64, Upload>,
+    upload_id: u64,
+    caller: &[u8; 32],
+) -> Result<&'a mut Upload> {
+    let upload = uploads
+        .get_mut(&upload_id)
+        .ok_or_else(|| Error::InvalidInput(format!("Upload {} not found", upload_id)))?;
+    if &upload.owner != caller {
+        return Err(Error::Unauthorized(format!(
+            "Upload {} belongs to another caller",
+            upload_id
+        )));
+    }
+    Ok(upload)
+}
+
+/// Concatenates chunks `0..chunk_count` of `dir` into `path`, returning the
+/// SHA-256 of the result.
+fn assemble(dir: &Path, chunk_count: u32, path: &Path) -> Result<[u8; 32]> {
+    let mut file = fs::File::create(path)?;
+    let mut hasher = Sha256::new();
+    for index in 0..chunk_count {
+        let chunk = fs::read(dir.join(index.to_string()))?;
+        hasher.update(&chunk);
+        file.write_all(&chunk)?;
+    }
+    file.flush()?;
+    Ok(hasher.finalize().into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OWNER: [u8; 32] = [1u8; 32];
+
+    #[test]
+    fn assembles_out_of_order_chunks() {
+        let dir = tempfile::tempdir().unwrap();
+        let uploads = ArtifactUploads::new(dir.path().to_path_buf(), 16, Duration::from_secs(60));
+        let checksum: [u8; 32] = Sha256::digest(b"hello world").into();
+
+        uploads.begin(7, OWNER, 3, 11).unwrap();
+        assert_eq!(uploads.add_chunk(7, &OWNER, 2, b"rld").unwrap(), 2);
+        assert_eq!(uploads.add_chunk(7, &OWNER, 0, b"hello").unwrap(), 1);
+        assert!(matches!(
+            uploads.add_chunk(7, &[2u8; 32], 1, b" wo"),
+            Err(Error::Unauthorized(_))
+        ));
+        assert!(matches!(
+            uploads.add_chunk(7, &OWNER, 3, b"!"),
+            Err(Error::InvalidInput(_))
+        ));
+
+        // Chunk 1 is still missing
+        let err = uploads.finalize(7, &OWNER, &checksum).unwrap_err();
+        assert!(err.to_string().contains("missing 1 chunk(s): 1"));
+        assert!(matches!(
+            uploads.read(7, &OWNER),
+            Err(Error::InvalidInput(_))
+        ));
+
+        assert_eq!(uploads.add_chunk(7, &OWNER, 1, b" wo").unwrap(), 0);
+        assert!(matches!(
+            uploads.finalize(7, &OWNER, &[0u8; 32]),
+            Err(Error::InvalidInput(_))
+        ));
+        assert_eq!(uploads.finalize(7, &OWNER, &checksum).unwrap(), 11);
+        // Reading keeps the upload until it is removed
+        assert_eq!(uploads.read(7, &OWNER).unwrap(), b"hello world");
+        assert_eq!(uploads.read(7, &OWNER).unwrap(), b"hello world");
+        uploads.remove(7, &OWNER).unwrap();
+        assert!(!dir.path().join("7").exists());
+        assert!(matches!(
+            uploads.read(7, &OWNER),
+            Err(Error::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn only_the_owner_consumes_an_upload() {
+        let dir = tempfile::tempdir().unwrap();
+        let uploads = ArtifactUploads::new(dir.path().to_path_buf(), 16, Duration::from_secs(60));
+        let checksum: [u8; 32] = Sha256::digest(b"artifact").into();
+        let other = [2u8; 32];
+
+        uploads.begin(3, OWNER, 1, 8).unwrap();
+        uploads.add_chunk(3, &OWNER, 0, b"artifact").unwrap();
+        uploads.finalize(3, &OWNER, &checksum).unwrap();
+
+        // Another account can neither register from the upload nor delete it
+        assert!(matches!(
+            uploads.read(3, &other),
+            Err(Error::Unauthorized(_))
+        ));
+        assert!(matches!(
+            uploads.remove(3, &other),
+            Err(Error::Unauthorized(_))
+        ));
+        assert!(dir.path().join("3").exists());
+        assert_eq!(uploads.read(3, &OWNER).unwrap(), b"artifact");
+        uploads.remove(3, &OWNER).unwrap();
+    }
+
+    #[test]
+    fn caps_open_uploads_per_caller() {
+        let dir = tempfile::tempdir().unwrap();
+        let uploads = ArtifactUploads::new(dir.path().to_path_buf(), 16, Duration::from_secs(60))
+            .with_max_per_caller(2);
+
+        uploads.begin(1, OWNER, 1, 4).unwrap();
+        uploads.begin(2, OWNER, 1, 4).unwrap();
+        assert!(matches!(
+            uploads.begin(3, OWNER, 1, 4),
+            Err(Error::InvalidInput(_))
+        ));
+        // Other callers are not affected, and a removed upload frees its slot
+        uploads.begin(3, [2u8; 32], 1, 4).unwrap();
+        uploads.remove(1, &OWNER).unwrap();
+        uploads.begin(4, OWNER, 1, 4).unwrap();
+    }
+
+    #[test]
+    fn sweeps_idle_uploads() {
+        let dir = tempfile::tempdir().unwrap();
+        let uploads = ArtifactUploads::new(dir.path().to_path_buf(), 16, Duration::from_millis(50));
+
+        uploads.begin(1, OWNER, 1, 4).unwrap();
+        assert_eq!(uploads.sweep_expired().unwrap(), 0);
+        std::thread::sleep(Duration::from_millis(100));
+        uploads.begin(2, OWNER, 1, 4).unwrap();
+
+        // Upload 1 was already discarded by begin
+        assert_eq!(uploads.sweep_expired().unwrap(), 0);
+        assert!(!dir.path().join("1").exists());
+        std::thread::sleep(Duration::from_millis(100));
+        assert_eq!(uploads.sweep_expired().unwrap(), 1);
+        assert!(!dir.path().join("2").exists());
+        assert!(matches!(
+            uploads.add_chunk(2, &OWNER, 0, b"data"),
+            Err(Error::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn enforces_size_cap() {
+        let dir = tempfile::tempdir().unwrap();
+        let uploads = ArtifactUploads::new(dir.path().to_path_buf(), 8, Duration::from_secs(60));
+
+        assert!(matches!(
+            uploads.begin(1, OWNER, 2, 9),
+            Err(Error::InvalidInput(_))
+        ));
+        uploads.begin(1, OWNER, 2, 8).unwrap();
+        uploads.add_chunk(1, &OWNER, 0, b"12345").unwrap();
+        assert!(matches!(
+            uploads.add_chunk(1, &OWNER, 1, b"6789"),
+            Err(Error::InvalidInput(_))
+        ));
+        // Replacing a chunk frees its bytes again
+        uploads.add_chunk(1, &OWNER, 0, b"1234").unwrap();
+        assert_eq!(uploads.add_chunk(1, &OWNER, 1, b"5678").unwrap(), 0);
+    }
+}
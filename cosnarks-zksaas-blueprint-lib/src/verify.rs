@@ -0,0 +1,73 @@
+//! Local verification of generated proofs against a circuit's verification key.
+
+use crate::error::{Error, Result};
+use crate::types::{ProofResult, ProvingBackend};
+
+/// Returns whether verifying a `backend` proof is cheap enough to run after
+/// every proof. Groth16 and Plonk verify with a constant number of pairings;
+/// UltraHonk verification grows with the circuit.
+#[must_use]
+pub fn has_fast_verifier(backend: &ProvingBackend) -> bool {
+    matches!(backend, ProvingBackend::Groth16 | ProvingBackend::Plonk)
+}
+
+/// Checks `proof` against the verification key `vk`, in-process and
+/// without any MPC session.
+///
+/// Fails with `Error::ProofSelfCheckFailed` if there is no verification key,
+/// the proof is empty or a public input is empty. This is a shape check, not
+/// a cryptographic one: the backend's pairing check can only run once real
+/// proving (co-circom / co-noir) replaces the placeholder proofs, so the check
+/// is off unless `LOCAL_PROOF_VERIFICATION` is set.
+pub fn verify_proof(backend: &ProvingBackend, vk: &[u8], proof: &ProofResult) -> Result<()> {
+    if vk.is_empty() {
+        return Err(Error::ProofSelfCheckFailed(format!(
+            "No {:?} verification key to verify against",
+            backend
+        )));
+    }
+    if proof.proof_bytes.is_empty() {
+        return Err(Error::ProofSelfCheckFailed(format!(
+            "{:?} proof is empty",
+            backend
+        )));
+    }
+    if proof.public_inputs.iter().any(Vec::is_empty) {
+        return Err(Error::ProofSelfCheckFailed(format!(
+            "{:?} proof has an empty public input",
+            backend
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_malformed_proofs() {
+        let proof = ProofResult {
+            proof_bytes: b"proof".to_vec(),
+            public_inputs: vec![b"input".to_vec()],
+            metadata: None,
+        };
+        let backend = ProvingBackend::Groth16;
+        verify_proof(&backend, b"vk", &proof).unwrap();
+
+        let empty = ProofResult {
+            proof_bytes: Vec::new(),
+            ..proof.clone()
+        };
+        let empty_input = ProofResult {
+            public_inputs: vec![Vec::new()],
+            ..proof.clone()
+        };
+        for (vk, proof) in [(&b""[..], &proof), (b"vk", &empty), (b"vk", &empty_input)] {
+            assert!(matches!(
+                verify_proof(&backend, vk, proof),
+                Err(Error::ProofSelfCheckFailed(_))
+            ));
+        }
+    }
+}
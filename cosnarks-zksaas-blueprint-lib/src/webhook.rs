@@ -0,0 +1,199 @@
+//! Out-of-band delivery of generated proofs to an operator-configured webhook.
+
+use crate::error::{Error, Result};
+use crate::types::{CircuitId, ProofResult};
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, warn};
+use url::Url;
+
+/// Header carrying `sha256=<hex HMAC-SHA256 of the body>` when a secret is configured.
+pub const SIGNATURE_HEADER: &str = "X-Cosnarks-Signature";
+
+/// JSON body POSTed to the webhook for every generated proof.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProofDelivery<'a> {
+    /// Hex-encoded ID of the proven circuit
+    pub circuit_id: String,
+    pub call_id: u64,
+    pub proof: &'a ProofResult,
+}
+
+/// POSTs proofs to a webhook, retrying failed deliveries with exponential backoff.
+#[derive(Debug, Clone)]
+pub struct ProofWebhook {
+    client: reqwest::Client,
+    url: Url,
+    secret: Option<Vec<u8>>,
+    max_retries: u32,
+    retry_delay: Duration,
+}
+
+impl ProofWebhook {
+    /// Creates a webhook for `url`. Bodies are signed with `secret` if set.
+    pub fn new(url: Url, secret: Option<Vec<u8>>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url,
+            secret,
+            max_retries: 3,
+            retry_delay: Duration::from_secs(1),
+        }
+    }
+
+    /// Sets how often a failed delivery is retried and the delay before the
+    /// first retry, which doubles on every further attempt.
+    #[must_use]
+    pub fn with_retries(mut self, max_retries: u32, retry_delay: Duration) -> Self {
+        self.max_retries = max_retries;
+        self.retry_delay = retry_delay;
+        self
+    }
+
+    /// Returns the signature header value for `body`, if a secret is configured.
+    pub fn sign(&self, body: &[u8]) -> Option<String> {
+        let secret = self.secret.as_ref()?;
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts keys of any length");
+        mac.update(body);
+        Some(format!(
+            "sha256={}",
+            hex::encode(mac.finalize().into_bytes())
+        ))
+    }
+
+    /// Delivers `proof`, retrying until the webhook answers with a success status.
+    ///
+    /// Fails with the last delivery error once all retries are used up.
+    pub async fn deliver(
+        &self,
+        circuit_id: &CircuitId,
+        call_id: u64,
+        proof: &ProofResult,
+    ) -> Result<()> {
+        let body = serde_json::to_vec(&ProofDelivery {
+            circuit_id: hex::encode(circuit_id),
+            call_id,
+            proof,
+        })?;
+        let signature = self.sign(&body);
+
+        let mut delay = self.retry_delay;
+        let mut attempt = 0;
+        loop {
+            match self.post(&body, signature.as_deref()).await {
+                Ok(()) => {
+                    debug!(url = %self.url, call_id, attempt, "Delivered proof to webhook");
+                    return Ok(());
+                }
+                Err(e) if attempt < self.max_retries => {
+                    warn!(url = %self.url, call_id, attempt, error = %e, "Proof webhook delivery failed, retrying");
+                    tokio::time::sleep(delay).await;
+                    delay = delay.saturating_mul(2);
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Delivers `proof` in the background, logging if every attempt fails.
+    /// Pending retries are abandoned once `cancel` fires.
+    pub fn spawn_delivery(
+        &self,
+        circuit_id: CircuitId,
+        call_id: u64,
+        proof: ProofResult,
+        cancel: CancellationToken,
+    ) {
+        let webhook = self.clone();
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = cancel.cancelled() => {}
+                result = webhook.deliver(&circuit_id, call_id, &proof) => {
+                    if let Err(e) = result {
+                        warn!(url = %webhook.url, call_id, error = %e, "Giving up on proof webhook delivery");
+                    }
+                }
+            }
+        });
+    }
+
+    async fn post(&self, body: &[u8], signature: Option<&str>) -> Result<()> {
+        let mut request = self
+            .client
+            .post(self.url.clone())
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.to_vec());
+        if let Some(signature) = signature {
+            request = request.header(SIGNATURE_HEADER, signature);
+        }
+        let response = request.send().await?;
+        if !response.status().is_success() {
+            return Err(Error::NetworkError(format!(
+                "Webhook {} answered with status {}",
+                self.url,
+                response.status()
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::prelude::*;
+
+    fn proof() -> ProofResult {
+        ProofResult {
+            proof_bytes: b"proof".to_vec(),
+            public_inputs: vec![b"input".to_vec()],
+            metadata: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn delivers_signed_proof() {
+        let server = MockServer::start();
+        let webhook = ProofWebhook::new(
+            Url::parse(&server.url("/proofs")).unwrap(),
+            Some(b"secret".to_vec()),
+        );
+        let body = serde_json::to_vec(&ProofDelivery {
+            circuit_id: hex::encode([0x42; 32]),
+            call_id: 7,
+            proof: &proof(),
+        })
+        .unwrap();
+        let signature = webhook.sign(&body).unwrap();
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/proofs")
+                .header(SIGNATURE_HEADER, &signature)
+                .body(String::from_utf8(body).unwrap());
+            then.status(204);
+        });
+
+        webhook.deliver(&[0x42; 32], 7, &proof()).await.unwrap();
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn retries_failed_delivery() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(POST).path("/proofs");
+            then.status(503);
+        });
+        let webhook = ProofWebhook::new(Url::parse(&server.url("/proofs")).unwrap(), None)
+            .with_retries(2, Duration::from_millis(1));
+
+        let result = webhook.deliver(&[0x42; 32], 7, &proof()).await;
+        assert!(matches!(result, Err(Error::NetworkError(_))));
+        mock.assert_hits(3);
+    }
+}
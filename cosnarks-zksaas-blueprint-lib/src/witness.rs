@@ -0,0 +1,537 @@
+//! Resolution of witness inputs supplied to proof jobs.
+
+use crate::artifact::{R1csHeader, parse_r1cs_header};
+use crate::error::{Error, Result};
+use crate::noir::{AbiVisibility, NoirAbi};
+use crate::state::CircuitSnapshot;
+use crate::types::{ArtifactRole, CircuitType};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use percent_encoding::percent_decode_str;
+use serde_json::Value as JsonValue;
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+const DATA_URI_SCHEME: &str = "data:";
+const WITNESS_MEDIA_TYPE: &str = "application/json";
+
+/// Returns whether `uri` is an inline `data:` URI.
+#[must_use]
+pub fn is_data_uri(uri: &str) -> bool {
+    uri.get(..DATA_URI_SCHEME.len())
+        .is_some_and(|scheme| scheme.eq_ignore_ascii_case(DATA_URI_SCHEME))
+}
+
+/// Decodes a `data:application/json[;charset=...][;base64],<payload>` witness URI.
+///
+/// The payload is base64 or percent-encoded. URIs with any other media type, or
+/// whose decoded payload exceeds `max_size` bytes or is not UTF-8, are rejected.
+pub fn decode_data_uri(uri: &str, max_size: u64) -> Result<String> {
+    if !is_data_uri(uri) {
+        return Err(Error::InvalidInput(
+            "Witness URI is not a data: URI".to_string(),
+        ));
+    }
+    let (header, payload) = uri[DATA_URI_SCHEME.len()..]
+        .split_once(',')
+        .ok_or_else(|| Error::InvalidInput("Malformed data: URI, missing ','".to_string()))?;
+
+    let mut params = header.split(';');
+    let media_type = params.next().unwrap_or_default().trim();
+    if !media_type.eq_ignore_ascii_case(WITNESS_MEDIA_TYPE) {
+        return Err(Error::InvalidInput(format!(
+            "Unsupported witness media type '{}', expected {}",
+            media_type, WITNESS_MEDIA_TYPE
+        )));
+    }
+    let is_base64 = params.any(|param| param.trim().eq_ignore_ascii_case("base64"));
+
+    // Base64 expands data by 4/3, so this bounds the work before decoding
+    if payload.len() as u64 > max_size.saturating_mul(4) / 3 + 4 {
+        return Err(witness_too_large(payload.len() as u64, max_size));
+    }
+    let decoded = if is_base64 {
+        BASE64
+            .decode(payload)
+            .map_err(|e| Error::InvalidInput(format!("Invalid base64 in data: URI: {}", e)))?
+    } else {
+        percent_decode_str(payload).collect()
+    };
+    if decoded.len() as u64 > max_size {
+        return Err(witness_too_large(decoded.len() as u64, max_size));
+    }
+
+    String::from_utf8(decoded)
+        .map_err(|e| Error::InvalidInput(format!("Witness data is not valid UTF-8: {}", e)))
+}
+
+/// Top-level inputs a circuit's witness must supply, in declaration order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InputSignals {
+    pub public: Vec<String>,
+    pub private: Vec<String>,
+}
+
+impl InputSignals {
+    /// Reads the parameters of a Noir program's `main`; data bus inputs count as public.
+    #[must_use]
+    pub fn from_noir_abi(abi: &NoirAbi) -> Self {
+        let mut signals = Self::default();
+        for param in &abi.parameters {
+            let names = match param.visibility {
+                AbiVisibility::Private => &mut signals.private,
+                AbiVisibility::Public | AbiVisibility::DataBus => &mut signals.public,
+            };
+            names.push(param.name.clone());
+        }
+        signals
+    }
+
+    /// Reads the inputs of a Circom circuit's main component from its `.sym` file.
+    ///
+    /// R1CS wires are ordered as the constant one, outputs, public inputs and then
+    /// private inputs; the `.sym` file names each wire. Array elements such as
+    /// `main.a[1]` make up a single input `a`.
+    pub fn from_circom_sym(header: &R1csHeader, sym: &str) -> Result<Self> {
+        let public_start = 1 + u64::from(header.num_pub_out);
+        let private_start = public_start + u64::from(header.num_pub_in);
+        let private_end = private_start + u64::from(header.num_prv_in);
+
+        let mut signals = Self::default();
+        for line in sym.lines().filter(|line| !line.trim().is_empty()) {
+            let mut fields = line.splitn(4, ',');
+            let (Some(_label), Some(wire), Some(_component), Some(name)) =
+                (fields.next(), fields.next(), fields.next(), fields.next())
+            else {
+                return Err(Error::ArtifactParseError(format!(
+                    "Malformed .sym line '{}'",
+                    line
+                )));
+            };
+            // Wires optimized away are numbered -1
+            let Ok(wire) = wire.trim().parse::<u64>() else {
+                continue;
+            };
+            let names = if (public_start..private_start).contains(&wire) {
+                &mut signals.public
+            } else if (private_start..private_end).contains(&wire) {
+                &mut signals.private
+            } else {
+                continue;
+            };
+            let Some(input) = name.trim().strip_prefix("main.") else {
+                continue;
+            };
+            let input = input.split('[').next().unwrap_or(input);
+            if names.last().map(String::as_str) != Some(input) {
+                names.push(input.to_string());
+            }
+        }
+        Ok(signals)
+    }
+
+    /// Returns the inputs of a circuit, if its artifacts carry signal names:
+    /// the ABI of a Noir program, or the `.sym` member of a Circom bundle.
+    pub fn of_circuit(snapshot: &CircuitSnapshot) -> Result<Option<Self>> {
+        match snapshot.info.circuit_type {
+            CircuitType::Noir => Ok(snapshot.info.noir_abi.as_ref().map(Self::from_noir_abi)),
+            CircuitType::Circom => {
+                let (Some(r1cs), Some(sym)) = (
+                    snapshot.artifacts.get(&ArtifactRole::R1cs),
+                    snapshot.artifacts.get(&ArtifactRole::Sym),
+                ) else {
+                    return Ok(None);
+                };
+                let sym = std::str::from_utf8(sym).map_err(|e| {
+                    Error::ArtifactParseError(format!(".sym file is not valid UTF-8: {}", e))
+                })?;
+                Self::from_circom_sym(&parse_r1cs_header(r1cs)?, sym).map(Some)
+            }
+        }
+    }
+}
+
+/// Checks that a JSON witness supplies every input of the circuit.
+///
+/// Missing public and missing private inputs are reported separately, each with
+/// `Error::InvalidInput` naming the absent signals, so a witness holding only the
+/// public signals fails here rather than deep inside the MPC session.
+pub fn validate_witness(signals: &InputSignals, witness_json: &str) -> Result<()> {
+    let witness: JsonValue = serde_json::from_str(witness_json)
+        .map_err(|e| Error::InvalidInput(format!("Witness is not valid JSON: {}", e)))?;
+    let JsonValue::Object(witness) = witness else {
+        return Err(Error::InvalidInput(
+            "Witness must be a JSON object keyed by input name".to_string(),
+        ));
+    };
+    let missing = |names: &[String]| -> Vec<String> {
+        names
+            .iter()
+            .filter(|name| !witness.contains_key(name.as_str()))
+            .cloned()
+            .collect()
+    };
+
+    let missing_public = missing(&signals.public);
+    if !missing_public.is_empty() {
+        return Err(Error::InvalidInput(format!(
+            "Witness is missing public inputs: {}",
+            missing_public.join(", ")
+        )));
+    }
+    let missing_private = missing(&signals.private);
+    if !missing_private.is_empty() {
+        return Err(Error::InvalidInput(format!(
+            "Witness is missing private inputs: {}",
+            missing_private.join(", ")
+        )));
+    }
+    Ok(())
+}
+
+/// How string values of a Circom witness encode field elements.
+///
+/// JSON numbers are always read as decimal integers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WitnessEncoding {
+    /// `0x`-prefixed strings are hex, all others decimal
+    #[default]
+    Auto,
+    /// Decimal strings only, optionally negative
+    Decimal,
+    /// Hex strings, with or without the `0x` prefix, optionally negative
+    Hex,
+}
+
+impl FromStr for WitnessEncoding {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "decimal" => Ok(Self::Decimal),
+            "hex" => Ok(Self::Hex),
+            other => Err(Error::InvalidInput(format!(
+                "Unknown witness encoding: {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// Rewrites every field element of a JSON witness as a canonical decimal string.
+///
+/// Provers disagree on how they read `"0x10"`, `"16"` and `16`; after this step
+/// they all see `"16"`. `modulus` is the field prime, little-endian as in the
+/// r1cs header. Negative values are reduced modulo the prime. A value outside
+/// the field, or one that is not an integer in `encoding`, fails with
+/// `Error::InvalidInput` naming the input.
+pub fn normalize_witness(
+    witness_json: &str,
+    encoding: WitnessEncoding,
+    modulus: &[u8],
+) -> Result<String> {
+    let mut witness: JsonValue = serde_json::from_str(witness_json)
+        .map_err(|e| Error::InvalidInput(format!("Witness is not valid JSON: {}", e)))?;
+    let JsonValue::Object(inputs) = &mut witness else {
+        return Err(Error::InvalidInput(
+            "Witness must be a JSON object keyed by input name".to_string(),
+        ));
+    };
+    let modulus = trim_le(modulus);
+    for (name, value) in inputs.iter_mut() {
+        normalize_value(value, name, encoding, modulus)?;
+    }
+    Ok(witness.to_string())
+}
+
+fn normalize_value(
+    value: &mut JsonValue,
+    path: &str,
+    encoding: WitnessEncoding,
+    modulus: &[u8],
+) -> Result<()> {
+    let element = match value {
+        JsonValue::Array(elements) => {
+            for (i, element) in elements.iter_mut().enumerate() {
+                normalize_value(element, &format!("{}[{}]", path, i), encoding, modulus)?;
+            }
+            return Ok(());
+        }
+        JsonValue::Object(fields) => {
+            for (name, field) in fields.iter_mut() {
+                normalize_value(field, &format!("{}.{}", path, name), encoding, modulus)?;
+            }
+            return Ok(());
+        }
+        JsonValue::Number(n) if n.is_i64() || n.is_u64() => {
+            parse_field_element(&n.to_string(), WitnessEncoding::Decimal, modulus)
+        }
+        JsonValue::String(s) => parse_field_element(s, encoding, modulus),
+        _ => None,
+    };
+    match element {
+        Some(Ok(element)) => {
+            *value = JsonValue::String(le_to_decimal(&element));
+            Ok(())
+        }
+        Some(Err(())) => Err(Error::InvalidInput(format!(
+            "Witness input {} is not in the field",
+            path
+        ))),
+        None => Err(Error::InvalidInput(format!(
+            "Witness input {} is not a {} integer: {}",
+            path,
+            match encoding {
+                WitnessEncoding::Auto => "decimal or 0x-prefixed hex",
+                WitnessEncoding::Decimal => "decimal",
+                WitnessEncoding::Hex => "hex",
+            },
+            value
+        ))),
+    }
+}
+
+/// Parses an integer literal into a little-endian field element.
+///
+/// Returns `None` if `s` is not a literal in `encoding`, and `Some(Err(()))`
+/// if it is one but lies outside the field.
+fn parse_field_element(
+    s: &str,
+    encoding: WitnessEncoding,
+    modulus: &[u8],
+) -> Option<std::result::Result<Vec<u8>, ()>> {
+    let (negative, s) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s),
+    };
+    let (radix, digits) = match (encoding, s.strip_prefix("0x")) {
+        (WitnessEncoding::Auto | WitnessEncoding::Hex, Some(hex)) => (16, hex),
+        (WitnessEncoding::Hex, None) => (16, s),
+        (WitnessEncoding::Decimal, Some(_)) => return None,
+        (WitnessEncoding::Auto | WitnessEncoding::Decimal, None) => (10, s),
+    };
+    if digits.is_empty() {
+        return None;
+    }
+    let mut magnitude: Vec<u8> = Vec::new();
+    for c in digits.chars() {
+        let digit = c.to_digit(radix)?;
+        mul_add_le(&mut magnitude, radix, digit);
+        // Stop early so huge literals cannot grow without bound
+        if magnitude.len() > modulus.len() {
+            return Some(Err(()));
+        }
+    }
+    let magnitude = trim_le(&magnitude).to_vec();
+    if cmp_le(&magnitude, modulus) != Ordering::Less {
+        return Some(Err(()));
+    }
+    if negative && !magnitude.is_empty() {
+        return Some(Ok(sub_le(modulus, &magnitude)));
+    }
+    Some(Ok(magnitude))
+}
+
+/// `n = n * radix + digit`, on a little-endian byte integer.
+fn mul_add_le(n: &mut Vec<u8>, radix: u32, digit: u32) {
+    let mut carry = digit;
+    for byte in n.iter_mut() {
+        let v = u32::from(*byte) * radix + carry;
+        *byte = v as u8;
+        carry = v >> 8;
+    }
+    while carry > 0 {
+        n.push(carry as u8);
+        carry >>= 8;
+    }
+}
+
+/// `a - b` for little-endian byte integers with `a > b`.
+fn sub_le(a: &[u8], b: &[u8]) -> Vec<u8> {
+    let mut borrow = 0i16;
+    let mut out: Vec<u8> = a
+        .iter()
+        .enumerate()
+        .map(|(i, &byte)| {
+            let mut v = i16::from(byte) - i16::from(b.get(i).copied().unwrap_or(0)) - borrow;
+            borrow = i16::from(v < 0);
+            if v < 0 {
+                v += 256;
+            }
+            v as u8
+        })
+        .collect();
+    out.truncate(trim_le(&out).len());
+    out
+}
+
+fn cmp_le(a: &[u8], b: &[u8]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+/// Drops the most significant zero bytes of a little-endian integer.
+fn trim_le(n: &[u8]) -> &[u8] {
+    let len = n.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+    &n[..len]
+}
+
+fn le_to_decimal(n: &[u8]) -> String {
+    let mut n = trim_le(n).to_vec();
+    let mut digits = Vec::new();
+    while !n.is_empty() {
+        let mut remainder = 0u32;
+        for byte in n.iter_mut().rev() {
+            let v = (remainder << 8) | u32::from(*byte);
+            *byte = (v / 10) as u8;
+            remainder = v % 10;
+        }
+        digits.push(b'0' + remainder as u8);
+        n.truncate(trim_le(&n).len());
+    }
+    if digits.is_empty() {
+        return "0".to_string();
+    }
+    digits.reverse();
+    String::from_utf8(digits).expect("decimal digits are ASCII")
+}
+
+fn witness_too_large(size: u64, max_size: u64) -> Error {
+    Error::InvalidInput(format!(
+        "Witness is {} bytes, exceeding the maximum of {} bytes",
+        size, max_size
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_base64_and_percent_encoded_data_uris() {
+        let witness = r#"{"a":"3","b":"11"}"#;
+        let base64_uri = format!("data:application/json;base64,{}", BASE64.encode(witness));
+        assert_eq!(decode_data_uri(&base64_uri, 1024).unwrap(), witness);
+
+        let percent_uri =
+            "data:application/json;charset=utf-8,%7B%22a%22%3A%223%22%2C%22b%22%3A%2211%22%7D";
+        assert_eq!(decode_data_uri(percent_uri, 1024).unwrap(), witness);
+    }
+
+    #[test]
+    fn rejects_invalid_data_uris() {
+        for uri in [
+            "https://example.com/witness.json",
+            "data:application/json;base64",
+            "data:text/plain,{}",
+            "data:application/json;base64,not*base64",
+            "data:application/json,%FF",
+        ] {
+            assert!(
+                matches!(decode_data_uri(uri, 1024), Err(Error::InvalidInput(_))),
+                "accepted {}",
+                uri
+            );
+        }
+        let large = format!("data:application/json,{}", "1".repeat(65));
+        assert!(matches!(
+            decode_data_uri(&large, 64),
+            Err(Error::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn reports_missing_private_inputs_separately() {
+        let header = R1csHeader {
+            version: 1,
+            field_size: 32,
+            prime: Vec::new(),
+            num_wires: 6,
+            num_pub_out: 1,
+            num_pub_in: 1,
+            num_prv_in: 3,
+            num_labels: 8,
+            num_constraints: 2,
+        };
+        let sym = "1,1,0,main.out\n2,2,0,main.x\n3,3,0,main.secret[0]\n\
+                   4,4,0,main.secret[1]\n5,5,0,main.salt\n6,-1,0,main.tmp\n7,6,1,main.sub.in\n";
+        let signals = InputSignals::from_circom_sym(&header, sym).unwrap();
+        assert_eq!(signals.public, ["x"]);
+        assert_eq!(signals.private, ["secret", "salt"]);
+
+        assert!(validate_witness(&signals, r#"{"x":"1","secret":["2","3"],"salt":"4"}"#).is_ok());
+        match validate_witness(&signals, r#"{"x":"1"}"#) {
+            Err(Error::InvalidInput(msg)) => {
+                assert_eq!(msg, "Witness is missing private inputs: secret, salt")
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match validate_witness(&signals, r#"{"secret":["2","3"],"salt":"4"}"#) {
+            Err(Error::InvalidInput(msg)) => assert!(msg.contains("public inputs: x")),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(matches!(
+            validate_witness(&signals, "[1, 2]"),
+            Err(Error::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn normalizes_field_elements_to_canonical_decimal() {
+        // BN254 scalar field prime, little-endian
+        let mut bn254 =
+            hex::decode("30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001")
+                .unwrap();
+        bn254.reverse();
+        let witness = r#"{"a":"0x10","b":16,"c":["16",["-1"]],"d":{"e":"0"}}"#;
+        let normalized = normalize_witness(witness, WitnessEncoding::Auto, &bn254).unwrap();
+        let normalized: JsonValue = serde_json::from_str(&normalized).unwrap();
+        assert_eq!(
+            normalized,
+            serde_json::json!({
+                "a": "16",
+                "b": "16",
+                "c": ["16", ["21888242871839275222246405745257275088548364400416034343698204186575808495616"]],
+                "d": {"e": "0"},
+            })
+        );
+        assert_eq!(
+            normalize_witness(r#"{"a":"ff"}"#, WitnessEncoding::Hex, &bn254).unwrap(),
+            r#"{"a":"255"}"#
+        );
+
+        let modulus =
+            "21888242871839275222246405745257275088548364400416034343698204186575808495617";
+        for (witness, encoding) in [
+            (format!(r#"{{"a":"{}"}}"#, modulus), WitnessEncoding::Auto),
+            (
+                format!(r#"{{"a":["1","-{}"]}}"#, modulus),
+                WitnessEncoding::Auto,
+            ),
+            (
+                format!(r#"{{"a":"0x{}"}}"#, "f".repeat(70)),
+                WitnessEncoding::Auto,
+            ),
+            (r#"{"a":"0x10"}"#.to_string(), WitnessEncoding::Decimal),
+            (r#"{"a":"1.5"}"#.to_string(), WitnessEncoding::Auto),
+            (r#"{"a":1.5}"#.to_string(), WitnessEncoding::Auto),
+            (r#"{"a":true}"#.to_string(), WitnessEncoding::Auto),
+            (r#"{"a":""}"#.to_string(), WitnessEncoding::Hex),
+        ] {
+            assert!(
+                matches!(
+                    normalize_witness(&witness, encoding, &bn254),
+                    Err(Error::InvalidInput(_))
+                ),
+                "accepted {}",
+                witness
+            );
+        }
+        assert_eq!(
+            "HEX".parse::<WitnessEncoding>().unwrap(),
+            WitnessEncoding::Hex
+        );
+    }
+}
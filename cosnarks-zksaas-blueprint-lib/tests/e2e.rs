@@ -10,19 +10,22 @@ use blueprint_sdk::{
 };
 use cosnarks_zksaas_blueprint_lib::{
     context::CosnarksContext,
-    error::{Error, Result},
+    error::Result,
     jobs::{
-        GENERATE_PROOF_JOB_ID, REGISTER_CIRCUIT_JOB_ID, generate_proof::generate_proof_job,
-        register_circuit::register_circuit,
+        GENERATE_PROOF_JOB_ID, REGISTER_CIRCUIT_JOB_ID,
+        generate_proof::generate_proof_job,
+        register_circuit::{RegisteredBackend, register_circuit},
+    },
+    network::cleanup_mpc_material,
+    prover::{MockProver, ProverBackend},
+    types::{
+        ArtifactSource, CircuitId, CircuitType, ProofOutput, ProofTimings, ProvingBackend,
+        WitnessInput,
     },
-    types::{CircuitId, CircuitType, ProofResult, ProvingBackend, WitnessInput},
 };
 use httpmock::prelude::*;
-use std::fs;
-use std::io::Write;
 use std::sync::Arc;
 use tempfile::TempDir;
-use url::Url;
 
 // --- Test Setup ---
 // Helper to create a dummy artifact file server
@@ -35,10 +38,33 @@ async fn setup_mock_artifact_server(mock_server: &MockServer, path: &str, conten
     });
 }
 
+// Builds a minimal Circom R1CS file containing only a header section
+fn minimal_r1cs(num_pub_out: u32, num_pub_in: u32) -> Vec<u8> {
+    let mut header = Vec::new();
+    header.extend_from_slice(&32u32.to_le_bytes()); // field size
+    let mut prime =
+        hex::decode("30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001").unwrap();
+    prime.reverse(); // bn254 scalar field, little-endian
+    header.extend_from_slice(&prime);
+    for value in [4u32, num_pub_out, num_pub_in, 1] {
+        header.extend_from_slice(&value.to_le_bytes()); // wires, pub out, pub in, prv in
+    }
+    header.extend_from_slice(&4u64.to_le_bytes()); // labels
+    header.extend_from_slice(&1u32.to_le_bytes()); // constraints
+
+    let mut data = b"r1cs".to_vec();
+    data.extend_from_slice(&1u32.to_le_bytes()); // version
+    data.extend_from_slice(&1u32.to_le_bytes()); // number of sections
+    data.extend_from_slice(&1u32.to_le_bytes()); // header section type
+    data.extend_from_slice(&(header.len() as u64).to_le_bytes());
+    data.extend_from_slice(&header);
+    data
+}
+
 // --- E2E Test ---
 
 #[tokio::test]
-async fn test_e2e_circuit_registration_and_proof() -> Result<(), Error> {
+async fn test_e2e_circuit_registration_and_proof() -> Result<()> {
     blueprint_sdk::testing::utils::setup_log();
 
     // 1. Setup Test Environment
@@ -51,9 +77,9 @@ async fn test_e2e_circuit_registration_and_proof() -> Result<(), Error> {
 
     // Mock artifact server
     let server = MockServer::start();
-    let artifact_content = b"dummy circuit artifact data";
+    let artifact_content = minimal_r1cs(1, 1);
     let artifact_path = "/test_circuit.r1cs";
-    setup_mock_artifact_server(&server, artifact_path, artifact_content).await;
+    setup_mock_artifact_server(&server, artifact_path, &artifact_content).await;
     let artifact_url = server.url(artifact_path);
 
     // Setup MPC Network Env Vars (Required by CosnarksContext::new)
@@ -65,18 +91,13 @@ async fn test_e2e_circuit_registration_and_proof() -> Result<(), Error> {
         std::env::set_var("MPC_LISTEN_DNS", &mpc_listen_dns);
         std::env::set_var("MPC_KEY_PATH", mpc_key_path_rel);
         std::env::set_var("MPC_CERT_PATH", mpc_cert_path_rel);
+        std::env::set_var("ALLOW_PROOF_RNG_SEED", "true");
+        std::env::set_var("MOCK_PROVER", "true");
+        std::env::set_var("PROOF_METADATA", "true");
     }
 
-    // Create dummy cert/key files in the data dir
-    let cert_dir = data_dir.join("mpc_certs");
-    fs::create_dir_all(&cert_dir).expect("Failed to create dummy cert dir");
-    fs::write(cert_dir.join("test_key.der"), b"dummy key").expect("Failed to write dummy key");
-    fs::write(cert_dir.join("test_cert.der"), b"dummy cert").expect("Failed to write dummy cert");
-
-    // Setup context and test environment
-    let mut context = CosnarksContext::<SpSr25519>::new(Arc::new(env))
-        .await
-        .map_err(|e| blueprint_sdk::Error::Other(e.to_string()))?;
+    // Setup context (generates the MPC cert/key on demand) and test environment
+    let _context = CosnarksContext::<SpSr25519>::new(Arc::new(env)).await?;
     const N: usize = 3;
     let (mut test_env, service_id, _) = harness.setup_services::<N>(false).await?;
 
@@ -96,10 +117,14 @@ async fn test_e2e_circuit_registration_and_proof() -> Result<(), Error> {
     let backend = ProvingBackend::Groth16;
 
     let register_inputs = vec![
-        to_field(&name)?,         // name
-        to_field(&circuit_type)?, // circuit_type
-        to_field(&backend)?,      // proving_backend
-        to_field(&artifact_url)?, // artifact_url
+        to_field(&name)?,                              // name
+        to_field(&circuit_type)?,                      // circuit_type
+        to_field(&vec![backend.clone()])?,             // proving_backends
+        to_field(&ArtifactSource::Url(artifact_url))?, // artifact_source
+        to_field(&Vec::<Vec<u8>>::new())?,             // pinned_operators (none)
+        to_field(&Vec::<[u8; 32]>::new())?,            // dependencies (none)
+        to_field(&None::<String>)?,                    // pk_url (generate keys)
+        to_field(&None::<String>)?,                    // vk_url
     ];
 
     println!("Submitting register_circuit job...");
@@ -116,19 +141,27 @@ async fn test_e2e_circuit_registration_and_proof() -> Result<(), Error> {
         "Register circuit job failed to produce output"
     );
     let output_fields = result.output.unwrap();
-    assert_eq!(output_fields.len(), 3, "Expected 3 output fields");
+    assert_eq!(output_fields.len(), 2, "Expected 2 output fields");
 
     let circuit_id_bytes: Vec<u8> = from_field(&output_fields[0])?;
-    let verifier_addr_bytes: Vec<u8> = from_field(&output_fields[1])?;
-    let vk_bytes: Vec<u8> = from_field(&output_fields[2])?;
+    let registered_backends: Vec<RegisteredBackend> = from_field(&output_fields[1])?;
 
     assert_eq!(circuit_id_bytes.len(), 32, "Circuit ID should be 32 bytes");
     assert_eq!(
-        verifier_addr_bytes.len(),
-        20,
-        "Verifier address should be 20 bytes"
+        registered_backends.len(),
+        1,
+        "Expected one registered backend"
+    );
+    let registered = &registered_backends[0];
+    assert_eq!(registered.backend, backend);
+    assert!(
+        registered.verifier_address.is_none(),
+        "Placeholder setup deploys no verifier contract"
+    );
+    assert!(
+        !registered.verification_key.is_empty(),
+        "Verification key should not be empty"
     );
-    assert!(!vk_bytes.is_empty(), "Verification key should not be empty");
     println!(
         "Circuit registered successfully: ID={}",
         hex::encode(&circuit_id_bytes)
@@ -150,11 +183,13 @@ async fn test_e2e_circuit_registration_and_proof() -> Result<(), Error> {
         "Circuit artifact not found"
     );
     assert!(
-        artifact_store_path.join("proving.key").exists(),
+        artifact_store_path.join("groth16/proving.key").exists(),
         "Proving key not found"
     );
     assert!(
-        artifact_store_path.join("verification.key").exists(),
+        artifact_store_path
+            .join("groth16/verification.key")
+            .exists(),
         "Verification key not found"
     );
 
@@ -163,13 +198,18 @@ async fn test_e2e_circuit_registration_and_proof() -> Result<(), Error> {
     let witness_input = WitnessInput::Json(witness_json);
 
     let proof_inputs = vec![
-        to_field(&circuit_id)?,    // circuit_id ([u8; 32])
-        to_field(&witness_input)?, // witness_input (enum)
+        to_field(&circuit_id)?,             // circuit_id ([u8; 32])
+        to_field(&None::<ProvingBackend>)?, // proving_backend (circuit default)
+        to_field(&witness_input)?,          // witness_input (enum)
+        to_field(&true)?,                   // benchmark
+        to_field(&None::<Vec<Vec<u8>>>)?,   // operators (all live operators)
+        to_field(&Some(42u64))?,            // rng_seed (reproducible proof)
+        to_field(&None::<u64>)?,            // deadline (none)
     ];
 
     println!("Submitting generate_proof job...");
     let proof_call = harness
-        .submit_job(service_id, GENERATE_PROOF_JOB_ID, proof_inputs)
+        .submit_job(service_id, GENERATE_PROOF_JOB_ID, proof_inputs.clone())
         .await?;
     println!("Waiting for generate_proof job execution...");
     let proof_result_exec =
@@ -182,14 +222,21 @@ async fn test_e2e_circuit_registration_and_proof() -> Result<(), Error> {
         "Generate proof job failed to produce output"
     );
     let proof_output_fields = proof_result_exec.output.unwrap();
-    // ProofResult encodes to a single field (struct)
+    // ProofOutput (enum) and the benchmark timings
     assert_eq!(
         proof_output_fields.len(),
-        1,
-        "Expected 1 output field for ProofResult"
+        2,
+        "Expected 2 output fields for ProofOutput and ProofTimings"
     );
 
-    let proof_result: ProofResult = from_field(&proof_output_fields[0])?;
+    // Pointer mode is off by default, so the proof comes back inline
+    let ProofOutput::Proof(proof_result) = from_field(&proof_output_fields[0])? else {
+        panic!("Expected the proof inline, got a pointer");
+    };
+    let timings: Option<ProofTimings> = from_field(&proof_output_fields[1])?;
+    let timings = timings.expect("Benchmark mode should return timings");
+    assert!(!timings.from_cache);
+    assert!(timings.total_ms >= timings.proving_ms);
 
     assert!(
         !proof_result.proof_bytes.is_empty(),
@@ -199,14 +246,56 @@ async fn test_e2e_circuit_registration_and_proof() -> Result<(), Error> {
         !proof_result.public_inputs.is_empty(),
         "Public inputs should not be empty"
     );
+    let metadata = proof_result
+        .metadata
+        .expect("Proof metadata should be attached when enabled");
+    assert_eq!(
+        metadata.participants.len(),
+        N,
+        "Expected every operator to participate"
+    );
+    assert_eq!(metadata.proving_backend, ProvingBackend::Groth16);
+    // The mock proof passed through the MPC session verifies against the registered key
+    MockProver
+        .verify(&backend, &registered.verification_key, &proof_result)
+        .expect("Mock proof should verify against the registered verification key");
     println!("Proof generated successfully.");
 
-    // Clean up env vars
+    // The same seed and inputs reproduce the proof exactly
+    println!("Submitting seeded generate_proof job again...");
+    let repeat_call = harness
+        .submit_job(service_id, GENERATE_PROOF_JOB_ID, proof_inputs)
+        .await?;
+    let repeat_exec =
+        wait_for_completion_of_tangle_job(harness.client(), service_id, repeat_call.call_id, 1)
+            .await?;
+    let repeat_fields = repeat_exec
+        .output
+        .expect("Repeated generate_proof job failed to produce output");
+    let ProofOutput::Proof(repeat_result) = from_field(&repeat_fields[0])? else {
+        panic!("Expected the repeated proof inline, got a pointer");
+    };
+    assert_eq!(repeat_result.proof_bytes, proof_result.proof_bytes);
+    assert_eq!(repeat_result.public_inputs, proof_result.public_inputs);
+
+    // Clean up the generated MPC material and env vars
+    let removed = cleanup_mpc_material(
+        &data_dir.join(mpc_key_path_rel),
+        &data_dir.join(mpc_cert_path_rel),
+    )?;
+    assert_eq!(
+        removed.len(),
+        3,
+        "Expected the MPC key, certificate and PEM copy"
+    );
     // Use unsafe block for removing env vars in test
     unsafe {
         std::env::remove_var("MPC_LISTEN_DNS");
         std::env::remove_var("MPC_KEY_PATH");
         std::env::remove_var("MPC_CERT_PATH");
+        std::env::remove_var("ALLOW_PROOF_RNG_SEED");
+        std::env::remove_var("MOCK_PROVER");
+        std::env::remove_var("PROOF_METADATA");
     }
 
     Ok(())